@@ -6,7 +6,7 @@ from typing import List, Union
 def check_and_convert_type(
     arr: Union[List[Union[str, float, int]], NDArray]
 ) -> NDArray:
-    if _is_numpy(arr):
+    if _is_numpy(arr) or _is_arrow(arr):
         return arr  # pyright: ignore
     if not _is_uniform_type(arr):  # pyright: ignore
         raise ValueError(f"Array needs to be of uniform type when of type {list}")
@@ -17,6 +17,12 @@ def _is_numpy(arr: Union[List[Union[str, float, int]], NDArray]) -> bool:
     return isinstance(arr, np.ndarray)
 
 
+def _is_arrow(arr) -> bool:
+    # pyarrow arrays, chunked arrays and anything exporting the arrow c data
+    # interface are read directly in rust
+    return hasattr(arr, "__arrow_c_array__") or hasattr(arr, "__arrow_c_stream__")
+
+
 def _is_uniform_type(arr: List[Union[str, float, int]]) -> bool:
     if not isinstance(arr, list):
         return False
@@ -26,3 +32,11 @@ def _is_uniform_type(arr: List[Union[str, float, int]]) -> bool:
 
 def _convert_obj_type(arr: List[Union[str, float, int]]) -> NDArray:
     return np.array(arr)
+
+
+def report_metrics(report, status):
+    # runtime checks take plain dicts, a report brings its own statuses
+    # unless the caller passes some
+    if isinstance(report, dict):
+        return report, status
+    return report.to_dict(), report.status if status is None else status
@@ -1,13 +1,21 @@
 from ._fair_perf_ml import (
+    register_data_bias_metric,
+    data_bias_metric_names,
+    DataBiasReport,
+    DataBiasAccumulator as _DataBiasAccumulator,
     data_bias_analyzer,
+    data_bias_multi_group_analyzer,
+    data_bias_intersectional_analyzer,
+    data_bias_conditional_disparity,
+    data_bias_categorical_divergences,
+    data_bias_continuous_analyzer,
     data_bias_runtime_check,
     data_bias_partial_check,
 )
-from typing import Union, Optional, List
+from typing import Any, Callable, Union, Optional, List
 from numpy.typing import NDArray
 from orjson import loads
-from .models import DataBiasBaseline
-from ._internal import check_and_convert_type
+from ._internal import check_and_convert_type, report_metrics
 
 
 def perform_analysis(
@@ -15,7 +23,17 @@ def perform_analysis(
     ground_truth: Union[List[Union[str, float, int]], NDArray],  # pyright: ignore
     feature_label_or_threshold: Union[str, float, int],
     ground_truth_label_or_threshold: Union[str, float, int],
-) -> dict[str, float]:
+    missing_policy: str = "raise",
+    sample_weight: Optional[Union[List[float], NDArray]] = None,  # pyright: ignore
+    confidence_level: Optional[float] = None,
+    resamples: int = 1000,
+    seed: int = 0,
+    significance: bool = False,
+    permutations: int = 1000,
+    min_facet_size: float = 30,
+    min_cell_count: float = 5,
+    group_variable: Optional[Union[List[Union[str, int]], NDArray]] = None,  # pyright: ignore
+) -> DataBiasReport:
     """
     interface into rust class
     makes sure we are passing numpy arrays to the rust function
@@ -26,6 +44,38 @@ def perform_analysis(
             most efficient to pass as numpy array
         feature_label_or_threshold: Union[str, float, int] -> segmentation parameter for the feature
         ground_truth_label_or_threshold: Union[str, float, int] -> segmenation parameter for ground truth
+        missing_policy: str="raise" -> rows where any input is missing (None or NaN) are
+            dropped with "drop", counted as neither facet with "own_group" (a missing outcome
+            is still dropped), treated as a negative label with "negative",
+            or raise MissingValuesError with "raise"
+        sample_weight: Optional[Union[List[float], NDArray]]=None -> non negative weight per row,
+            every count becomes the sum of the weights of its rows
+        confidence_level: Optional[float]=None -> fills the report's confidence_intervals with seeded
+            bootstrap bounds per metric,
+            keyed like the report with "lower", "upper", "droppedResamples" (resamples where
+            the metric was not finite, any of them make "status" "undefined" with a "reason"),
+            resampling within each facet
+        resamples: int=1000 -> number of bootstrap resamples, run in parallel
+        seed: int=0 -> seed of the bootstrap and of the permutation test, the same seed gives
+            the same bounds and p-values
+        significance: bool=False -> fills the report's significance with two sided p-values of
+            DifferenceInProportionOfLabels, keyed twoProportionZ, chiSquare, fisherExact and
+            permutation. Counts rows, so sample_weight must be None
+        permutations: int=1000 -> number of facet label permutations of the permutation test
+        min_facet_size: float=30 -> rows each facet needs before its metrics are ok
+        min_cell_count: float=5 -> smallest count a metric may divide by before it is ok
+        group_variable: Optional[Union[List[Union[str, int]], NDArray]]=None -> the stratum of
+            each row, e.g. department or region, present on every row. Fills the report's
+            conditional_disparity with the conditional demographic disparity in labels (CDDL),
+            see conditional_demographic_disparity. Counts rows, so sample_weight must be None
+    Returns:
+        DataBiasReport -> the metrics, read like a dict, plus the missing rows per facet (missing, keyed
+            facetA, facetD and feature, rows whose feature is missing), the Kish effective sample
+            size per facet (effective_sample_size, keyed facetA and facetD) and the status of every
+            metric (status, keyed like the report, each a dict with "status" of "ok", "undefined"
+            (a zero denominator or a value that is not finite) or "low-support", and a "reason"
+            unless ok). confidence_intervals, significance and conditional_disparity are None
+            unless requested
     """
     # want to pass numpy arrays to rust
     # type resolution in rust mod depends on numpy arrays
@@ -33,60 +83,350 @@ def perform_analysis(
     feature: NDArray = check_and_convert_type(feature)
     ground_truth: NDArray = check_and_convert_type(ground_truth)
 
-    res: dict[str, float] = data_bias_analyzer(
+    res = data_bias_analyzer(
         feature_array=feature,
         ground_truth_array=ground_truth,
         feature_label_or_threshold=feature_label_or_threshold,
         ground_truth_label_or_threshold=ground_truth_label_or_threshold,
+        missing_policy=missing_policy,
+        sample_weight=(
+            None if sample_weight is None else check_and_convert_type(sample_weight)
+        ),
+        confidence_level=confidence_level,
+        resamples=resamples,
+        seed=seed,
+        significance=significance,
+        permutations=permutations,
+        min_facet_size=min_facet_size,
+        min_cell_count=min_cell_count,
+        group_array=None if group_variable is None else check_and_convert_type(group_variable),
+    )
+
+    return res
+
+
+def perform_multi_group_analysis(
+    feature: Union[List[Union[str, int]], NDArray],  # pyright: ignore
+    ground_truth: Union[List[Union[str, float, int]], NDArray],  # pyright: ignore
+    ground_truth_label_or_threshold: Union[str, float, int],
+    comparison: str = "one_vs_rest",
+    reference_group: Optional[Union[str, int]] = None,
+    missing_policy: str = "raise",
+) -> dict[str, Any]:
+    """
+    data bias for a feature with more than two groups
+    Args:
+        feature: Union[List[Union[str, int]], NDArray] -> categorical feature, each distinct value is a group
+        ground_truth: Union[List[Union[str, float, int]], NDArray] -> the ground truth data
+        ground_truth_label_or_threshold: Union[str, float, int] -> segmenation parameter for ground truth
+        comparison: str="one_vs_rest" -> "one_vs_rest" compares each group against all other rows,
+            "pairwise" every pair of groups, "reference" every group against reference_group
+        reference_group: Optional[Union[str, int]]=None -> the group compared against in "reference" mode
+        missing_policy: str="raise" -> same as perform_analysis for the labeled inputs, every
+            row needs a group, a missing one raises MissingValuesError whatever the policy
+    Returns:
+        dict -> "results" holds one entry per comparison with "group", "versus" (None for the rest)
+            and "report", "worstCase" maps each metric to the comparison furthest from its ideal value
+    """
+    feature: NDArray = check_and_convert_type(feature)
+    ground_truth: NDArray = check_and_convert_type(ground_truth)
+    res: str = data_bias_multi_group_analyzer(
+        feature_array=feature,
+        ground_truth_array=ground_truth,
+        ground_truth_label_or_threshold=ground_truth_label_or_threshold,
+        comparison=comparison,
+        reference_group=reference_group,
+        missing_policy=missing_policy,
+    )
+    return loads(res)
+
+
+def perform_intersectional_analysis(
+    features: dict[str, Union[List[Union[str, float, int]], NDArray]],  # pyright: ignore
+    feature_labels_or_thresholds: dict[str, Union[str, float, int]],
+    ground_truth: Union[List[Union[str, float, int]], NDArray],  # pyright: ignore
+    ground_truth_label_or_threshold: Union[str, float, int],
+    min_subgroup_size: int = 30,
+    missing_policy: str = "raise",
+) -> dict[str, Any]:
+    """
+    data bias for every intersection of several protected attributes, each subgroup
+    compared against all rows outside it
+    Args:
+        features: dict[str, Union[List[Union[str, float, int]], NDArray]] -> protected attribute name to its data
+        feature_labels_or_thresholds: dict[str, Union[str, float, int]] -> segmentation parameter per attribute
+        ground_truth: Union[List[Union[str, float, int]], NDArray] -> the ground truth data
+        ground_truth_label_or_threshold: Union[str, float, int] -> segmenation parameter for ground truth
+        min_subgroup_size: int=30 -> subgroups with fewer rows are flagged as suppressed and get no report
+        missing_policy: str="raise" -> same as perform_analysis, a row missing an attribute has no
+            subgroup so "own_group" drops it like "drop"
+    Returns:
+        dict -> "results" holds one entry per subgroup with "subgroup" (attribute name to whether
+            the rows meet its label), "size", "suppressed" and "report" (None when suppressed)
+    """
+    ground_truth: NDArray = check_and_convert_type(ground_truth)
+    res: str = data_bias_intersectional_analyzer(
+        features=[
+            (name, check_and_convert_type(data), feature_labels_or_thresholds[name])
+            for name, data in features.items()
+        ],
+        ground_truth_array=ground_truth,
+        ground_truth_label_or_threshold=ground_truth_label_or_threshold,
+        min_subgroup_size=min_subgroup_size,
+        missing_policy=missing_policy,
+    )
+    return loads(res)
+
+
+def conditional_demographic_disparity(
+    feature: Union[List[Union[str, float, int]], NDArray],  # pyright: ignore
+    ground_truth: Union[List[Union[str, float, int]], NDArray],  # pyright: ignore
+    group_variable: Union[List[Union[str, int]], NDArray],  # pyright: ignore
+    feature_label_or_threshold: Union[str, float, int],
+    ground_truth_label_or_threshold: Union[str, float, int],
+    missing_policy: str = "raise",
+) -> dict[str, Any]:
+    """
+    conditional demographic disparity in labels (CDDL), the demographic disparity of facet d
+    (its share of the negative labels minus its share of the positive labels) within each
+    stratum of group_variable, averaged with the stratum sizes as weights
+    Args:
+        feature: Union[List[Union[str, float, int]], NDArray] -> the feature data
+        ground_truth: Union[List[Union[str, float, int]], NDArray] -> the ground truth data
+        group_variable: Union[List[Union[str, int]], NDArray] -> the stratum of each row,
+            e.g. department or region
+        feature_label_or_threshold: Union[str, float, int] -> segmentation parameter for the feature
+        ground_truth_label_or_threshold: Union[str, float, int] -> segmenation parameter for ground truth
+        missing_policy: str="raise" -> same as perform_analysis for the labeled inputs, every
+            row needs a group_variable, a missing one raises MissingValuesError whatever the policy
+    Returns:
+        dict -> "value" holds the weighted average, "strata" one entry per stratum with "group",
+            "size" and "disparity", a stratum without negative (or positive) labels counts 0
+            for that share
+    """
+    res: str = data_bias_conditional_disparity(
+        feature_array=check_and_convert_type(feature),
+        ground_truth_array=check_and_convert_type(ground_truth),
+        group_array=check_and_convert_type(group_variable),
+        feature_label_or_threshold=feature_label_or_threshold,
+        ground_truth_label_or_threshold=ground_truth_label_or_threshold,
+        missing_policy=missing_policy,
     )
+    return loads(res)
 
-    # simply for nice formatting
-    return DataBiasBaseline(**res).model_dump()
+
+def categorical_divergences(
+    feature: Union[List[Union[str, float, int]], NDArray],  # pyright: ignore
+    ground_truth: Union[List[Union[str, int]], NDArray],  # pyright: ignore
+    feature_label_or_threshold: Union[str, float, int],
+    lp_order: float = 2.0,
+    epsilon: float = 0.0,
+    missing_policy: str = "raise",
+) -> dict[str, Any]:
+    """
+    KlDivergence, JsDivergence, LpNorm, TotalVariationDistance and KolmorogvSmirnov between
+    the label distributions of the two facets, over every category of the ground truth,
+    e.g. a 5 point credit grade
+    categories are sorted by value, the order KolmorogvSmirnov accumulates them in
+    with two categories the second one is the positive outcome and the values match
+    perform_analysis
+    Args:
+        feature: Union[List[Union[str, float, int]], NDArray] -> the feature data
+        ground_truth: Union[List[Union[str, int]], NDArray] -> the categorical ground truth,
+            read as is
+        feature_label_or_threshold: Union[str, float, int] -> segmentation parameter for the feature
+        lp_order: float=2.0 -> order of the LpNorm, at least 1
+        epsilon: float=0.0 -> added to the share of every category before renormalizing,
+            keeps KlDivergence finite when a facet never saw a category
+        missing_policy: str="raise" -> same as perform_analysis for the feature, every row needs
+            a ground truth category, a missing one raises MissingValuesError whatever the policy
+    Returns:
+        dict -> "categories" holds the sorted categories, "divergences" the five metrics
+    """
+    res: str = data_bias_categorical_divergences(
+        feature_array=check_and_convert_type(feature),
+        ground_truth_array=check_and_convert_type(ground_truth),
+        feature_label_or_threshold=feature_label_or_threshold,
+        lp_order=lp_order,
+        epsilon=epsilon,
+        missing_policy=missing_policy,
+    )
+    return loads(res)
+
+
+def continuous_analysis(
+    feature: Union[List[Union[str, float, int]], NDArray],  # pyright: ignore
+    ground_truth: Union[List[Union[float, int]], NDArray],  # pyright: ignore
+    feature_label_or_threshold: Union[str, float, int],
+    missing_policy: str = "raise",
+) -> dict[str, float]:
+    """
+    compares the distributions of a continuous ground truth, e.g. loan amount or salary, between
+    the two facets without thresholding it
+    Args:
+        feature: Union[List[Union[str, float, int]], NDArray] -> the feature data
+        ground_truth: Union[List[Union[float, int]], NDArray] -> the raw ground truth values
+        feature_label_or_threshold: Union[str, float, int] -> segmentation parameter for the feature
+        missing_policy: str="raise" -> same as perform_analysis, a row missing its feature
+            belongs to neither facet so "own_group" leaves it out like "drop", and "negative"
+            fills a missing ground truth with 0
+    Returns:
+        dict -> DifferenceInMeans and DifferenceInMedians (facet a minus facet d),
+            KolmorogvSmirnov with its asymptotic KolmorogvSmirnovPValue, WassersteinDistance
+            in the unit of the ground truth, AndersonDarling (the two sample A2akN statistic,
+            not standardized) and "missing", the rows with a missing value per facet
+    """
+    res: str = data_bias_continuous_analyzer(
+        feature_array=check_and_convert_type(feature),
+        ground_truth_array=check_and_convert_type(ground_truth),
+        feature_label_or_threshold=feature_label_or_threshold,
+        missing_policy=missing_policy,
+    )
+    return loads(res)
 
 
 def runtime_comparison(
-    baseline: dict[str, float],
-    latest: dict[str, float],
+    baseline: Union[dict[str, float], DataBiasReport],
+    latest: Union[dict[str, float], DataBiasReport],
     threshold: Optional[float] = None,
+    baseline_status: Optional[dict[str, dict[str, str]]] = None,
+    latest_status: Optional[dict[str, dict[str, str]]] = None,
 ) -> dict[str, str]:
     """
     interface into rust module
     serves to nicely formats the return as dicts are ordered and hashmaps are not
     Args:
-        baseline: dict -> the result from calling perform_analysis on the baseline data
-        latest: dict -> the current data for comparison from calling perform_analysis
+        baseline: Union[dict, DataBiasReport] -> the result from calling perform_analysis on the baseline data,
+            the report or its to_dict()
+        latest: Union[dict, DataBiasReport] -> the current data for comparison from calling perform_analysis
         threshold: Optionl[float]=None -> the comparison threshold, defaults to 0.10 in rust mod
+        baseline_status: Optional[dict]=None -> status of each baseline metric, the status of
+            the report when baseline is one, a metric whose status is undefined fails the check
+        latest_status: Optional[dict]=None -> status of each latest metric, same as baseline_status
     Returns:
         dict
     """
+    baseline, baseline_status = report_metrics(baseline, baseline_status)
+    latest, latest_status = report_metrics(latest, latest_status)
     res: str = (
-        data_bias_runtime_check(baseline=baseline, latest=latest, threshold=threshold)
+        data_bias_runtime_check(
+            baseline=baseline,
+            latest=latest,
+            threshold=threshold,
+            baseline_status=baseline_status,
+            latest_status=latest_status,
+        )
         if threshold
-        else data_bias_runtime_check(baseline=baseline, latest=latest)
+        else data_bias_runtime_check(
+            baseline=baseline,
+            latest=latest,
+            baseline_status=baseline_status,
+            latest_status=latest_status,
+        )
     )
     # for nicer formatting on the return
     return loads(res)
 
 
 def partial_runtime_comparison(
-    baseline: dict[str, float],
-    latest: dict[str, float],
+    baseline: Union[dict[str, float], DataBiasReport],
+    latest: Union[dict[str, float], DataBiasReport],
     metrics: List[str],
     threshold: Optional[float] = 0.10,
+    baseline_status: Optional[dict[str, dict[str, str]]] = None,
+    latest_status: Optional[dict[str, dict[str, str]]] = None,
 ) -> dict[str, str]:
     """
     interface into rust module
     serves to nicely formats the return as dicts are ordered and hashmaps are not
     Args:
-        baseline: dict -> the result from calling perform_analysis on the baseline data
-        latest: dict -> the current data for comparison from calling perform_analysis
+        baseline: Union[dict, DataBiasReport] -> the result from calling perform_analysis on the baseline data,
+            the report or its to_dict()
+        latest: Union[dict, DataBiasReport] -> the current data for comparison from calling perform_analysis
         metrics: List[str] -> the list of metrics we want to evaluate on
         threshold: Optionl[float]=None -> the comparison threshold, defaults to 0.10 in rust mod
+        baseline_status: Optional[dict]=None -> status of each baseline metric, the status of
+            the report when baseline is one, a metric whose status is undefined fails the check
+        latest_status: Optional[dict]=None -> status of each latest metric, same as baseline_status
     Returns:
         dict
     """
+    baseline, baseline_status = report_metrics(baseline, baseline_status)
+    latest, latest_status = report_metrics(latest, latest_status)
     res: str = data_bias_partial_check(
-        baseline=baseline, latest=latest, metrics=metrics, threshold=threshold
+        baseline=baseline,
+        latest=latest,
+        metrics=metrics,
+        threshold=threshold,
+        baseline_status=baseline_status,
+        latest_status=latest_status,
     )
     # for nicer formatting on the return
     return loads(res)
+
+
+def register_metric(
+    name: str,
+    compute: Callable[[dict[str, float]], float],
+    direction: str = "magnitude",
+    ideal_value: float = 0.0,
+) -> None:
+    """
+    adds a metric to every analysis, runtime check and accumulator in this process
+    Args:
+        name: str -> key of the metric in reports and baselines, must not already exist
+        compute: Callable[[dict[str, float]], float] -> called with the per facet counts
+            facet_a_positives, facet_a_negatives, facet_d_positives, facet_d_negatives
+            (sums of the sample weights when the analysis is weighted)
+        direction: str="magnitude" -> "magnitude" flags growth of the absolute value,
+            "increase" flags growth of the raw value
+        ideal_value: float=0.0 -> value of the metric when both facets are treated alike
+    """
+    register_data_bias_metric(
+        name=name, compute=compute, direction=direction, ideal_value=ideal_value
+    )
+
+
+def metric_names() -> List[str]:
+    """
+    every registered metric, built in ones first
+    """
+    return data_bias_metric_names()
+
+
+class DataBiasAccumulator(_DataBiasAccumulator):
+    """
+    data bias over a stream of batches
+    only per facet label counts are kept, accumulators from different workers
+    can be combined with merge before calling finalize
+    state ships between processes with to_bytes/from_bytes, to_json/from_json or pickle
+    bare numeric and LabelSpec.auto labels are resolved per batch, so a batch holding
+    exactly two distinct values is matched on equality rather than thresholded
+    Args:
+        feature_label_or_threshold: Union[str, float, int] -> segmentation parameter for the feature
+        ground_truth_label_or_threshold: Union[str, float, int] -> segmenation parameter for ground truth
+        missing_policy: str="raise" -> same as perform_analysis, counts are kept in missing
+    """
+
+    def update(
+        self,
+        feature: Union[List[Union[str, float, int]], NDArray],  # pyright: ignore
+        ground_truth: Union[List[Union[str, float, int]], NDArray],  # pyright: ignore
+        sample_weight: Optional[Union[List[float], NDArray]] = None,  # pyright: ignore
+    ) -> None:
+        """
+        add a batch of raw feature and ground truth values, optionally weighted per row
+        """
+        feature: NDArray = check_and_convert_type(feature)
+        ground_truth: NDArray = check_and_convert_type(ground_truth)
+        if sample_weight is not None:
+            sample_weight = check_and_convert_type(sample_weight)
+        super().update(feature, ground_truth, sample_weight)
+
+    def finalize(self, as_report: bool = False) -> Union[dict[str, float], DataBiasReport]:
+        """
+        the same report perform_analysis returns, over every batch seen so far
+        as_report=True returns the DataBiasReport in place of its to_dict()
+        """
+        res = super().finalize()
+        return res if as_report else res.to_dict()
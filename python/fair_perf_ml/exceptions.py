@@ -0,0 +1,61 @@
+from ._fair_perf_ml import (
+    FairMlError,
+    LengthMismatchError,
+    EmptyFacetError,
+    EmptyInputError,
+    LabelTypeMismatchError,
+    UnsupportedDtypeError,
+    MissingBaselineKeyError,
+    InvalidMetricNameError,
+    InvalidModelTypeError,
+    DegenerateDenominatorError,
+    StateVersionMismatchError,
+    InvalidStateError,
+    DuplicateMetricNameError,
+    MetricComputationError,
+    InvalidDirectionError,
+    InvalidComparisonError,
+    UnknownGroupError,
+    UnmappedCategoryError,
+    InvalidLabelSpecError,
+    InvalidMissingPolicyError,
+    MissingValuesError,
+    InvalidSampleWeightError,
+    InvalidArrowDataError,
+    UnknownColumnError,
+    InvalidDivergenceOptionsError,
+    InvalidBootstrapConfigError,
+    InvalidSignificanceConfigError,
+    InvalidSupportThresholdsError,
+)
+
+__all__ = [
+    "FairMlError",
+    "LengthMismatchError",
+    "EmptyFacetError",
+    "EmptyInputError",
+    "LabelTypeMismatchError",
+    "UnsupportedDtypeError",
+    "MissingBaselineKeyError",
+    "InvalidMetricNameError",
+    "InvalidModelTypeError",
+    "DegenerateDenominatorError",
+    "StateVersionMismatchError",
+    "InvalidStateError",
+    "DuplicateMetricNameError",
+    "MetricComputationError",
+    "InvalidDirectionError",
+    "InvalidComparisonError",
+    "UnknownGroupError",
+    "UnmappedCategoryError",
+    "InvalidLabelSpecError",
+    "InvalidMissingPolicyError",
+    "MissingValuesError",
+    "InvalidSampleWeightError",
+    "InvalidArrowDataError",
+    "UnknownColumnError",
+    "InvalidDivergenceOptionsError",
+    "InvalidBootstrapConfigError",
+    "InvalidSignificanceConfigError",
+    "InvalidSupportThresholdsError",
+]
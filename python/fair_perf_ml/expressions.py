@@ -0,0 +1,200 @@
+"""
+bias metrics as polars expressions, for group_by/agg and select in eager and lazy queries
+
+    from fair_perf_ml import expressions as fair
+
+    df.group_by("date").agg(
+        fair.disparate_impact("pred", "gender", "label", LabelSpec.greater_equal(0.5), "F", 1),
+        fair.class_imbalance("label", "gender", 1, "F"),
+    )
+
+each group's columns are handed to the rust analyzers as they are, through the arrow
+c stream interface, without a numpy copy, a polars release without
+Series.__arrow_c_stream__ raises TypeError
+these are python expressions built on polars.map_groups rather than compiled polars
+plugins, so polars holds the GIL while a group is dispatched and the streaming engine
+is not used, see the changelog
+"""
+
+from ._fair_perf_ml import LabelSpec, data_bias_analyzer, model_bias_analyzer
+from typing import Any, Callable, Union
+import polars as pl
+
+Rule = Union[str, float, int, LabelSpec, set, dict]
+
+_DATA_BIAS_METRICS = {
+    "class_imbalance": "ClassImbalance",
+    "difference_in_proportion_of_labels": "DifferenceInProportionOfLabels",
+    "kl_divergence": "KlDivergence",
+    "js_divergence": "JsDivergence",
+    "lp_norm": "LpNorm",
+    "total_variation_distance": "TotalVariationDistance",
+    "kolmogorov_smirnov": "KolmorogvSmirnov",
+}
+
+_MODEL_BIAS_METRICS = {
+    "difference_in_positive_predicted_labels": "DifferenceInPositivePredictedLabels",
+    "disparate_impact": "DisparateImpact",
+    "accuracy_difference": "AccuracyDifference",
+    "recall_difference": "RecallDifference",
+    "difference_in_conditional_acceptance": "DifferenceInConditionalAcceptance",
+    "difference_in_acceptance_rate": "DifferenceInAcceptanceRate",
+    "speciality_difference": "SpecialityDifference",
+    "difference_in_conditional_rejection": "DifferenceInConditionalRejection",
+    "difference_in_rejection_rate": "DifferenceInRejectionRate",
+    "treatment_equity": "TreatmentEquity",
+    "conditional_demographic_disparity_predicted_labels": "ConditionalDemographicDesparityPredictedLabels",
+    "generalized_entropy": "GeneralizedEntropy",
+}
+
+__all__ = ["data_bias_metric", "model_bias_metric"] + list(_DATA_BIAS_METRICS) + list(
+    _MODEL_BIAS_METRICS
+)
+
+
+def _expr(column: Union[str, pl.Expr]) -> pl.Expr:
+    return pl.col(column) if isinstance(column, str) else column
+
+
+def _column(series: pl.Series) -> Any:
+    # older polars releases export arrow only through pyarrow, a silent numpy
+    # copy would undo the point of the expressions
+    if not hasattr(series, "__arrow_c_stream__"):
+        raise TypeError(
+            f"polars {pl.__version__} does not export Series.__arrow_c_stream__, "
+            "upgrade polars to a release with the Arrow PyCapsule interface"
+        )
+    return series
+
+
+def data_bias_metric(
+    metric: str,
+    ground_truth: Union[str, pl.Expr],
+    facet: Union[str, pl.Expr],
+    ground_truth_rule: Rule,
+    facet_rule: Rule,
+    missing_policy: str = "raise",
+) -> pl.Expr:
+    """
+    a data bias metric per group, built in or registered with data_bias.register_metric
+    Args:
+        metric: str -> key of the metric in the report, e.g. "ClassImbalance"
+        ground_truth: Union[str, pl.Expr] -> the ground truth column
+        facet: Union[str, pl.Expr] -> the feature column the facets are taken from
+        ground_truth_rule: Rule -> segmentation parameter for the ground truth
+        facet_rule: Rule -> segmentation parameter for the feature
+        missing_policy: str="raise" -> same as data_bias.perform_analysis
+    Returns:
+        pl.Expr -> a Float64 scalar per group, named after the metric
+    """
+
+    def compute(columns: list[pl.Series]) -> float:
+        report = data_bias_analyzer(
+            feature_array=_column(columns[1]),
+            ground_truth_array=_column(columns[0]),
+            feature_label_or_threshold=facet_rule,
+            ground_truth_label_or_threshold=ground_truth_rule,
+            missing_policy=missing_policy,
+        )
+        return report[metric]
+
+    return pl.map_groups(
+        exprs=[_expr(ground_truth), _expr(facet)],
+        function=compute,
+        return_dtype=pl.Float64,
+        returns_scalar=True,
+    ).alias(metric)
+
+
+def model_bias_metric(
+    metric: str,
+    prediction: Union[str, pl.Expr],
+    facet: Union[str, pl.Expr],
+    ground_truth: Union[str, pl.Expr],
+    prediction_rule: Rule,
+    facet_rule: Rule,
+    ground_truth_rule: Rule,
+    missing_policy: str = "raise",
+) -> pl.Expr:
+    """
+    a model bias metric per group, built in or registered with model_bias.register_metric
+    Args:
+        metric: str -> key of the metric in the report, e.g. "DisparateImpact"
+        prediction: Union[str, pl.Expr] -> the prediction column
+        facet: Union[str, pl.Expr] -> the feature column the facets are taken from
+        ground_truth: Union[str, pl.Expr] -> the ground truth column
+        prediction_rule: Rule -> segmentation parameter for the predictions
+        facet_rule: Rule -> segmentation parameter for the feature
+        ground_truth_rule: Rule -> segmentation parameter for the ground truth
+        missing_policy: str="raise" -> same as model_bias.perform_analysis
+    Returns:
+        pl.Expr -> a Float64 scalar per group, named after the metric
+    """
+
+    def compute(columns: list[pl.Series]) -> float:
+        report = model_bias_analyzer(
+            feature_array=_column(columns[1]),
+            ground_truth_array=_column(columns[2]),
+            prediction_array=_column(columns[0]),
+            feature_label_or_threshold=facet_rule,
+            ground_truth_label_or_threshold=ground_truth_rule,
+            prediction_label_or_threshold=prediction_rule,
+            missing_policy=missing_policy,
+        )
+        return report[metric]
+
+    return pl.map_groups(
+        exprs=[_expr(prediction), _expr(facet), _expr(ground_truth)],
+        function=compute,
+        return_dtype=pl.Float64,
+        returns_scalar=True,
+    ).alias(metric)
+
+
+def _data_bias_expression(key: str) -> Callable[..., pl.Expr]:
+    def expression(
+        ground_truth: Union[str, pl.Expr],
+        facet: Union[str, pl.Expr],
+        ground_truth_rule: Rule,
+        facet_rule: Rule,
+        missing_policy: str = "raise",
+    ) -> pl.Expr:
+        return data_bias_metric(
+            key, ground_truth, facet, ground_truth_rule, facet_rule, missing_policy
+        )
+
+    expression.__doc__ = f"{key} per group, see data_bias_metric"
+    return expression
+
+
+def _model_bias_expression(key: str) -> Callable[..., pl.Expr]:
+    def expression(
+        prediction: Union[str, pl.Expr],
+        facet: Union[str, pl.Expr],
+        ground_truth: Union[str, pl.Expr],
+        prediction_rule: Rule,
+        facet_rule: Rule,
+        ground_truth_rule: Rule,
+        missing_policy: str = "raise",
+    ) -> pl.Expr:
+        return model_bias_metric(
+            key,
+            prediction,
+            facet,
+            ground_truth,
+            prediction_rule,
+            facet_rule,
+            ground_truth_rule,
+            missing_policy,
+        )
+
+    expression.__doc__ = f"{key} per group, see model_bias_metric"
+    return expression
+
+
+for _name, _key in _DATA_BIAS_METRICS.items():
+    globals()[_name] = _data_bias_expression(_key)
+    globals()[_name].__name__ = _name
+for _name, _key in _MODEL_BIAS_METRICS.items():
+    globals()[_name] = _model_bias_expression(_key)
+    globals()[_name].__name__ = _name
@@ -0,0 +1,98 @@
+from ._fair_perf_ml import FrameReport, LabelSpec, dataframe_analyzer
+from .models import ModelType
+from typing import Any, List, Optional, Union
+
+Rule = Union[str, float, int, LabelSpec, set, dict]
+
+
+def analyze_dataframe(
+    df: Any,
+    facet: Union[str, List[str], dict[str, Rule]],
+    facet_rule: Optional[Rule] = None,
+    label: Optional[str] = None,
+    label_rule: Optional[Rule] = None,
+    prediction: Optional[str] = None,
+    prediction_rule: Optional[Rule] = None,
+    model_type: Optional[Union[str, ModelType]] = None,
+    decision_threshold: float = 0.5,
+    missing_policy: str = "raise",
+    sample_weight: Optional[str] = None,
+    confidence_level: Optional[float] = None,
+    resamples: int = 1000,
+    seed: int = 0,
+    significance: bool = False,
+    permutations: int = 1000,
+    min_facet_size: float = 30.0,
+    min_cell_count: float = 5.0,
+) -> FrameReport:
+    """
+    data bias, model bias and performance over the columns of a dataframe in one call
+    columns are read in rust, through arrow for polars frames, pyarrow tables and pandas
+    frames when pyarrow is installed, through numpy otherwise
+    Args:
+        df: Any -> a pandas or polars DataFrame, or a pyarrow Table
+        facet: Union[str, List[str], dict[str, Rule]] -> the facet column, several facet columns
+            sharing facet_rule, or facet column name to its own rule
+        facet_rule: Optional[Rule]=None -> segmentation parameter for the facet columns,
+            anything a feature_label_or_threshold accepts
+        label: str -> the ground truth column
+        label_rule: Rule -> segmentation parameter for the ground truth
+        prediction: Optional[str]=None -> the prediction column
+        prediction_rule: Optional[Rule]=None -> segmentation parameter for the predictions,
+            model bias is only computed when it is set
+        model_type: Optional[Union[str, ModelType]]=None -> adds the performance of the model,
+            BinaryClassification compares the labeled ground truth and predictions,
+            LogisticRegression the labeled ground truth and the prediction scores,
+            LinearRegression the raw values of both
+        decision_threshold: float=0.5 -> decision threshold for LogisticRegression
+        missing_policy: str="raise" -> same as data_bias.perform_analysis, applied per facet,
+            a row missing its label or prediction is left out of both bias reports
+        sample_weight: Optional[str]=None -> column holding a non negative weight per row
+        confidence_level: Optional[float]=None -> adds bootstrap bounds to the bias and
+            performance metrics, same as data_bias.perform_analysis
+        resamples: int=1000 -> bootstrap resamples
+        seed: int=0 -> seed of the bootstrap and the permutation test
+        significance: bool=False -> adds p-values, cannot be combined with sample_weight
+        permutations: int=1000 -> label permutations of the permutation test
+        min_facet_size: float=30.0 -> rows each facet needs for an ok status
+        min_cell_count: float=5.0 -> smallest count a metric may divide by for an ok status
+    Returns:
+        FrameReport -> facets holds a FacetReport per facet column, in order, also found by
+            name with report[facet]. Each has facet, data_bias, a DataBiasReport, and
+            model_bias, a ModelBiasReport (None without a prediction_rule), carrying the
+            missing counts, effective sample size, status, confidence_intervals and significance
+            like the reports of perform_analysis. performance is the BinaryClassificationReport,
+            LogisticRegressionReport or LinearRegressionReport of the model (None without a
+            model_type)
+    """
+    if label is None or label_rule is None:
+        raise ValueError("label and label_rule are required")
+    if isinstance(facet, dict):
+        if facet_rule is not None:
+            raise ValueError("facet_rule is taken from the facet dict")
+        facets = list(facet.items())
+    else:
+        if facet_rule is None:
+            raise ValueError("facet_rule is required")
+        names = [facet] if isinstance(facet, str) else facet
+        facets = [(name, facet_rule) for name in names]
+
+    return dataframe_analyzer(
+        frame=df,
+        facets=facets,
+        label=label,
+        label_rule=label_rule,
+        prediction=prediction,
+        prediction_rule=prediction_rule,
+        model_type=ModelType(model_type).value if model_type is not None else None,
+        decision_threshold=decision_threshold,
+        missing_policy=missing_policy,
+        sample_weight=sample_weight,
+        confidence_level=confidence_level,
+        resamples=resamples,
+        seed=seed,
+        significance=significance,
+        permutations=permutations,
+        min_facet_size=min_facet_size,
+        min_cell_count=min_cell_count,
+    )
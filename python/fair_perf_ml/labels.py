@@ -0,0 +1,24 @@
+from ._fair_perf_ml import LabelSpec, resolve_label as _resolve_label
+from ._internal import check_and_convert_type
+from numpy.typing import NDArray
+from typing import List, Union
+
+__all__ = ["LabelSpec", "resolve_label"]
+
+
+def resolve_label(
+    data: Union[List[Union[str, float, int]], NDArray],  # pyright: ignore
+    label_or_threshold: Union[str, float, int, LabelSpec, set, dict],
+) -> LabelSpec:
+    """
+    the explicit spec a label or threshold stands for on data
+    a bare string is matched with "equals", a bare number (deprecated) or LabelSpec.auto
+    goes through the two value heuristic: data holding exactly two distinct values
+    (truncated to ints) is matched with "equals", anything else with ">="
+    Args:
+        data: Union[List[Union[str, float, int]], NDArray] -> the feature, ground truth or prediction data
+        label_or_threshold: Union[str, float, int, LabelSpec, set, dict] -> the segmentation parameter
+    Returns:
+        LabelSpec -> spec.mode is the mode used
+    """
+    return _resolve_label(check_and_convert_type(data), label_or_threshold)
@@ -1,71 +1,136 @@
 from ._fair_perf_ml import (
+    BinaryClassificationAccumulator as _BinaryClassificationAccumulator,
+    LogisticRegressionAccumulator as _LogisticRegressionAccumulator,
+    LinearRegressionAccumulator as _LinearRegressionAccumulator,
+    BinaryClassificationReport,
+    LogisticRegressionReport,
+    LinearRegressionReport,
     model_performance_regression,
     model_performance_classification,
     model_performance_logisitic_regression,
     model_performance_runtime_entry_full,
     model_performance_runtime_entry_partial,
 )
-from .models import (
-    ModelType,
-    ModelPerformance,
-    LinearRegressionReport,
-    LogisticRegressionReport,
-    BinaryClassificationReport,
-)
+from .models import ModelType, ModelPerformance
 from ._internal import check_and_convert_type
+from .exceptions import InvalidModelTypeError, MissingBaselineKeyError
 from numpy.typing import NDArray
 from typing import Union, List, Optional
 import orjson
 
 
-class DifferentModelTypes(Exception):
+class DifferentModelTypes(InvalidModelTypeError):
     pass
 
 
-class InvalidMetricsBody(Exception):
+class InvalidMetricsBody(MissingBaselineKeyError):
     pass
 
 
+def _weights(
+    sample_weight: Optional[Union[NDArray, List[float]]]  # pyright: ignore
+) -> Optional[NDArray]:
+    if sample_weight is None:
+        return None
+    return check_and_convert_type(sample_weight)
+
+
 def linear_regression_analysis(
     y_true: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
     y_pred: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
+    missing_policy: str = "raise",
+    sample_weight: Optional[Union[NDArray, List[float]]] = None,  # pyright: ignore
+    confidence_level: Optional[float] = None,
+    resamples: int = 1000,
+    seed: int = 0,
 ) -> dict:
+    """
+    confidence_level adds seeded bootstrap bounds per metric under "confidenceIntervals",
+    each with "lower", "upper", "droppedResamples" and "status", from resamples resamples
+    run in parallel
+    """
     y_true: NDArray = check_and_convert_type(y_true)  # pyright: ignore
     y_pred: NDArray = check_and_convert_type(y_pred)  # pyright: ignore
-    res: dict = model_performance_regression(y_true=y_true, y_pred=y_pred)
+    res = model_performance_regression(
+        y_true=y_true,
+        y_pred=y_pred,
+        missing_policy=missing_policy,
+        sample_weight=_weights(sample_weight),
+        confidence_level=confidence_level,
+        resamples=resamples,
+        seed=seed,
+    )
     return ModelPerformance(
         modelType=ModelType.LinearRegression,
-        performanceData=LinearRegressionReport(**res),
-    ).model_dump()
+        performanceData=res.to_dict(),
+        confidenceIntervals=res.confidence_intervals,
+    ).model_dump(exclude_none=True)
 
 
 def logistic_regression_analysis(
     y_true: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
     y_pred: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
     decision_threshold: Optional[float] = 0.5,
+    missing_policy: str = "raise",
+    sample_weight: Optional[Union[NDArray, List[float]]] = None,  # pyright: ignore
+    confidence_level: Optional[float] = None,
+    resamples: int = 1000,
+    seed: int = 0,
 ) -> dict:
+    """
+    confidence_level adds seeded bootstrap bounds per metric under "confidenceIntervals",
+    each with "lower", "upper", "droppedResamples" and "status", from resamples resamples
+    run in parallel
+    """
     y_true: NDArray = check_and_convert_type(y_true)  # pyright: ignore
     y_pred: NDArray = check_and_convert_type(y_pred)  # pyright: ignore
-    res: dict = model_performance_logisitic_regression(
-        y_true=y_true, y_pred=y_pred, decision_threshold=decision_threshold
+    res = model_performance_logisitic_regression(
+        y_true=y_true,
+        y_pred=y_pred,
+        decision_threshold=decision_threshold,
+        missing_policy=missing_policy,
+        sample_weight=_weights(sample_weight),
+        confidence_level=confidence_level,
+        resamples=resamples,
+        seed=seed,
     )
     return ModelPerformance(
         modelType=ModelType.LogisticRegression,
-        performanceData=LogisticRegressionReport(**res),
-    ).model_dump()
+        performanceData=res.to_dict(),
+        confidenceIntervals=res.confidence_intervals,
+    ).model_dump(exclude_none=True)
 
 
 def binary_classification_analysis(
     y_true: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
     y_pred: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
+    missing_policy: str = "raise",
+    sample_weight: Optional[Union[NDArray, List[float]]] = None,  # pyright: ignore
+    confidence_level: Optional[float] = None,
+    resamples: int = 1000,
+    seed: int = 0,
 ) -> dict:
+    """
+    confidence_level adds seeded bootstrap bounds per metric under "confidenceIntervals",
+    each with "lower", "upper", "droppedResamples" and "status", from resamples resamples
+    run in parallel
+    """
     y_true: NDArray = check_and_convert_type(y_true)  # pyright: ignore
     y_pred: NDArray = check_and_convert_type(y_pred)  # pyright: ignore
-    res: dict = model_performance_classification(y_true=y_true, y_pred=y_pred)
+    res = model_performance_classification(
+        y_true=y_true,
+        y_pred=y_pred,
+        missing_policy=missing_policy,
+        sample_weight=_weights(sample_weight),
+        confidence_level=confidence_level,
+        resamples=resamples,
+        seed=seed,
+    )
     return ModelPerformance(
         modelType=ModelType.BinaryClassification,
-        performanceData=BinaryClassificationReport(**res),
-    ).model_dump()
+        performanceData=res.to_dict(),
+        confidenceIntervals=res.confidence_intervals,
+    ).model_dump(exclude_none=True)
 
 
 def runtime_check_full(
@@ -103,3 +168,109 @@ def partial_runtime_check(
         threshold=threshold,
     )
     return orjson.loads(perf)
+
+
+class LinearRegressionAccumulator(_LinearRegressionAccumulator):
+    """
+    linear regression performance over a stream of batches
+    keeps running sums only, serializable with to_bytes/to_json and pickle
+    Args:
+        missing_policy: str="raise" -> "drop" removes rows holding NaN or None,
+            "negative" replaces them with 0, "raise" raises MissingValuesError
+    update takes an optional sample_weight, a non negative weight per row
+    """
+
+    def update(
+        self,
+        y_true: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
+        y_pred: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
+        sample_weight: Optional[Union[NDArray, List[float]]] = None,  # pyright: ignore
+    ) -> None:
+        y_true: NDArray = check_and_convert_type(y_true)  # pyright: ignore
+        y_pred: NDArray = check_and_convert_type(y_pred)  # pyright: ignore
+        super().update(
+            y_pred=y_pred, y_true=y_true, sample_weight=_weights(sample_weight)
+        )
+
+    def finalize(self, as_report: bool = False) -> Union[dict, LinearRegressionReport]:
+        """
+        as_report=True returns the LinearRegressionReport in place of the ModelPerformance dict
+        """
+        res = super().finalize()
+        if as_report:
+            return res
+        return ModelPerformance(
+            modelType=ModelType.LinearRegression,
+            performanceData=res.to_dict(),
+        ).model_dump(exclude_none=True)
+
+
+class LogisticRegressionAccumulator(_LogisticRegressionAccumulator):
+    """
+    logistic regression performance over a stream of batches
+    the decision threshold is applied on update and stored with the state
+    Args:
+        decision_threshold: float=0.5 -> probabilities at or above are a positive prediction
+        missing_policy: str="raise" -> "drop" removes rows holding NaN or None,
+            "negative" replaces them with 0, "raise" raises MissingValuesError
+    update takes an optional sample_weight, a non negative weight per row
+    """
+
+    def update(
+        self,
+        y_true: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
+        y_pred: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
+        sample_weight: Optional[Union[NDArray, List[float]]] = None,  # pyright: ignore
+    ) -> None:
+        y_true: NDArray = check_and_convert_type(y_true)  # pyright: ignore
+        y_pred: NDArray = check_and_convert_type(y_pred)  # pyright: ignore
+        super().update(
+            y_pred=y_pred, y_true=y_true, sample_weight=_weights(sample_weight)
+        )
+
+    def finalize(self, as_report: bool = False) -> Union[dict, LogisticRegressionReport]:
+        """
+        as_report=True returns the LogisticRegressionReport in place of the ModelPerformance dict
+        """
+        res = super().finalize()
+        if as_report:
+            return res
+        return ModelPerformance(
+            modelType=ModelType.LogisticRegression,
+            performanceData=res.to_dict(),
+        ).model_dump(exclude_none=True)
+
+
+class BinaryClassificationAccumulator(_BinaryClassificationAccumulator):
+    """
+    binary classification performance over a stream of batches
+    keeps confusion counts only, serializable with to_bytes/to_json and pickle
+    Args:
+        missing_policy: str="raise" -> "drop" removes rows holding NaN or None,
+            "negative" replaces them with 0, "raise" raises MissingValuesError
+    update takes an optional sample_weight, a non negative weight per row
+    """
+
+    def update(
+        self,
+        y_true: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
+        y_pred: Union[NDArray, List[Union[int, float]]],  # pyright: ignore
+        sample_weight: Optional[Union[NDArray, List[float]]] = None,  # pyright: ignore
+    ) -> None:
+        y_true: NDArray = check_and_convert_type(y_true)  # pyright: ignore
+        y_pred: NDArray = check_and_convert_type(y_pred)  # pyright: ignore
+        super().update(
+            y_pred=y_pred, y_true=y_true, sample_weight=_weights(sample_weight)
+        )
+
+    def finalize(self, as_report: bool = False) -> Union[dict, BinaryClassificationReport]:
+        """
+        as_report=True returns the BinaryClassificationReport in place of the ModelPerformance dict
+        """
+        res = super().finalize()
+        if as_report:
+            return res
+        return ModelPerformance(
+            modelType=ModelType.BinaryClassification,
+            performanceData=res.to_dict(),
+        ).model_dump(exclude_none=True)
@@ -1,6 +1,6 @@
 from pydantic import BaseModel, ConfigDict
 from enum import Enum
-from typing import Union
+from typing import Optional
 
 
 class ModelType(str, Enum):
@@ -9,73 +9,10 @@ class ModelType(str, Enum):
     BinaryClassification = "BinaryClassification"
 
 
-class ModelBiasBaseline(BaseModel):
-    """data model for consistently formatted returns to users"""
-
-    model_config = ConfigDict(extra="forbid", strict=True)
-    DifferenceInPositivePredictedLabels: float
-    DisparateImpact: float
-    AccuracyDifference: float
-    RecallDifference: float
-    DifferenceInConditionalAcceptance: float
-    DifferenceInAcceptanceRate: float
-    SpecialityDifference: float
-    DifferenceInConditionalRejection: float
-    DifferenceInRejectionRate: float
-    TreatmentEquity: float
-    ConditionalDemographicDesparityPredictedLabels: float
-    GeneralizedEntropy: float
-
-
-class DataBiasBaseline(BaseModel):
-    """data model for consistently formatted returns to users"""
-
-    model_config = ConfigDict(extra="forbid", strict=True)
-    ClassImbalance: float
-    DifferenceInProportionOfLabels: float
-    KlDivergence: float
-    JsDivergence: float
-    LpNorm: float
-    TotalVarationDistance: float
-    KolmorogvSmirnov: float
-
-
-# some models for conistent formatting on metric objects
-class LinearRegressionReport(BaseModel):
-    RootMeanSquaredError: float
-    MeanSquaredError: float
-    MeanAbsoluteError: float
-    RSquared: float
-    MaxError: float
-    MeanSquaredLogError: float
-    RootMeanSquaredLogError: float
-    MeanAbsolutePercentageError: float
-
-
-class LogisticRegressionReport(BaseModel):
-    BalancedAccuracy: float
-    PrecisionPositive: float
-    PrecisionNegative: float
-    RecallPositive: float
-    RecallNegative: float
-    Accuracy: float
-    F1Score: float
-    LogLoss: float
-
-
-class BinaryClassificationReport(BaseModel):
-    BalancedAccuracy: float
-    PrecisionPositive: float
-    PrecisionNegative: float
-    RecallPositive: float
-    RecallNegative: float
-    Accuracy: float
-    F1Score: float
-
-
 class ModelPerformance(BaseModel):
     model_config = ConfigDict(extra="forbid", strict=True, use_enum_values=True)
     modelType: ModelType
-    performanceData: Union[
-        LinearRegressionReport, LogisticRegressionReport, BinaryClassificationReport
-    ]
+    # keys come from the report classes in fair_perf_ml.reports
+    performanceData: dict[str, float]
+    # bootstrap bounds per metric, only when a confidence level was asked for
+    confidenceIntervals: Optional[dict[str, dict[str, float]]] = None
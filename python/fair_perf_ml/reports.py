@@ -0,0 +1,19 @@
+from ._fair_perf_ml import (
+    DataBiasReport,
+    ModelBiasReport,
+    BinaryClassificationReport,
+    LogisticRegressionReport,
+    LinearRegressionReport,
+    FacetReport,
+    FrameReport,
+)
+
+__all__ = [
+    "DataBiasReport",
+    "ModelBiasReport",
+    "BinaryClassificationReport",
+    "LogisticRegressionReport",
+    "LinearRegressionReport",
+    "FacetReport",
+    "FrameReport",
+]
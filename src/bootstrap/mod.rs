@@ -0,0 +1,406 @@
+use crate::data_bias::{DataBiasRegistry, FacetLabels, PreTraining};
+use crate::data_handler::check_lengths;
+use crate::errors::FairMlError;
+use crate::model_bias::{FacetConfusion, ModelBiasRegistry, PostTrainingData};
+use crate::model_perf::{
+    BinaryClassificationReport, ClassificationPerf, LinearRegressionPerf, LinearRegressionReport,
+    LogisticRegressionPerf, LogisticRegressionReport,
+};
+use crate::status::MetricStatus;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Settings of a percentile bootstrap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BootstrapConfig {
+    pub resamples: usize,
+    /// Share of the resampled values between the bounds, e.g. 0.95.
+    pub confidence_level: f64,
+    pub seed: u64,
+}
+
+impl Default for BootstrapConfig {
+    fn default() -> Self {
+        BootstrapConfig {
+            resamples: 1000,
+            confidence_level: 0.95,
+            seed: 0,
+        }
+    }
+}
+
+impl BootstrapConfig {
+    fn validate(&self) -> Result<(), FairMlError> {
+        if self.resamples < 2 {
+            return Err(FairMlError::InvalidBootstrapConfig {
+                reason: format!("resamples must be at least 2, got {}", self.resamples),
+            });
+        }
+        if !(self.confidence_level > 0_f64 && self.confidence_level < 1_f64) {
+            return Err(FairMlError::InvalidBootstrapConfig {
+                reason: format!(
+                    "confidence_level must be between 0 and 1, got {}",
+                    self.confidence_level
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Percentile bounds of one metric. Resamples where the metric is not finite
+/// are left out of the bounds and counted in `dropped_resamples`, and any
+/// dropped resample marks the interval undefined, since the bounds then only
+/// describe the resamples where the metric happened to exist.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfidenceInterval {
+    #[serde(deserialize_with = "crate::runtime::f32_or_nan")]
+    pub lower: f32,
+    #[serde(deserialize_with = "crate::runtime::f32_or_nan")]
+    pub upper: f32,
+    pub dropped_resamples: usize,
+    #[serde(flatten)]
+    pub status: MetricStatus,
+}
+
+/// Bounds per metric, keyed like the report the metrics come from.
+pub type ConfidenceIntervals = BTreeMap<String, ConfidenceInterval>;
+
+/// Random draws for the resample, or permutation, numbered `stream`. Every
+/// stream is independent of the others and of the order they run in.
+pub(crate) fn stream_rng(seed: u64, stream: usize) -> ChaCha8Rng {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    rng.set_stream(stream as u64);
+    rng
+}
+
+/// Linear interpolation between the closest ranks of sorted values.
+fn quantile(sorted: &[f32], q: f64) -> f32 {
+    let position = q * (sorted.len() - 1) as f64;
+    let (below, above) = (position.floor() as usize, position.ceil() as usize);
+    let fraction = (position - below as f64) as f32;
+    sorted[below] + fraction * (sorted[above] - sorted[below])
+}
+
+/// Percentile bootstrap over the rows of each stratum. A resample draws as
+/// many rows from every stratum as it holds, with replacement, so stratified
+/// by facet the facet sizes never change and neither facet comes up empty.
+/// Resample `i` draws from stream `i` of the seed, which keeps the bounds
+/// the same whatever the number of threads. Resamples where a metric is not
+/// finite, e.g. a rate with no rows to divide by, are left out of its bounds
+/// and leave the interval undefined.
+fn percentile_bootstrap(
+    strata: &[Vec<usize>],
+    config: BootstrapConfig,
+    analyze: impl Fn(&[usize]) -> Result<Vec<(String, f32)>, FairMlError> + Sync,
+) -> Result<ConfidenceIntervals, FairMlError> {
+    config.validate()?;
+    let resampled = (0..config.resamples)
+        .into_par_iter()
+        .map(|resample| {
+            let mut rng = stream_rng(config.seed, resample);
+            let rows: Vec<usize> = strata
+                .iter()
+                .flat_map(|stratum| {
+                    (0..stratum.len())
+                        .map(|_| stratum[rng.gen_range(0..stratum.len())])
+                        .collect::<Vec<usize>>()
+                })
+                .collect();
+            analyze(&rows)
+        })
+        .collect::<Result<Vec<Vec<(String, f32)>>, FairMlError>>()?;
+
+    let mut values: BTreeMap<String, Vec<f32>> = BTreeMap::new();
+    for (metric, value) in resampled.into_iter().flatten() {
+        let metric_values = values.entry(metric).or_default();
+        if value.is_finite() {
+            metric_values.push(value);
+        }
+    }
+    let tail = (1_f64 - config.confidence_level) / 2_f64;
+    Ok(values
+        .into_iter()
+        .map(|(metric, mut metric_values)| {
+            let dropped_resamples = config.resamples - metric_values.len();
+            let status = if dropped_resamples == 0 {
+                MetricStatus::Ok
+            } else {
+                MetricStatus::Undefined(format!(
+                    "{metric} is not finite in {dropped_resamples} of {} resamples",
+                    config.resamples
+                ))
+            };
+            let (lower, upper) = if metric_values.is_empty() {
+                (f32::NAN, f32::NAN)
+            } else {
+                metric_values.sort_unstable_by(f32::total_cmp);
+                (
+                    quantile(&metric_values, tail),
+                    quantile(&metric_values, 1_f64 - tail),
+                )
+            };
+            let interval = ConfidenceInterval {
+                lower,
+                upper,
+                dropped_resamples,
+                status,
+            };
+            (metric, interval)
+        })
+        .collect())
+}
+
+fn owned_entries(entries: Vec<(&str, f32)>) -> Vec<(String, f32)> {
+    entries
+        .into_iter()
+        .map(|(metric, value)| (metric.to_string(), value))
+        .collect()
+}
+
+/// Rows of facet a and facet d, a row kept as its own group is in neither.
+fn facet_strata(feature_values: &[i16]) -> Vec<Vec<usize>> {
+    let mut strata = vec![Vec::new(), Vec::new()];
+    for (row, feature) in feature_values.iter().enumerate() {
+        match *feature {
+            1 => strata[0].push(row),
+            0 => strata[1].push(row),
+            _ => {}
+        }
+    }
+    strata
+}
+
+/// Bootstrap bounds for every metric of the data bias registry, resampling
+/// within each facet. The labels are the ones the report was computed from,
+/// after the missing value policy.
+pub fn bootstrap_data_bias(
+    registry: &DataBiasRegistry,
+    feature_values: &[i16],
+    ground_truth_values: &[i16],
+    sample_weights: Option<&[f64]>,
+    config: BootstrapConfig,
+) -> Result<ConfidenceIntervals, FairMlError> {
+    check_lengths(
+        "feature",
+        feature_values,
+        "ground_truth",
+        ground_truth_values,
+    )?;
+    percentile_bootstrap(&facet_strata(feature_values), config, |rows| {
+        let mut data = PreTraining {
+            facet_a: FacetLabels::default(),
+            facet_d: FacetLabels::default(),
+        };
+        for row in rows {
+            let facet = if feature_values[*row] == 1 {
+                &mut data.facet_a
+            } else {
+                &mut data.facet_d
+            };
+            let weight = sample_weights.map_or(1_f64, |weights| weights[*row]);
+            facet.record_weighted(ground_truth_values[*row], weight);
+        }
+        Ok(owned_entries(registry.analyze(&data)?.entries()))
+    })
+}
+
+/// Bootstrap bounds for every metric of the model bias registry, see
+/// `bootstrap_data_bias`.
+pub fn bootstrap_model_bias(
+    registry: &ModelBiasRegistry,
+    feature_values: &[i16],
+    prediction_values: &[i16],
+    ground_truth_values: &[i16],
+    sample_weights: Option<&[f64]>,
+    config: BootstrapConfig,
+) -> Result<ConfidenceIntervals, FairMlError> {
+    check_lengths("feature", feature_values, "prediction", prediction_values)?;
+    check_lengths(
+        "feature",
+        feature_values,
+        "ground_truth",
+        ground_truth_values,
+    )?;
+    percentile_bootstrap(&facet_strata(feature_values), config, |rows| {
+        let mut data = PostTrainingData {
+            facet_a: FacetConfusion::default(),
+            facet_d: FacetConfusion::default(),
+        };
+        for row in rows {
+            let facet = if feature_values[*row] == 1 {
+                &mut data.facet_a
+            } else {
+                &mut data.facet_d
+            };
+            let weight = sample_weights.map_or(1_f64, |weights| weights[*row]);
+            facet.record_weighted(prediction_values[*row], ground_truth_values[*row], weight);
+        }
+        Ok(owned_entries(registry.analyze(&data)?.entries()))
+    })
+}
+
+/// Resampled copies of the performance inputs, there are no facets so every
+/// row is one stratum.
+fn resample_rows(
+    rows: &[usize],
+    y_true: &[f32],
+    y_pred: &[f32],
+    sample_weights: Option<&[f32]>,
+) -> (Vec<f32>, Vec<f32>, Option<Vec<f32>>) {
+    let pick = |values: &[f32]| rows.iter().map(|row| values[*row]).collect::<Vec<f32>>();
+    (pick(y_true), pick(y_pred), sample_weights.map(pick))
+}
+
+fn performance_strata(
+    y_true: &[f32],
+    y_pred: &[f32],
+    sample_weights: Option<&[f32]>,
+) -> Result<Vec<Vec<usize>>, FairMlError> {
+    check_lengths("y_true", y_true, "y_pred", y_pred)?;
+    if let Some(weights) = sample_weights {
+        check_lengths("y_true", y_true, "sample_weight", weights)?;
+    }
+    Ok(vec![(0..y_true.len()).collect()])
+}
+
+/// Bootstrap bounds for every binary classification metric.
+pub fn bootstrap_classification(
+    y_true: &[f32],
+    y_pred: &[f32],
+    sample_weights: Option<&[f32]>,
+    config: BootstrapConfig,
+) -> Result<ConfidenceIntervals, FairMlError> {
+    let strata = performance_strata(y_true, y_pred, sample_weights)?;
+    percentile_bootstrap(&strata, config, |rows| {
+        let (y_true, y_pred, weights) = resample_rows(rows, y_true, y_pred, sample_weights);
+        let report = BinaryClassificationReport::from(ClassificationPerf::from_vecs(
+            y_true, y_pred, weights,
+        )?);
+        Ok(owned_entries(report.entries()))
+    })
+}
+
+/// Bootstrap bounds for every logistic regression metric.
+pub fn bootstrap_logistic_regression(
+    y_true: &[f32],
+    y_proba: &[f32],
+    threshold: f32,
+    sample_weights: Option<&[f32]>,
+    config: BootstrapConfig,
+) -> Result<ConfidenceIntervals, FairMlError> {
+    let strata = performance_strata(y_true, y_proba, sample_weights)?;
+    percentile_bootstrap(&strata, config, |rows| {
+        let (y_true, y_proba, weights) = resample_rows(rows, y_true, y_proba, sample_weights);
+        let report = LogisticRegressionReport::from(LogisticRegressionPerf::from_vecs(
+            y_true, y_proba, threshold, weights,
+        )?);
+        Ok(owned_entries(report.entries()))
+    })
+}
+
+/// Bootstrap bounds for every linear regression metric.
+pub fn bootstrap_linear_regression(
+    y_true: &[f32],
+    y_pred: &[f32],
+    sample_weights: Option<&[f32]>,
+    config: BootstrapConfig,
+) -> Result<ConfidenceIntervals, FairMlError> {
+    let strata = performance_strata(y_true, y_pred, sample_weights)?;
+    percentile_bootstrap(&strata, config, |rows| {
+        let (y_true, y_pred, weights) = resample_rows(rows, y_true, y_pred, sample_weights);
+        let report =
+            LinearRegressionReport::from(LinearRegressionPerf::from_vecs(y_true, y_pred, weights)?);
+        Ok(owned_entries(report.entries()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(seed: u64) -> BootstrapConfig {
+        BootstrapConfig {
+            resamples: 200,
+            confidence_level: 0.9,
+            seed,
+        }
+    }
+
+    /// Facet a has 5 positives out of 10 rows, facet d a single positive out
+    /// of 5, so resamples of facet d often miss it.
+    fn rows() -> (Vec<i16>, Vec<i16>) {
+        let feature = [vec![1; 10], vec![0; 5]].concat();
+        let ground_truth = [[1, 0].repeat(5), vec![1, 0, 0, 0, 0]].concat();
+        (feature, ground_truth)
+    }
+
+    #[test]
+    fn quantiles_interpolate_between_ranks() {
+        let sorted = [1_f32, 2_f32, 3_f32, 4_f32];
+        assert_eq!(quantile(&sorted, 0_f64), 1_f32);
+        assert_eq!(quantile(&sorted, 0.5), 2.5);
+        assert_eq!(quantile(&sorted, 1_f64), 4_f32);
+    }
+
+    #[test]
+    fn bounds_are_seeded_and_keep_the_facet_sizes() {
+        let (feature, ground_truth) = rows();
+        let registry = DataBiasRegistry::new();
+        let bounds = |seed| {
+            bootstrap_data_bias(&registry, &feature, &ground_truth, None, config(seed)).unwrap()
+        };
+        let intervals = bounds(7);
+        assert_eq!(intervals, bounds(7));
+        assert_ne!(intervals, bounds(8));
+        let names: Vec<&str> = intervals.keys().map(String::as_str).collect();
+        let mut expected = registry.names();
+        expected.sort_unstable();
+        assert_eq!(names, expected);
+        assert!(intervals
+            .values()
+            .filter(|interval| interval.dropped_resamples == 0)
+            .all(|interval| interval.lower <= interval.upper));
+        // every resample holds 10 rows of facet a and 5 of facet d
+        let class_imbalance = &intervals["ClassImbalance"];
+        assert_eq!(class_imbalance.lower, 1_f32 / 3_f32);
+        assert_eq!(class_imbalance.upper, 1_f32 / 3_f32);
+        assert_eq!(class_imbalance.status, MetricStatus::Ok);
+        let kl = &intervals["KlDivergence"];
+        assert!(kl.dropped_resamples > 0);
+        assert!(matches!(kl.status, MetricStatus::Undefined(_)));
+    }
+
+    #[test]
+    fn perfect_predictions_have_no_spread() {
+        let y = [1_f32, 0_f32, 1_f32, 1_f32, 0_f32, 0_f32];
+        let intervals = bootstrap_classification(&y, &y, None, config(0)).unwrap();
+        assert_eq!(intervals["Accuracy"].lower, 1_f32);
+        assert_eq!(intervals["Accuracy"].upper, 1_f32);
+    }
+
+    #[test]
+    fn invalid_settings_are_errors() {
+        let (feature, ground_truth) = rows();
+        let registry = DataBiasRegistry::new();
+        for config in [
+            BootstrapConfig {
+                resamples: 1,
+                ..config(0)
+            },
+            BootstrapConfig {
+                confidence_level: 1_f64,
+                ..config(0)
+            },
+        ] {
+            assert!(matches!(
+                bootstrap_data_bias(&registry, &feature, &ground_truth, None, config),
+                Err(FairMlError::InvalidBootstrapConfig { .. })
+            ));
+        }
+    }
+}
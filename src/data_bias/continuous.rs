@@ -0,0 +1,163 @@
+use crate::data_handler::MissingCounts;
+use rayon::prelude::*;
+use serde::Serialize;
+
+/// Raw outcome values of each facet, for a continuous outcome such as a loan
+/// amount or a salary that is compared without a threshold.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContinuousPreTraining {
+    pub facet_a: Vec<f64>,
+    pub facet_d: Vec<f64>,
+}
+
+/// Distribution bias between the outcome values of facet a and facet d.
+/// Differences are facet a minus facet d, like the difference in proportion
+/// of labels.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ContinuousDataBiasReport {
+    #[serde(rename = "DifferenceInMeans")]
+    pub difference_in_means: f32,
+    #[serde(rename = "DifferenceInMedians")]
+    pub difference_in_medians: f32,
+    /// Two sample Kolmogorov-Smirnov statistic, the largest gap between the
+    /// empirical CDFs.
+    #[serde(rename = "KolmorogvSmirnov")]
+    pub kolmorogv_smirnov: f32,
+    /// Asymptotic two sided p-value of `kolmorogv_smirnov`.
+    #[serde(rename = "KolmorogvSmirnovPValue")]
+    pub kolmorogv_smirnov_p_value: f32,
+    /// 1-Wasserstein distance, the area between the empirical CDFs, in the
+    /// unit of the outcome.
+    #[serde(rename = "WassersteinDistance")]
+    pub wasserstein_distance: f32,
+    /// Two sample Anderson-Darling statistic A2akN of Scholz and Stephens,
+    /// with midranks for ties. It is not standardized, two samples from the
+    /// same distribution average around 1.
+    #[serde(rename = "AndersonDarling")]
+    pub anderson_darling: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missing: Option<MissingCounts>,
+}
+
+/// One distinct value of the pooled outcomes, with how many rows of each
+/// facet hold it and how many hold a smaller value.
+struct Step {
+    value: f64,
+    a_equal: f64,
+    d_equal: f64,
+    a_below: f64,
+    d_below: f64,
+}
+
+/// Walks both sorted facets at once, one step per distinct value.
+fn steps(facet_a: &[f64], facet_d: &[f64]) -> Vec<Step> {
+    let (mut i, mut j) = (0, 0);
+    let mut steps = Vec::new();
+    while i < facet_a.len() || j < facet_d.len() {
+        let value = match (facet_a.get(i), facet_d.get(j)) {
+            (Some(a), Some(d)) => a.min(*d),
+            (Some(a), None) => *a,
+            (None, Some(d)) => *d,
+            (None, None) => unreachable!(),
+        };
+        let (a_below, d_below) = (i, j);
+        while facet_a.get(i) == Some(&value) {
+            i += 1;
+        }
+        while facet_d.get(j) == Some(&value) {
+            j += 1;
+        }
+        steps.push(Step {
+            value,
+            a_equal: (i - a_below) as f64,
+            d_equal: (j - d_below) as f64,
+            a_below: a_below as f64,
+            d_below: d_below as f64,
+        });
+    }
+    steps
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let middle = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        0.5 * (sorted[middle - 1] + sorted[middle])
+    } else {
+        sorted[middle]
+    }
+}
+
+/// Kolmogorov's limiting distribution with Stephens' small sample
+/// correction, Q(lambda) = 2 sum (-1)^(k-1) exp(-2 k^2 lambda^2). The series
+/// does not converge for tiny lambda, where the p-value is 1.
+fn kolmogorov_p_value(statistic: f64, n_a: f64, n_d: f64) -> f64 {
+    let effective = (n_a * n_d / (n_a + n_d)).sqrt();
+    let lambda = (effective + 0.12 + 0.11 / effective) * statistic;
+    let exponent = -2_f64 * lambda * lambda;
+    let (mut sign, mut sum, mut previous) = (2_f64, 0_f64, 0_f64);
+    for k in 1..=100 {
+        let k = f64::from(k);
+        let term = sign * (exponent * k * k).exp();
+        sum += term;
+        if term.abs() <= 1e-3 * previous || term.abs() <= 1e-8 * sum {
+            return sum.clamp(0_f64, 1_f64);
+        }
+        sign = -sign;
+        previous = term.abs();
+    }
+    1_f64
+}
+
+fn anderson_darling(steps: &[Step], n_a: f64, n_d: f64) -> f64 {
+    let n = n_a + n_d;
+    let mut statistic = 0_f64;
+    for step in steps {
+        let ties = step.a_equal + step.d_equal;
+        let pooled = step.a_below + step.d_below + ties / 2_f64;
+        let denominator = pooled * (n - pooled) - n * ties / 4_f64;
+        // only when every value is the same, the facets do not differ
+        if denominator <= 0_f64 {
+            continue;
+        }
+        let a_rank = step.a_below + step.a_equal / 2_f64;
+        let d_rank = step.d_below + step.d_equal / 2_f64;
+        statistic += ties
+            * ((n * a_rank - n_a * pooled).powi(2) / n_a
+                + (n * d_rank - n_d * pooled).powi(2) / n_d)
+            / denominator;
+    }
+    statistic * (n - 1_f64) / (n * n)
+}
+
+/// Compares the outcome distributions of both facets. Each facet needs at
+/// least one value, see `perform_segmentation_data_bias_continuous`.
+pub fn continuous_pre_training_bias(mut data: ContinuousPreTraining) -> ContinuousDataBiasReport {
+    data.facet_a.par_sort_unstable_by(f64::total_cmp);
+    data.facet_d.par_sort_unstable_by(f64::total_cmp);
+    let (facet_a, facet_d) = (&data.facet_a, &data.facet_d);
+    let (n_a, n_d) = (facet_a.len() as f64, facet_d.len() as f64);
+    let steps = steps(facet_a, facet_d);
+
+    let mut kolmorogv_smirnov = 0_f64;
+    let mut wasserstein_distance = 0_f64;
+    for (step, next) in steps.iter().zip(steps.iter().skip(1)) {
+        let cdf_gap =
+            ((step.a_below + step.a_equal) / n_a - (step.d_below + step.d_equal) / n_d).abs();
+        kolmorogv_smirnov = kolmorogv_smirnov.max(cdf_gap);
+        wasserstein_distance += cdf_gap * (next.value - step.value);
+    }
+
+    ContinuousDataBiasReport {
+        difference_in_means: (mean(facet_a) - mean(facet_d)) as f32,
+        difference_in_medians: (median(facet_a) - median(facet_d)) as f32,
+        kolmorogv_smirnov: kolmorogv_smirnov as f32,
+        kolmorogv_smirnov_p_value: kolmogorov_p_value(kolmorogv_smirnov, n_a, n_d) as f32,
+        wasserstein_distance: wasserstein_distance as f32,
+        anderson_darling: anderson_darling(&steps, n_a, n_d) as f32,
+        missing: None,
+    }
+}
@@ -1,61 +1,115 @@
+use crate::bootstrap::ConfidenceIntervals;
+use crate::data_handler::{
+    check_facets, check_lengths, count_facets_data_bias, effective_size, retain_rows,
+    EffectiveSampleSize, MissingCounts,
+};
+use crate::errors::FairMlError;
+use crate::metric_report;
+use crate::multi_group::{encode_groups, ConditionalDisparity};
+use crate::runtime::{canonical_name, compare_to_baseline, get_metric, CheckStatuses, Direction};
+use crate::significance::Significance;
+use crate::state::AccumulatorState;
+use crate::status::{metric_status, Facet, MetricStatuses, SupportThresholds};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::error::Error;
-
-pub enum DataBiasMetrics {
-    ClassImbalance,
-    DifferenceInProportionOfLabels,
-    KlDivergence,
-    JsDivergence,
-    LpNorm,
-    TotalVariationDistance,
-    KolmorogvSmirnov,
-}
-
-pub const FULL_DATA_BIAS_METRICS: [DataBiasMetrics; 7] = [
-    DataBiasMetrics::ClassImbalance,
-    DataBiasMetrics::DifferenceInProportionOfLabels,
-    DataBiasMetrics::KlDivergence,
-    DataBiasMetrics::JsDivergence,
-    DataBiasMetrics::LpNorm,
-    DataBiasMetrics::TotalVariationDistance,
-    DataBiasMetrics::KolmorogvSmirnov,
-];
+use std::sync::Arc;
 
-impl TryFrom<&str> for DataBiasMetrics {
-    type Error = String;
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "ClassImbalance" => Ok(Self::ClassImbalance),
-            "DifferenceInProportionOfLabels" => Ok(Self::DifferenceInProportionOfLabels),
-            "KlDivergence" => Ok(Self::KlDivergence),
-            "JsDivergence" => Ok(Self::JsDivergence),
-            "LpNorm" => Ok(Self::LpNorm),
-            "TotalVariationDistance" => Ok(Self::TotalVariationDistance),
-            "KolmorogvSmirnov" => Ok(Self::KolmorogvSmirnov),
-            _ => Err("Invalid metric name".into()),
-        }
+mod continuous;
+pub use continuous::{
+    continuous_pre_training_bias, ContinuousDataBiasReport, ContinuousPreTraining,
+};
+
+/// Label counts for one facet, in rows. `weighted` holds the same counts with
+/// each row adding its sample weight, the totals the metrics are computed
+/// from, and equals the row counts when unweighted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FacetLabels {
+    pub positives: u64,
+    pub negatives: u64,
+    pub weighted: WeightedLabels,
+    /// Sum of the squared row weights, for the effective sample size.
+    pub weight_squares: f64,
+}
+
+/// Sample weight totals of the positive and negative rows of a facet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeightedLabels {
+    pub positives: f64,
+    pub negatives: f64,
+}
+
+impl WeightedLabels {
+    pub fn len(&self) -> f64 {
+        self.positives + self.negatives
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() <= 0_f64
     }
 }
 
-pub fn map_string_to_metric(metrics: Vec<String>) -> Result<Vec<DataBiasMetrics>, Box<dyn Error>> {
-    let mut map: Vec<DataBiasMetrics> = Vec::with_capacity(metrics.len());
-    for m_str in metrics.iter() {
-        let m = DataBiasMetrics::try_from(m_str.as_str())?;
-        map.push(m);
+impl FacetLabels {
+    pub fn record(&mut self, ground_truth: i16) {
+        self.record_weighted(ground_truth, 1_f64);
     }
 
-    Ok(map)
+    pub fn record_weighted(&mut self, ground_truth: i16, weight: f64) {
+        if ground_truth == 1 {
+            self.positives += 1;
+            self.weighted.positives += weight;
+        } else {
+            self.negatives += 1;
+            self.weighted.negatives += weight;
+        }
+        self.weight_squares += weight * weight;
+    }
+
+    pub fn merge(self, other: FacetLabels) -> FacetLabels {
+        FacetLabels {
+            positives: self.positives + other.positives,
+            negatives: self.negatives + other.negatives,
+            weighted: WeightedLabels {
+                positives: self.weighted.positives + other.weighted.positives,
+                negatives: self.weighted.negatives + other.weighted.negatives,
+            },
+            weight_squares: self.weight_squares + other.weight_squares,
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.positives + self.negatives
+    }
+
+    /// Whether the facet has no weight to compute a rate from, which
+    /// includes a facet whose rows all weigh 0.
+    pub fn is_empty(&self) -> bool {
+        self.weighted.is_empty()
+    }
+
+    /// Kish effective sample size, (sum of weights)^2 / sum of squared weights.
+    pub fn effective_size(&self) -> f64 {
+        effective_size(self.weighted.len(), self.weight_squares)
+    }
 }
 
 pub struct PreTraining {
-    pub facet_a: Vec<i16>,
-    pub facet_d: Vec<i16>,
+    pub facet_a: FacetLabels,
+    pub facet_d: FacetLabels,
 }
 
 impl PreTraining {
+    pub fn effective_sample_size(&self) -> EffectiveSampleSize {
+        EffectiveSampleSize {
+            facet_a: self.facet_a.effective_size(),
+            facet_d: self.facet_d.effective_size(),
+        }
+    }
+
     pub fn generate(&self) -> PreTrainingComputations {
-        let a_acceptance: f32 = self.facet_a.iter().sum::<i16>() as f32 / self.facet_a.len() as f32;
-        let d_acceptance: f32 = self.facet_d.iter().sum::<i16>() as f32 / self.facet_d.len() as f32;
+        let a_acceptance: f32 =
+            self.facet_a.weighted.positives as f32 / self.facet_a.weighted.len() as f32;
+        let d_acceptance: f32 =
+            self.facet_d.weighted.positives as f32 / self.facet_d.weighted.len() as f32;
         PreTrainingComputations {
             a_acceptance,
             d_acceptance,
@@ -63,108 +117,744 @@ impl PreTraining {
     }
 }
 
+/// A label count of one facet that a built in metric divides by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LabelCount {
+    Rows,
+    Positives,
+    Negatives,
+}
+
+impl LabelCount {
+    fn name(self) -> &'static str {
+        match self {
+            LabelCount::Rows => "rows",
+            LabelCount::Positives => "positives",
+            LabelCount::Negatives => "negatives",
+        }
+    }
+
+    fn of(self, facet: &FacetLabels) -> f64 {
+        match self {
+            LabelCount::Rows => facet.weighted.len(),
+            LabelCount::Positives => facet.weighted.positives,
+            LabelCount::Negatives => facet.weighted.negatives,
+        }
+    }
+}
+
+const FACET_ROWS: &[(Facet, LabelCount)] =
+    &[(Facet::A, LabelCount::Rows), (Facet::D, LabelCount::Rows)];
+
+/// The log ratios of the divergences need both outcomes in both facets.
+const FACET_OUTCOMES: &[(Facet, LabelCount)] = &[
+    (Facet::A, LabelCount::Positives),
+    (Facet::A, LabelCount::Negatives),
+    (Facet::D, LabelCount::Positives),
+    (Facet::D, LabelCount::Negatives),
+];
+
 pub struct PreTrainingComputations {
     pub a_acceptance: f32,
     pub d_acceptance: f32,
 }
 
 pub fn class_imbalance(data: &PreTraining) -> f32 {
-    return (data.facet_a.len() as f32 - data.facet_d.len() as f32).abs() as f32
-        / (data.facet_a.len() + data.facet_d.len()) as f32;
+    (data.facet_a.weighted.len() as f32 - data.facet_d.weighted.len() as f32).abs()
+        / (data.facet_a.weighted.len() + data.facet_d.weighted.len()) as f32
 }
 
 pub fn diff_in_proportion_of_labels(data: &PreTraining) -> f32 {
-    let q_a: f32 = data.facet_a.iter().sum::<i16>() as f32 / data.facet_a.len() as f32;
-    let q_d: f32 = data.facet_d.iter().sum::<i16>() as f32 / data.facet_d.len() as f32;
+    let q_a: f32 = data.facet_a.weighted.positives as f32 / data.facet_a.weighted.len() as f32;
+    let q_d: f32 = data.facet_d.weighted.positives as f32 / data.facet_d.weighted.len() as f32;
 
-    return q_a - q_d;
+    q_a - q_d
 }
 
 pub fn kl_divergence(data: &PreTrainingComputations) -> f32 {
-    return data.a_acceptance * (data.a_acceptance / data.d_acceptance).ln()
+    data.a_acceptance * (data.a_acceptance / data.d_acceptance).ln()
         + (1.0_f32 - data.a_acceptance)
-            * ((1.0_f32 - data.a_acceptance) / (1.0_f32 - data.d_acceptance)).ln();
+            * ((1.0_f32 - data.a_acceptance) / (1.0_f32 - data.d_acceptance)).ln()
 }
 
 fn ks_kl_div(p_facet: f32, p: f32) -> f32 {
-    return p_facet * (p_facet / p).ln()
-        + (1.0_f32 - p_facet) * ((1.0_f32 - p_facet) / (1.0_f32 - p)).ln();
+    p_facet * (p_facet / p).ln() + (1.0_f32 - p_facet) * ((1.0_f32 - p_facet) / (1.0_f32 - p)).ln()
 }
 
 pub fn jensen_shannon(data: &PreTraining, pre_comp: &PreTrainingComputations) -> f32 {
     let p: f32 = 0.5_f32
-        * (data.facet_a.iter().sum::<i16>() as f32 / data.facet_d.len() as f32
-            + data.facet_d.iter().sum::<i16>() as f32 / data.facet_a.len() as f32);
+        * (data.facet_a.weighted.positives as f32 / data.facet_d.weighted.len() as f32
+            + data.facet_d.weighted.positives as f32 / data.facet_a.weighted.len() as f32);
 
-    return 0.5 * (ks_kl_div(pre_comp.a_acceptance, p) + ks_kl_div(pre_comp.d_acceptance, p));
+    0.5 * (ks_kl_div(pre_comp.a_acceptance, p) + ks_kl_div(pre_comp.d_acceptance, p))
 }
 
 pub fn lp_norm(data: &PreTrainingComputations) -> f32 {
-    return ((data.a_acceptance - data.d_acceptance).powf(2.0)
+    ((data.a_acceptance - data.d_acceptance).powf(2.0)
         + (1.0_f32 - data.a_acceptance - 1.0_f32 - data.d_acceptance).powf(2.0))
-    .sqrt();
+    .sqrt()
 }
 
 pub fn total_variation_distance(data: &PreTrainingComputations) -> f32 {
-    return (data.a_acceptance - data.d_acceptance).abs()
-        + ((1.0_f32 - data.a_acceptance) - (1.0_f32 - data.a_acceptance)).abs();
+    // the negative outcome term mirrors the positive one for a binary label
+    (data.a_acceptance - data.d_acceptance).abs()
 }
 
 pub fn kolmorogv_smirnov(data: &PreTraining) -> f32 {
-    let a_0_dist: f32 = data
-        .facet_a
-        .iter()
-        .map(|value| if *value == 0 { 1.0_f32 } else { 0.0_f32 })
-        .sum::<f32>()
-        / data.facet_a.len() as f32;
-
-    let a_1_dist = data
-        .facet_a
-        .iter()
-        .map(|value| if *value == 1 { 1.0_f32 } else { 0.0_f32 })
-        .sum::<f32>()
-        / data.facet_a.len() as f32;
-
-    let d_0_dist = data
-        .facet_d
-        .iter()
-        .map(|value| if *value == 0 { 1.0_f32 } else { 0.0_f32 })
-        .sum::<f32>()
-        / data.facet_d.len() as f32;
-
-    let d_1_dist = data
-        .facet_d
-        .iter()
-        .map(|value| if *value == 1 { 1.0_f32 } else { 0.0_f32 })
-        .sum::<f32>()
-        / data.facet_d.len() as f32;
+    let a_0_dist: f32 = data.facet_a.weighted.negatives as f32 / data.facet_a.weighted.len() as f32;
+    let a_1_dist = data.facet_a.weighted.positives as f32 / data.facet_a.weighted.len() as f32;
+    let d_0_dist = data.facet_d.weighted.negatives as f32 / data.facet_d.weighted.len() as f32;
+    let d_1_dist = data.facet_d.weighted.positives as f32 / data.facet_d.weighted.len() as f32;
 
     let neg_outcome_diff = (a_0_dist - d_0_dist).abs();
     let pos_outcome_diff = (a_1_dist - d_1_dist).abs();
 
     if neg_outcome_diff > pos_outcome_diff {
-        return pos_outcome_diff;
+        pos_outcome_diff
     } else {
-        return neg_outcome_diff;
-    }
-}
-
-pub fn pre_training_bias(data: PreTraining) -> Result<HashMap<String, f32>, String> {
-    let computed_data: PreTrainingComputations = data.generate();
-    let mut result: HashMap<String, f32> = HashMap::with_capacity(7);
-    result.insert("ClassImbalance".into(), class_imbalance(&data));
-    result.insert(
-        "DifferenceInProportionOfLabels".into(),
-        diff_in_proportion_of_labels(&data),
-    );
-    result.insert("KlDivergence".into(), kl_divergence(&computed_data));
-    result.insert("JsDivergence".into(), jensen_shannon(&data, &computed_data));
-    result.insert("LpNorm".into(), lp_norm(&computed_data));
-    result.insert(
-        "TotalVarationDistance".into(),
-        total_variation_distance(&computed_data),
-    );
-    result.insert("KolmorogvSmirnov".into(), kolmorogv_smirnov(&data));
-
-    Ok(result)
+        neg_outcome_diff
+    }
+}
+
+/// Label counts of both facets over every category of a categorical label,
+/// e.g. a 5 point credit grade. Categories are sorted by value, which is the
+/// order the Kolmogorov-Smirnov distance accumulates them in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoricalLabels {
+    pub categories: Vec<String>,
+    pub facet_a: Vec<u64>,
+    pub facet_d: Vec<u64>,
+}
+
+/// Settings of the categorical divergences. `epsilon` is added to the share
+/// of every category before the shares are renormalized, so a category one
+/// facet never saw no longer sends the KL divergence to infinity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DivergenceOptions {
+    pub lp_order: f64,
+    pub epsilon: f64,
+}
+
+impl Default for DivergenceOptions {
+    fn default() -> Self {
+        DivergenceOptions {
+            lp_order: 2_f64,
+            epsilon: 0_f64,
+        }
+    }
+}
+
+impl DivergenceOptions {
+    fn validate(&self) -> Result<(), FairMlError> {
+        if !self.lp_order.is_finite() || self.lp_order < 1_f64 {
+            return Err(FairMlError::InvalidDivergenceOptions {
+                reason: format!("lp_order must be at least 1, got {}", self.lp_order),
+            });
+        }
+        if !self.epsilon.is_finite() || self.epsilon < 0_f64 {
+            return Err(FairMlError::InvalidDivergenceOptions {
+                reason: format!("epsilon must not be negative, got {}", self.epsilon),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// The divergences of the data bias report over a categorical label, keyed
+/// like the report.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CategoricalDivergences {
+    #[serde(rename = "KlDivergence")]
+    pub kl_divergence: f32,
+    #[serde(rename = "JsDivergence")]
+    pub js_divergence: f32,
+    #[serde(rename = "LpNorm")]
+    pub lp_norm: f32,
+    #[serde(rename = "TotalVariationDistance")]
+    pub total_variation_distance: f32,
+    #[serde(rename = "KolmorogvSmirnov")]
+    pub kolmorogv_smirnov: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoricalDivergenceReport {
+    pub categories: Vec<String>,
+    pub divergences: CategoricalDivergences,
+}
+
+impl CategoricalLabels {
+    /// Counts the label categories of each facet, facet a being the rows
+    /// whose labeled feature is 1 and facet d the rows where it is 0. Rows in
+    /// neither facet are skipped, and so is a category only they hold.
+    pub fn count<T>(feature_values: &[i16], label_values: &[T]) -> Result<Self, FairMlError>
+    where
+        T: Ord + ToString,
+    {
+        check_lengths("feature", feature_values, "ground_truth", label_values)?;
+        let (mut categories, codes) = encode_groups(label_values);
+        let mut facet_a = vec![0_u64; categories.len()];
+        let mut facet_d = vec![0_u64; categories.len()];
+        for (feature, code) in feature_values.iter().zip(codes) {
+            match *feature {
+                1_i16 => facet_a[code] += 1,
+                0_i16 => facet_d[code] += 1,
+                // a missing feature kept as its own group belongs to neither facet
+                _ => {}
+            }
+        }
+        let counted: Vec<bool> = facet_a
+            .iter()
+            .zip(&facet_d)
+            .map(|(a, d)| a + d > 0)
+            .collect();
+        retain_rows(&mut categories, &counted);
+        retain_rows(&mut facet_a, &counted);
+        retain_rows(&mut facet_d, &counted);
+        Ok(CategoricalLabels {
+            categories,
+            facet_a,
+            facet_d,
+        })
+    }
+
+    /// KL, JS, Lp, total variation and Kolmogorov-Smirnov between the label
+    /// distributions of facet a and facet d, from the shares of every
+    /// category whatever their number, two included. Kolmogorov-Smirnov
+    /// accumulates the categories in sorted order.
+    pub fn divergences(
+        self,
+        options: DivergenceOptions,
+    ) -> Result<CategoricalDivergenceReport, FairMlError> {
+        options.validate()?;
+        let facet_a = smooth(&self.facet_a, options.epsilon);
+        let facet_d = smooth(&self.facet_d, options.epsilon);
+        let a_len: f64 = facet_a.iter().sum();
+        let d_len: f64 = facet_d.iter().sum();
+        check_facets(a_len <= 0_f64, d_len <= 0_f64)?;
+
+        let p_a: Vec<f64> = facet_a.iter().map(|count| count / a_len).collect();
+        let p_d: Vec<f64> = facet_d.iter().map(|count| count / d_len).collect();
+        let mixture: Vec<f64> = p_a.iter().zip(&p_d).map(|(a, d)| 0.5 * (a + d)).collect();
+        let lp: f64 = p_a
+            .iter()
+            .zip(&p_d)
+            .map(|(a, d)| (a - d).abs().powf(options.lp_order))
+            .sum();
+        let (mut cdf_a, mut cdf_d, mut ks) = (0_f64, 0_f64, 0_f64);
+        for (a, d) in p_a.iter().zip(&p_d) {
+            cdf_a += a;
+            cdf_d += d;
+            ks = ks.max((cdf_a - cdf_d).abs());
+        }
+        let divergences = CategoricalDivergences {
+            kl_divergence: categorical_kl(&p_a, &p_d) as f32,
+            js_divergence: (0.5 * (categorical_kl(&p_a, &mixture) + categorical_kl(&p_d, &mixture)))
+                as f32,
+            lp_norm: lp.powf(options.lp_order.recip()) as f32,
+            total_variation_distance: (0.5
+                * p_a
+                    .iter()
+                    .zip(&p_d)
+                    .map(|(a, d)| (a - d).abs())
+                    .sum::<f64>()) as f32,
+            kolmorogv_smirnov: ks as f32,
+        };
+        Ok(CategoricalDivergenceReport {
+            categories: self.categories,
+            divergences,
+        })
+    }
+}
+
+/// Adds `epsilon` times the facet size to every count, the counts are left
+/// as they are when `epsilon` is 0.
+fn smooth(counts: &[u64], epsilon: f64) -> Vec<f64> {
+    let total = counts.iter().sum::<u64>() as f64;
+    counts
+        .iter()
+        .map(|count| *count as f64 + epsilon * total)
+        .collect()
+}
+
+/// KL(p || q), a category p never takes adds nothing.
+fn categorical_kl(p: &[f64], q: &[f64]) -> f64 {
+    p.iter()
+        .zip(q)
+        .filter(|(p, _)| **p > 0_f64)
+        .map(|(p, q)| p * (p / q).ln())
+        .sum()
+}
+
+metric_report! {
+    /// Data bias metrics for one feature, followed by any registered metrics.
+    pub struct DataBiasReport {
+        class_imbalance: CLASS_IMBALANCE = "ClassImbalance",
+        difference_in_proportion_of_labels: DIFFERENCE_IN_PROPORTION_OF_LABELS =
+            "DifferenceInProportionOfLabels",
+        kl_divergence: KL_DIVERGENCE = "KlDivergence",
+        js_divergence: JS_DIVERGENCE = "JsDivergence",
+        lp_norm: LP_NORM = "LpNorm",
+        total_variation_distance: TOTAL_VARIATION_DISTANCE = "TotalVariationDistance",
+        kolmorogv_smirnov: KOLMOROGV_SMIRNOV = "KolmorogvSmirnov",
+        ..custom,
+        ?missing: MissingCounts = "missing",
+        ?effective_sample_size: EffectiveSampleSize = "effectiveSampleSize",
+        ?confidence_intervals: ConfidenceIntervals = "confidenceIntervals",
+        ?significance: Significance = "significance",
+        ?status: MetricStatuses = "status",
+        ?conditional_disparity: ConditionalDisparity = "conditionalDemographicDisparity",
+    }
+}
+
+/// A data bias metric computed from the per facet label counts. Built in and
+/// user defined metrics all go through `DataBiasRegistry`.
+pub trait DataBiasMetric: Send + Sync {
+    /// Key of the metric in reports and baselines.
+    fn name(&self) -> &str;
+
+    fn compute(
+        &self,
+        data: &PreTraining,
+        computed: &PreTrainingComputations,
+    ) -> Result<f32, FairMlError>;
+
+    /// How a runtime value is compared against the baseline.
+    fn direction(&self) -> Direction {
+        Direction::Magnitude
+    }
+
+    /// Value the metric takes when the facets are treated identically.
+    fn ideal_value(&self) -> f32 {
+        0_f32
+    }
+
+    /// Named counts the metric divides by, e.g. "positives of facet d". A
+    /// zero count leaves the metric undefined, see `MetricStatus`.
+    fn denominators(&self, _data: &PreTraining) -> Vec<(String, f64)> {
+        Vec::new()
+    }
+}
+
+type PreTrainingMetric = fn(&PreTraining, &PreTrainingComputations) -> f32;
+
+struct BuiltinDataBiasMetric {
+    name: &'static str,
+    compute: PreTrainingMetric,
+    direction: Direction,
+    denominators: &'static [(Facet, LabelCount)],
+}
+
+impl DataBiasMetric for BuiltinDataBiasMetric {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn compute(
+        &self,
+        data: &PreTraining,
+        computed: &PreTrainingComputations,
+    ) -> Result<f32, FairMlError> {
+        Ok((self.compute)(data, computed))
+    }
+
+    fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn denominators(&self, data: &PreTraining) -> Vec<(String, f64)> {
+        self.denominators
+            .iter()
+            .map(|(facet, count)| {
+                (
+                    format!("{} of {}", count.name(), facet.name()),
+                    count.of(facet.pick(&data.facet_a, &data.facet_d)),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The set of metrics used for data bias analysis and runtime checks, in
+/// report order.
+#[derive(Clone)]
+pub struct DataBiasRegistry {
+    metrics: Vec<Arc<dyn DataBiasMetric>>,
+}
+
+impl Default for DataBiasRegistry {
+    fn default() -> DataBiasRegistry {
+        type R = DataBiasReport;
+        type Denominators = &'static [(Facet, LabelCount)];
+        let builtin: [(&'static str, Direction, PreTrainingMetric, Denominators); 7] = [
+            (
+                R::CLASS_IMBALANCE,
+                Direction::Magnitude,
+                |data, _| class_imbalance(data),
+                &[],
+            ),
+            (
+                R::DIFFERENCE_IN_PROPORTION_OF_LABELS,
+                Direction::Magnitude,
+                |data, _| diff_in_proportion_of_labels(data),
+                FACET_ROWS,
+            ),
+            (
+                R::KL_DIVERGENCE,
+                Direction::Increase,
+                |_, computed| kl_divergence(computed),
+                FACET_OUTCOMES,
+            ),
+            (
+                R::JS_DIVERGENCE,
+                Direction::Increase,
+                jensen_shannon,
+                FACET_OUTCOMES,
+            ),
+            (
+                R::LP_NORM,
+                Direction::Increase,
+                |_, computed| lp_norm(computed),
+                FACET_ROWS,
+            ),
+            (
+                R::TOTAL_VARIATION_DISTANCE,
+                Direction::Increase,
+                |_, computed| total_variation_distance(computed),
+                FACET_ROWS,
+            ),
+            (
+                R::KOLMOROGV_SMIRNOV,
+                Direction::Increase,
+                |data, _| kolmorogv_smirnov(data),
+                FACET_ROWS,
+            ),
+        ];
+        DataBiasRegistry {
+            metrics: builtin
+                .into_iter()
+                .map(|(name, direction, compute, denominators)| {
+                    Arc::new(BuiltinDataBiasMetric {
+                        name,
+                        compute,
+                        direction,
+                        denominators,
+                    }) as Arc<dyn DataBiasMetric>
+                })
+                .collect(),
+        }
+    }
+}
+
+impl DataBiasRegistry {
+    /// Registry holding the built in metrics.
+    pub fn new() -> DataBiasRegistry {
+        DataBiasRegistry::default()
+    }
+
+    pub fn register(&mut self, metric: Arc<dyn DataBiasMetric>) -> Result<(), FairMlError> {
+        if self.get(metric.name()).is_ok() {
+            return Err(FairMlError::DuplicateMetricName {
+                name: metric.name().into(),
+            });
+        }
+        self.metrics.push(metric);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Arc<dyn DataBiasMetric>, FairMlError> {
+        let name = canonical_name(name).unwrap_or(name);
+        self.metrics
+            .iter()
+            .find(|metric| metric.name() == name)
+            .ok_or_else(|| FairMlError::invalid_metric(name))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.metrics.iter().map(|metric| metric.name()).collect()
+    }
+
+    pub fn analyze(&self, data: &PreTraining) -> Result<DataBiasReport, FairMlError> {
+        let computed_data: PreTrainingComputations = data.generate();
+        let values = self
+            .metrics
+            .iter()
+            .map(|metric| {
+                Ok((
+                    metric.name().to_string(),
+                    metric.compute(data, &computed_data)?,
+                ))
+            })
+            .collect::<Result<HashMap<String, f32>, FairMlError>>()?;
+        DataBiasReport::try_from(values)
+    }
+
+    /// Status of every metric of `report`, computed from `data`.
+    pub fn statuses(
+        &self,
+        data: &PreTraining,
+        report: &DataBiasReport,
+        thresholds: SupportThresholds,
+    ) -> Result<MetricStatuses, FairMlError> {
+        thresholds.validate()?;
+        let values = report.to_map();
+        let facet_sizes = [data.facet_a.weighted.len(), data.facet_d.weighted.len()];
+        self.metrics
+            .iter()
+            .map(|metric| {
+                let value = get_metric(&values, metric.name())?;
+                let status = metric_status(
+                    metric.name(),
+                    value,
+                    facet_sizes,
+                    &metric.denominators(data),
+                    thresholds,
+                );
+                Ok((metric.name().to_string(), status))
+            })
+            .collect()
+    }
+
+    /// Compares `latest` against `baseline` for the named metrics, or for every
+    /// registered metric when `metrics` is `None`. A metric whose status in
+    /// `statuses` is undefined fails the check.
+    pub fn runtime_check(
+        &self,
+        baseline: &HashMap<String, f32>,
+        latest: &HashMap<String, f32>,
+        threshold: f32,
+        metrics: Option<&[String]>,
+        statuses: CheckStatuses<'_>,
+    ) -> Result<HashMap<String, String>, FairMlError> {
+        let selected: Vec<&Arc<dyn DataBiasMetric>> = match metrics {
+            Some(names) => names
+                .iter()
+                .map(|name| self.get(name))
+                .collect::<Result<_, _>>()?,
+            None => self.metrics.iter().collect(),
+        };
+        let mut result: HashMap<String, String> = HashMap::with_capacity(selected.len());
+        for metric in selected {
+            if let Some(report) = compare_to_baseline(
+                metric.name(),
+                metric.direction(),
+                baseline,
+                latest,
+                threshold,
+                statuses,
+            )? {
+                result.insert(metric.name().to_string(), report);
+            }
+        }
+        Ok(result)
+    }
+}
+
+pub fn pre_training_bias(data: PreTraining) -> Result<DataBiasReport, FairMlError> {
+    DataBiasRegistry::new().analyze(&data)
+}
+
+/// Running label counts for data bias over a stream of labeled batches. Only
+/// the per facet counts are kept, so partial accumulators from separate
+/// workers can be merged before computing the report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DataBiasAccumulator {
+    facet_a: FacetLabels,
+    facet_d: FacetLabels,
+    missing: MissingCounts,
+}
+
+impl DataBiasAccumulator {
+    pub fn new() -> DataBiasAccumulator {
+        DataBiasAccumulator::default()
+    }
+
+    /// Adds a batch of labeled rows, the same input `perform_segmentation_data_bias` takes.
+    pub fn update(
+        &mut self,
+        feature_values: &[i16],
+        ground_truth_values: &[i16],
+    ) -> Result<(), FairMlError> {
+        self.update_with(feature_values, ground_truth_values, None)
+    }
+
+    /// Adds a batch of labeled rows, each counted with its sample weight.
+    pub fn update_weighted(
+        &mut self,
+        feature_values: &[i16],
+        ground_truth_values: &[i16],
+        sample_weights: &[f64],
+    ) -> Result<(), FairMlError> {
+        self.update_with(feature_values, ground_truth_values, Some(sample_weights))
+    }
+
+    fn update_with(
+        &mut self,
+        feature_values: &[i16],
+        ground_truth_values: &[i16],
+        sample_weights: Option<&[f64]>,
+    ) -> Result<(), FairMlError> {
+        let batch = count_facets_data_bias(feature_values, ground_truth_values, sample_weights)?;
+        self.facet_a = self.facet_a.merge(batch.facet_a);
+        self.facet_d = self.facet_d.merge(batch.facet_d);
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: &DataBiasAccumulator) {
+        self.facet_a = self.facet_a.merge(other.facet_a);
+        self.facet_d = self.facet_d.merge(other.facet_d);
+        self.missing = self.missing.merge(other.missing);
+    }
+
+    pub fn missing(&self) -> MissingCounts {
+        self.missing
+    }
+
+    /// Adds the missing value counts `apply_missing_policy` returned for a batch.
+    pub fn record_missing(&mut self, missing: MissingCounts) {
+        self.missing = self.missing.merge(missing);
+    }
+
+    pub fn finalize(&self) -> Result<DataBiasReport, FairMlError> {
+        self.finalize_with(&DataBiasRegistry::new())
+    }
+
+    pub fn finalize_with(
+        &self,
+        registry: &DataBiasRegistry,
+    ) -> Result<DataBiasReport, FairMlError> {
+        check_facets(self.facet_a.is_empty(), self.facet_d.is_empty())?;
+        let data = PreTraining {
+            facet_a: self.facet_a,
+            facet_d: self.facet_d,
+        };
+        let mut report = registry.analyze(&data)?;
+        report.missing = Some(self.missing);
+        report.effective_sample_size = Some(data.effective_sample_size());
+        report.status = Some(registry.statuses(&data, &report, SupportThresholds::default())?);
+        Ok(report)
+    }
+}
+
+impl AccumulatorState for DataBiasAccumulator {
+    const KIND: &'static str = "data_bias";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(left: f32, right: f64) -> bool {
+        (f64::from(left) - right).abs() < 1e-6
+    }
+
+    /// Facet a holds 8 positives out of 10 rows, facet d 1 out of 6.
+    fn rows() -> (Vec<i16>, Vec<i16>) {
+        let feature = [vec![1; 10], vec![0; 6]].concat();
+        let ground_truth = [vec![1; 8], vec![0; 2], vec![1], vec![0; 5]].concat();
+        (feature, ground_truth)
+    }
+
+    #[test]
+    fn metrics_match_hand_counts() {
+        let (feature, ground_truth) = rows();
+        let mut acc = DataBiasAccumulator::new();
+        acc.update(&feature, &ground_truth).unwrap();
+        let report = acc.finalize().unwrap();
+        let (q_a, q_d) = (0.8_f64, 1_f64 / 6_f64);
+        let kl = q_a * (q_a / q_d).ln() + (1.0 - q_a) * ((1.0 - q_a) / (1.0 - q_d)).ln();
+        assert!(close(report.class_imbalance, 0.25));
+        assert!(close(report.difference_in_proportion_of_labels, q_a - q_d));
+        assert!(close(report.kl_divergence, kl));
+        assert!(close(report.total_variation_distance, q_a - q_d));
+        assert!(close(report.kolmorogv_smirnov, q_a - q_d));
+        assert_eq!(
+            report.effective_sample_size,
+            Some(EffectiveSampleSize {
+                facet_a: 10_f64,
+                facet_d: 6_f64
+            })
+        );
+    }
+
+    #[test]
+    fn integer_weights_match_repeated_rows() {
+        let (feature, ground_truth) = rows();
+        let weights: Vec<f64> = (0..feature.len()).map(|row| (row % 3 + 1) as f64).collect();
+        let mut weighted = DataBiasAccumulator::new();
+        weighted
+            .update_weighted(&feature, &ground_truth, &weights)
+            .unwrap();
+        let repeat = |values: &[i16]| -> Vec<i16> {
+            values
+                .iter()
+                .zip(&weights)
+                .flat_map(|(value, weight)| std::iter::repeat_n(*value, *weight as usize))
+                .collect()
+        };
+        let mut repeated = DataBiasAccumulator::new();
+        repeated
+            .update(&repeat(&feature), &repeat(&ground_truth))
+            .unwrap();
+        let (weighted, repeated) = (weighted.finalize().unwrap(), repeated.finalize().unwrap());
+        assert_eq!(weighted.entries(), repeated.entries());
+        // repeating rows is less information than as many distinct rows
+        let size = weighted.effective_sample_size.unwrap();
+        assert!(size.facet_a < repeated.effective_sample_size.unwrap().facet_a);
+    }
+
+    #[test]
+    fn merged_batches_match_a_single_pass() {
+        let (feature, ground_truth) = rows();
+        let mut whole = DataBiasAccumulator::new();
+        whole.update(&feature, &ground_truth).unwrap();
+        let (mut first, mut second) = (DataBiasAccumulator::new(), DataBiasAccumulator::new());
+        first.update(&feature[..7], &ground_truth[..7]).unwrap();
+        second.update(&feature[7..], &ground_truth[7..]).unwrap();
+        first.merge(&second);
+        assert_eq!(first.finalize().unwrap(), whole.finalize().unwrap());
+        assert!(matches!(
+            DataBiasAccumulator::new().update(&feature, &ground_truth[1..]),
+            Err(FairMlError::LengthMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn two_categories_follow_the_general_formulas() {
+        let labels = CategoricalLabels {
+            categories: vec!["0".into(), "1".into()],
+            facet_a: vec![3, 1],
+            facet_d: vec![1, 1],
+        };
+        let (p_a, p_d) = ([0.75_f64, 0.25], [0.5_f64, 0.5]);
+        let mixture = [0.625_f64, 0.375];
+        let kl =
+            |p: &[f64], q: &[f64]| -> f64 { p.iter().zip(q).map(|(p, q)| p * (p / q).ln()).sum() };
+        for lp_order in [1_f64, 2_f64, 3_f64] {
+            let options = DivergenceOptions {
+                lp_order,
+                epsilon: 0_f64,
+            };
+            let report = labels.clone().divergences(options).unwrap().divergences;
+            let lp = (2_f64 * 0.25_f64.powf(lp_order)).powf(lp_order.recip());
+            assert!(close(report.kl_divergence, kl(&p_a, &p_d)));
+            assert!(close(
+                report.js_divergence,
+                0.5 * (kl(&p_a, &mixture) + kl(&p_d, &mixture))
+            ));
+            assert!(close(report.lp_norm, lp));
+            assert!(close(report.total_variation_distance, 0.25));
+            assert!(close(report.kolmorogv_smirnov, 0.25));
+        }
+    }
+
+    #[test]
+    fn an_empty_facet_is_an_error() {
+        let labels = CategoricalLabels {
+            categories: vec!["0".into(), "1".into()],
+            facet_a: vec![3, 1],
+            facet_d: vec![0, 0],
+        };
+        assert!(labels.divergences(DivergenceOptions::default()).is_err());
+    }
 }
@@ -1,190 +1,689 @@
-use super::data_bias::PreTraining;
-use super::model_bias::PostTrainingData;
-use crate::zip;
-use numpy::dtype_bound;
-use numpy::PyUntypedArrayMethods;
-use numpy::{PyArrayDescrMethods, PyUntypedArray};
-use pyo3::prelude::*;
-use pyo3::types::{PyFloat, PyInt, PyString};
-
-#[derive(PartialEq)]
-pub enum PassedType {
-    Float,
-    Integer,
-    String,
-}
-
-pub fn determine_type(py: Python<'_>, array: &Bound<'_, PyUntypedArray>) -> PassedType {
-    let element_type = array.dtype();
-
-    if element_type.is_equiv_to(&dtype_bound::<f64>(py))
-        | element_type.is_equiv_to(&dtype_bound::<f32>(py))
-    {
-        PassedType::Float
-    } else if element_type.is_equiv_to(&dtype_bound::<i32>(py))
-        | element_type.is_equiv_to(&dtype_bound::<i64>(py))
-        | element_type.is_equiv_to(&dtype_bound::<i16>(py))
+use super::data_bias::{ContinuousPreTraining, FacetLabels, PreTraining};
+use super::model_bias::{FacetConfusion, PostTrainingData};
+use crate::errors::FairMlError;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+pub fn perform_segmentation_data_bias(
+    feature_values: &[i16],
+    ground_truth_values: &[i16],
+) -> Result<PreTraining, FairMlError> {
+    let data = count_facets_data_bias(feature_values, ground_truth_values, None)?;
+    check_facets(data.facet_a.is_empty(), data.facet_d.is_empty())?;
+    Ok(data)
+}
+
+/// `perform_segmentation_data_bias` with every row counted at its sample weight.
+pub fn perform_segmentation_data_bias_weighted(
+    feature_values: &[i16],
+    ground_truth_values: &[i16],
+    sample_weights: &[f64],
+) -> Result<PreTraining, FairMlError> {
+    let data = count_facets_data_bias(feature_values, ground_truth_values, Some(sample_weights))?;
+    check_facets(data.facet_a.is_empty(), data.facet_d.is_empty())?;
+    Ok(data)
+}
+
+/// `perform_segmentation_data_bias` for a continuous outcome, keeping the
+/// raw outcome values of each facet instead of counting labels.
+pub fn perform_segmentation_data_bias_continuous(
+    feature_values: &[i16],
+    outcome_values: &[f64],
+) -> Result<ContinuousPreTraining, FairMlError> {
+    check_lengths("feature", feature_values, "ground_truth", outcome_values)?;
+    let mut data = ContinuousPreTraining::default();
+    for (feature, outcome) in feature_values.iter().zip(outcome_values) {
+        match *feature {
+            1_i16 => data.facet_a.push(*outcome),
+            0_i16 => data.facet_d.push(*outcome),
+            _ => {}
+        }
+    }
+    check_facets(data.facet_a.is_empty(), data.facet_d.is_empty())?;
+    Ok(data)
+}
+
+pub fn perform_segmentation_model_bias(
+    feature_values: &[i16],
+    prediction_values: &[i16],
+    ground_truth_values: &[i16],
+) -> Result<PostTrainingData, FairMlError> {
+    let data =
+        count_facets_model_bias(feature_values, prediction_values, ground_truth_values, None)?;
+    check_facets(data.facet_a.is_empty(), data.facet_d.is_empty())?;
+    Ok(data)
+}
+
+/// `perform_segmentation_model_bias` with every row counted at its sample weight.
+pub fn perform_segmentation_model_bias_weighted(
+    feature_values: &[i16],
+    prediction_values: &[i16],
+    ground_truth_values: &[i16],
+    sample_weights: &[f64],
+) -> Result<PostTrainingData, FairMlError> {
+    let data = count_facets_model_bias(
+        feature_values,
+        prediction_values,
+        ground_truth_values,
+        Some(sample_weights),
+    )?;
+    check_facets(data.facet_a.is_empty(), data.facet_d.is_empty())?;
+    Ok(data)
+}
+
+/// Splits labeled rows into facet label counts, each row counted at its
+/// weight or at 1 without weights. A batch may leave either facet empty,
+/// callers that need both check afterwards.
+pub(crate) fn count_facets_data_bias(
+    feature_values: &[i16],
+    ground_truth_values: &[i16],
+    sample_weights: Option<&[f64]>,
+) -> Result<PreTraining, FairMlError> {
+    check_lengths(
+        "feature",
+        feature_values,
+        "ground_truth",
+        ground_truth_values,
+    )?;
+    if let Some(weights) = sample_weights {
+        check_sample_weights("feature", feature_values, weights)?;
+    }
+    let (facet_a, facet_d) = feature_values
+        .par_iter()
+        .zip(ground_truth_values.par_iter())
+        .enumerate()
+        .fold(
+            || (FacetLabels::default(), FacetLabels::default()),
+            |(mut facet_a, mut facet_d), (row, (feature, ground_truth))| {
+                let weight = sample_weights.map_or(1_f64, |weights| weights[row]);
+                match *feature {
+                    1_i16 => facet_a.record_weighted(*ground_truth, weight),
+                    0_i16 => facet_d.record_weighted(*ground_truth, weight),
+                    // a missing feature kept as its own group belongs to neither facet
+                    _ => {}
+                }
+                (facet_a, facet_d)
+            },
+        )
+        .reduce(
+            || (FacetLabels::default(), FacetLabels::default()),
+            |(a_left, d_left), (a_right, d_right)| (a_left.merge(a_right), d_left.merge(d_right)),
+        );
+    Ok(PreTraining { facet_a, facet_d })
+}
+
+/// Splits labeled rows into per facet confusion counts, see `count_facets_data_bias`.
+pub(crate) fn count_facets_model_bias(
+    feature_values: &[i16],
+    prediction_values: &[i16],
+    ground_truth_values: &[i16],
+    sample_weights: Option<&[f64]>,
+) -> Result<PostTrainingData, FairMlError> {
+    check_lengths("feature", feature_values, "prediction", prediction_values)?;
+    check_lengths(
+        "feature",
+        feature_values,
+        "ground_truth",
+        ground_truth_values,
+    )?;
+    if let Some(weights) = sample_weights {
+        check_sample_weights("feature", feature_values, weights)?;
+    }
+    let (facet_a, facet_d) = feature_values
+        .par_iter()
+        .zip(prediction_values.par_iter())
+        .zip(ground_truth_values.par_iter())
+        .enumerate()
+        .fold(
+            || (FacetConfusion::default(), FacetConfusion::default()),
+            |(mut facet_a, mut facet_d), (row, ((feature, prediction), ground_truth))| {
+                let weight = sample_weights.map_or(1_f64, |weights| weights[row]);
+                match *feature {
+                    1_i16 => facet_a.record_weighted(*prediction, *ground_truth, weight),
+                    0_i16 => facet_d.record_weighted(*prediction, *ground_truth, weight),
+                    _ => {}
+                }
+                (facet_a, facet_d)
+            },
+        )
+        .reduce(
+            || (FacetConfusion::default(), FacetConfusion::default()),
+            |(a_left, d_left), (a_right, d_right)| (a_left.merge(a_right), d_left.merge(d_right)),
+        );
+    Ok(PostTrainingData { facet_a, facet_d })
+}
+
+/// Sample weights must line up with the rows and be finite and non negative.
+pub fn check_sample_weights<T, W>(
+    rows_name: &str,
+    rows: &[T],
+    sample_weights: &[W],
+) -> Result<(), FairMlError>
+where
+    W: Copy + Into<f64>,
+{
+    check_lengths(rows_name, rows, "sample_weight", sample_weights)?;
+    if let Some(weight) = sample_weights
+        .iter()
+        .map(|weight| (*weight).into())
+        .find(|weight: &f64| !weight.is_finite() || *weight < 0_f64)
     {
-        PassedType::Integer
-    } else {
-        PassedType::String
+        return Err(FairMlError::InvalidSampleWeight {
+            reason: format!("{weight}, weights must be finite and non negative"),
+        });
     }
+    Ok(())
 }
 
-pub fn apply_label<'py>(
-    py: Python<'_>,
-    array: &Bound<'_, PyUntypedArray>,
-    label: Bound<'py, PyAny>,
-) -> Result<Vec<i16>, String> {
-    let pred_type = determine_type(py, &array);
-    let iter = &array.iter().unwrap();
+/// Kish effective sample size of rows whose weights sum to `weight` and whose
+/// squared weights sum to `weight_squares`. Equals the row count when every
+/// weight is 1.
+pub fn effective_size(weight: f64, weight_squares: f64) -> f64 {
+    if weight_squares <= 0_f64 {
+        return 0_f64;
+    }
+    weight * weight / weight_squares
+}
 
-    let labeled_array: Vec<i16> = match pred_type {
-        PassedType::String => {
-            let data_vec: Vec<String> = iter
-                .clone()
-                .map(|item| item.unwrap().extract().unwrap())
-                .collect::<Vec<String>>();
+/// Effective sample size per facet, see `effective_size`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveSampleSize {
+    #[serde(deserialize_with = "crate::runtime::f64_or_nan")]
+    pub facet_a: f64,
+    #[serde(deserialize_with = "crate::runtime::f64_or_nan")]
+    pub facet_d: f64,
+}
 
-            if !label.is_instance_of::<PyString>() {
-                return Err("string".into());
-            }
+pub(crate) fn check_lengths<T, U>(
+    left: &str,
+    left_values: &[T],
+    right: &str,
+    right_values: &[U],
+) -> Result<(), FairMlError> {
+    if left_values.len() != right_values.len() {
+        return Err(FairMlError::length_mismatch(
+            left,
+            left_values.len(),
+            right,
+            right_values.len(),
+        ));
+    }
+    Ok(())
+}
 
-            let data_label: String = label.extract::<String>().unwrap();
-            apply_label_discrete(data_vec, data_label)
-        }
-        PassedType::Float => {
-            let data_vec: Vec<f64> = iter
-                .clone()
-                .map(|item| item.unwrap().extract().unwrap())
-                .collect::<Vec<f64>>();
-
-            // handling users passing float vs int as label_or_threshold
-            let data_label: f64 = if label.is_instance_of::<PyFloat>() {
-                label.extract::<f64>().unwrap()
-            } else if label.is_instance_of::<PyInt>() {
-                label.extract::<i64>().unwrap() as f64
-            } else {
-                return Err("float".into());
-            };
-
-            let data_set: std::collections::HashSet<i32> = data_vec
-                .iter()
-                .map(|value| *value as i32)
-                .collect::<std::collections::HashSet<_>>();
+pub(crate) fn check_facets(facet_a_empty: bool, facet_d_empty: bool) -> Result<(), FairMlError> {
+    if facet_a_empty {
+        return Err(FairMlError::EmptyFacet { facet: "a".into() });
+    }
+    if facet_d_empty {
+        return Err(FairMlError::EmptyFacet { facet: "d".into() });
+    }
+    Ok(())
+}
 
-            if data_set.len() == 2 {
-                apply_label_discrete(data_vec, data_label)
-            } else {
-                apply_label_continuous(data_vec, data_label)
-            }
+/// Label of a row whose raw value is missing, NaN or None. Resolved by
+/// `apply_missing_policy` before the rows are counted.
+pub const MISSING_LABEL: i16 = -1;
+
+/// What to do with rows holding a missing value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MissingPolicy {
+    /// Leave the row out of the analysis.
+    Drop,
+    /// Keep a row with a missing feature as a group of its own, in neither
+    /// facet. Rows missing an outcome are left out.
+    OwnGroup,
+    /// Label the missing value negative (0), the behavior of earlier releases
+    /// for NaN.
+    Negative,
+    /// Fail with `FairMlError::MissingValues`.
+    #[default]
+    Raise,
+}
+
+impl TryFrom<&str> for MissingPolicy {
+    type Error = FairMlError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "drop" => Ok(Self::Drop),
+            "own_group" => Ok(Self::OwnGroup),
+            "negative" => Ok(Self::Negative),
+            "raise" => Ok(Self::Raise),
+            _ => Err(FairMlError::InvalidMissingPolicy {
+                policy: value.into(),
+            }),
         }
-        PassedType::Integer => {
-            let data_vec: Vec<i64> = iter
-                .clone()
-                .map(|item| item.unwrap().extract().unwrap())
-                .collect::<Vec<i64>>();
+    }
+}
 
-            let data_set: std::collections::HashSet<i32> = data_vec
-                .iter()
-                .map(|value| *value as i32)
-                .collect::<std::collections::HashSet<_>>();
-
-            // handling users passing float vs int as label_or_threshold
-            let data_label: i64 = if label.is_instance_of::<PyFloat>() {
-                label.extract::<f64>().unwrap() as i64
-            } else if label.is_instance_of::<PyInt>() {
-                label.extract::<i64>().unwrap()
-            } else {
-                return Err("float".into());
-            };
-
-            if data_set.len() == 2 {
-                apply_label_discrete(data_vec, data_label)
-            } else {
-                apply_label_continuous(data_vec, data_label)
-            }
+impl MissingPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Drop => "drop",
+            Self::OwnGroup => "own_group",
+            Self::Negative => "negative",
+            Self::Raise => "raise",
         }
-    };
-    Ok(labeled_array)
+    }
 }
 
-pub fn perform_segmentation_data_bias(
-    feature_values: Vec<i16>,
-    ground_truth_values: Vec<i16>,
-) -> Result<PreTraining, String> {
-    let mut facet_a: Vec<i16> = Vec::new();
-    let mut facet_d: Vec<i16> = Vec::new();
+/// Rows with a missing value, counted before the policy is applied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingCounts {
+    /// Facet a rows missing a ground truth or prediction.
+    pub facet_a: u64,
+    /// Facet d rows missing a ground truth or prediction.
+    pub facet_d: u64,
+    /// Rows missing the feature value, which belong to neither facet.
+    pub feature: u64,
+}
 
-    for (feature, ground_truth) in zip!(feature_values, ground_truth_values) {
-        match *feature {
-            1_i16 => {
-                facet_a.push(ground_truth);
-            }
-            _ => facet_d.push(ground_truth),
+impl MissingCounts {
+    pub fn merge(self, other: MissingCounts) -> MissingCounts {
+        MissingCounts {
+            facet_a: self.facet_a + other.facet_a,
+            facet_d: self.facet_d + other.facet_d,
+            feature: self.feature + other.feature,
         }
     }
 
-    if facet_a.is_empty() | facet_d.is_empty() {
-        return Err("No deviation".into());
+    pub fn total(&self) -> u64 {
+        self.facet_a + self.facet_d + self.feature
     }
+}
 
-    Ok(PreTraining { facet_a, facet_d })
+/// Applies `policy` to labeled columns in place, returning the missing counts
+/// per facet. Without a feature, e.g. for grouped data, every missing row
+/// counts towards `feature` and `OwnGroup` leaves it out like `Drop`. Sample
+/// weights lose the same rows as the labels.
+pub fn apply_missing_policy(
+    policy: MissingPolicy,
+    feature: Option<(&str, &mut Vec<i16>)>,
+    outcomes: Vec<(&str, &mut Vec<i16>)>,
+    sample_weights: Option<&mut Vec<f64>>,
+) -> Result<MissingCounts, FairMlError> {
+    apply_missing_policy_rows(policy, feature, outcomes, sample_weights).map(|(counts, _)| counts)
 }
 
-pub fn perform_segmentation_model_bias(
-    feature_values: Vec<i16>,
-    prediction_values: Vec<i16>,
-    ground_truth_values: Vec<i16>,
-) -> Result<PostTrainingData, String> {
-    let mut facet_a_trues: Vec<i16> = Vec::new();
-    let mut facet_a_scores: Vec<i16> = Vec::new();
-    let mut facet_d_scores: Vec<i16> = Vec::new();
-    let mut facet_d_trues: Vec<i16> = Vec::new();
-
-    for (feature, (prediction, ground_truth)) in
-        zip!(feature_values, prediction_values, ground_truth_values)
-    {
-        match *feature {
-            1_i16 => {
-                facet_a_trues.push(ground_truth);
-                facet_a_scores.push(*prediction);
+/// `apply_missing_policy` that also returns whether each row was kept, for
+/// callers holding columns that are not labeled, e.g. the groups of a multi
+/// group analysis.
+pub fn apply_missing_policy_rows(
+    policy: MissingPolicy,
+    feature: Option<(&str, &mut Vec<i16>)>,
+    outcomes: Vec<(&str, &mut Vec<i16>)>,
+    sample_weights: Option<&mut Vec<f64>>,
+) -> Result<(MissingCounts, Vec<bool>), FairMlError> {
+    let (feature_name, feature) = match feature {
+        Some((name, values)) => (Some(name), Some(values)),
+        None => (None, None),
+    };
+    let lead = feature_name
+        .zip(feature.as_deref())
+        .or_else(|| outcomes.first().map(|(name, values)| (*name, &**values)));
+    let n_rows = lead.map_or(0, |(_, values)| values.len());
+    if let Some((lead_name, lead_values)) = lead {
+        for (name, values) in &outcomes {
+            check_lengths(lead_name, lead_values, name, values)?;
+        }
+        if let Some(weights) = sample_weights.as_deref() {
+            check_lengths(lead_name, lead_values, "sample_weight", weights)?;
+        }
+    }
+
+    if policy == MissingPolicy::Raise {
+        let feature_column = feature_name.zip(feature.as_deref());
+        let columns = outcomes.iter().map(|(name, values)| (*name, &**values));
+        for (name, values) in feature_column.into_iter().chain(columns) {
+            let count = values
+                .iter()
+                .filter(|value| **value == MISSING_LABEL)
+                .count();
+            if count > 0 {
+                return Err(FairMlError::MissingValues {
+                    array: name.into(),
+                    count,
+                });
             }
-            _ => {
-                facet_d_trues.push(ground_truth);
-                facet_d_scores.push(*prediction);
+        }
+    }
+
+    let mut counts = MissingCounts::default();
+    let mut keep = vec![true; n_rows];
+    for (row, keep) in keep.iter_mut().enumerate() {
+        let outcome_missing = outcomes
+            .iter()
+            .any(|(_, values)| values[row] == MISSING_LABEL);
+        let feature_label = feature.as_ref().map(|values| values[row]);
+        match feature_label {
+            Some(MISSING_LABEL) => counts.feature += 1,
+            None if outcome_missing => counts.feature += 1,
+            Some(1) if outcome_missing => counts.facet_a += 1,
+            Some(_) if outcome_missing => counts.facet_d += 1,
+            _ => continue,
+        }
+        *keep = match policy {
+            MissingPolicy::Drop => false,
+            MissingPolicy::OwnGroup => feature_label.is_some() && !outcome_missing,
+            MissingPolicy::Negative | MissingPolicy::Raise => true,
+        };
+    }
+
+    let columns = feature
+        .into_iter()
+        .chain(outcomes.into_iter().map(|(_, values)| values));
+    for values in columns {
+        if policy == MissingPolicy::Negative {
+            values
+                .iter_mut()
+                .filter(|value| **value == MISSING_LABEL)
+                .for_each(|value| *value = 0);
+        }
+        retain_rows(values, &keep);
+    }
+    if let Some(weights) = sample_weights {
+        retain_rows(weights, &keep);
+    }
+    Ok((counts, keep))
+}
+
+/// Keeps the rows `keep` marks, in order.
+pub fn retain_rows<T>(values: &mut Vec<T>, keep: &[bool]) {
+    if keep.contains(&false) {
+        let mut row = 0;
+        values.retain(|_| {
+            row += 1;
+            keep[row - 1]
+        });
+    }
+}
+
+/// A raw value that is missing when NaN, either float width.
+pub trait RawValue: Copy {
+    const ZERO: Self;
+    fn is_nan(self) -> bool;
+}
+
+impl RawValue for f32 {
+    const ZERO: f32 = 0_f32;
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+}
+
+impl RawValue for f64 {
+    const ZERO: f64 = 0_f64;
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+}
+
+/// `apply_missing_policy` for raw performance values, where NaN marks a
+/// missing value. Without facets `OwnGroup` is rejected and `Negative` fills
+/// in 0. Returns the number of rows holding a missing value.
+pub fn apply_missing_policy_values<T: RawValue>(
+    policy: MissingPolicy,
+    columns: Vec<(&str, &mut Vec<T>)>,
+    sample_weights: Option<&mut Vec<f64>>,
+) -> Result<u64, FairMlError> {
+    if policy == MissingPolicy::OwnGroup {
+        return Err(FairMlError::InvalidMissingPolicy {
+            policy: "own_group, performance data has no groups".into(),
+        });
+    }
+    if let Some((lead_name, lead_values)) = columns.first() {
+        for (name, values) in &columns {
+            check_lengths(lead_name, lead_values, name, values)?;
+        }
+        if let Some(weights) = sample_weights.as_deref() {
+            check_lengths(lead_name, lead_values, "sample_weight", weights)?;
+        }
+    }
+    if policy == MissingPolicy::Raise {
+        for (name, values) in &columns {
+            let count = values.iter().filter(|value| value.is_nan()).count();
+            if count > 0 {
+                return Err(FairMlError::MissingValues {
+                    array: (*name).into(),
+                    count,
+                });
             }
         }
     }
-    if facet_a_trues.is_empty() | facet_d_trues.is_empty() {
-        return Err("no deviaton".into());
+
+    let n_rows = columns.first().map_or(0, |(_, values)| values.len());
+    let keep: Vec<bool> = (0..n_rows)
+        .map(|row| !columns.iter().any(|(_, values)| values[row].is_nan()))
+        .collect();
+    let missing = keep.iter().filter(|keep| !**keep).count() as u64;
+    for (_, values) in columns {
+        match policy {
+            MissingPolicy::Negative => values
+                .iter_mut()
+                .filter(|value| value.is_nan())
+                .for_each(|value| *value = T::ZERO),
+            _ => retain_rows(values, &keep),
+        }
+    }
+    if let Some(weights) = sample_weights {
+        if policy != MissingPolicy::Negative {
+            retain_rows(weights, &keep);
+        }
     }
-    Ok(PostTrainingData {
-        facet_a_trues,
-        facet_a_scores,
-        facet_d_trues,
-        facet_d_scores,
-    })
+    Ok(missing)
 }
 
-fn apply_label_discrete<T>(array: Vec<T>, label: T) -> Vec<i16>
+/// Labels floating point data with the spec `auto_label_float` picks.
+#[deprecated(note = "pick the comparison with `LabelSpec::Equals` or `LabelSpec::GreaterEqual`")]
+pub fn apply_label_float<T>(array: &[T], label: f64) -> Vec<i16>
 where
-    T: PartialEq<T>,
+    T: Copy + Into<f64>,
+{
+    let spec = auto_label_float(array, label);
+    label_each(array, |value| spec.is_positive(&value) == Some(true))
+}
+
+/// Labels integer data with the spec `auto_label_integer` picks.
+#[deprecated(note = "pick the comparison with `LabelSpec::Equals` or `LabelSpec::GreaterEqual`")]
+pub fn apply_label_integer<T>(array: &[T], label: i64) -> Vec<i16>
+where
+    T: Copy + Into<i64>,
+{
+    let spec = auto_label_integer(array, label);
+    label_each(array, |value| spec.is_positive(&value) == Some(true))
+}
+
+/// The opt-in heuristic for a bare label on floating point data: data holding
+/// exactly two distinct values (after truncating to integers) is matched on
+/// equality with the label, anything else is thresholded with `>=`. NaN is
+/// missing and not counted as a value. Values truncating to two integers,
+/// e.g. probabilities, end up matched on equality, pass an explicit spec for
+/// those.
+pub fn auto_label_float<T>(array: &[T], label: f64) -> LabelSpec<f64>
+where
+    T: Copy + Into<f64>,
 {
-    let labeled_array: Vec<i16> = array
+    let data_set: HashSet<i32> = array
         .iter()
-        .map(|value| if *value == label { 1_i16 } else { 0_i16 })
+        .map(|value| (*value).into())
+        .filter(|value: &f64| !value.is_nan())
+        .map(|value| value as i32)
         .collect();
-    labeled_array
+    auto_label(data_set.len(), label)
+}
+
+/// Integer version of `auto_label_float`.
+pub fn auto_label_integer<T>(array: &[T], label: i64) -> LabelSpec<i64>
+where
+    T: Copy + Into<i64>,
+{
+    let data_set: HashSet<i32> = array.iter().map(|value| (*value).into() as i32).collect();
+    auto_label(data_set.len(), label)
+}
+
+fn auto_label<T>(distinct_values: usize, label: T) -> LabelSpec<T> {
+    if distinct_values == 2 {
+        LabelSpec::Equals(label)
+    } else {
+        LabelSpec::GreaterEqual(label)
+    }
+}
+
+fn label_each<T, U>(array: &[T], rule: impl Fn(U) -> bool) -> Vec<i16>
+where
+    T: Copy + Into<U>,
+{
+    array
+        .iter()
+        .map(|value| rule((*value).into()) as i16)
+        .collect()
+}
+
+pub fn apply_label_bool(array: &[bool]) -> Vec<i16> {
+    array.iter().map(|value| *value as i16).collect()
 }
 
-fn apply_label_continuous<T>(array: Vec<T>, threshold: T) -> Vec<i16>
+pub fn apply_label_discrete<T>(array: &[T], label: &T) -> Vec<i16>
+where
+    T: PartialEq<T>,
+{
+    array
+        .iter()
+        .map(|value| if value == label { 1_i16 } else { 0_i16 })
+        .collect()
+}
+
+pub fn apply_label_continuous<T>(array: &[T], threshold: &T) -> Vec<i16>
 where
     T: PartialOrd<T>,
 {
-    let labeled_array: Vec<i16> = array
+    array
         .iter()
-        .map(|value| if *value >= threshold { 1_i16 } else { 0_i16 })
-        .collect();
-    labeled_array
+        .map(|value| if value >= threshold { 1_i16 } else { 0_i16 })
+        .collect()
+}
+
+/// How raw values are split into positive (1) and negative (0) labels.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelSpec<T> {
+    /// Positive when `value == label`.
+    Equals(T),
+    /// Positive when `value >= threshold`.
+    GreaterEqual(T),
+    /// Positive when `value > threshold`.
+    Greater(T),
+    /// Positive when `value <= threshold`.
+    LessEqual(T),
+    /// Positive when `value < threshold`.
+    Less(T),
+    /// Positive when the value is one of these.
+    OneOf(Vec<T>),
+    /// Every category mapped to favorable (`true`) or unfavorable. A value
+    /// missing from the mapping is an error rather than silently negative.
+    Mapping(Vec<(T, bool)>),
+    /// Positive when `low <= value < high`.
+    Range { low: T, high: T },
+}
+
+impl<T> LabelSpec<T> {
+    /// The name of the mode, as accepted by the python `LabelSpec`.
+    pub fn mode(&self) -> &'static str {
+        match self {
+            Self::Equals(_) => "equals",
+            Self::GreaterEqual(_) => ">=",
+            Self::Greater(_) => ">",
+            Self::LessEqual(_) => "<=",
+            Self::Less(_) => "<",
+            Self::OneOf(_) => "in",
+            Self::Mapping(_) => "mapping",
+            Self::Range { .. } => "between",
+        }
+    }
+
+    /// Converts every value in the spec.
+    pub fn map<'a, U>(&'a self, convert: impl Fn(&'a T) -> U) -> LabelSpec<U> {
+        match self.try_map(|value| Some(convert(value))) {
+            Some(spec) => spec,
+            None => unreachable!("every value converts"),
+        }
+    }
+
+    /// Converts every value in the spec, `None` if any of them does not convert.
+    pub fn try_map<'a, U>(&'a self, convert: impl Fn(&'a T) -> Option<U>) -> Option<LabelSpec<U>> {
+        Some(match self {
+            Self::Equals(value) => LabelSpec::Equals(convert(value)?),
+            Self::GreaterEqual(value) => LabelSpec::GreaterEqual(convert(value)?),
+            Self::Greater(value) => LabelSpec::Greater(convert(value)?),
+            Self::LessEqual(value) => LabelSpec::LessEqual(convert(value)?),
+            Self::Less(value) => LabelSpec::Less(convert(value)?),
+            Self::OneOf(values) => {
+                LabelSpec::OneOf(values.iter().map(&convert).collect::<Option<_>>()?)
+            }
+            Self::Mapping(mapping) => LabelSpec::Mapping(
+                mapping
+                    .iter()
+                    .map(|(value, favorable)| Some((convert(value)?, *favorable)))
+                    .collect::<Option<_>>()?,
+            ),
+            Self::Range { low, high } => LabelSpec::Range {
+                low: convert(low)?,
+                high: convert(high)?,
+            },
+        })
+    }
+}
+
+impl<T> LabelSpec<T>
+where
+    T: PartialOrd,
+{
+    /// Whether `value` is positive, `None` for a category missing from a mapping.
+    pub fn is_positive(&self, value: &T) -> Option<bool> {
+        match self {
+            Self::Equals(label) => Some(value == label),
+            Self::GreaterEqual(threshold) => Some(value >= threshold),
+            Self::Greater(threshold) => Some(value > threshold),
+            Self::LessEqual(threshold) => Some(value <= threshold),
+            Self::Less(threshold) => Some(value < threshold),
+            Self::OneOf(values) => Some(values.contains(value)),
+            Self::Mapping(mapping) => mapping
+                .iter()
+                .find(|(category, _)| category == value)
+                .map(|(_, favorable)| *favorable),
+            Self::Range { low, high } => Some(low <= value && value < high),
+        }
+    }
+}
+
+impl<T> LabelSpec<T>
+where
+    T: PartialOrd + fmt::Display,
+{
+    /// Labels `array`, with `value` converting each element to the spec's type
+    /// or to `None` for a missing value, which is labeled `MISSING_LABEL`.
+    pub fn apply<'a, U>(
+        &self,
+        array: &'a [U],
+        name: &str,
+        value: impl Fn(&'a U) -> Option<T>,
+    ) -> Result<Vec<i16>, FairMlError> {
+        array
+            .iter()
+            .map(|element| {
+                let Some(element) = value(element) else {
+                    return Ok(MISSING_LABEL);
+                };
+                self.is_positive(&element)
+                    .map(|positive| positive as i16)
+                    .ok_or_else(|| FairMlError::UnmappedCategory {
+                        array: name.into(),
+                        value: element.to_string(),
+                    })
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,207 @@
+use std::error::Error;
+use std::fmt;
+
+/// Every failure the crate can report. Each variant keeps enough context to
+/// tell the caller which input or key was at fault.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FairMlError {
+    LengthMismatch {
+        left: String,
+        left_len: usize,
+        right: String,
+        right_len: usize,
+    },
+    EmptyFacet {
+        facet: String,
+    },
+    EmptyInput {
+        array: String,
+    },
+    LabelTypeMismatch {
+        array: String,
+        expected: String,
+    },
+    UnsupportedDtype {
+        array: String,
+        dtype: String,
+    },
+    MissingBaselineKey {
+        key: String,
+    },
+    InvalidMetricName {
+        name: String,
+    },
+    InvalidModelType {
+        name: String,
+    },
+    DegenerateDenominator {
+        metric: String,
+        denominator: String,
+    },
+    StateVersionMismatch {
+        found: String,
+        expected: String,
+    },
+    InvalidState {
+        reason: String,
+    },
+    DuplicateMetricName {
+        name: String,
+    },
+    MetricComputation {
+        metric: String,
+        reason: String,
+    },
+    InvalidDirection {
+        direction: String,
+    },
+    InvalidComparison {
+        comparison: String,
+    },
+    UnknownGroup {
+        group: String,
+    },
+    UnmappedCategory {
+        array: String,
+        value: String,
+    },
+    InvalidLabelSpec {
+        reason: String,
+    },
+    InvalidMissingPolicy {
+        policy: String,
+    },
+    MissingValues {
+        array: String,
+        count: usize,
+    },
+    InvalidSampleWeight {
+        reason: String,
+    },
+    InvalidArrowData {
+        reason: String,
+    },
+    UnknownColumn {
+        column: String,
+    },
+    InvalidDivergenceOptions {
+        reason: String,
+    },
+    InvalidBootstrapConfig {
+        reason: String,
+    },
+    InvalidSignificanceConfig {
+        reason: String,
+    },
+    InvalidSupportThresholds {
+        reason: String,
+    },
+}
+
+impl fmt::Display for FairMlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch {
+                left,
+                left_len,
+                right,
+                right_len,
+            } => write!(
+                f,
+                "length mismatch: {left} has {left_len} elements, {right} has {right_len}"
+            ),
+            Self::EmptyFacet { facet } => write!(
+                f,
+                "facet {facet} has no observations, the feature label matches every row or none"
+            ),
+            Self::EmptyInput { array } => write!(f, "{array} has no data"),
+            Self::LabelTypeMismatch { array, expected } => write!(
+                f,
+                "label or threshold for {array} must be {expected} to match the array dtype"
+            ),
+            Self::UnsupportedDtype { array, dtype } => {
+                write!(f, "{array} has unsupported dtype {dtype}")
+            }
+            Self::MissingBaselineKey { key } => {
+                write!(f, "{key} is not present in the metrics body")
+            }
+            Self::InvalidMetricName { name } => write!(f, "invalid metric name: {name}"),
+            Self::InvalidModelType { name } => write!(f, "invalid model type: {name}"),
+            Self::DegenerateDenominator {
+                metric,
+                denominator,
+            } => write!(f, "{metric} is undefined, {denominator} is zero"),
+            Self::StateVersionMismatch { found, expected } => write!(
+                f,
+                "accumulator state has format version {found}, this build reads version {expected}"
+            ),
+            Self::InvalidState { reason } => write!(f, "invalid accumulator state: {reason}"),
+            Self::DuplicateMetricName { name } => {
+                write!(f, "a metric named {name} is already registered")
+            }
+            Self::MetricComputation { metric, reason } => {
+                write!(f, "computing {metric} failed: {reason}")
+            }
+            Self::InvalidComparison { comparison } => write!(
+                f,
+                "invalid group comparison: {comparison}, expected one_vs_rest, pairwise or reference with a reference group"
+            ),
+            Self::UnknownGroup { group } => write!(f, "group {group} does not appear in the feature"),
+            Self::InvalidDirection { direction } => write!(
+                f,
+                "invalid comparison direction: {direction}, expected magnitude or increase"
+            ),
+            Self::UnmappedCategory { array, value } => {
+                write!(f, "{array} value {value} is not in the label mapping")
+            }
+            Self::InvalidLabelSpec { reason } => write!(f, "invalid label spec: {reason}"),
+            Self::InvalidMissingPolicy { policy } => write!(
+                f,
+                "invalid missing value policy: {policy}, expected drop, own_group, negative or raise"
+            ),
+            Self::MissingValues { array, count } => write!(
+                f,
+                "{array} has {count} missing values, pick a missing_policy other than raise to analyze it"
+            ),
+            Self::InvalidSampleWeight { reason } => write!(f, "invalid sample_weight: {reason}"),
+            Self::InvalidArrowData { reason } => write!(f, "invalid arrow data: {reason}"),
+            Self::UnknownColumn { column } => write!(f, "no column named {column}"),
+            Self::InvalidDivergenceOptions { reason } => write!(f, "invalid divergence options: {reason}"),
+            Self::InvalidBootstrapConfig { reason } => write!(f, "invalid bootstrap config: {reason}"),
+            Self::InvalidSignificanceConfig { reason } => write!(f, "invalid significance config: {reason}"),
+            Self::InvalidSupportThresholds { reason } => write!(f, "invalid support thresholds: {reason}"),
+        }
+    }
+}
+
+impl Error for FairMlError {}
+
+impl FairMlError {
+    pub(crate) fn length_mismatch(
+        left: &str,
+        left_len: usize,
+        right: &str,
+        right_len: usize,
+    ) -> Self {
+        Self::LengthMismatch {
+            left: left.into(),
+            left_len,
+            right: right.into(),
+            right_len,
+        }
+    }
+
+    pub(crate) fn missing_key(key: &str) -> Self {
+        Self::MissingBaselineKey { key: key.into() }
+    }
+
+    pub(crate) fn invalid_metric(name: &str) -> Self {
+        Self::InvalidMetricName { name: name.into() }
+    }
+
+    pub(crate) fn invalid_state(reason: impl fmt::Display) -> Self {
+        Self::InvalidState {
+            reason: reason.to_string(),
+        }
+    }
+}
@@ -0,0 +1,489 @@
+use crate::bootstrap::{
+    bootstrap_classification, bootstrap_data_bias, bootstrap_linear_regression,
+    bootstrap_logistic_regression, bootstrap_model_bias, BootstrapConfig,
+};
+use crate::data_bias::{
+    continuous_pre_training_bias, ContinuousDataBiasReport, DataBiasRegistry, DataBiasReport,
+    PreTraining,
+};
+use crate::data_handler::{
+    apply_missing_policy, apply_missing_policy_values, check_lengths, check_sample_weights,
+    perform_segmentation_data_bias, perform_segmentation_data_bias_continuous,
+    perform_segmentation_data_bias_weighted, perform_segmentation_model_bias,
+    perform_segmentation_model_bias_weighted, MissingCounts, MissingPolicy, MISSING_LABEL,
+};
+use crate::errors::FairMlError;
+use crate::model_bias::{ModelBiasRegistry, ModelBiasReport, PostTrainingData};
+use crate::model_perf::{
+    BinaryClassificationReport, ClassificationPerf, LinearRegressionPerf, LinearRegressionReport,
+    LogisticRegressionPerf, LogisticRegressionReport,
+};
+use crate::significance::{data_bias_significance, model_bias_significance, SignificanceConfig};
+use crate::status::SupportThresholds;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Data bias from labeled columns. The missing value policy is applied to
+/// the feature, ground truth and weights together before the facets are
+/// counted, and the report carries the missing counts and effective sample
+/// size, along with bootstrap bounds when `confidence` is set and p-values
+/// when `significance` is set. The tests count rows, so they cannot be
+/// combined with sample weights. With `support` set, the report holds the
+/// status of every metric as well.
+#[allow(clippy::too_many_arguments)]
+pub fn labeled_data_bias(
+    registry: &DataBiasRegistry,
+    policy: MissingPolicy,
+    mut feature: Vec<i16>,
+    mut ground_truth: Vec<i16>,
+    mut sample_weight: Option<Vec<f64>>,
+    confidence: Option<BootstrapConfig>,
+    significance: Option<SignificanceConfig>,
+    support: Option<SupportThresholds>,
+) -> Result<DataBiasReport, FairMlError> {
+    if let Some(weights) = &sample_weight {
+        check_sample_weights("feature", &feature, weights)?;
+    }
+    check_unweighted(&sample_weight, &significance)?;
+    let missing = apply_missing_policy(
+        policy,
+        Some(("feature", &mut feature)),
+        vec![("ground_truth", &mut ground_truth)],
+        sample_weight.as_mut(),
+    )?;
+    let pre_training: PreTraining = match &sample_weight {
+        Some(weights) => perform_segmentation_data_bias_weighted(&feature, &ground_truth, weights)?,
+        None => perform_segmentation_data_bias(&feature, &ground_truth)?,
+    };
+    let mut report = registry.analyze(&pre_training)?;
+    report.status = support
+        .map(|thresholds| registry.statuses(&pre_training, &report, thresholds))
+        .transpose()?;
+    report.missing = Some(missing);
+    report.effective_sample_size = Some(pre_training.effective_sample_size());
+    report.confidence_intervals = confidence
+        .map(|config| {
+            bootstrap_data_bias(
+                registry,
+                &feature,
+                &ground_truth,
+                sample_weight.as_deref(),
+                config,
+            )
+        })
+        .transpose()?;
+    report.significance = significance
+        .map(|config| data_bias_significance(&pre_training, config))
+        .transpose()?;
+    Ok(report)
+}
+
+fn check_unweighted(
+    sample_weight: &Option<Vec<f64>>,
+    significance: &Option<SignificanceConfig>,
+) -> Result<(), FairMlError> {
+    if sample_weight.is_some() && significance.is_some() {
+        return Err(FairMlError::InvalidSampleWeight {
+            reason: "significance tests need unweighted counts".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Continuous data bias from a labeled feature and raw outcome values, NaN
+/// marking a missing outcome. A row missing its feature belongs to neither
+/// facet, so `OwnGroup` leaves it out like `Drop`, and `Negative` labels it
+/// facet d and fills a missing outcome with 0.
+pub fn labeled_continuous_data_bias(
+    policy: MissingPolicy,
+    feature: Vec<i16>,
+    mut outcomes: Vec<f64>,
+) -> Result<ContinuousDataBiasReport, FairMlError> {
+    check_lengths("feature", &feature, "ground_truth", &outcomes)?;
+    let mut missing = MissingCounts::default();
+    for (label, outcome) in feature.iter().zip(&outcomes) {
+        match *label {
+            MISSING_LABEL => missing.feature += 1,
+            1 if outcome.is_nan() => missing.facet_a += 1,
+            _ if outcome.is_nan() => missing.facet_d += 1,
+            _ => {}
+        }
+    }
+    let policy = match policy {
+        MissingPolicy::OwnGroup => MissingPolicy::Drop,
+        policy => policy,
+    };
+    let mut feature: Vec<f64> = feature
+        .into_iter()
+        .map(|label| match label {
+            MISSING_LABEL => f64::NAN,
+            label => f64::from(label),
+        })
+        .collect();
+    apply_missing_policy_values(
+        policy,
+        vec![("feature", &mut feature), ("ground_truth", &mut outcomes)],
+        None,
+    )?;
+    let feature: Vec<i16> = feature.into_iter().map(|label| label as i16).collect();
+    let data = perform_segmentation_data_bias_continuous(&feature, &outcomes)?;
+    let mut report = continuous_pre_training_bias(data);
+    report.missing = Some(missing);
+    Ok(report)
+}
+
+/// Model bias from labeled columns, see `labeled_data_bias`.
+#[allow(clippy::too_many_arguments)]
+pub fn labeled_model_bias(
+    registry: &ModelBiasRegistry,
+    policy: MissingPolicy,
+    mut feature: Vec<i16>,
+    mut predictions: Vec<i16>,
+    mut ground_truth: Vec<i16>,
+    mut sample_weight: Option<Vec<f64>>,
+    confidence: Option<BootstrapConfig>,
+    significance: Option<SignificanceConfig>,
+    support: Option<SupportThresholds>,
+) -> Result<ModelBiasReport, FairMlError> {
+    if let Some(weights) = &sample_weight {
+        check_sample_weights("feature", &feature, weights)?;
+    }
+    check_unweighted(&sample_weight, &significance)?;
+    let missing = apply_missing_policy(
+        policy,
+        Some(("feature", &mut feature)),
+        vec![
+            ("prediction", &mut predictions),
+            ("ground_truth", &mut ground_truth),
+        ],
+        sample_weight.as_mut(),
+    )?;
+    let post_training: PostTrainingData = match &sample_weight {
+        Some(weights) => perform_segmentation_model_bias_weighted(
+            &feature,
+            &predictions,
+            &ground_truth,
+            weights,
+        )?,
+        None => perform_segmentation_model_bias(&feature, &predictions, &ground_truth)?,
+    };
+    let mut report = registry.analyze(&post_training)?;
+    report.status = support
+        .map(|thresholds| registry.statuses(&post_training, &report, thresholds))
+        .transpose()?;
+    report.missing = Some(missing);
+    report.effective_sample_size = Some(post_training.effective_sample_size());
+    report.confidence_intervals = confidence
+        .map(|config| {
+            bootstrap_model_bias(
+                registry,
+                &feature,
+                &predictions,
+                &ground_truth,
+                sample_weight.as_deref(),
+                config,
+            )
+        })
+        .transpose()?;
+    report.significance = significance
+        .map(|config| model_bias_significance(&post_training, config))
+        .transpose()?;
+    Ok(report)
+}
+
+/// Outcome values for the performance metrics of a frame, NaN marks a
+/// missing value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PerformanceInput {
+    BinaryClassification {
+        y_true: Vec<f32>,
+        y_pred: Vec<f32>,
+    },
+    LogisticRegression {
+        y_true: Vec<f32>,
+        y_proba: Vec<f32>,
+        threshold: f32,
+    },
+    LinearRegression {
+        y_true: Vec<f32>,
+        y_pred: Vec<f32>,
+    },
+}
+
+/// Performance of a frame, serialized like the baselines the performance
+/// runtime checks take, with the model type next to the report.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "modelType", content = "performanceData")]
+pub enum PerformanceData {
+    BinaryClassification(BinaryClassificationReport),
+    LogisticRegression(LogisticRegressionReport),
+    LinearRegression(LinearRegressionReport),
+}
+
+impl PerformanceInput {
+    /// `OwnGroup` has no meaning without facets, rows with a missing value
+    /// are dropped instead.
+    fn analyze(
+        self,
+        policy: MissingPolicy,
+        mut sample_weight: Option<Vec<f64>>,
+        confidence: Option<BootstrapConfig>,
+    ) -> Result<PerformanceData, FairMlError> {
+        let policy = match policy {
+            MissingPolicy::OwnGroup => MissingPolicy::Drop,
+            policy => policy,
+        };
+        let mut prepare = |mut y_true: Vec<f32>, mut y_pred: Vec<f32>| {
+            if let Some(weights) = &sample_weight {
+                check_sample_weights("y_true", &y_true, weights)?;
+            }
+            apply_missing_policy_values(
+                policy,
+                vec![("y_true", &mut y_true), ("y_pred", &mut y_pred)],
+                sample_weight.as_mut(),
+            )?;
+            let weights: Option<Vec<f32>> = sample_weight
+                .take()
+                .map(|weights| weights.into_iter().map(|w| w as f32).collect());
+            Ok::<_, FairMlError>((y_true, y_pred, weights))
+        };
+        Ok(match self {
+            PerformanceInput::BinaryClassification { y_true, y_pred } => {
+                let (y_true, y_pred, weights) = prepare(y_true, y_pred)?;
+                let intervals = confidence
+                    .map(|config| {
+                        bootstrap_classification(&y_true, &y_pred, weights.as_deref(), config)
+                    })
+                    .transpose()?;
+                let mut report: BinaryClassificationReport =
+                    ClassificationPerf::from_vecs(y_true, y_pred, weights)?.into();
+                report.confidence_intervals = intervals;
+                PerformanceData::BinaryClassification(report)
+            }
+            PerformanceInput::LogisticRegression {
+                y_true,
+                y_proba,
+                threshold,
+            } => {
+                let (y_true, y_proba, weights) = prepare(y_true, y_proba)?;
+                let intervals = confidence
+                    .map(|config| {
+                        bootstrap_logistic_regression(
+                            &y_true,
+                            &y_proba,
+                            threshold,
+                            weights.as_deref(),
+                            config,
+                        )
+                    })
+                    .transpose()?;
+                let mut report: LogisticRegressionReport =
+                    LogisticRegressionPerf::from_vecs(y_true, y_proba, threshold, weights)?.into();
+                report.confidence_intervals = intervals;
+                PerformanceData::LogisticRegression(report)
+            }
+            PerformanceInput::LinearRegression { y_true, y_pred } => {
+                let (y_true, y_pred, weights) = prepare(y_true, y_pred)?;
+                let intervals = confidence
+                    .map(|config| {
+                        bootstrap_linear_regression(&y_true, &y_pred, weights.as_deref(), config)
+                    })
+                    .transpose()?;
+                let mut report: LinearRegressionReport =
+                    LinearRegressionPerf::from_vecs(y_true, y_pred, weights)?.into();
+                report.confidence_intervals = intervals;
+                PerformanceData::LinearRegression(report)
+            }
+        })
+    }
+}
+
+/// Labeled columns of a frame. Every facet column is labeled with its own
+/// rule, the label and prediction columns are shared by all of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameColumns {
+    pub facets: Vec<(String, Vec<i16>)>,
+    pub label: Vec<i16>,
+    pub prediction: Option<Vec<i16>>,
+    pub performance: Option<PerformanceInput>,
+    pub sample_weight: Option<Vec<f64>>,
+}
+
+/// Bias reports of one facet column. `model_bias` is `None` when the frame
+/// has no prediction column. Both reports cover the same rows, so they carry
+/// the same missing counts and effective sample size.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FacetReport {
+    pub facet: String,
+    pub data_bias: DataBiasReport,
+    pub model_bias: Option<ModelBiasReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameReport {
+    pub facets: Vec<FacetReport>,
+    pub performance: Option<PerformanceData>,
+}
+
+/// Data bias and model bias for every facet column, facets in parallel, and
+/// the performance of the model over every row. The missing value policy is
+/// applied per facet, so a row missing one facet still counts for the others,
+/// and a row missing its prediction is left out of the data bias as well.
+/// `confidence`, `significance` and `support` are passed on to
+/// `labeled_data_bias` and `labeled_model_bias`, and `confidence` bounds the
+/// performance metrics as well.
+pub fn analyze_frame(
+    columns: FrameColumns,
+    policy: MissingPolicy,
+    data_registry: &DataBiasRegistry,
+    model_registry: &ModelBiasRegistry,
+    confidence: Option<BootstrapConfig>,
+    significance: Option<SignificanceConfig>,
+    support: Option<SupportThresholds>,
+) -> Result<FrameReport, FairMlError> {
+    let FrameColumns {
+        facets,
+        label,
+        prediction,
+        performance,
+        sample_weight,
+    } = columns;
+    if facets.is_empty() {
+        return Err(FairMlError::EmptyInput {
+            array: "facets".into(),
+        });
+    }
+    let facets = facets
+        .into_par_iter()
+        .map(|(facet, mut feature)| {
+            let mut label = label.clone();
+            let mut prediction = prediction.clone();
+            let mut sample_weight = sample_weight.clone();
+            let mut outcomes = vec![("ground_truth", &mut label)];
+            if let Some(prediction) = &mut prediction {
+                outcomes.push(("prediction", prediction));
+            }
+            if let Some(weights) = &sample_weight {
+                check_sample_weights(&facet, &feature, weights)?;
+            }
+            let missing = apply_missing_policy(
+                policy,
+                Some((&facet, &mut feature)),
+                outcomes,
+                sample_weight.as_mut(),
+            )?;
+            // the rows are settled, the policy has nothing left to change
+            let model_bias = prediction
+                .map(|prediction| {
+                    let mut report = labeled_model_bias(
+                        model_registry,
+                        policy,
+                        feature.clone(),
+                        prediction,
+                        label.clone(),
+                        sample_weight.clone(),
+                        confidence,
+                        significance,
+                        support,
+                    )?;
+                    report.missing = Some(missing);
+                    Ok::<_, FairMlError>(report)
+                })
+                .transpose()?;
+            let mut data_bias = labeled_data_bias(
+                data_registry,
+                policy,
+                feature,
+                label,
+                sample_weight,
+                confidence,
+                significance,
+                support,
+            )?;
+            data_bias.missing = Some(missing);
+            Ok(FacetReport {
+                facet,
+                data_bias,
+                model_bias,
+            })
+        })
+        .collect::<Result<Vec<FacetReport>, FairMlError>>()?;
+    let performance = performance
+        .map(|input| input.analyze(policy, sample_weight, confidence))
+        .transpose()?;
+    Ok(FrameReport {
+        facets,
+        performance,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_reports_round_trip_through_json() {
+        let columns = FrameColumns {
+            facets: vec![("sex".into(), vec![1, 1, 1, 0, 0, 0])],
+            label: vec![1, 0, 1, 1, 0, 0],
+            prediction: Some(vec![1, 1, 0, 1, 0, 1]),
+            performance: Some(PerformanceInput::BinaryClassification {
+                y_true: vec![1.0, 0.0, 1.0, 1.0, 0.0, 0.0],
+                y_pred: vec![1.0, 1.0, 0.0, 1.0, 0.0, 1.0],
+            }),
+            sample_weight: None,
+        };
+        let report = analyze_frame(
+            columns,
+            MissingPolicy::Raise,
+            &DataBiasRegistry::new(),
+            &ModelBiasRegistry::new(),
+            None,
+            None,
+            Some(SupportThresholds::default()),
+        )
+        .unwrap();
+        let facet = &report.facets[0];
+        assert!(facet.data_bias.status.is_some() && facet.data_bias.missing.is_some());
+        assert_eq!(
+            facet.model_bias.as_ref().unwrap().missing,
+            facet.data_bias.missing
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["performance"]["modelType"], "BinaryClassification");
+        let read: FrameReport = serde_json::from_value(json).unwrap();
+        assert_eq!(read, report);
+    }
+
+    #[test]
+    fn continuous_outcomes_keep_their_precision() {
+        // as f32 both facets would round to 1e9
+        let outcomes = vec![1e9 + 1_f64, 1e9 + 1_f64, 1e9, 1e9];
+        let report =
+            labeled_continuous_data_bias(MissingPolicy::Raise, vec![1, 1, 0, 0], outcomes).unwrap();
+        assert_eq!(report.difference_in_means, 1_f32);
+        assert_eq!(report.wasserstein_distance, 1_f32);
+    }
+
+    #[test]
+    fn missing_continuous_outcomes_follow_the_policy() {
+        let outcomes = vec![1_f64, f64::NAN, 3_f64, 5_f64];
+        let report =
+            labeled_continuous_data_bias(MissingPolicy::Drop, vec![1, 1, 0, 0], outcomes.clone())
+                .unwrap();
+        assert_eq!(report.difference_in_means, -3_f32);
+        assert_eq!(
+            report.missing,
+            Some(MissingCounts {
+                facet_a: 1,
+                facet_d: 0,
+                feature: 0
+            })
+        );
+        assert!(
+            labeled_continuous_data_bias(MissingPolicy::Raise, vec![1, 1, 0, 0], outcomes).is_err()
+        );
+    }
+}
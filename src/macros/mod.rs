@@ -6,3 +6,89 @@ macro_rules! zip {
             zip!($($y), +))
     )
 }
+
+/// Declares a report with one `f32` field per metric. The key written next to
+/// each field is the only place the metric name is spelled out: it becomes the
+/// serde key, an associated constant and the entry in `NAMES`. A trailing
+/// `extra` field collects metrics that are not built in, e.g. ones registered
+/// at runtime. Fields marked `?` are optional context that is not a metric,
+/// such as missing value counts, kept under their own serde key and left out
+/// of the metric entries.
+#[macro_export]
+macro_rules! metric_report {
+    (
+        $(#[$meta:meta])*
+        pub struct $report:ident {
+            $($field:ident: $constant:ident = $key:literal,)+
+            $(..$extra:ident,)?
+            $(?$optional:ident: $optional_ty:ty = $optional_key:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+        pub struct $report {
+            $(
+                #[serde(rename = $key, deserialize_with = "crate::runtime::f32_or_nan")]
+                pub $field: f32,
+            )+
+            $(
+                #[serde(flatten, deserialize_with = "crate::runtime::metrics_or_nan")]
+                pub $extra: std::collections::BTreeMap<String, f32>,
+            )?
+            $(
+                #[serde(rename = $optional_key, default, skip_serializing_if = "Option::is_none")]
+                pub $optional: Option<$optional_ty>,
+            )*
+        }
+
+        impl $report {
+            $(pub const $constant: &'static str = $key;)+
+
+            /// Keys of the built in metrics, in report order.
+            pub const NAMES: &'static [&'static str] = &[$($key),+];
+
+            /// (key, value) pairs in report order.
+            pub fn entries(&self) -> Vec<(&str, f32)> {
+                #[allow(unused_mut)]
+                let mut entries: Vec<(&str, f32)> = vec![$(($key, self.$field)),+];
+                $(entries.extend(self.$extra.iter().map(|(k, v)| (k.as_str(), *v)));)?
+                entries
+            }
+
+            /// Value of a metric by its rust field name.
+            pub fn field(&self, field: &str) -> Option<f32> {
+                match field {
+                    $(stringify!($field) => Some(self.$field),)+
+                    _ => None,
+                }
+            }
+
+            pub fn to_map(&self) -> std::collections::HashMap<String, f32> {
+                self.entries()
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v))
+                    .collect()
+            }
+        }
+
+        impl TryFrom<std::collections::HashMap<String, f32>> for $report {
+            type Error = $crate::errors::FairMlError;
+            #[allow(unused_mut)]
+            fn try_from(
+                mut map: std::collections::HashMap<String, f32>,
+            ) -> Result<Self, Self::Error> {
+                $crate::runtime::canonicalize_keys(&mut map);
+                Ok($report {
+                    $($field: $crate::runtime::get_metric(&map, $key)?,)+
+                    $(
+                        $extra: map
+                            .into_iter()
+                            .filter(|(k, _)| !Self::NAMES.contains(&k.as_str()))
+                            .collect(),
+                    )?
+                    $($optional: None,)*
+                })
+            }
+        }
+    };
+}
@@ -1,230 +1,222 @@
+use crate::bootstrap::ConfidenceIntervals;
+use crate::data_handler::{
+    check_facets, count_facets_model_bias, effective_size, EffectiveSampleSize, MissingCounts,
+};
+use crate::errors::FairMlError;
+use crate::metric_report;
+use crate::multi_group::ConditionalDisparity;
+use crate::runtime::{compare_to_baseline, get_metric, CheckStatuses, Direction};
+use crate::significance::Significance;
+use crate::state::AccumulatorState;
+use crate::status::{metric_status, Facet, MetricStatuses, SupportThresholds};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::error::Error;
-
-pub enum ModelBiasMetrics {
-    DifferenceInPositivePredictedLabels,
-    DisparateImpact,
-    AccuracyDifference,
-    RecallDifference,
-    DifferenceInConditionalAcceptance,
-    DifferenceInAcceptanceRate,
-    SpecialityDifference,
-    DifferenceInConditionalRejection,
-    DifferenceInRejectionRate,
-    TreatmentEquity,
-    ConditionalDemographicDesparityPredictedLabels,
-    GeneralizedEntropy,
-}
-
-pub const FULL_MODEL_BIAS_METRICS: [ModelBiasMetrics; 12] = [
-    ModelBiasMetrics::DifferenceInPositivePredictedLabels,
-    ModelBiasMetrics::DisparateImpact,
-    ModelBiasMetrics::AccuracyDifference,
-    ModelBiasMetrics::RecallDifference,
-    ModelBiasMetrics::DifferenceInConditionalAcceptance,
-    ModelBiasMetrics::DifferenceInAcceptanceRate,
-    ModelBiasMetrics::SpecialityDifference,
-    ModelBiasMetrics::DifferenceInConditionalRejection,
-    ModelBiasMetrics::DifferenceInRejectionRate,
-    ModelBiasMetrics::TreatmentEquity,
-    ModelBiasMetrics::ConditionalDemographicDesparityPredictedLabels,
-    ModelBiasMetrics::GeneralizedEntropy,
-];
-
-pub fn map_string_to_metrics(
-    metrics: Vec<String>,
-) -> Result<Vec<ModelBiasMetrics>, Box<dyn Error>> {
-    let mut tms: Vec<ModelBiasMetrics> = Vec::with_capacity(metrics.len());
-    for str_m in metrics.into_iter() {
-        let m = ModelBiasMetrics::try_from(str_m.as_str())?;
-        tms.push(m)
-    }
-    Ok(tms)
-}
-
-impl TryFrom<&str> for ModelBiasMetrics {
-    type Error = String;
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "DifferenceInPositivePredictedLabels" => Ok(Self::DifferenceInPositivePredictedLabels),
-            "DisparateImpact" => Ok(Self::DisparateImpact),
-            "AccuracyDifference" => Ok(Self::AccuracyDifference),
-            "RecallDifference" => Ok(Self::RecallDifference),
-            "DifferenceInConditionalAcceptance" => Ok(Self::DifferenceInConditionalAcceptance),
-            "DifferenceInAcceptanceRate" => Ok(Self::DifferenceInAcceptanceRate),
-            "SpecialityDifference" => Ok(Self::SpecialityDifference),
-            "DifferenceInConditionalRejection" => Ok(Self::DifferenceInConditionalRejection),
-            "DifferenceInRejectionRate" => Ok(Self::DifferenceInRejectionRate),
-            "TreatmentEquity" => Ok(Self::TreatmentEquity),
-            "ConditionalDemographicDesparityPredictedLabels" => {
-                Ok(Self::ConditionalDemographicDesparityPredictedLabels)
-            }
-            "GeneralizedEntropy" => Ok(Self::GeneralizedEntropy),
-            _ => Err("Invalid metric passed".into()),
-        }
-    }
+use std::sync::Arc;
+
+/// Confusion matrix counts for one facet, in rows, filled in a single pass
+/// over the labeled rows. `weighted` holds the same counts with each row
+/// adding its sample weight, the totals the metrics are computed from, and
+/// equals the row counts when unweighted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FacetConfusion {
+    pub true_positives: u64,
+    pub false_positives: u64,
+    pub false_negatives: u64,
+    pub true_negatives: u64,
+    pub weighted: WeightedConfusion,
+    /// Sum of the squared row weights, for the effective sample size.
+    pub weight_squares: f64,
 }
 
-pub struct PostTrainingData {
-    pub facet_a_scores: Vec<i16>,
-    pub facet_d_scores: Vec<i16>,
-    pub facet_a_trues: Vec<i16>,
-    pub facet_d_trues: Vec<i16>,
+/// Sample weight totals of the confusion matrix cells of a facet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeightedConfusion {
+    pub true_positives: f64,
+    pub false_positives: f64,
+    pub false_negatives: f64,
+    pub true_negatives: f64,
 }
 
-pub struct PostTrainingComputations {
-    pub true_positives_a: f32,
-    pub true_positives_d: f32,
-    pub false_positives_a: f32,
-    pub false_positives_d: f32,
-    pub false_negatives_a: f32,
-    pub false_negatives_d: f32,
-    pub true_negatives_a: f32,
-    pub true_negatives_d: f32,
+impl WeightedConfusion {
+    pub fn merge(self, other: WeightedConfusion) -> WeightedConfusion {
+        WeightedConfusion {
+            true_positives: self.true_positives + other.true_positives,
+            false_positives: self.false_positives + other.false_positives,
+            false_negatives: self.false_negatives + other.false_negatives,
+            true_negatives: self.true_negatives + other.true_negatives,
+        }
+    }
+
+    pub fn len(&self) -> f64 {
+        self.true_positives + self.false_positives + self.false_negatives + self.true_negatives
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() <= 0_f64
+    }
+
+    pub fn predicted_positives(&self) -> f64 {
+        self.true_positives + self.false_positives
+    }
+
+    pub fn predicted_negatives(&self) -> f64 {
+        self.false_negatives + self.true_negatives
+    }
+
+    pub fn actual_positives(&self) -> f64 {
+        self.true_positives + self.false_negatives
+    }
+
+    pub fn actual_negatives(&self) -> f64 {
+        self.false_positives + self.true_negatives
+    }
 }
 
-impl PostTrainingData {
-    pub fn general_data_computations(&self) -> PostTrainingComputations {
-        PostTrainingComputations {
-            true_positives_a: self.true_positives_a(),
-            true_positives_d: self.true_positives_d(),
-            false_positives_a: self.false_positives_a(),
-            false_positives_d: self.false_positives_d(),
-            false_negatives_a: self.false_negatives_a(),
-            false_negatives_d: self.false_negatives_d(),
-            true_negatives_a: self.true_negatives_a(),
-            true_negatives_d: self.true_negatives_d(),
+impl FacetConfusion {
+    pub fn record(&mut self, prediction: i16, ground_truth: i16) {
+        self.record_weighted(prediction, ground_truth, 1_f64);
+    }
+
+    pub fn record_weighted(&mut self, prediction: i16, ground_truth: i16, weight: f64) {
+        match (prediction == 1, ground_truth == 1) {
+            (true, true) => {
+                self.true_positives += 1;
+                self.weighted.true_positives += weight;
+            }
+            (true, false) => {
+                self.false_positives += 1;
+                self.weighted.false_positives += weight;
+            }
+            (false, true) => {
+                self.false_negatives += 1;
+                self.weighted.false_negatives += weight;
+            }
+            (false, false) => {
+                self.true_negatives += 1;
+                self.weighted.true_negatives += weight;
+            }
         }
+        self.weight_squares += weight * weight;
     }
 
-    fn true_positives_a(&self) -> f32 {
-        self.facet_a_scores
-            .iter()
-            .zip(self.facet_a_trues.iter())
-            .map(|(y_pred, y_true)| {
-                if *y_pred == 1_i16 && *y_true == 1_i16 {
-                    1_f32
-                } else {
-                    0_f32
-                }
-            })
-            .sum::<f32>()
-            .into()
+    pub fn merge(self, other: FacetConfusion) -> FacetConfusion {
+        FacetConfusion {
+            true_positives: self.true_positives + other.true_positives,
+            false_positives: self.false_positives + other.false_positives,
+            false_negatives: self.false_negatives + other.false_negatives,
+            true_negatives: self.true_negatives + other.true_negatives,
+            weighted: self.weighted.merge(other.weighted),
+            weight_squares: self.weight_squares + other.weight_squares,
+        }
     }
 
-    fn true_positives_d(&self) -> f32 {
-        self.facet_d_scores
-            .iter()
-            .zip(self.facet_d_trues.iter())
-            .map(|(y_pred, y_true)| {
-                if *y_pred == 1_i16 && *y_true == 1_i16 {
-                    1_f32
-                } else {
-                    0_f32
-                }
-            })
-            .sum::<f32>()
+    pub fn len(&self) -> u64 {
+        self.true_positives + self.false_positives + self.false_negatives + self.true_negatives
     }
 
-    fn false_positives_a(&self) -> f32 {
-        self.facet_a_scores
-            .iter()
-            .zip(self.facet_a_trues.iter())
-            .map(|(y_pred, y_true)| {
-                if *y_pred == 1_i16 && *y_true == 0_i16 {
-                    1_f32
-                } else {
-                    0_f32
-                }
-            })
-            .sum::<f32>()
+    /// Whether the facet has no weight to compute a rate from, which
+    /// includes a facet whose rows all weigh 0.
+    pub fn is_empty(&self) -> bool {
+        self.weighted.is_empty()
     }
 
-    fn false_positives_d(&self) -> f32 {
-        self.facet_d_scores
-            .iter()
-            .zip(self.facet_d_trues.iter())
-            .map(|(y_pred, y_true)| {
-                if *y_pred == 1 && *y_true == 0 {
-                    1_f32
-                } else {
-                    0_f32
-                }
-            })
-            .sum::<f32>()
+    /// Kish effective sample size, (sum of weights)^2 / sum of squared weights.
+    pub fn effective_size(&self) -> f64 {
+        effective_size(self.weighted.len(), self.weight_squares)
     }
 
-    fn false_negatives_a(&self) -> f32 {
-        self.facet_a_scores
-            .iter()
-            .zip(self.facet_a_trues.iter())
-            .map(|(y_pred, y_true)| {
-                if *y_pred == 0 && *y_true == 1 {
-                    1_f32
-                } else {
-                    0_f32
-                }
-            })
-            .sum::<f32>()
+    pub fn predicted_positives(&self) -> u64 {
+        self.true_positives + self.false_positives
     }
 
-    fn false_negatives_d(&self) -> f32 {
-        self.facet_d_scores
-            .iter()
-            .zip(self.facet_d_trues.iter())
-            .map(|(y_pred, y_true)| {
-                if *y_pred == 0 && *y_true == 1 {
-                    1_f32
-                } else {
-                    0_f32
-                }
-            })
-            .sum::<f32>()
+    pub fn predicted_negatives(&self) -> u64 {
+        self.false_negatives + self.true_negatives
     }
 
-    fn true_negatives_a(&self) -> f32 {
-        self.facet_a_scores
-            .iter()
-            .zip(self.facet_a_trues.iter())
-            .map(|(y_pred, y_true)| {
-                if *y_pred == 0 && *y_true == 0 {
-                    1_f32
-                } else {
-                    0_f32
-                }
-            })
-            .sum::<f32>()
+    pub fn actual_positives(&self) -> u64 {
+        self.true_positives + self.false_negatives
     }
 
-    fn true_negatives_d(&self) -> f32 {
-        self.facet_d_scores
-            .iter()
-            .zip(self.facet_d_trues.iter())
-            .map(|(y_pred, y_true)| {
-                if *y_pred == 0 && *y_true == 0 {
-                    1_f32
-                } else {
-                    0_f32
-                }
-            })
-            .sum::<f32>()
+    pub fn actual_negatives(&self) -> u64 {
+        self.false_positives + self.true_negatives
+    }
+}
+
+/// A confusion count of one facet that a built in metric divides by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfusionCount {
+    Rows,
+    FalsePositives,
+    PredictedPositives,
+    PredictedNegatives,
+    ActualPositives,
+    ActualNegatives,
+}
+
+impl ConfusionCount {
+    fn name(self) -> &'static str {
+        match self {
+            ConfusionCount::Rows => "rows",
+            ConfusionCount::FalsePositives => "false positives",
+            ConfusionCount::PredictedPositives => "predicted positives",
+            ConfusionCount::PredictedNegatives => "predicted negatives",
+            ConfusionCount::ActualPositives => "actual positives",
+            ConfusionCount::ActualNegatives => "actual negatives",
+        }
+    }
+
+    fn of(self, facet: &FacetConfusion) -> f64 {
+        match self {
+            ConfusionCount::Rows => facet.weighted.len(),
+            ConfusionCount::FalsePositives => facet.weighted.false_positives,
+            ConfusionCount::PredictedPositives => facet.weighted.predicted_positives(),
+            ConfusionCount::PredictedNegatives => facet.weighted.predicted_negatives(),
+            ConfusionCount::ActualPositives => facet.weighted.actual_positives(),
+            ConfusionCount::ActualNegatives => facet.weighted.actual_negatives(),
+        }
+    }
+
+    /// The count in both facets, for a difference of two rates.
+    const fn both(self) -> [(Facet, ConfusionCount); 2] {
+        [(Facet::A, self), (Facet::D, self)]
+    }
+}
+
+const ROWS: [(Facet, ConfusionCount); 2] = ConfusionCount::Rows.both();
+const FALSE_POSITIVES: [(Facet, ConfusionCount); 2] = ConfusionCount::FalsePositives.both();
+const PREDICTED_POSITIVES: [(Facet, ConfusionCount); 2] = ConfusionCount::PredictedPositives.both();
+const PREDICTED_NEGATIVES: [(Facet, ConfusionCount); 2] = ConfusionCount::PredictedNegatives.both();
+const ACTUAL_POSITIVES: [(Facet, ConfusionCount); 2] = ConfusionCount::ActualPositives.both();
+const ACTUAL_NEGATIVES: [(Facet, ConfusionCount); 2] = ConfusionCount::ActualNegatives.both();
+
+pub struct PostTrainingData {
+    pub facet_a: FacetConfusion,
+    pub facet_d: FacetConfusion,
+}
+
+impl PostTrainingData {
+    pub fn effective_sample_size(&self) -> EffectiveSampleSize {
+        EffectiveSampleSize {
+            facet_a: self.facet_a.effective_size(),
+            facet_d: self.facet_d.effective_size(),
+        }
     }
 }
 
 pub fn diff_in_pos_proportion_in_pred_labels(data: &PostTrainingData) -> f32 {
-    let q_prime_a: f32 = data.facet_a_scores.iter().sum::<i16>() as f32
-        / data.facet_a_trues.iter().sum::<i16>() as f32;
-    let q_prime_d: f32 = data.facet_d_scores.iter().sum::<i16>() as f32
-        / data.facet_d_trues.iter().sum::<i16>() as f32;
+    let q_prime_a: f32 = data.facet_a.weighted.predicted_positives() as f32
+        / data.facet_a.weighted.actual_positives() as f32;
+    let q_prime_d: f32 = data.facet_d.weighted.predicted_positives() as f32
+        / data.facet_d.weighted.actual_positives() as f32;
 
-    return q_prime_a - q_prime_d;
+    q_prime_a - q_prime_d
 }
 
 pub fn disparate_impact(data: &PostTrainingData) -> f32 {
-    let q_prime_a: f32 = data.facet_a_scores.iter().sum::<i16>() as f32
-        / data.facet_d_trues.iter().sum::<i16>() as f32;
-    let q_prime_d: f32 = data.facet_d_scores.iter().sum::<i16>() as f32
-        / data.facet_d_trues.iter().sum::<i16>() as f32;
+    let q_prime_a: f32 = data.facet_a.weighted.predicted_positives() as f32
+        / data.facet_d.weighted.actual_positives() as f32;
+    let q_prime_d: f32 = data.facet_d.weighted.predicted_positives() as f32
+        / data.facet_d.weighted.actual_positives() as f32;
 
     if q_prime_d == 0.0 {
         return 0.0;
@@ -232,213 +224,610 @@ pub fn disparate_impact(data: &PostTrainingData) -> f32 {
     q_prime_a / q_prime_d
 }
 
-pub fn accuracy_difference(
-    pre_computed_data: &PostTrainingComputations,
-    data: &PostTrainingData,
-) -> f32 {
-    let acc_a: f32 = (pre_computed_data.true_positives_a + pre_computed_data.true_negatives_a)
-        / data.facet_a_scores.len() as f32;
+pub fn accuracy_difference(data: &PostTrainingData) -> f32 {
+    let acc_a: f32 = (data.facet_a.weighted.true_positives + data.facet_a.weighted.true_negatives)
+        as f32
+        / data.facet_a.weighted.len() as f32;
 
-    let acc_d: f32 = (pre_computed_data.true_positives_d + pre_computed_data.true_negatives_d)
-        / data.facet_d_scores.len() as f32;
+    let acc_d: f32 = (data.facet_d.weighted.true_positives + data.facet_d.weighted.true_negatives)
+        as f32
+        / data.facet_d.weighted.len() as f32;
 
-    return acc_a - acc_d;
+    acc_a - acc_d
 }
 
-pub fn recall_difference(pre_computed_data: &PostTrainingComputations) -> f32 {
-    let recall_a: f32 = pre_computed_data.true_positives_a
-        / (pre_computed_data.true_positives_a + pre_computed_data.false_negatives_a);
-    let recall_d: f32 = pre_computed_data.true_positives_d
-        / (pre_computed_data.true_positives_d + pre_computed_data.false_negatives_d);
+pub fn recall_difference(data: &PostTrainingData) -> f32 {
+    let recall_a: f32 = data.facet_a.weighted.true_positives as f32
+        / data.facet_a.weighted.actual_positives() as f32;
+    let recall_d: f32 = data.facet_d.weighted.true_positives as f32
+        / data.facet_d.weighted.actual_positives() as f32;
 
     recall_a - recall_d
 }
 
 pub fn diff_in_cond_acceptance(data: &PostTrainingData) -> f32 {
-    let sum_true_facet_a: f32 = data.facet_a_trues.iter().sum::<i16>().into();
-    let sum_scores_facet_a: f32 = data.facet_a_scores.iter().sum::<i16>().into();
-    let c_facet_a: f32 = sum_true_facet_a / sum_scores_facet_a;
-
-    let sum_true_facet_d: f32 = data.facet_d_trues.iter().sum::<i16>().into();
-    let sum_scores_facet_d: f32 = data.facet_d_scores.iter().sum::<i16>().into();
-    let c_facet_d: f32 = sum_true_facet_d / sum_scores_facet_d;
+    let c_facet_a: f32 = data.facet_a.weighted.actual_positives() as f32
+        / data.facet_a.weighted.predicted_positives() as f32;
+    let c_facet_d: f32 = data.facet_d.weighted.actual_positives() as f32
+        / data.facet_d.weighted.predicted_positives() as f32;
 
     c_facet_a - c_facet_d
 }
 
-pub fn diff_in_acceptance_rate(pre_computed_data: &PostTrainingComputations) -> f32 {
-    let precision_a: f32 = pre_computed_data.true_positives_a
-        / (pre_computed_data.true_positives_a + pre_computed_data.false_positives_a);
-    let precision_d: f32 = pre_computed_data.true_positives_d
-        / (pre_computed_data.true_positives_d + pre_computed_data.false_positives_d);
+pub fn diff_in_acceptance_rate(data: &PostTrainingData) -> f32 {
+    let precision_a: f32 = data.facet_a.weighted.true_positives as f32
+        / data.facet_a.weighted.predicted_positives() as f32;
+    let precision_d: f32 = data.facet_d.weighted.true_positives as f32
+        / data.facet_d.weighted.predicted_positives() as f32;
 
     precision_a - precision_d
 }
 
-pub fn specailty_difference(pre_computed_data: &PostTrainingComputations) -> f32 {
-    let true_negative_rate_d: f32 = pre_computed_data.true_negatives_d
-        / (pre_computed_data.true_negatives_d + pre_computed_data.false_positives_d);
-    let true_negative_rate_a: f32 = pre_computed_data.true_negatives_a
-        / (pre_computed_data.true_negatives_a + pre_computed_data.false_positives_a);
+pub fn specailty_difference(data: &PostTrainingData) -> f32 {
+    let true_negative_rate_d: f32 = data.facet_d.weighted.true_negatives as f32
+        / data.facet_d.weighted.actual_negatives() as f32;
+    let true_negative_rate_a: f32 = data.facet_a.weighted.true_negatives as f32
+        / data.facet_a.weighted.actual_negatives() as f32;
 
     true_negative_rate_d - true_negative_rate_a
 }
 
 pub fn diff_in_cond_rejection(data: &PostTrainingData) -> f32 {
-    let n_prime_d: f32 = data
-        .facet_d_scores
-        .iter()
-        .map(|value| if *value == 0 { 1_f32 } else { 0_f32 })
-        .sum::<f32>();
-    let n_d: f32 = data
-        .facet_d_trues
-        .iter()
-        .map(|value| if *value == 0 { 1_f32 } else { 0_f32 })
-        .sum::<f32>();
-    let r_d: f32 = n_d / n_prime_d;
-
-    let n_prime_a: f32 = data
-        .facet_a_scores
-        .iter()
-        .map(|value| if *value == 0 { 1_f32 } else { 0_f32 })
-        .sum::<f32>();
-    let n_a: f32 = data
-        .facet_a_trues
-        .iter()
-        .map(|value| if *value == 0 { 1_f32 } else { 0_f32 })
-        .sum::<f32>();
-    let r_a: f32 = n_a / n_prime_a;
+    let r_d: f32 = data.facet_d.weighted.actual_negatives() as f32
+        / data.facet_d.weighted.predicted_negatives() as f32;
+    let r_a: f32 = data.facet_a.weighted.actual_negatives() as f32
+        / data.facet_a.weighted.predicted_negatives() as f32;
 
     r_d - r_a
 }
 
-pub fn diff_in_rejection_rate(pre_computed_data: &PostTrainingComputations) -> f32 {
-    let value_d: f32 = pre_computed_data.true_negatives_d
-        / (pre_computed_data.true_negatives_d + pre_computed_data.false_negatives_d);
-    let value_a: f32 = pre_computed_data.true_negatives_a
-        / (pre_computed_data.true_negatives_a + pre_computed_data.false_negatives_a);
+pub fn diff_in_rejection_rate(data: &PostTrainingData) -> f32 {
+    let value_d: f32 = data.facet_d.weighted.true_negatives as f32
+        / data.facet_d.weighted.predicted_negatives() as f32;
+    let value_a: f32 = data.facet_a.weighted.true_negatives as f32
+        / data.facet_a.weighted.predicted_negatives() as f32;
 
     value_d - value_a
 }
 
-pub fn treatment_equity(pre_computed_data: &PostTrainingComputations) -> f32 {
-    let value_d: f32 = pre_computed_data.false_negatives_d / pre_computed_data.false_positives_d;
-    let value_a: f32 = pre_computed_data.false_negatives_a / pre_computed_data.false_positives_a;
+pub fn treatment_equity(data: &PostTrainingData) -> f32 {
+    let value_d: f32 =
+        data.facet_d.weighted.false_negatives as f32 / data.facet_d.weighted.false_positives as f32;
+    let value_a: f32 =
+        data.facet_a.weighted.false_negatives as f32 / data.facet_a.weighted.false_positives as f32;
 
     value_d - value_a
 }
 
 pub fn cond_dem_desp_in_pred_labels(data: &PostTrainingData) -> f32 {
-    let n_prime_0: f32 = data
-        .facet_a_scores
-        .iter()
-        .map(|value| if *value == 0 { 1_f32 } else { 0_f32 })
-        .sum::<f32>()
-        + data
-            .facet_d_scores
-            .iter()
-            .map(|value| if *value == 0 { 1_f32 } else { 0_f32 })
-            .sum::<f32>();
-
-    let n_prime_1: f32 = data
-        .facet_a_scores
-        .iter()
-        .map(|value| if *value == 1 { 1_f32 } else { 0_f32 })
-        .sum::<f32>()
-        + data
-            .facet_d_scores
-            .iter()
-            .map(|value| if *value == 1 { 1_f32 } else { 0_f32 })
-            .sum::<f32>();
-
-    let n_prime_d_0: f32 = data
-        .facet_d_scores
-        .iter()
-        .map(|value| if *value == 0 { 1_f32 } else { 0_f32 })
-        .sum::<f32>();
-    let n_prime_d_1: f32 = data
-        .facet_d_scores
-        .iter()
-        .map(|value| if *value == 1 { 1_f32 } else { 0_f32 })
-        .sum::<f32>();
+    let n_prime_0: f32 = (data.facet_a.weighted.predicted_negatives()
+        + data.facet_d.weighted.predicted_negatives()) as f32;
+    let n_prime_1: f32 = (data.facet_a.weighted.predicted_positives()
+        + data.facet_d.weighted.predicted_positives()) as f32;
+
+    let n_prime_d_0: f32 = data.facet_d.weighted.predicted_negatives() as f32;
+    let n_prime_d_1: f32 = data.facet_d.weighted.predicted_positives() as f32;
 
     n_prime_d_0 / n_prime_0 - n_prime_d_1 / n_prime_1
 }
 
 pub fn generalized_entropy(data: &PostTrainingData) -> f32 {
-    let y_true = [data.facet_a_trues.clone(), data.facet_d_trues.clone()].concat();
-    let y_pred = [data.facet_a_scores.clone(), data.facet_d_scores.clone()].concat();
-
-    let benefits: Vec<f32> = y_true
-        .iter()
-        .zip(y_pred.iter())
-        .map(|(y_true, y_pred)| {
-            if *y_pred == 0 && *y_true == 1 {
-                0_f32
-            } else if *y_pred == 1 && *y_true == 1 {
-                1_f32
-            } else {
-                2_f32
+    let all = data.facet_a.merge(data.facet_d);
+    // benefit per row: 0 for a false negative, 1 for a true positive, 2 otherwise
+    let n_zero = all.weighted.false_negatives;
+    let n_one = all.weighted.true_positives;
+    let n_two = all.weighted.false_positives + all.weighted.true_negatives;
+
+    let n = all.weighted.len();
+    let mean: f64 = (n_one + 2.0 * n_two) / n;
+    let transformed = |benefit: f64| (benefit / mean).powi(2) - 1.0;
+    let result: f64 =
+        n_zero * transformed(0.0) + n_one * transformed(1.0) + n_two * transformed(2.0);
+    (result * (0.5 * n)) as f32
+}
+
+metric_report! {
+    /// Model bias metrics for one feature, followed by any registered metrics.
+    pub struct ModelBiasReport {
+        difference_in_positive_predicted_labels: DIFFERENCE_IN_POSITIVE_PREDICTED_LABELS =
+            "DifferenceInPositivePredictedLabels",
+        disparate_impact: DISPARATE_IMPACT = "DisparateImpact",
+        accuracy_difference: ACCURACY_DIFFERENCE = "AccuracyDifference",
+        recall_difference: RECALL_DIFFERENCE = "RecallDifference",
+        difference_in_conditional_acceptance: DIFFERENCE_IN_CONDITIONAL_ACCEPTANCE =
+            "DifferenceInConditionalAcceptance",
+        difference_in_acceptance_rate: DIFFERENCE_IN_ACCEPTANCE_RATE =
+            "DifferenceInAcceptanceRate",
+        speciality_difference: SPECIALITY_DIFFERENCE = "SpecialityDifference",
+        difference_in_conditional_rejection: DIFFERENCE_IN_CONDITIONAL_REJECTION =
+            "DifferenceInConditionalRejection",
+        difference_in_rejection_rate: DIFFERENCE_IN_REJECTION_RATE =
+            "DifferenceInRejectionRate",
+        treatment_equity: TREATMENT_EQUITY = "TreatmentEquity",
+        conditional_demographic_desparity_predicted_labels: CONDITIONAL_DEMOGRAPHIC_DESPARITY_PREDICTED_LABELS =
+            "ConditionalDemographicDesparityPredictedLabels",
+        generalized_entropy: GENERALIZED_ENTROPY = "GeneralizedEntropy",
+        ..custom,
+        ?missing: MissingCounts = "missing",
+        ?effective_sample_size: EffectiveSampleSize = "effectiveSampleSize",
+        ?confidence_intervals: ConfidenceIntervals = "confidenceIntervals",
+        ?significance: Significance = "significance",
+        ?status: MetricStatuses = "status",
+        ?conditional_disparity: ConditionalDisparity = "conditionalDemographicDisparity",
+    }
+}
+
+/// A model bias metric computed from the per facet confusion counts.
+/// Built in and user defined metrics all go through `ModelBiasRegistry`.
+pub trait ModelBiasMetric: Send + Sync {
+    /// Key of the metric in reports and baselines.
+    fn name(&self) -> &str;
+
+    fn compute(&self, data: &PostTrainingData) -> Result<f32, FairMlError>;
+
+    /// How a runtime value is compared against the baseline.
+    fn direction(&self) -> Direction {
+        Direction::Magnitude
+    }
+
+    /// Value the metric takes when the model treats both facets alike.
+    fn ideal_value(&self) -> f32 {
+        0_f32
+    }
+
+    /// Named counts the metric divides by, e.g. "false positives of facet
+    /// a". A zero count leaves the metric undefined, see `MetricStatus`.
+    fn denominators(&self, _data: &PostTrainingData) -> Vec<(String, f64)> {
+        Vec::new()
+    }
+}
+
+type PostTrainingMetric = fn(&PostTrainingData) -> f32;
+
+struct BuiltinModelBiasMetric {
+    name: &'static str,
+    compute: PostTrainingMetric,
+    direction: Direction,
+    ideal_value: f32,
+    denominators: &'static [(Facet, ConfusionCount)],
+}
+
+impl ModelBiasMetric for BuiltinModelBiasMetric {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn compute(&self, data: &PostTrainingData) -> Result<f32, FairMlError> {
+        Ok((self.compute)(data))
+    }
+
+    fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn ideal_value(&self) -> f32 {
+        self.ideal_value
+    }
+
+    fn denominators(&self, data: &PostTrainingData) -> Vec<(String, f64)> {
+        self.denominators
+            .iter()
+            .map(|(facet, count)| {
+                (
+                    format!("{} of {}", count.name(), facet.name()),
+                    count.of(facet.pick(&data.facet_a, &data.facet_d)),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The set of metrics used for model bias analysis and runtime checks, in
+/// report order.
+#[derive(Clone)]
+pub struct ModelBiasRegistry {
+    metrics: Vec<Arc<dyn ModelBiasMetric>>,
+}
+
+impl Default for ModelBiasRegistry {
+    fn default() -> ModelBiasRegistry {
+        type R = ModelBiasReport;
+        // the last two divide by counts pooled over both facets, only a value
+        // that is not finite marks them undefined
+        type Denominators = &'static [(Facet, ConfusionCount)];
+        let builtin: [(
+            &'static str,
+            Direction,
+            f32,
+            PostTrainingMetric,
+            Denominators,
+        ); 12] = [
+            (
+                R::DIFFERENCE_IN_POSITIVE_PREDICTED_LABELS,
+                Direction::Magnitude,
+                0_f32,
+                diff_in_pos_proportion_in_pred_labels,
+                &ACTUAL_POSITIVES,
+            ),
+            (
+                R::DISPARATE_IMPACT,
+                Direction::Increase,
+                1_f32,
+                disparate_impact,
+                &[
+                    (Facet::D, ConfusionCount::ActualPositives),
+                    (Facet::D, ConfusionCount::PredictedPositives),
+                ],
+            ),
+            (
+                R::ACCURACY_DIFFERENCE,
+                Direction::Magnitude,
+                0_f32,
+                accuracy_difference,
+                &ROWS,
+            ),
+            (
+                R::RECALL_DIFFERENCE,
+                Direction::Magnitude,
+                0_f32,
+                recall_difference,
+                &ACTUAL_POSITIVES,
+            ),
+            (
+                R::DIFFERENCE_IN_CONDITIONAL_ACCEPTANCE,
+                Direction::Magnitude,
+                0_f32,
+                diff_in_cond_acceptance,
+                &PREDICTED_POSITIVES,
+            ),
+            (
+                R::DIFFERENCE_IN_ACCEPTANCE_RATE,
+                Direction::Magnitude,
+                0_f32,
+                diff_in_acceptance_rate,
+                &PREDICTED_POSITIVES,
+            ),
+            (
+                R::SPECIALITY_DIFFERENCE,
+                Direction::Magnitude,
+                0_f32,
+                specailty_difference,
+                &ACTUAL_NEGATIVES,
+            ),
+            (
+                R::DIFFERENCE_IN_CONDITIONAL_REJECTION,
+                Direction::Magnitude,
+                0_f32,
+                diff_in_cond_rejection,
+                &PREDICTED_NEGATIVES,
+            ),
+            (
+                R::DIFFERENCE_IN_REJECTION_RATE,
+                Direction::Magnitude,
+                0_f32,
+                diff_in_rejection_rate,
+                &PREDICTED_NEGATIVES,
+            ),
+            (
+                R::TREATMENT_EQUITY,
+                Direction::Magnitude,
+                0_f32,
+                treatment_equity,
+                &FALSE_POSITIVES,
+            ),
+            (
+                R::CONDITIONAL_DEMOGRAPHIC_DESPARITY_PREDICTED_LABELS,
+                Direction::Magnitude,
+                0_f32,
+                cond_dem_desp_in_pred_labels,
+                &[],
+            ),
+            (
+                R::GENERALIZED_ENTROPY,
+                Direction::Increase,
+                0_f32,
+                generalized_entropy,
+                &[],
+            ),
+        ];
+        ModelBiasRegistry {
+            metrics: builtin
+                .into_iter()
+                .map(|(name, direction, ideal_value, compute, denominators)| {
+                    Arc::new(BuiltinModelBiasMetric {
+                        name,
+                        compute,
+                        direction,
+                        ideal_value,
+                        denominators,
+                    }) as Arc<dyn ModelBiasMetric>
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ModelBiasRegistry {
+    /// Registry holding the built in metrics.
+    pub fn new() -> ModelBiasRegistry {
+        ModelBiasRegistry::default()
+    }
+
+    pub fn register(&mut self, metric: Arc<dyn ModelBiasMetric>) -> Result<(), FairMlError> {
+        if self.get(metric.name()).is_ok() {
+            return Err(FairMlError::DuplicateMetricName {
+                name: metric.name().into(),
+            });
+        }
+        self.metrics.push(metric);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Arc<dyn ModelBiasMetric>, FairMlError> {
+        self.metrics
+            .iter()
+            .find(|metric| metric.name() == name)
+            .ok_or_else(|| FairMlError::invalid_metric(name))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.metrics.iter().map(|metric| metric.name()).collect()
+    }
+
+    pub fn analyze(&self, data: &PostTrainingData) -> Result<ModelBiasReport, FairMlError> {
+        let values = self
+            .metrics
+            .iter()
+            .map(|metric| Ok((metric.name().to_string(), metric.compute(data)?)))
+            .collect::<Result<HashMap<String, f32>, FairMlError>>()?;
+        ModelBiasReport::try_from(values)
+    }
+
+    /// Status of every metric of `report`, computed from `data`.
+    pub fn statuses(
+        &self,
+        data: &PostTrainingData,
+        report: &ModelBiasReport,
+        thresholds: SupportThresholds,
+    ) -> Result<MetricStatuses, FairMlError> {
+        thresholds.validate()?;
+        let values = report.to_map();
+        let facet_sizes = [data.facet_a.weighted.len(), data.facet_d.weighted.len()];
+        self.metrics
+            .iter()
+            .map(|metric| {
+                let value = get_metric(&values, metric.name())?;
+                let status = metric_status(
+                    metric.name(),
+                    value,
+                    facet_sizes,
+                    &metric.denominators(data),
+                    thresholds,
+                );
+                Ok((metric.name().to_string(), status))
+            })
+            .collect()
+    }
+
+    /// Compares `latest` against `baseline` for the named metrics, or for every
+    /// registered metric when `metrics` is `None`. A metric whose status in
+    /// `statuses` is undefined fails the check.
+    pub fn runtime_check(
+        &self,
+        baseline: &HashMap<String, f32>,
+        latest: &HashMap<String, f32>,
+        threshold: f32,
+        metrics: Option<&[String]>,
+        statuses: CheckStatuses<'_>,
+    ) -> Result<HashMap<String, String>, FairMlError> {
+        let selected: Vec<&Arc<dyn ModelBiasMetric>> = match metrics {
+            Some(names) => names
+                .iter()
+                .map(|name| self.get(name))
+                .collect::<Result<_, _>>()?,
+            None => self.metrics.iter().collect(),
+        };
+        let mut result: HashMap<String, String> = HashMap::with_capacity(selected.len());
+        for metric in selected {
+            if let Some(report) = compare_to_baseline(
+                metric.name(),
+                metric.direction(),
+                baseline,
+                latest,
+                threshold,
+                statuses,
+            )? {
+                result.insert(metric.name().to_string(), report);
+            }
+        }
+        Ok(result)
+    }
+}
+
+pub fn post_training_bias(data: PostTrainingData) -> Result<ModelBiasReport, FairMlError> {
+    ModelBiasRegistry::new().analyze(&data)
+}
+
+/// Running confusion counts for model bias over a stream of labeled batches.
+/// Accumulators built on separate workers merge by adding their counts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelBiasAccumulator {
+    facet_a: FacetConfusion,
+    facet_d: FacetConfusion,
+    missing: MissingCounts,
+}
+
+impl ModelBiasAccumulator {
+    pub fn new() -> ModelBiasAccumulator {
+        ModelBiasAccumulator::default()
+    }
+
+    /// Adds a batch of labeled rows, the same input `perform_segmentation_model_bias` takes.
+    pub fn update(
+        &mut self,
+        feature_values: &[i16],
+        prediction_values: &[i16],
+        ground_truth_values: &[i16],
+    ) -> Result<(), FairMlError> {
+        self.update_with(feature_values, prediction_values, ground_truth_values, None)
+    }
+
+    /// Adds a batch of labeled rows, each counted with its sample weight.
+    pub fn update_weighted(
+        &mut self,
+        feature_values: &[i16],
+        prediction_values: &[i16],
+        ground_truth_values: &[i16],
+        sample_weights: &[f64],
+    ) -> Result<(), FairMlError> {
+        self.update_with(
+            feature_values,
+            prediction_values,
+            ground_truth_values,
+            Some(sample_weights),
+        )
+    }
+
+    fn update_with(
+        &mut self,
+        feature_values: &[i16],
+        prediction_values: &[i16],
+        ground_truth_values: &[i16],
+        sample_weights: Option<&[f64]>,
+    ) -> Result<(), FairMlError> {
+        let batch = count_facets_model_bias(
+            feature_values,
+            prediction_values,
+            ground_truth_values,
+            sample_weights,
+        )?;
+        self.facet_a = self.facet_a.merge(batch.facet_a);
+        self.facet_d = self.facet_d.merge(batch.facet_d);
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: &ModelBiasAccumulator) {
+        self.facet_a = self.facet_a.merge(other.facet_a);
+        self.facet_d = self.facet_d.merge(other.facet_d);
+        self.missing = self.missing.merge(other.missing);
+    }
+
+    pub fn missing(&self) -> MissingCounts {
+        self.missing
+    }
+
+    /// Adds the missing value counts `apply_missing_policy` returned for a batch.
+    pub fn record_missing(&mut self, missing: MissingCounts) {
+        self.missing = self.missing.merge(missing);
+    }
+
+    pub fn finalize(&self) -> Result<ModelBiasReport, FairMlError> {
+        self.finalize_with(&ModelBiasRegistry::new())
+    }
+
+    pub fn finalize_with(
+        &self,
+        registry: &ModelBiasRegistry,
+    ) -> Result<ModelBiasReport, FairMlError> {
+        check_facets(self.facet_a.is_empty(), self.facet_d.is_empty())?;
+        let data = PostTrainingData {
+            facet_a: self.facet_a,
+            facet_d: self.facet_d,
+        };
+        let mut report = registry.analyze(&data)?;
+        report.missing = Some(self.missing);
+        report.effective_sample_size = Some(data.effective_sample_size());
+        report.status = Some(registry.statuses(&data, &report, SupportThresholds::default())?);
+        Ok(report)
+    }
+}
+
+impl AccumulatorState for ModelBiasAccumulator {
+    const KIND: &'static str = "model_bias";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::MetricStatus;
+
+    fn close(left: f32, right: f64) -> bool {
+        (f64::from(left) - right).abs() < 1e-6
+    }
+
+    /// Feature, prediction and ground truth rows of two facets, each given
+    /// as true positives, false positives, false negatives and true negatives.
+    fn rows(facet_a: [usize; 4], facet_d: [usize; 4]) -> [Vec<i16>; 3] {
+        let mut columns: [Vec<i16>; 3] = Default::default();
+        for (feature, cells) in [(1, facet_a), (0, facet_d)] {
+            for ((prediction, ground_truth), count) in
+                [(1, 1), (1, 0), (0, 1), (0, 0)].into_iter().zip(cells)
+            {
+                for (column, value) in columns.iter_mut().zip([feature, prediction, ground_truth]) {
+                    column.extend(std::iter::repeat_n(value, count));
+                }
             }
-        })
-        .collect();
-
-    let sum: f32 = benefits.iter().sum::<f32>();
-    let n = benefits.len() as f32;
-    let mean: f32 = sum / n;
-    let transformed_benefits: Vec<f32> = benefits
-        .iter()
-        .map(|value| ((*value / mean).powf(2.0)) - 1.0)
-        .collect();
-    let result: f32 = transformed_benefits.iter().sum::<f32>();
-    result * (0.5 * n)
-}
-
-pub fn post_training_bias(data: PostTrainingData) -> Result<HashMap<String, f32>, String> {
-    let pre_computed_data: PostTrainingComputations = data.general_data_computations();
-    let mut result: HashMap<String, f32> = HashMap::with_capacity(12);
-    result.insert(
-        "DifferenceInPositivePredictedLabels".into(),
-        diff_in_pos_proportion_in_pred_labels(&data),
-    );
-    result.insert("DisparateImpact".into(), disparate_impact(&data));
-    result.insert(
-        "AccuracyDifference".into(),
-        accuracy_difference(&pre_computed_data, &data),
-    );
-    result.insert(
-        "RecallDifference".into(),
-        recall_difference(&pre_computed_data),
-    );
-    result.insert(
-        "DifferenceInConditionalAcceptance".into(),
-        diff_in_cond_acceptance(&data),
-    );
-    result.insert(
-        "DifferenceInAcceptanceRate".into(),
-        diff_in_acceptance_rate(&pre_computed_data),
-    );
-    result.insert(
-        "SpecialityDifference".into(),
-        specailty_difference(&pre_computed_data),
-    );
-    result.insert(
-        "DifferenceInConditionalRejection".into(),
-        diff_in_cond_rejection(&data),
-    );
-    result.insert(
-        "DifferenceInRejectionRate".into(),
-        diff_in_rejection_rate(&pre_computed_data),
-    );
-    result.insert(
-        "TreatmentEquity".into(),
-        treatment_equity(&pre_computed_data),
-    );
-    result.insert(
-        "ConditionalDemographicDesparityPredictedLabels".into(),
-        cond_dem_desp_in_pred_labels(&data),
-    );
-    result.insert("GeneralizedEntropy".into(), generalized_entropy(&data));
-
-    Ok(result)
+        }
+        columns
+    }
+
+    fn report(facet_a: [usize; 4], facet_d: [usize; 4]) -> ModelBiasReport {
+        let [feature, prediction, ground_truth] = rows(facet_a, facet_d);
+        let mut acc = ModelBiasAccumulator::new();
+        acc.update(&feature, &prediction, &ground_truth).unwrap();
+        acc.finalize().unwrap()
+    }
+
+    #[test]
+    fn metrics_match_hand_counts() {
+        let report = report([4, 1, 2, 3], [1, 2, 1, 2]);
+        assert!(close(report.accuracy_difference, 0.7 - 0.5));
+        assert!(close(report.recall_difference, 4.0 / 6.0 - 0.5));
+        assert!(close(report.difference_in_acceptance_rate, 0.8 - 1.0 / 3.0));
+        assert!(close(report.speciality_difference, 0.5 - 0.75));
+        assert!(close(report.treatment_equity, 0.5 - 2.0));
+    }
+
+    #[test]
+    fn generalized_entropy_matches_the_row_by_row_sum() {
+        let [_, prediction, ground_truth] = rows([4, 1, 2, 3], [1, 2, 1, 2]);
+        let benefits: Vec<f64> = prediction
+            .iter()
+            .zip(&ground_truth)
+            .map(|pair| match pair {
+                (0, 1) => 0.0,
+                (1, 1) => 1.0,
+                _ => 2.0,
+            })
+            .collect();
+        let n = benefits.len() as f64;
+        let mean = benefits.iter().sum::<f64>() / n;
+        let sum: f64 = benefits.iter().map(|b| (b / mean).powi(2) - 1.0).sum();
+        let report = report([4, 1, 2, 3], [1, 2, 1, 2]);
+        assert!((f64::from(report.generalized_entropy) - sum * 0.5 * n).abs() < 1e-4);
+    }
+
+    #[test]
+    fn a_zero_denominator_is_undefined() {
+        // facet d has no false positives to divide its false negatives by
+        let report = report([4, 1, 2, 3], [1, 0, 1, 2]);
+        let status = report.status.unwrap();
+        assert!(!report.treatment_equity.is_finite());
+        assert!(matches!(
+            status["TreatmentEquity"],
+            MetricStatus::Undefined(_)
+        ));
+        // 16 rows are below the default minimum facet size
+        assert!(matches!(
+            status["AccuracyDifference"],
+            MetricStatus::LowSupport(_)
+        ));
+        let [feature, prediction, ground_truth] = rows([4, 1, 2, 3], [0; 4]);
+        let mut acc = ModelBiasAccumulator::new();
+        acc.update(&feature, &prediction, &ground_truth).unwrap();
+        assert!(matches!(
+            acc.finalize(),
+            Err(FairMlError::EmptyFacet { .. })
+        ));
+    }
 }
@@ -1,9 +1,13 @@
-use crate::data_handler::{determine_type, PassedType};
+use crate::bootstrap::ConfidenceIntervals;
+use crate::data_handler::{check_lengths, check_sample_weights, effective_size};
+use crate::errors::FairMlError;
+use crate::metric_report;
+use crate::model_bias::FacetConfusion;
+use crate::state::AccumulatorState;
 use crate::zip;
-use numpy::PyUntypedArray;
-use pyo3::prelude::*;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::error::Error;
 
 pub const FULL_REGRESSION_METRICS: [LinearRegressionEvaluationMetrics; 8] = [
     LinearRegressionEvaluationMetrics::RootMeanSquaredError,
@@ -60,67 +64,67 @@ pub enum LinearRegressionEvaluationMetrics {
 }
 
 impl TryFrom<&str> for ClassificationEvaluationMetrics {
-    type Error = String;
+    type Error = FairMlError;
     fn try_from(val: &str) -> Result<Self, Self::Error> {
         match val {
-            "BalancedAccuracy" => Ok(Self::BalancedAccuracy),
-            "PrecisionPositive" => Ok(Self::PrecisionPositive),
-            "PrecisionNegative" => Ok(Self::PrecisionNegative),
-            "RecallPositive" => Ok(Self::RecallPositive),
-            "RecallNegative" => Ok(Self::RecallNegative),
-            "Accuracy" => Ok(Self::Accuracy),
-            "F1Score" => Ok(Self::F1Score),
-            "LogLoss" => Ok(Self::LogLoss),
-            _ => Err("Invalid metric type".into()),
+            LogisticRegressionReport::BALANCED_ACCURACY => Ok(Self::BalancedAccuracy),
+            LogisticRegressionReport::PRECISION_POSITIVE => Ok(Self::PrecisionPositive),
+            LogisticRegressionReport::PRECISION_NEGATIVE => Ok(Self::PrecisionNegative),
+            LogisticRegressionReport::RECALL_POSITIVE => Ok(Self::RecallPositive),
+            LogisticRegressionReport::RECALL_NEGATIVE => Ok(Self::RecallNegative),
+            LogisticRegressionReport::ACCURACY => Ok(Self::Accuracy),
+            LogisticRegressionReport::F1_SCORE => Ok(Self::F1Score),
+            LogisticRegressionReport::LOG_LOSS => Ok(Self::LogLoss),
+            _ => Err(FairMlError::invalid_metric(val)),
         }
     }
 }
 
 impl TryFrom<&str> for LinearRegressionEvaluationMetrics {
-    type Error = String;
+    type Error = FairMlError;
     fn try_from(val: &str) -> Result<Self, Self::Error> {
         match val {
-            "RootMeanSquaredError" => Ok(Self::RootMeanSquaredError),
-            "MeanSquaredError" => Ok(Self::MeanSquaredError),
-            "MeanAbsoluteError" => Ok(Self::MeanAbsoluteError),
-            "RSquared" => Ok(Self::RSquared),
-            "MaxError" => Ok(Self::MaxError),
-            "MeanSquaredLogError" => Ok(Self::MeanSquaredLogError),
-            "RootMeanSquaredLogError" => Ok(Self::RootMeanSquaredLogError),
-            "MeanAbsolutePercentageError" => Ok(Self::MeanAbsolutePercentageError),
-            _ => Err("Invalid metric name passed".into()),
+            LinearRegressionReport::ROOT_MEAN_SQUARED_ERROR => Ok(Self::RootMeanSquaredError),
+            LinearRegressionReport::MEAN_SQUARED_ERROR => Ok(Self::MeanSquaredError),
+            LinearRegressionReport::MEAN_ABSOLUTE_ERROR => Ok(Self::MeanAbsoluteError),
+            LinearRegressionReport::R_SQUARED => Ok(Self::RSquared),
+            LinearRegressionReport::MAX_ERROR => Ok(Self::MaxError),
+            LinearRegressionReport::MEAN_SQUARED_LOG_ERROR => Ok(Self::MeanSquaredLogError),
+            LinearRegressionReport::ROOT_MEAN_SQUARED_LOG_ERROR => {
+                Ok(Self::RootMeanSquaredLogError)
+            }
+            LinearRegressionReport::MEAN_ABSOLUTE_PERCENTAGE_ERROR => {
+                Ok(Self::MeanAbsolutePercentageError)
+            }
+            _ => Err(FairMlError::invalid_metric(val)),
         }
     }
 }
 
 pub fn map_string_to_linear_metric(
     metrics_string: Vec<String>,
-) -> Result<Vec<LinearRegressionEvaluationMetrics>, Box<dyn Error>> {
+) -> Result<Vec<LinearRegressionEvaluationMetrics>, FairMlError> {
     let mut v: Vec<LinearRegressionEvaluationMetrics> = Vec::with_capacity(metrics_string.len());
     for m_str in metrics_string.iter() {
-        let new = match LinearRegressionEvaluationMetrics::try_from(m_str.as_str()) {
-            Ok(val) => val,
-            Err(_) => return Err("Invalid metric name".into()),
-        };
-        v.push(new);
+        v.push(LinearRegressionEvaluationMetrics::try_from(m_str.as_str())?);
     }
     Ok(v)
 }
 
 pub fn map_string_to_bin_metric(
     metrics_string: Vec<String>,
-) -> Result<Vec<ClassificationEvaluationMetrics>, Box<dyn Error>> {
+) -> Result<Vec<ClassificationEvaluationMetrics>, FairMlError> {
     let mut v: Vec<ClassificationEvaluationMetrics> = Vec::with_capacity(metrics_string.len());
     for m_str in metrics_string.iter() {
-        let new = match ClassificationEvaluationMetrics::try_from(m_str.as_str()) {
-            Ok(val) => val,
-            Err(_) => return Err("Invalid metric name".into()),
-        };
-        v.push(new);
+        v.push(ClassificationEvaluationMetrics::try_from(m_str.as_str())?);
     }
     Ok(v)
 }
 
+fn cast_f32(values: &[f64]) -> Vec<f32> {
+    values.iter().map(|value| *value as f32).collect()
+}
+
 fn update_failure_report_above(map: &mut HashMap<String, String>, metric: String, diff: f32) {
     map.insert(metric, format!("Exceeded threshold by {diff}"));
 }
@@ -129,38 +133,73 @@ fn update_failure_report_below(map: &mut HashMap<String, String>, metric: String
     map.insert(metric, format!("Below threshold by {diff}"));
 }
 
-pub fn model_perf_regression<'py>(
-    py: Python<'_>,
-    y_pred_src: &Bound<'_, PyUntypedArray>,
-    y_true_src: &Bound<'_, PyUntypedArray>,
-) -> Result<HashMap<String, f32>, Box<dyn Error>> {
-    let perf: LinearRegressionPerf = LinearRegressionPerf::new(py, y_true_src, y_pred_src)?;
-    let report: LinearRegressionReport = perf.into();
-    Ok(report.generate_report())
+/// Number of (y_true, y_pred) pairs satisfying `rule`, counted in parallel.
+/// With sample weights the matching rows add their weight instead of 1.
+fn count_matches(
+    y_pred: &[f32],
+    y_true: &[f32],
+    weights: Option<&[f32]>,
+    rule: impl Fn(f32, f32) -> bool + Sync,
+) -> f32 {
+    match weights {
+        None => y_true
+            .par_iter()
+            .zip(y_pred.par_iter())
+            .filter(|(t, p)| rule(**t, **p))
+            .count() as f32,
+        Some(weights) => y_true
+            .par_iter()
+            .zip(y_pred.par_iter())
+            .zip(weights.par_iter())
+            .filter(|((t, p), _)| rule(**t, **p))
+            .map(|(_, w)| *w)
+            .sum::<f32>(),
+    }
+}
+
+/// Sum of `values`, each scaled by its sample weight when there are weights.
+fn weighted_sum(values: &[f32], weights: Option<&[f32]>) -> f32 {
+    match weights {
+        None => values.iter().sum::<f32>(),
+        Some(weights) => zip!(values, weights).map(|(v, w)| v * w).sum::<f32>(),
+    }
 }
 
-pub fn model_perf_classification<'py>(
-    py: Python<'_>,
-    y_pred_src: &Bound<'_, PyUntypedArray>,
-    y_true_src: &Bound<'_, PyUntypedArray>,
-) -> Result<HashMap<String, f32>, Box<dyn Error>> {
-    let perf: ClassificationPerf = ClassificationPerf::new(py, y_true_src, y_pred_src)?;
-    let report: BinaryClassificationReport = perf.into();
-    Ok(report.generate_report())
+/// Weight of the row at `row`, 1 without sample weights.
+fn weight_at(weights: Option<&[f32]>, row: usize) -> f32 {
+    weights.map_or(1_f32, |weights| weights[row])
 }
 
-pub fn model_perf_logistic_regression<'py>(
-    py: Python<'_>,
-    y_pred_src: &Bound<'_, PyUntypedArray>,
-    y_true_src: &Bound<'_, PyUntypedArray>,
-    threshold: f32,
-) -> Result<HashMap<String, f32>, Box<dyn Error>> {
-    let perf: LogisticRegressionPerf =
-        LogisticRegressionPerf::new(py, y_pred_src, y_true_src, threshold)?;
-    let lr_report: LogisticRegressionReport = perf.into();
-    let map = lr_report.report();
-    Ok(map)
+/// Validates the sample weights of a batch and returns their total, the row
+/// count without weights.
+fn total_weight(weights: Option<&[f32]>, y_true: &[f32]) -> Result<f32, FairMlError> {
+    let Some(weights) = weights else {
+        return Ok(y_true.len() as f32);
+    };
+    check_sample_weights("y_true", y_true, weights)?;
+    let total: f32 = weights.iter().sum::<f32>();
+    if total <= 0_f32 {
+        return Err(FairMlError::InvalidSampleWeight {
+            reason: "weights sum to zero".into(),
+        });
+    }
+    Ok(total)
+}
+
+/// Kish effective sample size of a batch, the row count without weights.
+fn batch_effective_size(weights: Option<&[f32]>, rows: usize) -> f64 {
+    match weights {
+        None => rows as f64,
+        Some(weights) => {
+            let (weight, weight_squares) = weights.iter().fold((0_f64, 0_f64), |(w, w2), x| {
+                let x = f64::from(*x);
+                (w + x, w2 + x * x)
+            });
+            effective_size(weight, weight_squares)
+        }
+    }
 }
+
 struct GeneralClassificationMetrics;
 
 impl GeneralClassificationMetrics {
@@ -168,57 +207,45 @@ impl GeneralClassificationMetrics {
         rp * rn * 0.5_f32
     }
 
-    fn precision_positive(y_pred: &[f32], y_true: &[f32]) -> f32 {
-        let total_pred_positives: f32 = y_pred.iter().sum::<f32>();
-        let mut true_positives: f32 = 0_f32;
-        for (t, p) in zip!(y_true, y_pred) {
-            if (*t - 1_f32).abs() <= f32::EPSILON && (t - p).abs() <= f32::EPSILON {
-                true_positives += 1_f32;
-            }
-        }
+    fn precision_positive(y_pred: &[f32], y_true: &[f32], weights: Option<&[f32]>) -> f32 {
+        let total_pred_positives: f32 = weighted_sum(y_pred, weights);
+        let true_positives: f32 = count_matches(y_pred, y_true, weights, |t, p| {
+            (t - 1_f32).abs() <= f32::EPSILON && (t - p).abs() <= f32::EPSILON
+        });
         true_positives / total_pred_positives
     }
 
-    fn precision_negative(y_pred: &[f32], y_true: &[f32], len: f32) -> f32 {
-        let total_pred_negatives: f32 = len - y_pred.iter().sum::<f32>();
-        let mut true_negatives: f32 = 0_f32;
-        for (t, p) in zip!(y_true, y_pred) {
-            if (*t - 0_f32).abs() <= f32::EPSILON && (t - p).abs() <= f32::EPSILON {
-                true_negatives += 1_f32;
-            }
-        }
+    fn precision_negative(
+        y_pred: &[f32],
+        y_true: &[f32],
+        weights: Option<&[f32]>,
+        len: f32,
+    ) -> f32 {
+        let total_pred_negatives: f32 = len - weighted_sum(y_pred, weights);
+        let true_negatives: f32 = count_matches(y_pred, y_true, weights, |t, p| {
+            (t - 0_f32).abs() <= f32::EPSILON && (t - p).abs() <= f32::EPSILON
+        });
         true_negatives / total_pred_negatives
     }
 
-    fn recall_positive(y_pred: &[f32], y_true: &[f32]) -> f32 {
-        let total_true_positives: f32 = y_true.iter().sum::<f32>();
-        let mut true_positives: f32 = 0_f32;
-        for (t, p) in zip!(y_true, y_pred) {
-            if (*t - 1_f32).abs() <= f32::EPSILON && (t - p).abs() <= f32::EPSILON {
-                true_positives += 1_f32;
-            }
-        }
+    fn recall_positive(y_pred: &[f32], y_true: &[f32], weights: Option<&[f32]>) -> f32 {
+        let total_true_positives: f32 = weighted_sum(y_true, weights);
+        let true_positives: f32 = count_matches(y_pred, y_true, weights, |t, p| {
+            (t - 1_f32).abs() <= f32::EPSILON && (t - p).abs() <= f32::EPSILON
+        });
         true_positives / total_true_positives
     }
 
-    fn recall_negative(y_pred: &[f32], y_true: &[f32], len: f32) -> f32 {
-        let total_true_negatives: f32 = len - y_true.iter().sum::<f32>();
-        let mut true_negatives: f32 = 0_f32;
-        for (t, p) in zip!(y_true, y_pred) {
-            if (*t - 0_f32).abs() <= f32::EPSILON && (t - p).abs() <= f32::EPSILON {
-                true_negatives += 1_f32;
-            }
-        }
+    fn recall_negative(y_pred: &[f32], y_true: &[f32], weights: Option<&[f32]>, len: f32) -> f32 {
+        let total_true_negatives: f32 = len - weighted_sum(y_true, weights);
+        let true_negatives: f32 = count_matches(y_pred, y_true, weights, |t, p| {
+            (t - 0_f32).abs() <= f32::EPSILON && (t - p).abs() <= f32::EPSILON
+        });
         true_negatives / total_true_negatives
     }
 
-    fn accuracy(y_pred: &Vec<f32>, y_true: &Vec<f32>, mean_f: f32) -> f32 {
-        let mut correct: f32 = 0_f32;
-        for (t, p) in zip!(y_true, y_pred) {
-            if t == p {
-                correct += 1_f32;
-            }
-        }
+    fn accuracy(y_pred: &[f32], y_true: &[f32], weights: Option<&[f32]>, mean_f: f32) -> f32 {
+        let correct: f32 = count_matches(y_pred, y_true, weights, |t, p| t == p);
         correct * mean_f
     }
 
@@ -226,12 +253,18 @@ impl GeneralClassificationMetrics {
         2_f32 * rp * pp / (rp + pp)
     }
 
-    fn log_loss_score(y_proba: &[f32], y_true: &[f32], mean_f: f32) -> f32 {
+    fn log_loss_score(
+        y_proba: &[f32],
+        y_true: &[f32],
+        weights: Option<&[f32]>,
+        mean_f: f32,
+    ) -> f32 {
         let mut penalties = 0_f32;
-        for (t, p) in zip!(y_true, y_proba) {
-            penalties += t * f32::log10(*p) + (1_f32 - t) * f32::log10(1_f32 - p);
+        for (row, (t, p)) in zip!(y_true, y_proba).enumerate() {
+            penalties += weight_at(weights, row)
+                * (t * f32::log10(*p) + (1_f32 - t) * f32::log10(1_f32 - p));
         }
-        let res = -1_f32 * mean_f * penalties;
+        let res = -mean_f * penalties;
 
         if res.is_nan() {
             0_f32
@@ -240,145 +273,17 @@ impl GeneralClassificationMetrics {
         }
     }
 }
-pub struct PerfEntry;
-
-impl PerfEntry {
-    fn validate_and_cast_classification(
-        py: Python<'_>,
-        y_true_src: &Bound<'_, PyUntypedArray>,
-        y_pred_src: &Bound<'_, PyUntypedArray>,
-        needs_decision: bool,
-        threshold: Option<f32>,
-    ) -> Result<(Vec<f32>, Vec<f32>), Box<dyn Error>> {
-        let pred_type: PassedType = determine_type(py, y_pred_src);
-        let gt_type: PassedType = determine_type(py, y_true_src);
-
-        if pred_type != gt_type {
-            return Err("Type between y_true and y_pred do not match".into());
-        }
-
-        if needs_decision {
-            let Some(thres) = threshold else {
-                return Err("Threshold must be set for logisitc model type".into());
-            };
-            Self::convert_w_label_application(py, y_true_src, y_pred_src, thres, gt_type, pred_type)
-        } else {
-            let y_pred = Self::convert_f32(py, y_pred_src, pred_type)?;
-            let y_true = Self::convert_f32(py, y_true_src, gt_type)?;
-            Ok((y_pred, y_true))
-        }
-    }
-
-    pub fn validate_and_cast_regression(
-        py: Python<'_>,
-        y_true_src: &Bound<'_, PyUntypedArray>,
-        y_pred_src: &Bound<'_, PyUntypedArray>,
-    ) -> Result<(Vec<f32>, Vec<f32>), Box<dyn Error>> {
-        let y_true: Vec<f32> = Self::convert_f32(py, y_pred_src, determine_type(py, y_true_src))?;
-        let y_pred: Vec<f32> = Self::convert_f32(py, y_true_src, determine_type(py, y_pred_src))?;
-        Ok((y_true, y_pred))
-    }
-
-    fn convert_f32(
-        _py: Python<'_>,
-        arr: &Bound<'_, PyUntypedArray>,
-        passed_type: PassedType,
-    ) -> Result<Vec<f32>, Box<dyn Error>> {
-        // pulls the py data type out
-        // applying labels as usize
-        let res: Vec<f32> = match passed_type {
-            PassedType::Float => arr
-                .iter()?
-                .map(|item| item.unwrap().extract::<f64>().unwrap() as f32)
-                .collect::<Vec<f32>>(),
-            PassedType::Integer => arr
-                .iter()?
-                .clone()
-                .map(|item| item.unwrap().extract::<f32>().unwrap() as f32)
-                .collect::<Vec<f32>>(),
-            _ => panic!("Data of type String is not supported"),
-        };
-        Ok(res)
-    }
-
-    fn convert_w_label_application(
-        py: Python<'_>,
-        y_true_src: &Bound<'_, PyUntypedArray>,
-        y_pred_src: &Bound<'_, PyUntypedArray>,
-        threshold: f32,
-        true_passed_type: PassedType,
-        pred_passed_type: PassedType,
-    ) -> Result<(Vec<f32>, Vec<f32>), Box<dyn Error>> {
-        let y_pred: Vec<f32> = Self::convert_f32(py, y_pred_src, pred_passed_type)?
-            .iter()
-            .map(|x| if x >= &threshold { 1_f32 } else { 0_f32 })
-            .collect::<Vec<f32>>();
-        let y_true: Vec<f32> = Self::convert_f32(py, y_true_src, true_passed_type)?
-            .iter()
-            .map(|x| if x >= &threshold { 1_f32 } else { 0_f32 })
-            .collect::<Vec<f32>>();
-        Ok((y_pred, y_true))
-    }
-}
-
-pub struct BinaryClassificationReport {
-    balanced_accuracy: f32,
-    precision_positive: f32,
-    precision_negative: f32,
-    recall_positive: f32,
-    recall_negative: f32,
-    accuracy: f32,
-    f1_score: f32,
-}
-
-impl BinaryClassificationReport {
-    pub fn generate_report(&self) -> HashMap<String, f32> {
-        let mut map: HashMap<String, f32> = HashMap::with_capacity(7);
-        map.insert("BalancedAccuracy".into(), self.balanced_accuracy);
-        map.insert("PrecisionPositive".into(), self.precision_positive);
-        map.insert("PrecisionNegative".into(), self.precision_negative);
-        map.insert("RecallPositive".into(), self.recall_positive);
-        map.insert("RecallNegative".into(), self.recall_negative);
-        map.insert("Accuracy".into(), self.accuracy);
-        map.insert("F1Score".into(), self.f1_score);
-        map
-    }
-}
-
-impl TryFrom<HashMap<String, f32>> for BinaryClassificationReport {
-    type Error = String;
-    fn try_from(map: HashMap<String, f32>) -> Result<Self, Self::Error> {
-        let Some(balanced_accuracy) = map.get("BalancedAccuracy") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(precision_positive) = map.get("PrecisionPositive") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(precision_negative) = map.get("PrecisionNegative") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(recall_positive) = map.get("RecallPositive") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(recall_negative) = map.get("RecallNegative") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(accuracy) = map.get("Accuracy") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(f1_score) = map.get("F1Score") else {
-            return Err("Invalid regression report".into());
-        };
-
-        Ok(BinaryClassificationReport {
-            balanced_accuracy: *balanced_accuracy,
-            precision_positive: *precision_positive,
-            precision_negative: *precision_negative,
-            recall_positive: *recall_positive,
-            recall_negative: *recall_negative,
-            accuracy: *accuracy,
-            f1_score: *f1_score,
-        })
+metric_report! {
+    pub struct BinaryClassificationReport {
+        balanced_accuracy: BALANCED_ACCURACY = "BalancedAccuracy",
+        precision_positive: PRECISION_POSITIVE = "PrecisionPositive",
+        precision_negative: PRECISION_NEGATIVE = "PrecisionNegative",
+        recall_positive: RECALL_POSITIVE = "RecallPositive",
+        recall_negative: RECALL_NEGATIVE = "RecallNegative",
+        accuracy: ACCURACY = "Accuracy",
+        f1_score: F1_SCORE = "F1Score",
+        ?effective_sample_size: f64 = "effectiveSampleSize",
+        ?confidence_intervals: ConfidenceIntervals = "confidenceIntervals",
     }
 }
 
@@ -388,7 +293,7 @@ impl BinaryClassificationReport {
         metrics: &[ClassificationEvaluationMetrics],
         baseline: &Self,
         drift_threshold: f32,
-    ) -> Result<HashMap<String, String>, Box<dyn Error>> {
+    ) -> Result<HashMap<String, String>, FairMlError> {
         use ClassificationEvaluationMetrics as C;
         let mut res: HashMap<String, String> = HashMap::with_capacity(7);
         let drift_factor = 1_f32 - drift_threshold;
@@ -400,7 +305,7 @@ impl BinaryClassificationReport {
                     if self.balanced_accuracy < baseline.balanced_accuracy * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "BalancedAccuracy".into(),
+                            Self::BALANCED_ACCURACY.into(),
                             baseline.balanced_accuracy - self.balanced_accuracy,
                         );
                     }
@@ -409,7 +314,7 @@ impl BinaryClassificationReport {
                     if self.precision_positive < baseline.precision_positive * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "PrecisionPositive".into(),
+                            Self::PRECISION_POSITIVE.into(),
                             baseline.precision_positive - self.precision_positive,
                         );
                     }
@@ -418,7 +323,7 @@ impl BinaryClassificationReport {
                     if self.precision_negative < baseline.precision_negative * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "PrecisionNegative".into(),
+                            Self::PRECISION_NEGATIVE.into(),
                             baseline.precision_negative - self.precision_negative,
                         );
                     }
@@ -427,7 +332,7 @@ impl BinaryClassificationReport {
                     if self.recall_positive < baseline.recall_positive * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "RecallPositive".into(),
+                            Self::RECALL_POSITIVE.into(),
                             baseline.recall_positive - self.recall_positive,
                         );
                     }
@@ -436,7 +341,7 @@ impl BinaryClassificationReport {
                     if self.recall_negative < baseline.recall_negative * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "RecallNegative".into(),
+                            Self::RECALL_NEGATIVE.into(),
                             baseline.recall_negative - self.recall_negative,
                         );
                     }
@@ -445,7 +350,7 @@ impl BinaryClassificationReport {
                     if self.accuracy < baseline.accuracy * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "Accuracy".into(),
+                            Self::ACCURACY.into(),
                             baseline.accuracy - self.accuracy,
                         );
                     }
@@ -454,12 +359,17 @@ impl BinaryClassificationReport {
                     if self.f1_score < baseline.f1_score * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "F1Score".into(),
+                            Self::F1_SCORE.into(),
                             baseline.f1_score - self.f1_score,
                         );
                     }
                 }
-                C::LogLoss => return Err("LogLoss is not valid for Binary Classification".into()),
+                // log loss needs probabilities, which a binary classification report does not have
+                C::LogLoss => {
+                    return Err(FairMlError::invalid_metric(
+                        LogisticRegressionReport::LOG_LOSS,
+                    ))
+                }
             }
         }
 
@@ -467,70 +377,18 @@ impl BinaryClassificationReport {
     }
 }
 
-pub struct LogisticRegressionReport {
-    balanced_accuracy: f32,
-    precision_positive: f32,
-    precision_negative: f32,
-    recall_positive: f32,
-    recall_negative: f32,
-    accuracy: f32,
-    f1_score: f32,
-    log_loss: f32,
-}
-
-impl LogisticRegressionReport {
-    pub fn report(&self) -> HashMap<String, f32> {
-        let mut map: HashMap<String, f32> = HashMap::with_capacity(8);
-        map.insert("BalancedAccuracy".into(), self.balanced_accuracy);
-        map.insert("PrecisionPositive".into(), self.precision_positive);
-        map.insert("PrecisionNegative".into(), self.precision_negative);
-        map.insert("RecallPositive".into(), self.recall_positive);
-        map.insert("RecallNegative".into(), self.recall_negative);
-        map.insert("Accuracy".into(), self.accuracy);
-        map.insert("F1Score".into(), self.f1_score);
-        map.insert("LogLoss".into(), self.log_loss);
-        map
-    }
-}
-
-impl TryFrom<HashMap<String, f32>> for LogisticRegressionReport {
-    type Error = String;
-    fn try_from(map: HashMap<String, f32>) -> Result<Self, Self::Error> {
-        let Some(balanced_accuracy) = map.get("BalancedAccuracy") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(precision_positive) = map.get("PrecisionPositive") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(precision_negative) = map.get("PrecisionNegative") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(recall_positive) = map.get("RecallPositive") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(recall_negative) = map.get("RecallNegative") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(accuracy) = map.get("Accuracy") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(f1_score) = map.get("F1Score") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(log_loss) = map.get("LogLoss") else {
-            return Err("Invalid regression report".into());
-        };
-
-        Ok(LogisticRegressionReport {
-            balanced_accuracy: *balanced_accuracy,
-            precision_positive: *precision_positive,
-            precision_negative: *precision_negative,
-            recall_positive: *recall_positive,
-            recall_negative: *recall_negative,
-            accuracy: *accuracy,
-            f1_score: *f1_score,
-            log_loss: *log_loss,
-        })
+metric_report! {
+    pub struct LogisticRegressionReport {
+        balanced_accuracy: BALANCED_ACCURACY = "BalancedAccuracy",
+        precision_positive: PRECISION_POSITIVE = "PrecisionPositive",
+        precision_negative: PRECISION_NEGATIVE = "PrecisionNegative",
+        recall_positive: RECALL_POSITIVE = "RecallPositive",
+        recall_negative: RECALL_NEGATIVE = "RecallNegative",
+        accuracy: ACCURACY = "Accuracy",
+        f1_score: F1_SCORE = "F1Score",
+        log_loss: LOG_LOSS = "LogLoss",
+        ?effective_sample_size: f64 = "effectiveSampleSize",
+        ?confidence_intervals: ConfidenceIntervals = "confidenceIntervals",
     }
 }
 
@@ -552,7 +410,7 @@ impl LogisticRegressionReport {
                     if self.balanced_accuracy < baseline.balanced_accuracy * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "BalancedAccuracy".into(),
+                            Self::BALANCED_ACCURACY.into(),
                             baseline.balanced_accuracy - self.balanced_accuracy,
                         );
                     }
@@ -561,7 +419,7 @@ impl LogisticRegressionReport {
                     if self.precision_positive < baseline.precision_positive * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "PrecisionPositive".into(),
+                            Self::PRECISION_POSITIVE.into(),
                             baseline.precision_positive - self.precision_positive,
                         );
                     }
@@ -570,7 +428,7 @@ impl LogisticRegressionReport {
                     if self.precision_negative < baseline.precision_negative * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "PrecisionNegative".into(),
+                            Self::PRECISION_NEGATIVE.into(),
                             baseline.precision_negative - self.precision_negative,
                         );
                     }
@@ -579,7 +437,7 @@ impl LogisticRegressionReport {
                     if self.recall_positive < baseline.recall_positive * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "RecallPositive".into(),
+                            Self::RECALL_POSITIVE.into(),
                             baseline.recall_positive - self.recall_positive,
                         );
                     }
@@ -588,7 +446,7 @@ impl LogisticRegressionReport {
                     if self.recall_negative < baseline.recall_negative * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "RecallNegative".into(),
+                            Self::RECALL_NEGATIVE.into(),
                             baseline.recall_negative - self.recall_negative,
                         );
                     }
@@ -597,7 +455,7 @@ impl LogisticRegressionReport {
                     if self.accuracy < baseline.accuracy * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "Accuracy".into(),
+                            Self::ACCURACY.into(),
                             baseline.accuracy - self.accuracy,
                         );
                     }
@@ -606,7 +464,7 @@ impl LogisticRegressionReport {
                     if self.f1_score < baseline.f1_score * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "F1Score".into(),
+                            Self::F1_SCORE.into(),
                             baseline.f1_score - self.f1_score,
                         );
                     }
@@ -615,7 +473,7 @@ impl LogisticRegressionReport {
                     if self.log_loss < baseline.log_loss * drift_factor {
                         update_failure_report_below(
                             &mut res,
-                            "F1Score".into(),
+                            Self::F1_SCORE.into(),
                             baseline.log_loss - self.log_loss,
                         );
                     }
@@ -631,16 +489,22 @@ pub struct ClassificationPerf {
     mean_f: f32,
     y_pred: Vec<f32>,
     y_true: Vec<f32>,
+    weights: Option<Vec<f32>>,
 }
 
-impl Into<BinaryClassificationReport> for ClassificationPerf {
-    fn into(self) -> BinaryClassificationReport {
+impl From<ClassificationPerf> for BinaryClassificationReport {
+    fn from(perf: ClassificationPerf) -> BinaryClassificationReport {
+        let weights = perf.weights.as_deref();
         let recall_positive =
-            GeneralClassificationMetrics::recall_positive(&self.y_pred, &self.y_true);
+            GeneralClassificationMetrics::recall_positive(&perf.y_pred, &perf.y_true, weights);
         let precision_positive =
-            GeneralClassificationMetrics::precision_positive(&self.y_pred, &self.y_true);
-        let recall_negative =
-            GeneralClassificationMetrics::recall_negative(&self.y_pred, &self.y_true, self.len);
+            GeneralClassificationMetrics::precision_positive(&perf.y_pred, &perf.y_true, weights);
+        let recall_negative = GeneralClassificationMetrics::recall_negative(
+            &perf.y_pred,
+            &perf.y_true,
+            weights,
+            perf.len,
+        );
         BinaryClassificationReport {
             balanced_accuracy: GeneralClassificationMetrics::balanced_accuracy(
                 recall_positive,
@@ -648,101 +512,144 @@ impl Into<BinaryClassificationReport> for ClassificationPerf {
             ),
             precision_positive,
             precision_negative: GeneralClassificationMetrics::precision_negative(
-                &self.y_pred,
-                &self.y_true,
-                self.len,
+                &perf.y_pred,
+                &perf.y_true,
+                weights,
+                perf.len,
             ),
             recall_positive,
             recall_negative,
             accuracy: GeneralClassificationMetrics::accuracy(
-                &self.y_pred,
-                &self.y_true,
-                self.mean_f,
+                &perf.y_pred,
+                &perf.y_true,
+                weights,
+                perf.mean_f,
             ),
             f1_score: GeneralClassificationMetrics::f1_score(recall_positive, precision_positive),
+            effective_sample_size: Some(batch_effective_size(weights, perf.y_true.len())),
+            confidence_intervals: None,
         }
     }
 }
 
 impl ClassificationPerf {
-    pub fn new(
-        py: Python<'_>,
-        y_true_src: &Bound<'_, PyUntypedArray>,
-        y_pred_src: &Bound<'_, PyUntypedArray>,
-    ) -> Result<ClassificationPerf, Box<dyn Error>> {
-        let (y_pred, y_true) =
-            PerfEntry::validate_and_cast_classification(py, y_true_src, y_pred_src, false, None)?;
-        if y_true.len() != y_pred.len() {
-            return Err("Arrays have different lengths".into());
-        }
-        if y_pred.len() == 0 {
-            return Err("Arrays have no data".into());
+    pub fn new(y_true: &[f64], y_pred: &[f64]) -> Result<ClassificationPerf, FairMlError> {
+        Self::from_vecs(cast_f32(y_true), cast_f32(y_pred), None)
+    }
+
+    /// `new` with every row counted at its sample weight.
+    pub fn new_weighted(
+        y_true: &[f64],
+        y_pred: &[f64],
+        sample_weights: &[f64],
+    ) -> Result<ClassificationPerf, FairMlError> {
+        Self::from_vecs(
+            cast_f32(y_true),
+            cast_f32(y_pred),
+            Some(cast_f32(sample_weights)),
+        )
+    }
+
+    pub(crate) fn from_vecs(
+        y_true: Vec<f32>,
+        y_pred: Vec<f32>,
+        weights: Option<Vec<f32>>,
+    ) -> Result<ClassificationPerf, FairMlError> {
+        check_lengths("y_true", &y_true, "y_pred", &y_pred)?;
+        if y_pred.is_empty() {
+            return Err(FairMlError::EmptyInput {
+                array: "y_pred".into(),
+            });
         }
-        let len: f32 = y_pred.len() as f32;
+        let len: f32 = total_weight(weights.as_deref(), &y_true)?;
         let mean_f: f32 = 1_f32 / len;
         Ok(ClassificationPerf {
             y_true,
             y_pred,
+            weights,
             mean_f,
             len,
         })
     }
 }
 
-struct LogisticRegressionPerf {
+pub struct LogisticRegressionPerf {
     y_true: Vec<f32>,
     y_pred: Vec<f32>,
     y_proba: Vec<f32>,
+    weights: Option<Vec<f32>>,
     mean_f: f32,
     len: f32,
 }
 
 impl LogisticRegressionPerf {
-    fn new(
-        py: Python<'_>,
-        y_pred_src: &Bound<'_, PyUntypedArray>,
-        y_true_src: &Bound<'_, PyUntypedArray>,
+    pub fn new(
+        y_true: &[f64],
+        y_proba: &[f64],
         threshold: f32,
-    ) -> Result<LogisticRegressionPerf, Box<dyn Error>> {
-        let true_type: PassedType = determine_type(py, y_true_src);
-        let y_true: Vec<f32> = PerfEntry::convert_f32(py, y_true_src, true_type)?;
-        let pred_type = determine_type(py, y_pred_src);
-        let y_proba: Vec<f32> = PerfEntry::convert_f32(py, y_pred_src, pred_type)?;
-
-        if y_true.len() != y_proba.len() {
-            return Err("Arrays have different lengths".into());
-        }
+    ) -> Result<LogisticRegressionPerf, FairMlError> {
+        Self::from_vecs(cast_f32(y_true), cast_f32(y_proba), threshold, None)
+    }
 
-        if y_proba.len() == 0 {
-            return Err("Arrays have no data".into());
+    /// `new` with every row counted at its sample weight.
+    pub fn new_weighted(
+        y_true: &[f64],
+        y_proba: &[f64],
+        threshold: f32,
+        sample_weights: &[f64],
+    ) -> Result<LogisticRegressionPerf, FairMlError> {
+        Self::from_vecs(
+            cast_f32(y_true),
+            cast_f32(y_proba),
+            threshold,
+            Some(cast_f32(sample_weights)),
+        )
+    }
+
+    pub(crate) fn from_vecs(
+        y_true: Vec<f32>,
+        y_proba: Vec<f32>,
+        threshold: f32,
+        weights: Option<Vec<f32>>,
+    ) -> Result<LogisticRegressionPerf, FairMlError> {
+        check_lengths("y_true", &y_true, "y_pred", &y_proba)?;
+        if y_proba.is_empty() {
+            return Err(FairMlError::EmptyInput {
+                array: "y_pred".into(),
+            });
         }
 
         let y_pred = y_proba
-            .clone()
             .iter()
             .map(|x| if *x >= threshold { 1_f32 } else { 0_f32 })
             .collect::<Vec<f32>>();
 
-        let len: f32 = y_true.len() as f32;
+        let len: f32 = total_weight(weights.as_deref(), &y_true)?;
 
         Ok(LogisticRegressionPerf {
             y_true,
             y_pred,
             y_proba,
+            weights,
             mean_f: 1_f32 / len,
             len,
         })
     }
 }
 
-impl Into<LogisticRegressionReport> for LogisticRegressionPerf {
-    fn into(self) -> LogisticRegressionReport {
+impl From<LogisticRegressionPerf> for LogisticRegressionReport {
+    fn from(perf: LogisticRegressionPerf) -> LogisticRegressionReport {
+        let weights = perf.weights.as_deref();
         let recall_positive =
-            GeneralClassificationMetrics::recall_positive(&self.y_pred, &self.y_true);
+            GeneralClassificationMetrics::recall_positive(&perf.y_pred, &perf.y_true, weights);
         let precision_positive =
-            GeneralClassificationMetrics::precision_positive(&self.y_pred, &self.y_true);
-        let recall_negative =
-            GeneralClassificationMetrics::recall_negative(&self.y_pred, &self.y_true, self.len);
+            GeneralClassificationMetrics::precision_positive(&perf.y_pred, &perf.y_true, weights);
+        let recall_negative = GeneralClassificationMetrics::recall_negative(
+            &perf.y_pred,
+            &perf.y_true,
+            weights,
+            perf.len,
+        );
         LogisticRegressionReport {
             balanced_accuracy: GeneralClassificationMetrics::balanced_accuracy(
                 recall_positive,
@@ -750,90 +657,44 @@ impl Into<LogisticRegressionReport> for LogisticRegressionPerf {
             ),
             precision_positive,
             precision_negative: GeneralClassificationMetrics::precision_negative(
-                &self.y_pred,
-                &self.y_true,
-                self.len,
+                &perf.y_pred,
+                &perf.y_true,
+                weights,
+                perf.len,
             ),
             recall_positive,
             recall_negative,
             accuracy: GeneralClassificationMetrics::accuracy(
-                &self.y_pred,
-                &self.y_true,
-                self.mean_f,
+                &perf.y_pred,
+                &perf.y_true,
+                weights,
+                perf.mean_f,
             ),
             f1_score: GeneralClassificationMetrics::f1_score(recall_positive, precision_positive),
             log_loss: GeneralClassificationMetrics::log_loss_score(
-                &self.y_proba,
-                &self.y_true,
-                self.mean_f,
+                &perf.y_proba,
+                &perf.y_true,
+                weights,
+                perf.mean_f,
             ),
+            effective_sample_size: Some(batch_effective_size(weights, perf.y_true.len())),
+            confidence_intervals: None,
         }
     }
 }
 
-pub struct LinearRegressionReport {
-    rmse: f32,
-    mse: f32,
-    mae: f32,
-    r_squared: f32,
-    max_error: f32,
-    msle: f32,
-    rmsle: f32,
-    mape: f32,
-}
-
-impl TryFrom<HashMap<String, f32>> for LinearRegressionReport {
-    type Error = String;
-    fn try_from(map: HashMap<String, f32>) -> Result<Self, Self::Error> {
-        let Some(rmse) = map.get("RootMeanSquaredError") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(mse) = map.get("MeanSquaredError") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(mae) = map.get("MeanAbsoluteError") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(r_squared) = map.get("RSquared") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(max_error) = map.get("MaxError") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(msle) = map.get("MeanSquaredLogError") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(rmsle) = map.get("RootMeanSquaredLogError") else {
-            return Err("Invalid regression report".into());
-        };
-        let Some(mape) = map.get("MeanAbsolutePercentageError") else {
-            return Err("Invalid regression report".into());
-        };
-        Ok(LinearRegressionReport {
-            rmse: *rmse,
-            mse: *mse,
-            mae: *mae,
-            r_squared: *r_squared,
-            max_error: *max_error,
-            msle: *msle,
-            rmsle: *rmsle,
-            mape: *mape,
-        })
-    }
-}
-
-impl LinearRegressionReport {
-    pub fn generate_report(&self) -> HashMap<String, f32> {
-        let mut map: HashMap<String, f32> = HashMap::with_capacity(8);
-        map.insert("RootMeanSquaredError".into(), self.rmse);
-        map.insert("MeanSquaredError".into(), self.mse);
-        map.insert("MeanAbsoluteError".into(), self.mae);
-        map.insert("RSquared".into(), self.r_squared);
-        map.insert("MaxError".into(), self.max_error);
-        map.insert("MeanSquaredLogError".into(), self.msle);
-        map.insert("RootMeanSquaredLogError".into(), self.rmsle);
-        map.insert("MeanAbsolutePercentageError".into(), self.mape);
-        map
+metric_report! {
+    pub struct LinearRegressionReport {
+        rmse: ROOT_MEAN_SQUARED_ERROR = "RootMeanSquaredError",
+        mse: MEAN_SQUARED_ERROR = "MeanSquaredError",
+        mae: MEAN_ABSOLUTE_ERROR = "MeanAbsoluteError",
+        r_squared: R_SQUARED = "RSquared",
+        max_error: MAX_ERROR = "MaxError",
+        msle: MEAN_SQUARED_LOG_ERROR = "MeanSquaredLogError",
+        rmsle: ROOT_MEAN_SQUARED_LOG_ERROR = "RootMeanSquaredLogError",
+        mape: MEAN_ABSOLUTE_PERCENTAGE_ERROR = "MeanAbsolutePercentageError",
+        ?effective_sample_size: f64 = "effectiveSampleSize",
+        ?confidence_intervals: ConfidenceIntervals = "confidenceIntervals",
     }
 }
 
@@ -852,7 +713,7 @@ impl LinearRegressionReport {
                     if self.rmse > baseline.rmse * (1_f32 + drift_threshold) {
                         update_failure_report_above(
                             &mut res,
-                            "RootMeanSquaredError".into(),
+                            Self::ROOT_MEAN_SQUARED_ERROR.into(),
                             self.rmse - baseline.rmse,
                         );
                     }
@@ -861,7 +722,7 @@ impl LinearRegressionReport {
                     if self.mse > baseline.mse * (1_f32 + drift_threshold) {
                         update_failure_report_above(
                             &mut res,
-                            "MeanSquaredError".into(),
+                            Self::MEAN_SQUARED_ERROR.into(),
                             self.mse - baseline.mse,
                         );
                     }
@@ -870,7 +731,7 @@ impl LinearRegressionReport {
                     if self.mae > baseline.mae * (1_f32 + drift_threshold) {
                         update_failure_report_above(
                             &mut res,
-                            "MeanAbsoluteError".into(),
+                            Self::MEAN_ABSOLUTE_ERROR.into(),
                             self.mae - baseline.mae,
                         );
                     }
@@ -879,7 +740,7 @@ impl LinearRegressionReport {
                     if self.r_squared > baseline.r_squared * (1_f32 + drift_threshold) {
                         update_failure_report_above(
                             &mut res,
-                            "RSquared".into(),
+                            Self::R_SQUARED.into(),
                             self.r_squared - baseline.r_squared,
                         );
                     }
@@ -888,7 +749,7 @@ impl LinearRegressionReport {
                     if self.max_error > baseline.max_error * (1_f32 + drift_threshold) {
                         update_failure_report_above(
                             &mut res,
-                            "MaxError".into(),
+                            Self::MAX_ERROR.into(),
                             self.max_error - baseline.max_error,
                         );
                     }
@@ -897,7 +758,7 @@ impl LinearRegressionReport {
                     if self.msle > baseline.msle * (1_f32 + drift_threshold) {
                         update_failure_report_above(
                             &mut res,
-                            "MeanSquaredLogError".into(),
+                            Self::MEAN_SQUARED_LOG_ERROR.into(),
                             self.msle - baseline.msle,
                         );
                     }
@@ -906,7 +767,7 @@ impl LinearRegressionReport {
                     if self.rmsle > baseline.rmsle * (1_f32 + drift_threshold) {
                         update_failure_report_above(
                             &mut res,
-                            "RootMeanSquaredLogError".into(),
+                            Self::ROOT_MEAN_SQUARED_LOG_ERROR.into(),
                             self.rmsle - baseline.rmsle,
                         );
                     }
@@ -915,7 +776,7 @@ impl LinearRegressionReport {
                     if self.mape > baseline.mape * (1_f32 + drift_threshold) {
                         update_failure_report_above(
                             &mut res,
-                            "MeanAbsolutePercentageError".into(),
+                            Self::MEAN_ABSOLUTE_PERCENTAGE_ERROR.into(),
                             self.mape - baseline.mape,
                         );
                     }
@@ -929,112 +790,558 @@ impl LinearRegressionReport {
 pub struct LinearRegressionPerf {
     y_pred: Vec<f32>,
     y_true: Vec<f32>,
+    weights: Option<Vec<f32>>,
     mean_f: f32,
 }
 
-impl Into<LinearRegressionReport> for LinearRegressionPerf {
-    fn into(self) -> LinearRegressionReport {
+type RegressionMetric = fn(&LinearRegressionPerf) -> f32;
+
+impl From<LinearRegressionPerf> for LinearRegressionReport {
+    fn from(perf: LinearRegressionPerf) -> LinearRegressionReport {
+        // each metric is a full pass over the data, so they run side by side
+        let metrics: [RegressionMetric; 8] = [
+            LinearRegressionPerf::root_mean_squared_error,
+            LinearRegressionPerf::mean_squared_error,
+            LinearRegressionPerf::mean_absolute_error,
+            LinearRegressionPerf::r_squared,
+            LinearRegressionPerf::max_error,
+            LinearRegressionPerf::mean_squared_log_error,
+            LinearRegressionPerf::root_mean_squared_log_error,
+            LinearRegressionPerf::mean_absolute_percentage_error,
+        ];
+        let values: Vec<f32> = metrics.par_iter().map(|metric| metric(&perf)).collect();
         LinearRegressionReport {
-            rmse: self.root_mean_squared_error(),
-            mse: self.mean_squared_error(),
-            mae: self.mean_absolute_error(),
-            r_squared: self.r_squared(),
-            max_error: self.max_error(),
-            msle: self.mean_squared_log_error(),
-            rmsle: self.root_mean_squared_log_error(),
-            mape: self.mean_absolute_percentage_error(),
+            rmse: values[0],
+            mse: values[1],
+            mae: values[2],
+            r_squared: values[3],
+            max_error: values[4],
+            msle: values[5],
+            rmsle: values[6],
+            mape: values[7],
+            effective_sample_size: Some(batch_effective_size(
+                perf.weights.as_deref(),
+                perf.y_true.len(),
+            )),
+            confidence_intervals: None,
         }
     }
 }
 
 impl LinearRegressionPerf {
-    pub fn new(
-        py: Python<'_>,
-        y_pred_src: &Bound<'_, PyUntypedArray>,
-        y_true_src: &Bound<'_, PyUntypedArray>,
-    ) -> Result<LinearRegressionPerf, Box<dyn Error>> {
-        let (y_true, y_pred) = PerfEntry::validate_and_cast_regression(py, y_true_src, y_pred_src)?;
-        if y_true.len() != y_pred.len() {
-            return Err("Arrays have different lengths".into());
-        }
-        if y_true.len() == 0 {
-            return Err("Arrays are emtpy".into());
+    pub fn new(y_true: &[f64], y_pred: &[f64]) -> Result<LinearRegressionPerf, FairMlError> {
+        Self::from_vecs(cast_f32(y_true), cast_f32(y_pred), None)
+    }
+
+    /// `new` with every row counted at its sample weight.
+    pub fn new_weighted(
+        y_true: &[f64],
+        y_pred: &[f64],
+        sample_weights: &[f64],
+    ) -> Result<LinearRegressionPerf, FairMlError> {
+        Self::from_vecs(
+            cast_f32(y_true),
+            cast_f32(y_pred),
+            Some(cast_f32(sample_weights)),
+        )
+    }
+
+    pub(crate) fn from_vecs(
+        y_true: Vec<f32>,
+        y_pred: Vec<f32>,
+        weights: Option<Vec<f32>>,
+    ) -> Result<LinearRegressionPerf, FairMlError> {
+        check_lengths("y_true", &y_true, "y_pred", &y_pred)?;
+        if y_true.is_empty() {
+            return Err(FairMlError::EmptyInput {
+                array: "y_true".into(),
+            });
         }
-        let mean_f: f32 = 1_f32 / y_pred.len() as f32;
+        let mean_f: f32 = 1_f32 / total_weight(weights.as_deref(), &y_true)?;
         Ok(LinearRegressionPerf {
             y_true,
             y_pred,
+            weights,
             mean_f,
         })
     }
 
+    fn weight(&self, row: usize) -> f32 {
+        weight_at(self.weights.as_deref(), row)
+    }
+
     fn root_mean_squared_error(&self) -> f32 {
         let mut errors = 0_f32;
-        for (t, p) in zip!(self.y_true, &self.y_pred) {
-            errors += (t - p).powi(2);
+        for (row, (t, p)) in zip!(self.y_true, &self.y_pred).enumerate() {
+            errors += self.weight(row) * (t - p).powi(2);
         }
         (errors * self.mean_f).powf(0.5_f32)
     }
 
     fn mean_squared_error(&self) -> f32 {
         let mut errors = 0_f32;
-        for (t, p) in zip!(&self.y_true, &self.y_pred) {
-            errors += (t - p).powi(2);
+        for (row, (t, p)) in zip!(&self.y_true, &self.y_pred).enumerate() {
+            errors += self.weight(row) * (t - p).powi(2);
         }
         errors * self.mean_f
     }
 
     fn mean_absolute_error(&self) -> f32 {
         let mut errors = 0_f32;
-        for (t, p) in zip!(&self.y_true, &self.y_pred) {
-            errors += (t - p).abs();
+        for (row, (t, p)) in zip!(&self.y_true, &self.y_pred).enumerate() {
+            errors += self.weight(row) * (t - p).abs();
         }
         errors * self.mean_f
     }
 
     fn r_squared(&self) -> f32 {
-        let y_mean: f32 = self.y_true.iter().sum::<f32>() * self.mean_f;
+        let y_mean: f32 = weighted_sum(&self.y_true, self.weights.as_deref()) * self.mean_f;
         let mut ss_regression: f32 = 0_f32;
-        for (t, p) in zip!(self.y_true, &self.y_pred) {
-            ss_regression += (t - p).powi(2);
+        for (row, (t, p)) in zip!(self.y_true, &self.y_pred).enumerate() {
+            ss_regression += self.weight(row) * (t - p).powi(2);
         }
         let ss_total: f32 = self
             .y_true
             .iter()
-            .map(|y| (y - y_mean).powi(2))
+            .enumerate()
+            .map(|(row, y)| self.weight(row) * (y - y_mean).powi(2))
             .sum::<f32>();
         ss_regression / ss_total
     }
 
     fn max_error(&self) -> f32 {
         let mut res = 0_f32;
-        for (t, p) in zip!(&self.y_true, &self.y_pred) {
-            res = f32::max(t - p, res);
+        for (row, (t, p)) in zip!(&self.y_true, &self.y_pred).enumerate() {
+            // a row weighted zero is not part of the sample
+            if self.weight(row) > 0_f32 {
+                res = f32::max(t - p, res);
+            }
         }
         res
     }
 
     fn mean_squared_log_error(&self) -> f32 {
         let mut sum = 0_f32;
-        for (t, p) in zip!(&self.y_true, &self.y_pred) {
-            sum += (1_f32 + t).log10() - (1_f32 + p).log10();
+        for (row, (t, p)) in zip!(&self.y_true, &self.y_pred).enumerate() {
+            sum += self.weight(row) * ((1_f32 + t).log10() - (1_f32 + p).log10());
         }
         sum.powi(2) / self.mean_f
     }
 
     fn root_mean_squared_log_error(&self) -> f32 {
         let mut sum = 0_f32;
-        for (t, p) in zip!(&self.y_true, &self.y_pred) {
-            sum += (1_f32 + t).log10() - (1_f32 + p).log10();
+        for (row, (t, p)) in zip!(&self.y_true, &self.y_pred).enumerate() {
+            sum += self.weight(row) * ((1_f32 + t).log10() - (1_f32 + p).log10());
         }
         sum.powi(2).sqrt() / self.mean_f
     }
 
     fn mean_absolute_percentage_error(&self) -> f32 {
         let mut sum = 0_f32;
-        for (t, p) in zip!(&self.y_true, &self.y_pred) {
-            sum += (t - p).abs() / t;
+        for (row, (t, p)) in zip!(&self.y_true, &self.y_pred).enumerate() {
+            sum += self.weight(row) * (t - p).abs() / t;
         }
         sum * self.mean_f * 100_f32
     }
 }
+
+fn is_positive(value: f64) -> i16 {
+    ((value - 1_f64).abs() <= f64::from(f32::EPSILON)) as i16
+}
+
+fn count_confusion<T>(y_true: &[T], y_pred: &[T], weights: Option<&[f64]>) -> FacetConfusion
+where
+    T: Copy + Into<f64> + Sync,
+{
+    y_true
+        .par_iter()
+        .zip(y_pred.par_iter())
+        .enumerate()
+        .fold(FacetConfusion::default, |mut counts, (row, (t, p))| {
+            let weight = weights.map_or(1_f64, |weights| weights[row]);
+            counts.record_weighted(is_positive((*p).into()), is_positive((*t).into()), weight);
+            counts
+        })
+        .reduce(FacetConfusion::default, FacetConfusion::merge)
+}
+
+impl BinaryClassificationReport {
+    fn from_confusion(counts: &FacetConfusion) -> BinaryClassificationReport {
+        // the rates use the weighted totals
+        let weighted = &counts.weighted;
+        let recall_positive = weighted.true_positives as f32 / weighted.actual_positives() as f32;
+        let precision_positive =
+            weighted.true_positives as f32 / weighted.predicted_positives() as f32;
+        let recall_negative = weighted.true_negatives as f32 / weighted.actual_negatives() as f32;
+        BinaryClassificationReport {
+            balanced_accuracy: GeneralClassificationMetrics::balanced_accuracy(
+                recall_positive,
+                recall_negative,
+            ),
+            precision_positive,
+            precision_negative: weighted.true_negatives as f32
+                / weighted.predicted_negatives() as f32,
+            recall_positive,
+            recall_negative,
+            accuracy: (weighted.true_positives + weighted.true_negatives) as f32
+                * (1_f32 / weighted.len() as f32),
+            f1_score: GeneralClassificationMetrics::f1_score(recall_positive, precision_positive),
+            effective_sample_size: Some(counts.effective_size()),
+            confidence_intervals: None,
+        }
+    }
+}
+
+/// Running confusion counts for a binary classifier, values equal to 1 are
+/// the positive class.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BinaryClassificationAccumulator {
+    counts: FacetConfusion,
+}
+
+impl BinaryClassificationAccumulator {
+    pub fn new() -> BinaryClassificationAccumulator {
+        BinaryClassificationAccumulator::default()
+    }
+
+    pub fn update<T>(&mut self, y_true: &[T], y_pred: &[T]) -> Result<(), FairMlError>
+    where
+        T: Copy + Into<f64> + Sync,
+    {
+        self.update_with(y_true, y_pred, None)
+    }
+
+    /// Adds a batch with every row counted at its sample weight.
+    pub fn update_weighted<T>(
+        &mut self,
+        y_true: &[T],
+        y_pred: &[T],
+        sample_weights: &[f64],
+    ) -> Result<(), FairMlError>
+    where
+        T: Copy + Into<f64> + Sync,
+    {
+        self.update_with(y_true, y_pred, Some(sample_weights))
+    }
+
+    fn update_with<T>(
+        &mut self,
+        y_true: &[T],
+        y_pred: &[T],
+        sample_weights: Option<&[f64]>,
+    ) -> Result<(), FairMlError>
+    where
+        T: Copy + Into<f64> + Sync,
+    {
+        check_lengths("y_true", y_true, "y_pred", y_pred)?;
+        if let Some(weights) = sample_weights {
+            check_sample_weights("y_true", y_true, weights)?;
+        }
+        self.counts = self
+            .counts
+            .merge(count_confusion(y_true, y_pred, sample_weights));
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: &BinaryClassificationAccumulator) {
+        self.counts = self.counts.merge(other.counts);
+    }
+
+    pub fn finalize(&self) -> Result<BinaryClassificationReport, FairMlError> {
+        if self.counts.is_empty() {
+            return Err(FairMlError::EmptyInput {
+                array: "y_pred".into(),
+            });
+        }
+        Ok(BinaryClassificationReport::from_confusion(&self.counts))
+    }
+}
+
+impl AccumulatorState for BinaryClassificationAccumulator {
+    const KIND: &'static str = "binary_classification";
+}
+
+/// Running statistics for a logistic regression model. Probabilities are
+/// thresholded as they arrive, so accumulators are only mergeable when they
+/// were built with the same threshold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogisticRegressionAccumulator {
+    threshold: f32,
+    counts: FacetConfusion,
+    log_penalties: f64,
+}
+
+impl LogisticRegressionAccumulator {
+    pub fn new(threshold: f32) -> LogisticRegressionAccumulator {
+        LogisticRegressionAccumulator {
+            threshold,
+            counts: FacetConfusion::default(),
+            log_penalties: 0_f64,
+        }
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    pub fn update<T>(&mut self, y_true: &[T], y_proba: &[T]) -> Result<(), FairMlError>
+    where
+        T: Copy + Into<f64> + Sync,
+    {
+        self.update_with(y_true, y_proba, None)
+    }
+
+    /// Adds a batch with every row counted at its sample weight.
+    pub fn update_weighted<T>(
+        &mut self,
+        y_true: &[T],
+        y_proba: &[T],
+        sample_weights: &[f64],
+    ) -> Result<(), FairMlError>
+    where
+        T: Copy + Into<f64> + Sync,
+    {
+        self.update_with(y_true, y_proba, Some(sample_weights))
+    }
+
+    fn update_with<T>(
+        &mut self,
+        y_true: &[T],
+        y_proba: &[T],
+        sample_weights: Option<&[f64]>,
+    ) -> Result<(), FairMlError>
+    where
+        T: Copy + Into<f64> + Sync,
+    {
+        check_lengths("y_true", y_true, "y_pred", y_proba)?;
+        if let Some(weights) = sample_weights {
+            check_sample_weights("y_true", y_true, weights)?;
+        }
+        let threshold = f64::from(self.threshold);
+        let y_pred: Vec<f64> = y_proba
+            .iter()
+            .map(|p| {
+                if (*p).into() >= threshold {
+                    1_f64
+                } else {
+                    0_f64
+                }
+            })
+            .collect();
+        let y_true_f64: Vec<f64> = y_true.iter().map(|t| (*t).into()).collect();
+        self.counts = self
+            .counts
+            .merge(count_confusion(&y_true_f64, &y_pred, sample_weights));
+        for (row, (t, p)) in zip!(y_true_f64, y_proba).enumerate() {
+            let p: f64 = (*p).into();
+            let weight = sample_weights.map_or(1_f64, |weights| weights[row]);
+            self.log_penalties += weight * (t * p.log10() + (1_f64 - t) * (1_f64 - p).log10());
+        }
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: &LogisticRegressionAccumulator) {
+        self.counts = self.counts.merge(other.counts);
+        self.log_penalties += other.log_penalties;
+    }
+
+    pub fn finalize(&self) -> Result<LogisticRegressionReport, FairMlError> {
+        if self.counts.is_empty() {
+            return Err(FairMlError::EmptyInput {
+                array: "y_pred".into(),
+            });
+        }
+        let binary = BinaryClassificationReport::from_confusion(&self.counts);
+        let log_loss = (-self.log_penalties / self.counts.weighted.len()) as f32;
+        Ok(LogisticRegressionReport {
+            balanced_accuracy: binary.balanced_accuracy,
+            precision_positive: binary.precision_positive,
+            precision_negative: binary.precision_negative,
+            recall_positive: binary.recall_positive,
+            recall_negative: binary.recall_negative,
+            accuracy: binary.accuracy,
+            f1_score: binary.f1_score,
+            log_loss: if log_loss.is_nan() { 0_f32 } else { log_loss },
+            effective_sample_size: binary.effective_sample_size,
+            confidence_intervals: None,
+        })
+    }
+}
+
+impl AccumulatorState for LogisticRegressionAccumulator {
+    const KIND: &'static str = "logistic_regression";
+}
+
+/// Running sums for a linear regression model. Every metric in
+/// `LinearRegressionReport` is recovered from these. Rows add their sample
+/// weight to `weight`, 1 when unweighted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinearRegressionAccumulator {
+    weight: f64,
+    weight_squares: f64,
+    sum_true: f64,
+    sum_true_squared: f64,
+    sum_squared_error: f64,
+    sum_absolute_error: f64,
+    sum_log_error: f64,
+    sum_percentage_error: f64,
+    max_error: f64,
+}
+
+impl LinearRegressionAccumulator {
+    pub fn new() -> LinearRegressionAccumulator {
+        LinearRegressionAccumulator::default()
+    }
+
+    pub fn update<T>(&mut self, y_true: &[T], y_pred: &[T]) -> Result<(), FairMlError>
+    where
+        T: Copy + Into<f64>,
+    {
+        self.update_with(y_true, y_pred, None)
+    }
+
+    /// Adds a batch with every row counted at its sample weight.
+    pub fn update_weighted<T>(
+        &mut self,
+        y_true: &[T],
+        y_pred: &[T],
+        sample_weights: &[f64],
+    ) -> Result<(), FairMlError>
+    where
+        T: Copy + Into<f64>,
+    {
+        self.update_with(y_true, y_pred, Some(sample_weights))
+    }
+
+    fn update_with<T>(
+        &mut self,
+        y_true: &[T],
+        y_pred: &[T],
+        sample_weights: Option<&[f64]>,
+    ) -> Result<(), FairMlError>
+    where
+        T: Copy + Into<f64>,
+    {
+        check_lengths("y_true", y_true, "y_pred", y_pred)?;
+        if let Some(weights) = sample_weights {
+            check_sample_weights("y_true", y_true, weights)?;
+        }
+        for (row, (t, p)) in zip!(y_true, y_pred).enumerate() {
+            let (t, p): (f64, f64) = ((*t).into(), (*p).into());
+            let w = sample_weights.map_or(1_f64, |weights| weights[row]);
+            self.weight += w;
+            self.weight_squares += w * w;
+            self.sum_true += w * t;
+            self.sum_true_squared += w * t.powi(2);
+            self.sum_squared_error += w * (t - p).powi(2);
+            self.sum_absolute_error += w * (t - p).abs();
+            self.sum_log_error += w * ((1_f64 + t).log10() - (1_f64 + p).log10());
+            self.sum_percentage_error += w * (t - p).abs() / t;
+            if w > 0_f64 {
+                self.max_error = f64::max(t - p, self.max_error);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn merge(&mut self, other: &LinearRegressionAccumulator) {
+        self.weight += other.weight;
+        self.weight_squares += other.weight_squares;
+        self.sum_true += other.sum_true;
+        self.sum_true_squared += other.sum_true_squared;
+        self.sum_squared_error += other.sum_squared_error;
+        self.sum_absolute_error += other.sum_absolute_error;
+        self.sum_log_error += other.sum_log_error;
+        self.sum_percentage_error += other.sum_percentage_error;
+        self.max_error = f64::max(self.max_error, other.max_error);
+    }
+
+    pub fn finalize(&self) -> Result<LinearRegressionReport, FairMlError> {
+        if self.weight <= 0_f64 {
+            return Err(FairMlError::EmptyInput {
+                array: "y_true".into(),
+            });
+        }
+        let n = self.weight;
+        let mse = self.sum_squared_error / n;
+        let ss_total = self.sum_true_squared - self.sum_true.powi(2) / n;
+        // msle and rmsle scale by n rather than divide, matching LinearRegressionPerf
+        Ok(LinearRegressionReport {
+            rmse: mse.sqrt() as f32,
+            mse: mse as f32,
+            mae: (self.sum_absolute_error / n) as f32,
+            r_squared: (self.sum_squared_error / ss_total) as f32,
+            max_error: self.max_error as f32,
+            msle: (self.sum_log_error.powi(2) * n) as f32,
+            rmsle: (self.sum_log_error.powi(2).sqrt() * n) as f32,
+            mape: (self.sum_percentage_error / n * 100_f64) as f32,
+            effective_sample_size: Some(effective_size(self.weight, self.weight_squares)),
+            confidence_intervals: None,
+        })
+    }
+}
+
+impl AccumulatorState for LinearRegressionAccumulator {
+    const KIND: &'static str = "linear_regression";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(left: f32, right: f64) -> bool {
+        (f64::from(left) - right).abs() < 1e-6
+    }
+
+    #[test]
+    fn classification_metrics_match_hand_counts() {
+        // 2 true positives, 1 false negative, 1 false positive, 1 true negative
+        let y_true = vec![1_f32, 1_f32, 1_f32, 0_f32, 0_f32];
+        let y_pred = vec![1_f32, 1_f32, 0_f32, 1_f32, 0_f32];
+        let report = BinaryClassificationReport::from(
+            ClassificationPerf::from_vecs(y_true.clone(), y_pred.clone(), None).unwrap(),
+        );
+        assert!(close(report.accuracy, 0.6));
+        assert!(close(report.precision_positive, 2.0 / 3.0));
+        assert!(close(report.recall_positive, 2.0 / 3.0));
+        assert!(close(report.precision_negative, 0.5));
+        assert!(close(report.recall_negative, 0.5));
+        assert!(close(report.f1_score, 2.0 / 3.0));
+
+        // a weight of 2 counts the first row twice
+        let weighted = BinaryClassificationReport::from(
+            ClassificationPerf::from_vecs(
+                y_true.clone(),
+                y_pred.clone(),
+                Some(vec![2_f32, 1_f32, 1_f32, 1_f32, 1_f32]),
+            )
+            .unwrap(),
+        );
+        let repeated = BinaryClassificationReport::from(
+            ClassificationPerf::from_vecs(
+                [vec![1_f32], y_true].concat(),
+                [vec![1_f32], y_pred].concat(),
+                None,
+            )
+            .unwrap(),
+        );
+        assert_eq!(weighted.entries(), repeated.entries());
+    }
+
+    #[test]
+    fn regression_errors_match_hand_computation() {
+        let report = LinearRegressionReport::from(
+            LinearRegressionPerf::from_vecs(
+                vec![1_f32, 2_f32, 5_f32],
+                vec![1_f32, 2_f32, 3_f32],
+                None,
+            )
+            .unwrap(),
+        );
+        assert!(close(report.mse, 4.0 / 3.0));
+        assert!(close(report.rmse, (4_f64 / 3_f64).sqrt()));
+        assert!(close(report.mae, 2.0 / 3.0));
+        assert!(close(report.max_error, 2.0));
+        assert!(LinearRegressionPerf::from_vecs(vec![1_f32], vec![], None).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::errors::FairMlError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -13,7 +14,7 @@ pub struct PassedRuntimeReturn {
     pub passed: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ModelType {
     LinearRegression,
     LogisticRegression,
@@ -21,13 +22,13 @@ pub enum ModelType {
 }
 
 impl TryFrom<&str> for ModelType {
-    type Error = String;
+    type Error = FairMlError;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "LinearRegression" => Ok(Self::LinearRegression),
             "LogisticRegression" => Ok(Self::LogisticRegression),
             "BinaryClassification" => Ok(Self::BinaryClassification),
-            _ => Err("invalid model type".into()),
+            _ => Err(FairMlError::InvalidModelType { name: value.into() }),
         }
     }
 }
@@ -0,0 +1,653 @@
+use crate::data_bias::{DataBiasRegistry, DataBiasReport, FacetLabels, PreTraining};
+use crate::data_handler::{check_facets, check_lengths};
+use crate::errors::FairMlError;
+use crate::model_bias::{FacetConfusion, ModelBiasRegistry, ModelBiasReport, PostTrainingData};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Which facets each group is compared against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupComparison {
+    /// Every group as facet `a` against all other rows as facet `d`.
+    OneVsRest,
+    /// Every pair of groups, the group that sorts first as facet `a`.
+    Pairwise,
+    /// Every other group as facet `a` against the named group as facet `d`.
+    Reference(String),
+}
+
+impl GroupComparison {
+    pub fn parse(comparison: &str, reference: Option<String>) -> Result<Self, FairMlError> {
+        match (comparison, reference) {
+            ("one_vs_rest", None) => Ok(Self::OneVsRest),
+            ("pairwise", None) => Ok(Self::Pairwise),
+            ("reference", Some(group)) => Ok(Self::Reference(group)),
+            _ => Err(FairMlError::InvalidComparison {
+                comparison: comparison.into(),
+            }),
+        }
+    }
+}
+
+/// Per group counts of a categorical feature, groups sorted by value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupedData<T> {
+    pub groups: Vec<String>,
+    pub counts: Vec<T>,
+}
+
+/// One comparison in a multi group report. `versus` is `None` when the group
+/// was compared against every other row.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupResult<R> {
+    pub group: String,
+    pub versus: Option<String>,
+    pub report: R,
+}
+
+/// The comparison furthest from a metric's ideal value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorstCase {
+    pub group: String,
+    pub versus: Option<String>,
+    pub value: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiGroupReport<R> {
+    pub results: Vec<GroupResult<R>>,
+    pub worst_case: BTreeMap<String, WorstCase>,
+}
+
+trait FacetCounts: Copy + Default + Send + Sync {
+    fn merge(self, other: Self) -> Self;
+    fn size(&self) -> u64;
+}
+
+impl FacetCounts for FacetLabels {
+    fn merge(self, other: Self) -> Self {
+        FacetLabels::merge(self, other)
+    }
+
+    fn size(&self) -> u64 {
+        self.len()
+    }
+}
+
+impl FacetCounts for FacetConfusion {
+    fn merge(self, other: Self) -> Self {
+        FacetConfusion::merge(self, other)
+    }
+
+    fn size(&self) -> u64 {
+        self.len()
+    }
+}
+
+/// Maps each row to the index of its key, keys sorted.
+fn encode_keys<T>(values: &[T]) -> (Vec<&T>, Vec<usize>)
+where
+    T: Ord,
+{
+    let mut index: BTreeMap<&T, usize> = values.iter().map(|value| (value, 0)).collect();
+    for (position, slot) in index.values_mut().enumerate() {
+        *slot = position;
+    }
+    let codes = values.iter().map(|value| index[value]).collect();
+    (index.into_keys().collect(), codes)
+}
+
+/// Maps each row to the index of its group, groups sorted by value.
+pub(crate) fn encode_groups<T>(feature_values: &[T]) -> (Vec<String>, Vec<usize>)
+where
+    T: Ord + ToString,
+{
+    let (keys, codes) = encode_keys(feature_values);
+    (keys.into_iter().map(ToString::to_string).collect(), codes)
+}
+
+fn count_groups<T, R>(
+    groups: Vec<String>,
+    codes: &[usize],
+    rows: impl IndexedParallelIterator<Item = R>,
+    record: impl Fn(&mut T, R) + Send + Sync,
+) -> GroupedData<T>
+where
+    T: FacetCounts,
+{
+    let n_groups = groups.len();
+    let counts = codes
+        .par_iter()
+        .zip(rows)
+        .fold(
+            || vec![T::default(); n_groups],
+            |mut counts, (code, row)| {
+                record(&mut counts[*code], row);
+                counts
+            },
+        )
+        .reduce(
+            || vec![T::default(); n_groups],
+            |left, right| {
+                left.into_iter()
+                    .zip(right)
+                    .map(|(l, r)| l.merge(r))
+                    .collect()
+            },
+        );
+    GroupedData { groups, counts }
+}
+
+/// Label counts per group of a categorical feature.
+pub fn group_data_bias<T>(
+    feature_values: &[T],
+    ground_truth_values: &[i16],
+) -> Result<GroupedData<FacetLabels>, FairMlError>
+where
+    T: Ord + ToString,
+{
+    check_lengths(
+        "feature",
+        feature_values,
+        "ground_truth",
+        ground_truth_values,
+    )?;
+    let (groups, codes) = encode_groups(feature_values);
+    Ok(count_groups(
+        groups,
+        &codes,
+        ground_truth_values.par_iter(),
+        |counts: &mut FacetLabels, ground_truth| counts.record(*ground_truth),
+    ))
+}
+
+/// Confusion counts per group of a categorical feature.
+pub fn group_model_bias<T>(
+    feature_values: &[T],
+    prediction_values: &[i16],
+    ground_truth_values: &[i16],
+) -> Result<GroupedData<FacetConfusion>, FairMlError>
+where
+    T: Ord + ToString,
+{
+    check_lengths("feature", feature_values, "prediction", prediction_values)?;
+    check_lengths(
+        "feature",
+        feature_values,
+        "ground_truth",
+        ground_truth_values,
+    )?;
+    let (groups, codes) = encode_groups(feature_values);
+    Ok(count_groups(
+        groups,
+        &codes,
+        prediction_values
+            .par_iter()
+            .zip(ground_truth_values.par_iter()),
+        |counts: &mut FacetConfusion, (prediction, ground_truth)| {
+            counts.record(*prediction, *ground_truth)
+        },
+    ))
+}
+
+type Facets<T> = (String, Option<String>, T, T);
+
+/// The (facet a, facet d) counts for every comparison requested.
+fn facets<T: FacetCounts>(
+    data: &GroupedData<T>,
+    comparison: &GroupComparison,
+) -> Result<Vec<Facets<T>>, FairMlError> {
+    if data.groups.len() < 2 {
+        return Err(FairMlError::EmptyFacet { facet: "d".into() });
+    }
+    let named = |i: usize| data.groups[i].clone();
+    let facets = match comparison {
+        GroupComparison::OneVsRest => (0..data.groups.len())
+            .map(|i| {
+                let rest = data
+                    .counts
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .fold(T::default(), |acc, (_, counts)| acc.merge(*counts));
+                (named(i), None, data.counts[i], rest)
+            })
+            .collect(),
+        GroupComparison::Pairwise => {
+            let n = data.groups.len();
+            (0..n)
+                .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+                .map(|(i, j)| (named(i), Some(named(j)), data.counts[i], data.counts[j]))
+                .collect()
+        }
+        GroupComparison::Reference(reference) => {
+            let r = data
+                .groups
+                .iter()
+                .position(|group| group == reference)
+                .ok_or_else(|| FairMlError::UnknownGroup {
+                    group: reference.clone(),
+                })?;
+            (0..data.groups.len())
+                .filter(|i| *i != r)
+                .map(|i| (named(i), Some(named(r)), data.counts[i], data.counts[r]))
+                .collect()
+        }
+    };
+    Ok(facets)
+}
+
+fn worst_case<R>(
+    results: &[GroupResult<R>],
+    values: impl Fn(&R) -> HashMap<String, f32>,
+    ideal_value: impl Fn(&str) -> f32,
+) -> BTreeMap<String, WorstCase> {
+    let mut worst: BTreeMap<String, WorstCase> = BTreeMap::new();
+    for result in results {
+        for (metric, value) in values(&result.report) {
+            if value.is_nan() {
+                continue;
+            }
+            let distance = (value - ideal_value(&metric)).abs();
+            let is_worse = worst
+                .get(&metric)
+                .is_none_or(|current| distance > (current.value - ideal_value(&metric)).abs());
+            if is_worse {
+                worst.insert(
+                    metric,
+                    WorstCase {
+                        group: result.group.clone(),
+                        versus: result.versus.clone(),
+                        value,
+                    },
+                );
+            }
+        }
+    }
+    worst
+}
+
+fn analyze_groups<T, R>(
+    data: &GroupedData<T>,
+    comparison: &GroupComparison,
+    analyze: impl Fn(T, T) -> Result<R, FairMlError> + Send + Sync,
+) -> Result<Vec<GroupResult<R>>, FairMlError>
+where
+    T: FacetCounts,
+    R: Send,
+{
+    facets(data, comparison)?
+        .into_par_iter()
+        .map(|(group, versus, facet_a, facet_d)| {
+            Ok(GroupResult {
+                group,
+                versus,
+                report: analyze(facet_a, facet_d)?,
+            })
+        })
+        .collect()
+}
+
+/// Every data bias metric for each comparison, with the worst case group per
+/// metric judged by distance from the metric's ideal value.
+pub fn multi_group_data_bias(
+    data: &GroupedData<FacetLabels>,
+    comparison: &GroupComparison,
+    registry: &DataBiasRegistry,
+) -> Result<MultiGroupReport<DataBiasReport>, FairMlError> {
+    let results = analyze_groups(data, comparison, |facet_a, facet_d| {
+        registry.analyze(&PreTraining { facet_a, facet_d })
+    })?;
+    let worst_case = worst_case(&results, DataBiasReport::to_map, |name| {
+        registry
+            .get(name)
+            .map_or(0_f32, |metric| metric.ideal_value())
+    });
+    Ok(MultiGroupReport {
+        results,
+        worst_case,
+    })
+}
+
+/// Every model bias metric for each comparison, see `multi_group_data_bias`.
+pub fn multi_group_model_bias(
+    data: &GroupedData<FacetConfusion>,
+    comparison: &GroupComparison,
+    registry: &ModelBiasRegistry,
+) -> Result<MultiGroupReport<ModelBiasReport>, FairMlError> {
+    let results = analyze_groups(data, comparison, |facet_a, facet_d| {
+        registry.analyze(&PostTrainingData { facet_a, facet_d })
+    })?;
+    let worst_case = worst_case(&results, ModelBiasReport::to_map, |name| {
+        registry
+            .get(name)
+            .map_or(0_f32, |metric| metric.ideal_value())
+    });
+    Ok(MultiGroupReport {
+        results,
+        worst_case,
+    })
+}
+
+/// One intersectional subgroup against its complement. `subgroup` says, for
+/// each attribute, whether the rows meet that attribute's label. Subgroups
+/// smaller than the minimum size are flagged and carry no report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubgroupResult<R> {
+    pub subgroup: BTreeMap<String, bool>,
+    pub size: u64,
+    pub suppressed: bool,
+    pub report: Option<R>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntersectionalReport<R> {
+    pub min_subgroup_size: u64,
+    pub results: Vec<SubgroupResult<R>>,
+}
+
+/// Labeled protected attributes and the subgroup every row falls in.
+struct Intersections {
+    attributes: Vec<String>,
+    subgroups: Vec<Vec<bool>>,
+    codes: Vec<usize>,
+}
+
+/// Combines the labeled attributes one at a time, re-indexing after each so
+/// the number of attributes is not bounded by the width of an integer code.
+fn encode_intersections(
+    attributes: &[(&str, &[i16])],
+    n_rows: usize,
+) -> Result<Intersections, FairMlError> {
+    if attributes.is_empty() {
+        return Err(FairMlError::EmptyInput {
+            array: "features".into(),
+        });
+    }
+    let mut subgroups: Vec<Vec<bool>> = vec![Vec::new()];
+    let mut codes: Vec<usize> = vec![0; n_rows];
+    for (name, labels) in attributes {
+        check_lengths(name, labels, "ground_truth", &codes)?;
+        let combined: Vec<(usize, bool)> = codes
+            .iter()
+            .zip(labels.iter())
+            .map(|(code, label)| (*code, *label == 1))
+            .collect();
+        let (keys, next) = encode_keys(&combined);
+        subgroups = keys
+            .into_iter()
+            .map(|(code, member)| {
+                let mut subgroup = subgroups[*code].clone();
+                subgroup.push(*member);
+                subgroup
+            })
+            .collect();
+        codes = next;
+    }
+    Ok(Intersections {
+        attributes: attributes
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        subgroups,
+        codes,
+    })
+}
+
+fn analyze_subgroups<T, R>(
+    intersections: &Intersections,
+    data: &GroupedData<T>,
+    min_subgroup_size: u64,
+    analyze: impl Fn(T, T) -> Result<R, FairMlError> + Send + Sync,
+) -> Result<IntersectionalReport<R>, FairMlError>
+where
+    T: FacetCounts,
+    R: Send,
+{
+    // one vs rest keeps group order, so result i is subgroup i
+    let results = facets(data, &GroupComparison::OneVsRest)?
+        .into_par_iter()
+        .zip(intersections.subgroups.par_iter())
+        .map(|((_, _, facet_a, facet_d), members)| {
+            let size = facet_a.size();
+            let suppressed = size < min_subgroup_size;
+            Ok(SubgroupResult {
+                subgroup: intersections
+                    .attributes
+                    .iter()
+                    .cloned()
+                    .zip(members.iter().copied())
+                    .collect(),
+                size,
+                suppressed,
+                report: if suppressed {
+                    None
+                } else {
+                    Some(analyze(facet_a, facet_d)?)
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, FairMlError>>()?;
+    Ok(IntersectionalReport {
+        min_subgroup_size,
+        results,
+    })
+}
+
+fn subgroup_names(intersections: &Intersections) -> Vec<String> {
+    intersections
+        .subgroups
+        .iter()
+        .map(|members| format!("{members:?}"))
+        .collect()
+}
+
+/// Every data bias metric for each intersection of the labeled protected
+/// attributes, against all rows outside it.
+pub fn intersectional_data_bias(
+    attributes: &[(&str, &[i16])],
+    ground_truth_values: &[i16],
+    min_subgroup_size: u64,
+    registry: &DataBiasRegistry,
+) -> Result<IntersectionalReport<DataBiasReport>, FairMlError> {
+    let intersections = encode_intersections(attributes, ground_truth_values.len())?;
+    let data = count_groups(
+        subgroup_names(&intersections),
+        &intersections.codes,
+        ground_truth_values.par_iter(),
+        |counts: &mut FacetLabels, ground_truth| counts.record(*ground_truth),
+    );
+    analyze_subgroups(
+        &intersections,
+        &data,
+        min_subgroup_size,
+        |facet_a, facet_d| registry.analyze(&PreTraining { facet_a, facet_d }),
+    )
+}
+
+/// Every model bias metric for each intersection, see `intersectional_data_bias`.
+pub fn intersectional_model_bias(
+    attributes: &[(&str, &[i16])],
+    prediction_values: &[i16],
+    ground_truth_values: &[i16],
+    min_subgroup_size: u64,
+    registry: &ModelBiasRegistry,
+) -> Result<IntersectionalReport<ModelBiasReport>, FairMlError> {
+    check_lengths(
+        "prediction",
+        prediction_values,
+        "ground_truth",
+        ground_truth_values,
+    )?;
+    let intersections = encode_intersections(attributes, ground_truth_values.len())?;
+    let data = count_groups(
+        subgroup_names(&intersections),
+        &intersections.codes,
+        prediction_values
+            .par_iter()
+            .zip(ground_truth_values.par_iter()),
+        |counts: &mut FacetConfusion, (prediction, ground_truth)| {
+            counts.record(*prediction, *ground_truth)
+        },
+    );
+    analyze_subgroups(
+        &intersections,
+        &data,
+        min_subgroup_size,
+        |facet_a, facet_d| registry.analyze(&PostTrainingData { facet_a, facet_d }),
+    )
+}
+
+/// Label counts of both facets within one stratum.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct StratumLabels {
+    facet_a: FacetLabels,
+    facet_d: FacetLabels,
+}
+
+impl FacetCounts for StratumLabels {
+    fn merge(self, other: Self) -> Self {
+        StratumLabels {
+            facet_a: self.facet_a.merge(other.facet_a),
+            facet_d: self.facet_d.merge(other.facet_d),
+        }
+    }
+
+    fn size(&self) -> u64 {
+        self.facet_a.len() + self.facet_d.len()
+    }
+}
+
+impl StratumLabels {
+    /// Facet d's share of the negative outcomes minus its share of the
+    /// positive outcomes. A stratum without any negative (or positive)
+    /// outcome contributes 0 for that share.
+    fn demographic_disparity(&self) -> f32 {
+        let share = |facet_d: f64, total: f64| {
+            if total > 0_f64 {
+                (facet_d / total) as f32
+            } else {
+                0_f32
+            }
+        };
+        let (a, d) = (&self.facet_a.weighted, &self.facet_d.weighted);
+        share(d.negatives, a.negatives + d.negatives)
+            - share(d.positives, a.positives + d.positives)
+    }
+}
+
+/// Demographic disparity within one stratum of the group variable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StratumDisparity {
+    pub group: String,
+    pub size: u64,
+    pub disparity: f32,
+}
+
+/// Conditional demographic disparity, the average of the per stratum
+/// disparities weighted by stratum size. Strata are sorted by value, so a
+/// stratum whose sign differs from `value` points at Simpson's paradox.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConditionalDisparity {
+    pub value: f32,
+    pub strata: Vec<StratumDisparity>,
+}
+
+/// Conditional demographic disparity of facet d, in the labels (CDDL) when
+/// `outcomes` is the ground truth or in the predicted labels (CDDPL) when it
+/// is the predictions. `strata` holds the group variable, e.g. department.
+pub fn conditional_demographic_disparity<T>(
+    strata: &[T],
+    feature_values: &[i16],
+    (outcome_name, outcome_values): (&str, &[i16]),
+) -> Result<ConditionalDisparity, FairMlError>
+where
+    T: Ord + ToString,
+{
+    check_lengths("group_variable", strata, "feature", feature_values)?;
+    check_lengths("feature", feature_values, outcome_name, outcome_values)?;
+    if strata.is_empty() {
+        return Err(FairMlError::EmptyInput {
+            array: "group_variable".into(),
+        });
+    }
+    let (groups, codes) = encode_groups(strata);
+    let data = count_groups(
+        groups,
+        &codes,
+        feature_values.par_iter().zip(outcome_values.par_iter()),
+        |counts: &mut StratumLabels, (feature, outcome)| match *feature {
+            1_i16 => counts.facet_a.record(*outcome),
+            0_i16 => counts.facet_d.record(*outcome),
+            // a missing feature kept as its own group belongs to neither facet
+            _ => {}
+        },
+    );
+    // every row may sit outside both facets, e.g. a missing feature kept as
+    // its own group, which leaves no stratum to average over
+    let facet_size = |facet: fn(&StratumLabels) -> &FacetLabels| -> u64 {
+        data.counts.iter().map(|counts| facet(counts).len()).sum()
+    };
+    check_facets(
+        facet_size(|counts| &counts.facet_a) == 0,
+        facet_size(|counts| &counts.facet_d) == 0,
+    )?;
+    let strata: Vec<StratumDisparity> = data
+        .groups
+        .into_iter()
+        .zip(data.counts)
+        .map(|(group, counts)| StratumDisparity {
+            group,
+            size: counts.size(),
+            disparity: counts.demographic_disparity(),
+        })
+        .collect();
+    let total: f64 = strata.iter().map(|stratum| stratum.size as f64).sum();
+    let weighted: f64 = strata
+        .iter()
+        .map(|stratum| stratum.size as f64 * f64::from(stratum.disparity))
+        .sum();
+    Ok(ConditionalDisparity {
+        value: (weighted / total) as f32,
+        strata,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_handler::MISSING_LABEL;
+
+    #[test]
+    fn strata_are_weighted_by_size() {
+        let strata = ["A", "A", "A", "A", "B", "B"];
+        let feature = [1, 1, 0, 0, 1, 0];
+        let outcome = [1, 0, 1, 0, 1, 0];
+        let report =
+            conditional_demographic_disparity(&strata, &feature, ("ground_truth", &outcome))
+                .unwrap();
+        // A: facet d holds 1/2 of both outcomes, B: 1/1 of the negatives and 0/1 of the positives
+        assert_eq!(report.strata[0].disparity, 0_f32);
+        assert_eq!(report.strata[1].disparity, 1_f32);
+        assert!((report.value - 1_f32 / 3_f32).abs() < 1e-6);
+    }
+
+    #[test]
+    fn rows_outside_both_facets_are_an_error() {
+        let strata = ["A", "B"];
+        let feature = [MISSING_LABEL, MISSING_LABEL];
+        let outcome = [1, 0];
+        assert_eq!(
+            conditional_demographic_disparity(&strata, &feature, ("ground_truth", &outcome)),
+            Err(FairMlError::EmptyFacet { facet: "a".into() })
+        );
+    }
+}
@@ -0,0 +1,667 @@
+use super::ingestion::{apply_label, sample_weights, Column, PerfEntry};
+use super::metrics::{data_bias_registry, model_bias_registry};
+use super::reports::missing_dict;
+use super::reports::{
+    PyBinaryClassificationReport, PyDataBiasReport, PyLinearRegressionReport,
+    PyLogisticRegressionReport, PyModelBiasReport,
+};
+use crate::data_bias::DataBiasAccumulator;
+use crate::data_handler::{
+    apply_missing_policy, apply_missing_policy_values, check_sample_weights, MissingPolicy,
+};
+use crate::errors::FairMlError;
+use crate::model_bias::ModelBiasAccumulator;
+use crate::model_perf::{
+    BinaryClassificationAccumulator, LinearRegressionAccumulator, LogisticRegressionAccumulator,
+};
+use crate::state::AccumulatorState;
+use pyo3::prelude::*;
+use pyo3::pyclass::boolean_struct::False;
+use pyo3::types::{PyBytes, PyDict, PyTuple, PyType};
+use pyo3::PyClass;
+
+fn state_bytes<'py, T: AccumulatorState>(
+    py: Python<'py>,
+    state: &T,
+) -> PyResult<Bound<'py, PyBytes>> {
+    Ok(PyBytes::new_bound(py, &state.to_bytes()?))
+}
+
+/// Builds an instance of `cls`, which may be a python subclass, from the
+/// constructor arguments, then swaps in the deserialized state.
+fn restore<'py, C, T>(
+    cls: &Bound<'py, PyType>,
+    args: &Bound<'py, PyTuple>,
+    state: T,
+    set: impl FnOnce(&mut C, T),
+) -> PyResult<Bound<'py, PyAny>>
+where
+    C: PyClass<Frozen = False>,
+{
+    let instance = cls.call1(args)?;
+    set(&mut instance.downcast::<C>()?.borrow_mut(), state);
+    Ok(instance)
+}
+
+/// Streaming data bias. The labels and the missing value policy are fixed at
+/// construction and applied to every batch passed to `update`.
+#[pyclass(
+    subclass,
+    name = "DataBiasAccumulator",
+    module = "fair_perf_ml._fair_perf_ml"
+)]
+pub struct PyDataBiasAccumulator {
+    inner: DataBiasAccumulator,
+    feature_label_or_threshold: PyObject,
+    ground_truth_label_or_threshold: PyObject,
+    missing_policy: MissingPolicy,
+}
+
+#[pymethods]
+impl PyDataBiasAccumulator {
+    #[new]
+    #[pyo3(signature = (
+        feature_label_or_threshold,
+        ground_truth_label_or_threshold,
+        missing_policy="raise")
+    )]
+    fn new(
+        feature_label_or_threshold: PyObject,
+        ground_truth_label_or_threshold: PyObject,
+        missing_policy: &str,
+    ) -> PyResult<PyDataBiasAccumulator> {
+        Ok(PyDataBiasAccumulator {
+            inner: DataBiasAccumulator::new(),
+            feature_label_or_threshold,
+            ground_truth_label_or_threshold,
+            missing_policy: MissingPolicy::try_from(missing_policy)?,
+        })
+    }
+
+    /// Rows with a missing value per facet, over every batch seen so far.
+    #[getter]
+    fn missing<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        missing_dict(py, &self.inner.missing())
+    }
+
+    #[pyo3(signature = (feature_array, ground_truth_array, sample_weight=None))]
+    fn update(
+        &mut self,
+        py: Python<'_>,
+        feature_array: Column<'_>,
+        ground_truth_array: Column<'_>,
+        sample_weight: Option<Column<'_>>,
+    ) -> PyResult<()> {
+        let mut sample_weight = sample_weights(sample_weight.as_ref())?;
+        let mut labeled_ground_truth = apply_label(
+            &ground_truth_array,
+            self.ground_truth_label_or_threshold.bind(py).clone(),
+            "ground_truth",
+        )?;
+        let mut labeled_feature = apply_label(
+            &feature_array,
+            self.feature_label_or_threshold.bind(py).clone(),
+            "feature",
+        )?;
+        let (inner, missing_policy) = (&mut self.inner, self.missing_policy);
+        py.allow_threads(|| {
+            if let Some(weights) = &sample_weight {
+                check_sample_weights("feature", &labeled_feature, weights)?;
+            }
+            let missing = apply_missing_policy(
+                missing_policy,
+                Some(("feature", &mut labeled_feature)),
+                vec![("ground_truth", &mut labeled_ground_truth)],
+                sample_weight.as_mut(),
+            )?;
+            match &sample_weight {
+                Some(weights) => {
+                    inner.update_weighted(&labeled_feature, &labeled_ground_truth, weights)?
+                }
+                None => inner.update(&labeled_feature, &labeled_ground_truth)?,
+            }
+            inner.record_missing(missing);
+            Ok::<_, FairMlError>(())
+        })?;
+        Ok(())
+    }
+
+    fn merge(&mut self, other: PyRef<'_, PyDataBiasAccumulator>) {
+        self.inner.merge(&other.inner);
+    }
+
+    fn finalize(&self) -> PyResult<PyDataBiasReport> {
+        Ok(self.inner.finalize_with(&data_bias_registry())?.into())
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        state_bytes(py, &self.inner)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(self.inner.to_json()?)
+    }
+
+    /// `args` are the constructor arguments, the labels and the missing value
+    /// policy are not part of the state.
+    #[classmethod]
+    #[pyo3(signature = (data, *args))]
+    fn from_bytes<'py>(
+        cls: &Bound<'py, PyType>,
+        data: &[u8],
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = DataBiasAccumulator::from_bytes(data)?;
+        restore(cls, args, state, |acc: &mut Self, state| acc.inner = state)
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (data, *args))]
+    fn from_json<'py>(
+        cls: &Bound<'py, PyType>,
+        data: &str,
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = DataBiasAccumulator::from_json(data)?;
+        restore(cls, args, state, |acc: &mut Self, state| acc.inner = state)
+    }
+
+    fn __getnewargs__(&self, py: Python<'_>) -> (PyObject, PyObject, &'static str) {
+        (
+            self.feature_label_or_threshold.clone_ref(py),
+            self.ground_truth_label_or_threshold.clone_ref(py),
+            self.missing_policy.as_str(),
+        )
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        state_bytes(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = DataBiasAccumulator::from_bytes(state)?;
+        Ok(())
+    }
+}
+
+/// Streaming model bias, labels and missing value policy fixed at construction
+/// like `DataBiasAccumulator`.
+#[pyclass(
+    subclass,
+    name = "ModelBiasAccumulator",
+    module = "fair_perf_ml._fair_perf_ml"
+)]
+pub struct PyModelBiasAccumulator {
+    inner: ModelBiasAccumulator,
+    feature_label_or_threshold: PyObject,
+    ground_truth_label_or_threshold: PyObject,
+    prediction_label_or_threshold: PyObject,
+    missing_policy: MissingPolicy,
+}
+
+#[pymethods]
+impl PyModelBiasAccumulator {
+    #[new]
+    #[pyo3(signature = (
+        feature_label_or_threshold,
+        ground_truth_label_or_threshold,
+        prediction_label_or_threshold,
+        missing_policy="raise")
+    )]
+    fn new(
+        feature_label_or_threshold: PyObject,
+        ground_truth_label_or_threshold: PyObject,
+        prediction_label_or_threshold: PyObject,
+        missing_policy: &str,
+    ) -> PyResult<PyModelBiasAccumulator> {
+        Ok(PyModelBiasAccumulator {
+            inner: ModelBiasAccumulator::new(),
+            feature_label_or_threshold,
+            ground_truth_label_or_threshold,
+            prediction_label_or_threshold,
+            missing_policy: MissingPolicy::try_from(missing_policy)?,
+        })
+    }
+
+    /// Rows with a missing value per facet, over every batch seen so far.
+    #[getter]
+    fn missing<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        missing_dict(py, &self.inner.missing())
+    }
+
+    #[pyo3(signature = (feature_array, ground_truth_array, prediction_array, sample_weight=None))]
+    fn update(
+        &mut self,
+        py: Python<'_>,
+        feature_array: Column<'_>,
+        ground_truth_array: Column<'_>,
+        prediction_array: Column<'_>,
+        sample_weight: Option<Column<'_>>,
+    ) -> PyResult<()> {
+        let mut sample_weight = sample_weights(sample_weight.as_ref())?;
+        let mut labeled_predictions = apply_label(
+            &prediction_array,
+            self.prediction_label_or_threshold.bind(py).clone(),
+            "prediction",
+        )?;
+        let mut labeled_ground_truth = apply_label(
+            &ground_truth_array,
+            self.ground_truth_label_or_threshold.bind(py).clone(),
+            "ground_truth",
+        )?;
+        let mut labeled_features = apply_label(
+            &feature_array,
+            self.feature_label_or_threshold.bind(py).clone(),
+            "feature",
+        )?;
+        let (inner, missing_policy) = (&mut self.inner, self.missing_policy);
+        py.allow_threads(|| {
+            if let Some(weights) = &sample_weight {
+                check_sample_weights("feature", &labeled_features, weights)?;
+            }
+            let missing = apply_missing_policy(
+                missing_policy,
+                Some(("feature", &mut labeled_features)),
+                vec![
+                    ("prediction", &mut labeled_predictions),
+                    ("ground_truth", &mut labeled_ground_truth),
+                ],
+                sample_weight.as_mut(),
+            )?;
+            match &sample_weight {
+                Some(weights) => inner.update_weighted(
+                    &labeled_features,
+                    &labeled_predictions,
+                    &labeled_ground_truth,
+                    weights,
+                )?,
+                None => inner.update(
+                    &labeled_features,
+                    &labeled_predictions,
+                    &labeled_ground_truth,
+                )?,
+            }
+            inner.record_missing(missing);
+            Ok::<_, FairMlError>(())
+        })?;
+        Ok(())
+    }
+
+    fn merge(&mut self, other: PyRef<'_, PyModelBiasAccumulator>) {
+        self.inner.merge(&other.inner);
+    }
+
+    fn finalize(&self) -> PyResult<PyModelBiasReport> {
+        Ok(self.inner.finalize_with(&model_bias_registry())?.into())
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        state_bytes(py, &self.inner)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(self.inner.to_json()?)
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (data, *args))]
+    fn from_bytes<'py>(
+        cls: &Bound<'py, PyType>,
+        data: &[u8],
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = ModelBiasAccumulator::from_bytes(data)?;
+        restore(cls, args, state, |acc: &mut Self, state| acc.inner = state)
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (data, *args))]
+    fn from_json<'py>(
+        cls: &Bound<'py, PyType>,
+        data: &str,
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = ModelBiasAccumulator::from_json(data)?;
+        restore(cls, args, state, |acc: &mut Self, state| acc.inner = state)
+    }
+
+    fn __getnewargs__(&self, py: Python<'_>) -> (PyObject, PyObject, PyObject, &'static str) {
+        (
+            self.feature_label_or_threshold.clone_ref(py),
+            self.ground_truth_label_or_threshold.clone_ref(py),
+            self.prediction_label_or_threshold.clone_ref(py),
+            self.missing_policy.as_str(),
+        )
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        state_bytes(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = ModelBiasAccumulator::from_bytes(state)?;
+        Ok(())
+    }
+}
+
+/// Streaming binary classification performance.
+#[pyclass(
+    subclass,
+    name = "BinaryClassificationAccumulator",
+    module = "fair_perf_ml._fair_perf_ml"
+)]
+pub struct PyBinaryClassificationAccumulator {
+    inner: BinaryClassificationAccumulator,
+    missing_policy: MissingPolicy,
+}
+
+#[pymethods]
+impl PyBinaryClassificationAccumulator {
+    #[new]
+    #[pyo3(signature = (missing_policy="raise"))]
+    fn new(missing_policy: &str) -> PyResult<PyBinaryClassificationAccumulator> {
+        Ok(PyBinaryClassificationAccumulator {
+            inner: BinaryClassificationAccumulator::new(),
+            missing_policy: MissingPolicy::try_from(missing_policy)?,
+        })
+    }
+
+    #[pyo3(signature = (y_pred, y_true, sample_weight=None))]
+    fn update(
+        &mut self,
+        py: Python<'_>,
+        y_pred: Column<'_>,
+        y_true: Column<'_>,
+        sample_weight: Option<Column<'_>>,
+    ) -> PyResult<()> {
+        let mut sample_weight = sample_weights(sample_weight.as_ref())?;
+        let (mut y_true, mut y_pred) =
+            PerfEntry::validate_and_cast_classification(&y_true, &y_pred)?;
+        let (inner, missing_policy) = (&mut self.inner, self.missing_policy);
+        py.allow_threads(|| {
+            if let Some(weights) = &sample_weight {
+                check_sample_weights("y_true", &y_true, weights)?;
+            }
+            apply_missing_policy_values(
+                missing_policy,
+                vec![("y_true", &mut y_true), ("y_pred", &mut y_pred)],
+                sample_weight.as_mut(),
+            )?;
+            match &sample_weight {
+                Some(weights) => inner.update_weighted(&y_true, &y_pred, weights),
+                None => inner.update(&y_true, &y_pred),
+            }
+        })?;
+        Ok(())
+    }
+
+    fn merge(&mut self, other: PyRef<'_, PyBinaryClassificationAccumulator>) {
+        self.inner.merge(&other.inner);
+    }
+
+    fn finalize(&self) -> PyResult<PyBinaryClassificationReport> {
+        Ok(self.inner.finalize()?.into())
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        state_bytes(py, &self.inner)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(self.inner.to_json()?)
+    }
+
+    /// `args` are the constructor arguments, the missing value policy is not
+    /// part of the state.
+    #[classmethod]
+    #[pyo3(signature = (data, *args))]
+    fn from_bytes<'py>(
+        cls: &Bound<'py, PyType>,
+        data: &[u8],
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = BinaryClassificationAccumulator::from_bytes(data)?;
+        restore(cls, args, state, |acc: &mut Self, state| acc.inner = state)
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (data, *args))]
+    fn from_json<'py>(
+        cls: &Bound<'py, PyType>,
+        data: &str,
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = BinaryClassificationAccumulator::from_json(data)?;
+        restore(cls, args, state, |acc: &mut Self, state| acc.inner = state)
+    }
+
+    fn __getnewargs__(&self) -> (&'static str,) {
+        (self.missing_policy.as_str(),)
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        state_bytes(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = BinaryClassificationAccumulator::from_bytes(state)?;
+        Ok(())
+    }
+}
+
+/// Streaming logistic regression performance. The decision threshold is part
+/// of the serialized state.
+#[pyclass(
+    subclass,
+    name = "LogisticRegressionAccumulator",
+    module = "fair_perf_ml._fair_perf_ml"
+)]
+pub struct PyLogisticRegressionAccumulator {
+    inner: LogisticRegressionAccumulator,
+    missing_policy: MissingPolicy,
+}
+
+#[pymethods]
+impl PyLogisticRegressionAccumulator {
+    #[new]
+    #[pyo3(signature = (decision_threshold=0.5, missing_policy="raise"))]
+    fn new(
+        decision_threshold: f32,
+        missing_policy: &str,
+    ) -> PyResult<PyLogisticRegressionAccumulator> {
+        Ok(PyLogisticRegressionAccumulator {
+            inner: LogisticRegressionAccumulator::new(decision_threshold),
+            missing_policy: MissingPolicy::try_from(missing_policy)?,
+        })
+    }
+
+    #[getter]
+    fn decision_threshold(&self) -> f32 {
+        self.inner.threshold()
+    }
+
+    #[pyo3(signature = (y_pred, y_true, sample_weight=None))]
+    fn update(
+        &mut self,
+        py: Python<'_>,
+        y_pred: Column<'_>,
+        y_true: Column<'_>,
+        sample_weight: Option<Column<'_>>,
+    ) -> PyResult<()> {
+        let mut sample_weight = sample_weights(sample_weight.as_ref())?;
+        let (mut y_true, mut y_proba) = PerfEntry::validate_and_cast_regression(&y_true, &y_pred)?;
+        let (inner, missing_policy) = (&mut self.inner, self.missing_policy);
+        py.allow_threads(|| {
+            if let Some(weights) = &sample_weight {
+                check_sample_weights("y_true", &y_true, weights)?;
+            }
+            apply_missing_policy_values(
+                missing_policy,
+                vec![("y_true", &mut y_true), ("y_pred", &mut y_proba)],
+                sample_weight.as_mut(),
+            )?;
+            match &sample_weight {
+                Some(weights) => inner.update_weighted(&y_true, &y_proba, weights),
+                None => inner.update(&y_true, &y_proba),
+            }
+        })?;
+        Ok(())
+    }
+
+    fn merge(&mut self, other: PyRef<'_, PyLogisticRegressionAccumulator>) {
+        self.inner.merge(&other.inner);
+    }
+
+    fn finalize(&self) -> PyResult<PyLogisticRegressionReport> {
+        Ok(self.inner.finalize()?.into())
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        state_bytes(py, &self.inner)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(self.inner.to_json()?)
+    }
+
+    /// `args` are the constructor arguments, the missing value policy is not
+    /// part of the state and the decision threshold is taken from it.
+    #[classmethod]
+    #[pyo3(signature = (data, *args))]
+    fn from_bytes<'py>(
+        cls: &Bound<'py, PyType>,
+        data: &[u8],
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = LogisticRegressionAccumulator::from_bytes(data)?;
+        restore(cls, args, state, |acc: &mut Self, state| acc.inner = state)
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (data, *args))]
+    fn from_json<'py>(
+        cls: &Bound<'py, PyType>,
+        data: &str,
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = LogisticRegressionAccumulator::from_json(data)?;
+        restore(cls, args, state, |acc: &mut Self, state| acc.inner = state)
+    }
+
+    fn __getnewargs__(&self) -> (f32, &'static str) {
+        (self.inner.threshold(), self.missing_policy.as_str())
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        state_bytes(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = LogisticRegressionAccumulator::from_bytes(state)?;
+        Ok(())
+    }
+}
+
+/// Streaming linear regression performance.
+#[pyclass(
+    subclass,
+    name = "LinearRegressionAccumulator",
+    module = "fair_perf_ml._fair_perf_ml"
+)]
+pub struct PyLinearRegressionAccumulator {
+    inner: LinearRegressionAccumulator,
+    missing_policy: MissingPolicy,
+}
+
+#[pymethods]
+impl PyLinearRegressionAccumulator {
+    #[new]
+    #[pyo3(signature = (missing_policy="raise"))]
+    fn new(missing_policy: &str) -> PyResult<PyLinearRegressionAccumulator> {
+        Ok(PyLinearRegressionAccumulator {
+            inner: LinearRegressionAccumulator::new(),
+            missing_policy: MissingPolicy::try_from(missing_policy)?,
+        })
+    }
+
+    #[pyo3(signature = (y_pred, y_true, sample_weight=None))]
+    fn update(
+        &mut self,
+        py: Python<'_>,
+        y_pred: Column<'_>,
+        y_true: Column<'_>,
+        sample_weight: Option<Column<'_>>,
+    ) -> PyResult<()> {
+        let mut sample_weight = sample_weights(sample_weight.as_ref())?;
+        let (mut y_true, mut y_pred) = PerfEntry::validate_and_cast_regression(&y_true, &y_pred)?;
+        let (inner, missing_policy) = (&mut self.inner, self.missing_policy);
+        py.allow_threads(|| {
+            if let Some(weights) = &sample_weight {
+                check_sample_weights("y_true", &y_true, weights)?;
+            }
+            apply_missing_policy_values(
+                missing_policy,
+                vec![("y_true", &mut y_true), ("y_pred", &mut y_pred)],
+                sample_weight.as_mut(),
+            )?;
+            match &sample_weight {
+                Some(weights) => inner.update_weighted(&y_true, &y_pred, weights),
+                None => inner.update(&y_true, &y_pred),
+            }
+        })?;
+        Ok(())
+    }
+
+    fn merge(&mut self, other: PyRef<'_, PyLinearRegressionAccumulator>) {
+        self.inner.merge(&other.inner);
+    }
+
+    fn finalize(&self) -> PyResult<PyLinearRegressionReport> {
+        Ok(self.inner.finalize()?.into())
+    }
+
+    fn to_bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        state_bytes(py, &self.inner)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        Ok(self.inner.to_json()?)
+    }
+
+    /// `args` are the constructor arguments, the missing value policy is not
+    /// part of the state.
+    #[classmethod]
+    #[pyo3(signature = (data, *args))]
+    fn from_bytes<'py>(
+        cls: &Bound<'py, PyType>,
+        data: &[u8],
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = LinearRegressionAccumulator::from_bytes(data)?;
+        restore(cls, args, state, |acc: &mut Self, state| acc.inner = state)
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (data, *args))]
+    fn from_json<'py>(
+        cls: &Bound<'py, PyType>,
+        data: &str,
+        args: &Bound<'py, PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let state = LinearRegressionAccumulator::from_json(data)?;
+        restore(cls, args, state, |acc: &mut Self, state| acc.inner = state)
+    }
+
+    fn __getnewargs__(&self) -> (&'static str,) {
+        (self.missing_policy.as_str(),)
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        state_bytes(py, &self.inner)
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        self.inner = LinearRegressionAccumulator::from_bytes(state)?;
+        Ok(())
+    }
+}
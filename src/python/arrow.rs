@@ -0,0 +1,640 @@
+//! Columns read through the Arrow C data interface, from any object with
+//! `__arrow_c_array__` or `__arrow_c_stream__`, e.g. a pyarrow `Array` or
+//! `ChunkedArray`. The buffers are read in place, numpy is never involved.
+
+use super::ingestion::f16_to_f32;
+use crate::errors::FairMlError;
+use pyo3::prelude::*;
+use pyo3::types::{PyCapsule, PyTuple};
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::ptr;
+
+#[repr(C)]
+struct RawSchema {
+    format: *const c_char,
+    name: *const c_char,
+    metadata: *const c_char,
+    flags: i64,
+    n_children: i64,
+    children: *mut *mut RawSchema,
+    dictionary: *mut RawSchema,
+    release: Option<unsafe extern "C" fn(*mut RawSchema)>,
+    private_data: *mut c_void,
+}
+
+#[repr(C)]
+struct RawArray {
+    length: i64,
+    null_count: i64,
+    offset: i64,
+    n_buffers: i64,
+    n_children: i64,
+    buffers: *mut *const c_void,
+    children: *mut *mut RawArray,
+    dictionary: *mut RawArray,
+    release: Option<unsafe extern "C" fn(*mut RawArray)>,
+    private_data: *mut c_void,
+}
+
+#[repr(C)]
+struct RawArrayStream {
+    get_schema: Option<unsafe extern "C" fn(*mut RawArrayStream, *mut RawSchema) -> c_int>,
+    get_next: Option<unsafe extern "C" fn(*mut RawArrayStream, *mut RawArray) -> c_int>,
+    get_last_error: Option<unsafe extern "C" fn(*mut RawArrayStream) -> *const c_char>,
+    release: Option<unsafe extern "C" fn(*mut RawArrayStream)>,
+    private_data: *mut c_void,
+}
+
+impl RawSchema {
+    fn empty() -> RawSchema {
+        RawSchema {
+            format: ptr::null(),
+            name: ptr::null(),
+            metadata: ptr::null(),
+            flags: 0,
+            n_children: 0,
+            children: ptr::null_mut(),
+            dictionary: ptr::null_mut(),
+            release: None,
+            private_data: ptr::null_mut(),
+        }
+    }
+
+    fn format(&self) -> &str {
+        if self.format.is_null() {
+            return "";
+        }
+        // SAFETY: a released-or-valid schema holds a nul terminated format
+        unsafe { CStr::from_ptr(self.format) }
+            .to_str()
+            .unwrap_or("")
+    }
+
+    fn name(&self) -> Option<&str> {
+        if self.name.is_null() {
+            return None;
+        }
+        // SAFETY: the name is nul terminated when set
+        unsafe { CStr::from_ptr(self.name) }.to_str().ok()
+    }
+
+    fn child(&self, index: usize) -> &RawSchema {
+        // SAFETY: callers stay below `n_children`
+        unsafe { &**self.children.add(index) }
+    }
+}
+
+impl RawArray {
+    fn empty() -> RawArray {
+        RawArray {
+            length: 0,
+            null_count: 0,
+            offset: 0,
+            n_buffers: 0,
+            n_children: 0,
+            buffers: ptr::null_mut(),
+            children: ptr::null_mut(),
+            dictionary: ptr::null_mut(),
+            release: None,
+            private_data: ptr::null_mut(),
+        }
+    }
+
+    /// Buffer `index`, null when absent, e.g. a validity bitmap without nulls.
+    fn buffer<T>(&self, index: usize) -> *const T {
+        if index as i64 >= self.n_buffers {
+            return ptr::null();
+        }
+        // SAFETY: `index` is below `n_buffers`
+        unsafe { *self.buffers.add(index) as *const T }
+    }
+
+    fn child(&self, index: usize) -> &RawArray {
+        // SAFETY: callers stay below `n_children`
+        unsafe { &**self.children.add(index) }
+    }
+}
+
+/// Owns a schema moved out of its producer, released on drop.
+struct Schema(RawSchema);
+
+impl Drop for Schema {
+    fn drop(&mut self) {
+        if let Some(release) = self.0.release {
+            // SAFETY: the producer's release callback, called once
+            unsafe { release(&mut self.0) }
+        }
+    }
+}
+
+/// Owns an array moved out of its producer, released on drop.
+struct Array(RawArray);
+
+impl Drop for Array {
+    fn drop(&mut self) {
+        if let Some(release) = self.0.release {
+            // SAFETY: the producer's release callback, called once
+            unsafe { release(&mut self.0) }
+        }
+    }
+}
+
+struct Stream(RawArrayStream);
+
+impl Stream {
+    fn error(&mut self, call: &str, code: c_int) -> FairMlError {
+        let message = self
+            .0
+            .get_last_error
+            // SAFETY: the stream is valid until released
+            .map(|last_error| unsafe { last_error(&mut self.0) })
+            .filter(|message| !message.is_null())
+            // SAFETY: a non null error message is nul terminated
+            .map(|message| {
+                unsafe { CStr::from_ptr(message) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .unwrap_or_else(|| format!("error code {code}"));
+        FairMlError::InvalidArrowData {
+            reason: format!("{call} failed, {message}"),
+        }
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        if let Some(release) = self.0.release {
+            // SAFETY: the producer's release callback, called once
+            unsafe { release(&mut self.0) }
+        }
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> FairMlError {
+    FairMlError::InvalidArrowData {
+        reason: reason.into(),
+    }
+}
+
+/// Pointer held by a capsule of the given name, as the PyCapsule interface
+/// of the Arrow C data interface hands out.
+fn capsule_pointer<T>(capsule: &Bound<'_, PyAny>, name: &str) -> Result<*mut T, FairMlError> {
+    let capsule = capsule
+        .downcast::<PyCapsule>()
+        .map_err(|_| invalid(format!("expected a {name} capsule")))?;
+    match capsule.name() {
+        Ok(Some(found)) if found.to_bytes() == name.as_bytes() => {}
+        _ => return Err(invalid(format!("expected a {name} capsule"))),
+    }
+    let pointer = capsule.pointer() as *mut T;
+    if pointer.is_null() {
+        return Err(invalid(format!("empty {name} capsule")));
+    }
+    Ok(pointer)
+}
+
+/// A column as one schema and its chunks, moved out of the producing object.
+/// A table, exported as a struct column, reads one of its fields once it is
+/// selected with `select_field`.
+pub struct ArrowChunks {
+    schema: Schema,
+    chunks: Vec<Array>,
+    field: Option<usize>,
+}
+
+impl ArrowChunks {
+    /// Imports `object` through `__arrow_c_array__` or `__arrow_c_stream__`,
+    /// `None` when it implements neither.
+    pub fn import(object: &Bound<'_, PyAny>) -> PyResult<Option<ArrowChunks>> {
+        if object.hasattr("__arrow_c_array__")? {
+            let capsules = object.call_method0("__arrow_c_array__")?;
+            let capsules = capsules.downcast::<PyTuple>()?;
+            let schema = capsule_pointer::<RawSchema>(&capsules.get_item(0)?, "arrow_schema")?;
+            let array = capsule_pointer::<RawArray>(&capsules.get_item(1)?, "arrow_array")?;
+            // SAFETY: the capsules hold valid structs, moving them out and
+            // clearing the source release callbacks transfers ownership
+            let (schema, array) = unsafe {
+                let moved = (Schema(ptr::read(schema)), Array(ptr::read(array)));
+                (*schema).release = None;
+                (*array).release = None;
+                moved
+            };
+            return Ok(Some(ArrowChunks {
+                schema,
+                chunks: vec![array],
+                field: None,
+            }));
+        }
+        if object.hasattr("__arrow_c_stream__")? {
+            let capsule = object.call_method0("__arrow_c_stream__")?;
+            let pointer = capsule_pointer::<RawArrayStream>(&capsule, "arrow_array_stream")?;
+            // SAFETY: as above for the stream struct
+            let mut stream = unsafe {
+                let moved = Stream(ptr::read(pointer));
+                (*pointer).release = None;
+                moved
+            };
+            return Ok(Some(Self::from_stream(&mut stream)?));
+        }
+        Ok(None)
+    }
+
+    fn from_stream(stream: &mut Stream) -> Result<ArrowChunks, FairMlError> {
+        let (Some(get_schema), Some(get_next)) = (stream.0.get_schema, stream.0.get_next) else {
+            return Err(invalid("released arrow stream"));
+        };
+        let mut schema = RawSchema::empty();
+        // SAFETY: the stream is valid and `schema` is writable
+        let code = unsafe { get_schema(&mut stream.0, &mut schema) };
+        if code != 0 {
+            return Err(stream.error("get_schema", code));
+        }
+        let schema = Schema(schema);
+        let mut chunks = Vec::new();
+        loop {
+            let mut array = RawArray::empty();
+            // SAFETY: as for get_schema
+            let code = unsafe { get_next(&mut stream.0, &mut array) };
+            if code != 0 {
+                return Err(stream.error("get_next", code));
+            }
+            // a released array marks the end of the stream
+            if array.release.is_none() {
+                break;
+            }
+            chunks.push(Array(array));
+        }
+        Ok(ArrowChunks {
+            schema,
+            chunks,
+            field: None,
+        })
+    }
+
+    /// Reads the field named `field` of a struct column from now on, e.g. a
+    /// column of a table exported through `__arrow_c_stream__`.
+    pub fn select_field(mut self, field: &str) -> Result<ArrowChunks, FairMlError> {
+        let schema = &self.schema.0;
+        if schema.format() != "+s" {
+            return Err(FairMlError::UnsupportedDtype {
+                array: field.into(),
+                dtype: format!("arrow format {}, expected a table", schema.format()),
+            });
+        }
+        let index = (0..schema.n_children as usize)
+            .find(|index| schema.child(*index).name() == Some(field))
+            .ok_or_else(|| invalid(format!("no field named {field}")))?;
+        for chunk in &self.chunks {
+            let chunk = &chunk.0;
+            if chunk.n_children != schema.n_children {
+                return Err(invalid("struct array does not match its schema"));
+            }
+            // sliced struct arrays shift their children and null rows hide
+            // theirs, neither occurs in an exported table
+            if chunk.offset != 0 || chunk.null_count != 0 {
+                return Err(invalid(
+                    "sliced or nullable struct arrays are not supported",
+                ));
+            }
+        }
+        self.field = Some(index);
+        Ok(self)
+    }
+
+    fn column_schema(&self) -> &RawSchema {
+        match self.field {
+            Some(index) => self.schema.0.child(index),
+            None => &self.schema.0,
+        }
+    }
+
+    /// The Arrow format string of the column, e.g. `u` for utf8.
+    pub fn format(&self) -> &str {
+        self.column_schema().format()
+    }
+
+    /// Decodes every chunk into one column, nulls become `None`.
+    pub fn decode(&self, name: &str) -> Result<ArrowColumn, FairMlError> {
+        let columns = self
+            .chunks
+            .iter()
+            .map(|chunk| {
+                let array = match self.field {
+                    Some(index) => chunk.0.child(index),
+                    None => &chunk.0,
+                };
+                decode(self.column_schema(), array, name)
+            })
+            .collect::<Result<Vec<ArrowColumn>, FairMlError>>()?;
+        ArrowColumn::concat(columns, name)
+    }
+}
+
+/// Values of an Arrow column, with nulls as `None`.
+#[derive(Clone)]
+pub enum ArrowColumn {
+    Float(Vec<Option<f64>>),
+    /// Integers and booleans, `true` as 1.
+    Integer(Vec<Option<i64>>),
+    Text(Vec<Option<String>>),
+    /// Dictionary encoded values, e.g. a categorical, each row an index into
+    /// `values`. The dictionary is decoded once rather than per row.
+    Dictionary {
+        values: Box<ArrowColumn>,
+        keys: Vec<Option<usize>>,
+    },
+}
+
+impl ArrowColumn {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Float(_) => "arrow float",
+            Self::Integer(_) => "arrow integer",
+            Self::Text(_) => "arrow string",
+            Self::Dictionary { .. } => "arrow dictionary",
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Float(values) => values.len(),
+            Self::Integer(values) => values.len(),
+            Self::Text(values) => values.len(),
+            Self::Dictionary { keys, .. } => keys.len(),
+        }
+    }
+
+    /// The values at `rows`, in that order.
+    pub fn select(&self, rows: &[usize]) -> ArrowColumn {
+        match self {
+            Self::Float(values) => Self::Float(rows.iter().map(|row| values[*row]).collect()),
+            Self::Integer(values) => Self::Integer(rows.iter().map(|row| values[*row]).collect()),
+            Self::Text(values) => Self::Text(rows.iter().map(|row| values[*row].clone()).collect()),
+            Self::Dictionary { .. } => self.decoded().select(rows),
+        }
+    }
+
+    /// Every row with its dictionary looked up.
+    pub fn decoded(&self) -> ArrowColumn {
+        match self {
+            Self::Dictionary { values, keys } => values.lookup(keys),
+            other => other.clone(),
+        }
+    }
+
+    /// The value of every key, `None` for a null key.
+    fn lookup(&self, keys: &[Option<usize>]) -> ArrowColumn {
+        match self {
+            Self::Float(values) => Self::Float(
+                keys.iter()
+                    .map(|key| key.and_then(|key| values[key]))
+                    .collect(),
+            ),
+            Self::Integer(values) => Self::Integer(
+                keys.iter()
+                    .map(|key| key.and_then(|key| values[key]))
+                    .collect(),
+            ),
+            Self::Text(values) => Self::Text(
+                keys.iter()
+                    .map(|key| key.and_then(|key| values[key].clone()))
+                    .collect(),
+            ),
+            Self::Dictionary { .. } => self.decoded().lookup(keys),
+        }
+    }
+
+    fn concat(mut columns: Vec<ArrowColumn>, name: &str) -> Result<ArrowColumn, FairMlError> {
+        if columns.len() == 1 {
+            return Ok(columns.remove(0));
+        }
+        let mut columns = columns.into_iter();
+        let Some(mut out) = columns.next() else {
+            return Err(FairMlError::EmptyInput { array: name.into() });
+        };
+        for column in columns {
+            match (&mut out, column) {
+                (Self::Float(out), Self::Float(values)) => out.extend(values),
+                (Self::Integer(out), Self::Integer(values)) => out.extend(values),
+                (Self::Text(out), Self::Text(values)) => out.extend(values),
+                // chunks may carry different dictionaries, keys are shifted
+                // past the values of the chunks before them
+                (
+                    Self::Dictionary { values, keys },
+                    Self::Dictionary {
+                        values: chunk_values,
+                        keys: chunk_keys,
+                    },
+                ) => {
+                    let shift = values.len();
+                    let merged =
+                        Self::concat(vec![values.decoded(), chunk_values.decoded()], name)?;
+                    **values = merged;
+                    keys.extend(chunk_keys.into_iter().map(|key| key.map(|key| key + shift)));
+                }
+                _ => return Err(invalid(format!("chunks of {name} have different types"))),
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn is_valid(bitmap: *const u8, index: usize) -> bool {
+    // SAFETY: a validity bitmap holds a bit for every element
+    bitmap.is_null() || unsafe { *bitmap.add(index / 8) } >> (index % 8) & 1 == 1
+}
+
+fn primitive<T: Copy>(array: &RawArray, convert: impl Fn(T) -> i64) -> Vec<Option<i64>> {
+    let validity = array.buffer::<u8>(0);
+    let values = array.buffer::<T>(1);
+    let offset = array.offset as usize;
+    (offset..offset + array.length as usize)
+        .map(|index| {
+            // SAFETY: the values buffer holds `offset + length` elements
+            is_valid(validity, index)
+                .then(|| convert(unsafe { values.add(index).read_unaligned() }))
+        })
+        .collect()
+}
+
+fn floats<T: Copy>(array: &RawArray, convert: impl Fn(T) -> f64) -> Vec<Option<f64>> {
+    let validity = array.buffer::<u8>(0);
+    let values = array.buffer::<T>(1);
+    let offset = array.offset as usize;
+    (offset..offset + array.length as usize)
+        .map(|index| {
+            // SAFETY: as in `primitive`
+            is_valid(validity, index)
+                .then(|| convert(unsafe { values.add(index).read_unaligned() }))
+        })
+        .collect()
+}
+
+fn booleans(array: &RawArray) -> Vec<Option<i64>> {
+    let validity = array.buffer::<u8>(0);
+    let values = array.buffer::<u8>(1);
+    let offset = array.offset as usize;
+    (offset..offset + array.length as usize)
+        .map(|index| is_valid(validity, index).then(|| is_valid(values, index) as i64))
+        .collect()
+}
+
+fn strings<O: Copy + TryInto<usize>>(
+    array: &RawArray,
+    name: &str,
+) -> Result<ArrowColumn, FairMlError> {
+    let validity = array.buffer::<u8>(0);
+    let offsets = array.buffer::<O>(1);
+    let data = array.buffer::<u8>(2);
+    let offset = array.offset as usize;
+    let position = |index: usize| -> Result<usize, FairMlError> {
+        // SAFETY: the offsets buffer holds `offset + length + 1` elements
+        unsafe { offsets.add(index).read_unaligned() }
+            .try_into()
+            .map_err(|_| invalid(format!("negative string offset in {name}")))
+    };
+    (offset..offset + array.length as usize)
+        .map(|index| {
+            if !is_valid(validity, index) {
+                return Ok(None);
+            }
+            let (start, end) = (position(index)?, position(index + 1)?);
+            if end < start {
+                return Err(invalid(format!("decreasing string offsets in {name}")));
+            }
+            // the data buffer may be null when every string is empty
+            if end == start {
+                return Ok(Some(String::new()));
+            }
+            // SAFETY: offsets index into the data buffer
+            let bytes = unsafe { std::slice::from_raw_parts(data.add(start), end - start) };
+            std::str::from_utf8(bytes)
+                .map(|value| Some(value.to_string()))
+                .map_err(|_| invalid(format!("{name} holds invalid utf-8")))
+        })
+        .collect::<Result<Vec<Option<String>>, FairMlError>>()
+        .map(ArrowColumn::Text)
+}
+
+/// Utf8View (`vu`) and BinaryView (`vz`) columns, as polars exports its
+/// strings. Each row is a 16 byte view: the length, then the bytes inline
+/// when they fit in 12, else a prefix, a data buffer index and an offset.
+/// The variadic data buffers follow the views, and their sizes close the
+/// buffer list.
+fn string_views(array: &RawArray, name: &str) -> Result<ArrowColumn, FairMlError> {
+    if array.n_buffers < 3 {
+        return Err(invalid(format!(
+            "{name} is a view array without buffer sizes"
+        )));
+    }
+    let validity = array.buffer::<u8>(0);
+    let views = array.buffer::<[u8; 16]>(1);
+    let n_data = array.n_buffers as usize - 3;
+    let sizes = array.buffer::<i64>(array.n_buffers as usize - 1);
+    let word = |view: &[u8; 16], at: usize| {
+        i32::from_le_bytes([view[at], view[at + 1], view[at + 2], view[at + 3]])
+    };
+    let offset = array.offset as usize;
+    (offset..offset + array.length as usize)
+        .map(|index| {
+            if !is_valid(validity, index) {
+                return Ok(None);
+            }
+            // SAFETY: the views buffer holds `offset + length` views
+            let view = unsafe { views.add(index).read_unaligned() };
+            let length = usize::try_from(word(&view, 0))
+                .map_err(|_| invalid(format!("negative view length in {name}")))?;
+            let bytes = if length <= 12 {
+                view[4..4 + length].to_vec()
+            } else {
+                let (buffer, start) = (word(&view, 8), word(&view, 12));
+                let (Ok(buffer), Ok(start)) = (usize::try_from(buffer), usize::try_from(start))
+                else {
+                    return Err(invalid(format!("negative view buffer or offset in {name}")));
+                };
+                if buffer >= n_data {
+                    return Err(invalid(format!("{name} views a missing data buffer")));
+                }
+                // SAFETY: `buffer` is below the number of data buffers, whose
+                // sizes follow them
+                let size = unsafe { sizes.add(buffer).read_unaligned() };
+                if (start + length) as i64 > size {
+                    return Err(invalid(format!("{name} views past its data buffer")));
+                }
+                let data = array.buffer::<u8>(2 + buffer);
+                // SAFETY: the view lies within its data buffer, checked above
+                unsafe { std::slice::from_raw_parts(data.add(start), length) }.to_vec()
+            };
+            String::from_utf8(bytes)
+                .map(Some)
+                .map_err(|_| invalid(format!("{name} holds invalid utf-8")))
+        })
+        .collect::<Result<Vec<Option<String>>, FairMlError>>()
+        .map(ArrowColumn::Text)
+}
+
+fn decode(schema: &RawSchema, array: &RawArray, name: &str) -> Result<ArrowColumn, FairMlError> {
+    if array.length < 0 || array.offset < 0 {
+        return Err(invalid(format!("{name} has a negative length or offset")));
+    }
+    let unsupported = || FairMlError::UnsupportedDtype {
+        array: name.into(),
+        dtype: format!("arrow format {}", schema.format()),
+    };
+    let integers = match schema.format() {
+        "b" => booleans(array),
+        "c" => primitive(array, |value: i8| i64::from(value)),
+        "C" => primitive(array, |value: u8| i64::from(value)),
+        "s" => primitive(array, |value: i16| i64::from(value)),
+        "S" => primitive(array, |value: u16| i64::from(value)),
+        "i" => primitive(array, |value: i32| i64::from(value)),
+        "I" => primitive(array, |value: u32| i64::from(value)),
+        "l" => primitive(array, |value: i64| value),
+        // values past i64::MAX are clamped, as for numpy uint64
+        "L" => primitive(array, |value: u64| i64::try_from(value).unwrap_or(i64::MAX)),
+        "e" => {
+            return Ok(ArrowColumn::Float(floats(array, |bits: u16| {
+                f64::from(f16_to_f32(bits))
+            })))
+        }
+        "f" => {
+            return Ok(ArrowColumn::Float(floats(array, |value: f32| {
+                f64::from(value)
+            })))
+        }
+        "g" => return Ok(ArrowColumn::Float(floats(array, |value: f64| value))),
+        "u" => return strings::<i32>(array, name),
+        "U" => return strings::<i64>(array, name),
+        "vu" | "vz" => return string_views(array, name),
+        _ => return Err(unsupported()),
+    };
+    if schema.dictionary.is_null() {
+        return Ok(ArrowColumn::Integer(integers));
+    }
+    if array.dictionary.is_null() {
+        return Err(invalid(format!(
+            "{name} is dictionary encoded without a dictionary"
+        )));
+    }
+    // SAFETY: both dictionaries are set and live as long as their parents
+    let values = decode(
+        unsafe { &*schema.dictionary },
+        unsafe { &*array.dictionary },
+        name,
+    )?;
+    let keys = integers
+        .into_iter()
+        .map(|key| match key {
+            Some(key) if key >= 0 && (key as usize) < values.len() => Ok(Some(key as usize)),
+            Some(key) => Err(invalid(format!(
+                "{name} has dictionary key {key} out of range"
+            ))),
+            None => Ok(None),
+        })
+        .collect::<Result<Vec<Option<usize>>, FairMlError>>()?;
+    Ok(ArrowColumn::Dictionary {
+        values: Box::new(values),
+        keys,
+    })
+}
@@ -0,0 +1,202 @@
+// create_exception! in pyo3 0.22 checks a `gil-refs` feature this crate does not define
+#![allow(unexpected_cfgs)]
+
+use crate::errors;
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+create_exception!(
+    _fair_perf_ml,
+    FairMlError,
+    PyValueError,
+    "Base class for every error raised by fair_perf_ml."
+);
+create_exception!(_fair_perf_ml, LengthMismatchError, FairMlError);
+create_exception!(_fair_perf_ml, EmptyFacetError, FairMlError);
+create_exception!(_fair_perf_ml, EmptyInputError, FairMlError);
+create_exception!(_fair_perf_ml, LabelTypeMismatchError, FairMlError);
+create_exception!(_fair_perf_ml, UnsupportedDtypeError, FairMlError);
+create_exception!(_fair_perf_ml, MissingBaselineKeyError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidMetricNameError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidModelTypeError, FairMlError);
+create_exception!(_fair_perf_ml, DegenerateDenominatorError, FairMlError);
+create_exception!(_fair_perf_ml, StateVersionMismatchError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidStateError, FairMlError);
+create_exception!(_fair_perf_ml, DuplicateMetricNameError, FairMlError);
+create_exception!(_fair_perf_ml, MetricComputationError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidDirectionError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidComparisonError, FairMlError);
+create_exception!(_fair_perf_ml, UnknownGroupError, FairMlError);
+create_exception!(_fair_perf_ml, UnmappedCategoryError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidLabelSpecError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidMissingPolicyError, FairMlError);
+create_exception!(_fair_perf_ml, MissingValuesError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidSampleWeightError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidArrowDataError, FairMlError);
+create_exception!(_fair_perf_ml, UnknownColumnError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidDivergenceOptionsError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidBootstrapConfigError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidSignificanceConfigError, FairMlError);
+create_exception!(_fair_perf_ml, InvalidSupportThresholdsError, FairMlError);
+
+impl From<errors::FairMlError> for PyErr {
+    fn from(err: errors::FairMlError) -> PyErr {
+        let msg = err.to_string();
+        match err {
+            errors::FairMlError::LengthMismatch { .. } => LengthMismatchError::new_err(msg),
+            errors::FairMlError::EmptyFacet { .. } => EmptyFacetError::new_err(msg),
+            errors::FairMlError::EmptyInput { .. } => EmptyInputError::new_err(msg),
+            errors::FairMlError::LabelTypeMismatch { .. } => LabelTypeMismatchError::new_err(msg),
+            errors::FairMlError::UnsupportedDtype { .. } => UnsupportedDtypeError::new_err(msg),
+            errors::FairMlError::MissingBaselineKey { .. } => MissingBaselineKeyError::new_err(msg),
+            errors::FairMlError::InvalidMetricName { .. } => InvalidMetricNameError::new_err(msg),
+            errors::FairMlError::InvalidModelType { .. } => InvalidModelTypeError::new_err(msg),
+            errors::FairMlError::DegenerateDenominator { .. } => {
+                DegenerateDenominatorError::new_err(msg)
+            }
+            errors::FairMlError::StateVersionMismatch { .. } => {
+                StateVersionMismatchError::new_err(msg)
+            }
+            errors::FairMlError::InvalidState { .. } => InvalidStateError::new_err(msg),
+            errors::FairMlError::DuplicateMetricName { .. } => {
+                DuplicateMetricNameError::new_err(msg)
+            }
+            errors::FairMlError::MetricComputation { .. } => MetricComputationError::new_err(msg),
+            errors::FairMlError::InvalidDirection { .. } => InvalidDirectionError::new_err(msg),
+            errors::FairMlError::InvalidComparison { .. } => InvalidComparisonError::new_err(msg),
+            errors::FairMlError::UnknownGroup { .. } => UnknownGroupError::new_err(msg),
+            errors::FairMlError::UnmappedCategory { .. } => UnmappedCategoryError::new_err(msg),
+            errors::FairMlError::InvalidLabelSpec { .. } => InvalidLabelSpecError::new_err(msg),
+            errors::FairMlError::InvalidMissingPolicy { .. } => {
+                InvalidMissingPolicyError::new_err(msg)
+            }
+            errors::FairMlError::MissingValues { .. } => MissingValuesError::new_err(msg),
+            errors::FairMlError::InvalidSampleWeight { .. } => {
+                InvalidSampleWeightError::new_err(msg)
+            }
+            errors::FairMlError::InvalidArrowData { .. } => InvalidArrowDataError::new_err(msg),
+            errors::FairMlError::UnknownColumn { .. } => UnknownColumnError::new_err(msg),
+            errors::FairMlError::InvalidDivergenceOptions { .. } => {
+                InvalidDivergenceOptionsError::new_err(msg)
+            }
+            errors::FairMlError::InvalidBootstrapConfig { .. } => {
+                InvalidBootstrapConfigError::new_err(msg)
+            }
+            errors::FairMlError::InvalidSignificanceConfig { .. } => {
+                InvalidSignificanceConfigError::new_err(msg)
+            }
+            errors::FairMlError::InvalidSupportThresholds { .. } => {
+                InvalidSupportThresholdsError::new_err(msg)
+            }
+        }
+    }
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("FairMlError", py.get_type_bound::<FairMlError>())?;
+    m.add(
+        "LengthMismatchError",
+        py.get_type_bound::<LengthMismatchError>(),
+    )?;
+    m.add("EmptyFacetError", py.get_type_bound::<EmptyFacetError>())?;
+    m.add("EmptyInputError", py.get_type_bound::<EmptyInputError>())?;
+    m.add(
+        "LabelTypeMismatchError",
+        py.get_type_bound::<LabelTypeMismatchError>(),
+    )?;
+    m.add(
+        "UnsupportedDtypeError",
+        py.get_type_bound::<UnsupportedDtypeError>(),
+    )?;
+    m.add(
+        "MissingBaselineKeyError",
+        py.get_type_bound::<MissingBaselineKeyError>(),
+    )?;
+    m.add(
+        "InvalidMetricNameError",
+        py.get_type_bound::<InvalidMetricNameError>(),
+    )?;
+    m.add(
+        "InvalidModelTypeError",
+        py.get_type_bound::<InvalidModelTypeError>(),
+    )?;
+    m.add(
+        "DegenerateDenominatorError",
+        py.get_type_bound::<DegenerateDenominatorError>(),
+    )?;
+    m.add(
+        "StateVersionMismatchError",
+        py.get_type_bound::<StateVersionMismatchError>(),
+    )?;
+    m.add(
+        "InvalidStateError",
+        py.get_type_bound::<InvalidStateError>(),
+    )?;
+    m.add(
+        "DuplicateMetricNameError",
+        py.get_type_bound::<DuplicateMetricNameError>(),
+    )?;
+    m.add(
+        "MetricComputationError",
+        py.get_type_bound::<MetricComputationError>(),
+    )?;
+    m.add(
+        "InvalidDirectionError",
+        py.get_type_bound::<InvalidDirectionError>(),
+    )?;
+    m.add(
+        "InvalidComparisonError",
+        py.get_type_bound::<InvalidComparisonError>(),
+    )?;
+    m.add(
+        "UnknownGroupError",
+        py.get_type_bound::<UnknownGroupError>(),
+    )?;
+    m.add(
+        "UnmappedCategoryError",
+        py.get_type_bound::<UnmappedCategoryError>(),
+    )?;
+    m.add(
+        "InvalidLabelSpecError",
+        py.get_type_bound::<InvalidLabelSpecError>(),
+    )?;
+    m.add(
+        "InvalidMissingPolicyError",
+        py.get_type_bound::<InvalidMissingPolicyError>(),
+    )?;
+    m.add(
+        "MissingValuesError",
+        py.get_type_bound::<MissingValuesError>(),
+    )?;
+    m.add(
+        "InvalidSampleWeightError",
+        py.get_type_bound::<InvalidSampleWeightError>(),
+    )?;
+    m.add(
+        "InvalidArrowDataError",
+        py.get_type_bound::<InvalidArrowDataError>(),
+    )?;
+    m.add(
+        "UnknownColumnError",
+        py.get_type_bound::<UnknownColumnError>(),
+    )?;
+    m.add(
+        "InvalidDivergenceOptionsError",
+        py.get_type_bound::<InvalidDivergenceOptionsError>(),
+    )?;
+    m.add(
+        "InvalidBootstrapConfigError",
+        py.get_type_bound::<InvalidBootstrapConfigError>(),
+    )?;
+    m.add(
+        "InvalidSignificanceConfigError",
+        py.get_type_bound::<InvalidSignificanceConfigError>(),
+    )?;
+    m.add(
+        "InvalidSupportThresholdsError",
+        py.get_type_bound::<InvalidSupportThresholdsError>(),
+    )?;
+    Ok(())
+}
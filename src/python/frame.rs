@@ -0,0 +1,190 @@
+use super::arrow::ArrowChunks;
+use super::bootstrap_config;
+use super::ingestion::{apply_label, sample_weights, Column};
+use super::metrics::{data_bias_registry, model_bias_registry};
+use super::reports::PyFrameReport;
+use crate::data_handler::{MissingPolicy, MISSING_LABEL};
+use crate::errors::FairMlError;
+use crate::frame::{analyze_frame, FrameColumns, PerformanceInput};
+use crate::models::ModelType;
+use crate::significance::SignificanceConfig;
+use crate::status::SupportThresholds;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+
+/// Whether `frame` has a column `name`. pyarrow tables list their names in
+/// `column_names`, their `columns` are the arrays themselves.
+fn has_column(frame: &Bound<'_, PyAny>, name: &str) -> PyResult<bool> {
+    let names = if frame.hasattr("column_names")? {
+        frame.getattr("column_names")?
+    } else {
+        frame.getattr("columns")?
+    };
+    names.contains(name)
+}
+
+/// Reads the column `name` of a pandas, polars or pyarrow frame. A column
+/// exporting Arrow data, e.g. a polars series, is read as is. Otherwise the
+/// column alone is exported as a one column table, which pandas does through
+/// pyarrow when it is installed, and numpy is the last resort.
+fn frame_column<'py>(frame: &Bound<'py, PyAny>, name: &str) -> PyResult<Column<'py>> {
+    if !has_column(frame, name)? {
+        return Err(FairMlError::UnknownColumn {
+            column: name.into(),
+        }
+        .into());
+    }
+    let series = frame.get_item(name)?;
+    if let Ok(column) = series.extract::<Column>() {
+        return Ok(column);
+    }
+    let selected = frame.get_item(PyList::new_bound(frame.py(), [name]))?;
+    if let Ok(Some(chunks)) = ArrowChunks::import(&selected) {
+        return Ok(Column::Arrow(chunks.select_field(name)?));
+    }
+    series.call_method0("to_numpy")?.extract()
+}
+
+fn labeled_values(labels: &[i16]) -> Vec<f32> {
+    labels
+        .iter()
+        .map(|label| match *label {
+            MISSING_LABEL => f32::NAN,
+            label => f32::from(label),
+        })
+        .collect()
+}
+
+/// Data bias and model bias for every facet column of a frame, along with the
+/// performance of the model when `model_type` is set. Binary classification
+/// performance compares the labeled ground truth and predictions, logistic
+/// regression the labeled ground truth and the raw prediction scores, and
+/// linear regression the raw values of both. The bootstrap, significance and
+/// support settings are the same as for the single facet analyses.
+#[pyfunction]
+#[pyo3(signature = (
+    frame,
+    facets,
+    label,
+    label_rule,
+    prediction=None,
+    prediction_rule=None,
+    model_type=None,
+    decision_threshold=0.5,
+    missing_policy="raise",
+    sample_weight=None,
+    confidence_level=None,
+    resamples=1000,
+    seed=0,
+    significance=false,
+    permutations=1000,
+    min_facet_size=30.0,
+    min_cell_count=5.0
+)
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn dataframe_analyzer<'py>(
+    py: Python<'py>,
+    frame: Bound<'py, PyAny>,
+    facets: Vec<(String, Bound<'py, PyAny>)>,
+    label: &str,
+    label_rule: Bound<'py, PyAny>,
+    prediction: Option<&str>,
+    prediction_rule: Option<Bound<'py, PyAny>>,
+    model_type: Option<&str>,
+    decision_threshold: f32,
+    missing_policy: &str,
+    sample_weight: Option<&str>,
+    confidence_level: Option<f64>,
+    resamples: usize,
+    seed: u64,
+    significance: bool,
+    permutations: usize,
+    min_facet_size: f64,
+    min_cell_count: f64,
+) -> PyResult<PyFrameReport> {
+    let missing_policy = MissingPolicy::try_from(missing_policy)?;
+    let model_type = model_type.map(ModelType::try_from).transpose()?;
+    let weights = match sample_weight {
+        Some(name) => sample_weights(Some(&frame_column(&frame, name)?))?,
+        None => None,
+    };
+    let label_column = frame_column(&frame, label)?;
+    let labeled = apply_label(&label_column, label_rule, label)?;
+    let prediction_column = prediction
+        .map(|name| frame_column(&frame, name).map(|column| (name, column)))
+        .transpose()?;
+    let labeled_prediction = match (&prediction_column, prediction_rule) {
+        (Some((name, column)), Some(rule)) => Some(apply_label(column, rule, name)?),
+        (None, Some(_)) => {
+            return Err(PyValueError::new_err(
+                "prediction_rule needs a prediction column",
+            ))
+        }
+        _ => None,
+    };
+
+    let performance = match (model_type, &prediction_column) {
+        (None, _) => None,
+        (Some(_), None) => {
+            return Err(PyValueError::new_err(
+                "model_type needs a prediction column",
+            ));
+        }
+        (Some(ModelType::BinaryClassification), Some(_)) => {
+            let y_pred = labeled_prediction.as_deref().ok_or_else(|| {
+                PyValueError::new_err("binary classification needs a prediction_rule")
+            })?;
+            Some(PerformanceInput::BinaryClassification {
+                y_true: labeled_values(&labeled),
+                y_pred: labeled_values(y_pred),
+            })
+        }
+        (Some(ModelType::LogisticRegression), Some((name, column))) => {
+            Some(PerformanceInput::LogisticRegression {
+                y_true: labeled_values(&labeled),
+                y_proba: column.perf_values(name)?.1,
+                threshold: decision_threshold,
+            })
+        }
+        (Some(ModelType::LinearRegression), Some((name, column))) => {
+            Some(PerformanceInput::LinearRegression {
+                y_true: label_column.perf_values(label)?.1,
+                y_pred: column.perf_values(name)?.1,
+            })
+        }
+    };
+
+    let facets = facets
+        .into_iter()
+        .map(|(name, rule)| {
+            let labeled = apply_label(&frame_column(&frame, &name)?, rule, &name)?;
+            Ok((name, labeled))
+        })
+        .collect::<PyResult<Vec<(String, Vec<i16>)>>>()?;
+    let columns = FrameColumns {
+        facets,
+        label: labeled,
+        prediction: labeled_prediction,
+        performance,
+        sample_weight: weights,
+    };
+    let data_registry = data_bias_registry();
+    let model_registry = model_bias_registry();
+    let report = py.allow_threads(|| {
+        analyze_frame(
+            columns,
+            missing_policy,
+            &data_registry,
+            &model_registry,
+            bootstrap_config(confidence_level, resamples, seed),
+            significance.then_some(SignificanceConfig { permutations, seed }),
+            Some(SupportThresholds {
+                min_facet_size,
+                min_cell_count,
+            }),
+        )
+    })?;
+    Ok(report.into())
+}
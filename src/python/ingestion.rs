@@ -0,0 +1,849 @@
+use super::arrow::{ArrowChunks, ArrowColumn};
+use super::labels::{label_rule, LabelRule, LabelValue};
+use crate::data_handler::{
+    auto_label_float, auto_label_integer, retain_rows, LabelSpec, MISSING_LABEL,
+};
+use crate::errors::FairMlError;
+use numpy::{Element, PyArray1, PyArrayMethods, PyReadonlyArray1, PyUntypedArrayMethods};
+use numpy::{PyArrayDescrMethods, PyUntypedArray};
+use pyo3::exceptions::{PyDeprecationWarning, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyFloat, PyInt, PyString};
+use std::borrow::Cow;
+
+/// Evaluates `$body` with `$slice` bound to the array data for the first
+/// listed dtype the array matches, or to `None` when it matches none of them.
+/// Contiguous arrays are borrowed straight from the numpy buffer.
+macro_rules! with_slice {
+    ($array:expr, |$slice:ident| $body:expr, [$($t:ty),+]) => {{
+        let any = $array.as_any();
+        let mut out = None;
+        $(
+            if out.is_none() {
+                if let Ok(Ok(readonly)) = any
+                    .downcast::<PyArray1<$t>>()
+                    .map(|typed| typed.try_readonly())
+                {
+                    let $slice: &[$t] = &contiguous(&readonly);
+                    out = Some($body);
+                }
+            }
+        )+
+        out
+    }};
+}
+
+/// `with_slice` over every float dtype. float16 has no rust element type
+/// here, so it is widened to f32 from the raw buffer first.
+macro_rules! with_float_slice {
+    ($array:expr, |$slice:ident| $body:expr) => {
+        match with_slice!($array, |$slice| $body, [f64, f32]) {
+            Some(out) => Some(out),
+            None => half_values($array).map(|halves| {
+                let $slice: &[f32] = &halves;
+                $body
+            }),
+        }
+    };
+}
+
+/// `with_slice` over every integer and boolean dtype. u64 does not convert
+/// into i64 losslessly, so it is copied with values past `i64::MAX` clamped.
+macro_rules! with_integer_slice {
+    ($array:expr, |$slice:ident| $body:expr) => {
+        match with_slice!(
+            $array,
+            |$slice| $body,
+            [i64, i32, i16, i8, u32, u16, u8, bool]
+        ) {
+            Some(out) => Some(out),
+            None => with_slice!(
+                $array,
+                |wide| {
+                    let clamped: Vec<i64> = wide
+                        .iter()
+                        .map(|value| i64::try_from(*value).unwrap_or(i64::MAX))
+                        .collect();
+                    let $slice: &[i64] = &clamped;
+                    $body
+                },
+                [u64]
+            ),
+        }
+    };
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PassedType {
+    Float,
+    Integer,
+    /// Object arrays and numpy's fixed width `<U` and `S` strings.
+    String,
+}
+
+/// Groups the numpy dtype of `array` by how its values are read. Booleans
+/// are integers with `True` as 1. Dates, complex numbers and the like have no
+/// label semantics and are rejected.
+pub fn determine_type(
+    array: &Bound<'_, PyUntypedArray>,
+    name: &str,
+) -> Result<PassedType, FairMlError> {
+    let dtype = array.dtype();
+    match dtype.kind() {
+        b'f' => Ok(PassedType::Float),
+        b'i' | b'u' | b'b' => Ok(PassedType::Integer),
+        b'O' | b'U' | b'S' => Ok(PassedType::String),
+        _ => Err(FairMlError::UnsupportedDtype {
+            array: name.into(),
+            dtype: format!("{dtype}, expected numbers, booleans or strings"),
+        }),
+    }
+}
+
+fn contiguous<'a, T>(array: &'a PyReadonlyArray1<'_, T>) -> Cow<'a, [T]>
+where
+    T: Element + Copy,
+{
+    match array.as_slice() {
+        Ok(slice) => Cow::Borrowed(slice),
+        // strided views, e.g. a column sliced out of a 2d array
+        Err(_) => Cow::Owned(array.as_array().iter().copied().collect()),
+    }
+}
+
+/// Bytes of every element of a 1d array, read straight from the numpy buffer.
+/// Only used for dtypes rust-numpy has no element type for here, float16 and
+/// the fixed width strings.
+fn raw_elements<'a>(array: &'a Bound<'_, PyUntypedArray>) -> Option<Vec<&'a [u8]>> {
+    if array.ndim() != 1 {
+        return None;
+    }
+    let itemsize = array.dtype().itemsize();
+    let stride = array.strides()[0];
+    // SAFETY: a 1d array holds `len` elements of `itemsize` bytes, `stride`
+    // bytes apart, and the buffer lives at least as long as `array`
+    let data = unsafe { (*array.as_array_ptr()).data } as *const u8;
+    Some(
+        (0..array.len())
+            .map(|i| unsafe {
+                std::slice::from_raw_parts(data.offset(i as isize * stride), itemsize)
+            })
+            .collect(),
+    )
+}
+
+fn swapped_if<const N: usize>(bytes: &[u8], swap: bool) -> [u8; N] {
+    let mut out = [0; N];
+    out.copy_from_slice(bytes);
+    if swap {
+        out.reverse();
+    }
+    out
+}
+
+pub(super) fn f16_to_f32(bits: u16) -> f32 {
+    let exponent = u32::from((bits >> 10) & 0x1f);
+    let mantissa = u32::from(bits & 0x3ff);
+    let magnitude = match exponent {
+        0 => mantissa as f32 * 2f32.powi(-24),
+        0x1f if mantissa == 0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        // rebias the exponent from 15 to 127
+        _ => f32::from_bits(((exponent + 112) << 23) | (mantissa << 13)),
+    };
+    if bits >> 15 == 1 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Values of a float16 array widened to f32, `None` for any other dtype.
+fn half_values(array: &Bound<'_, PyUntypedArray>) -> Option<Vec<f32>> {
+    let dtype = array.dtype();
+    if dtype.kind() != b'f' || dtype.itemsize() != 2 {
+        return None;
+    }
+    let swap = dtype.is_native_byteorder() == Some(false);
+    Some(
+        raw_elements(array)?
+            .into_iter()
+            .map(|bytes| f16_to_f32(u16::from_ne_bytes(swapped_if(bytes, swap))))
+            .collect(),
+    )
+}
+
+/// Values of a numpy `<U` or `S` array decoded from the buffer, without a
+/// python object per element. Trailing nulls are padding, as in numpy.
+/// `None` for any other dtype.
+fn fixed_width_text(
+    array: &Bound<'_, PyUntypedArray>,
+    name: &str,
+) -> Option<Result<Vec<String>, FairMlError>> {
+    let dtype = array.dtype();
+    let swap = dtype.is_native_byteorder() == Some(false);
+    let decode: fn(&[u8], bool) -> Option<String> = match dtype.kind() {
+        b'U' => |bytes, swap| {
+            bytes
+                .chunks_exact(4)
+                .map(|code| char::from_u32(u32::from_ne_bytes(swapped_if(code, swap))))
+                .collect()
+        },
+        b'S' => |bytes, _| String::from_utf8(bytes.to_vec()).ok(),
+        _ => return None,
+    };
+    let values = raw_elements(array)?
+        .into_iter()
+        .map(|bytes| decode(bytes, swap).map(|value| value.trim_end_matches('\0').to_string()))
+        .collect::<Option<Vec<String>>>()
+        .ok_or_else(|| FairMlError::UnsupportedDtype {
+            array: name.into(),
+            dtype: format!("{dtype}, values are not valid text"),
+        });
+    Some(values)
+}
+
+/// String values with missing ones as `None`, fixed width numpy strings
+/// never hold a missing value.
+fn text_values(
+    array: &Bound<'_, PyUntypedArray>,
+    name: &str,
+) -> Result<Vec<Option<String>>, FairMlError> {
+    match fixed_width_text(array, name) {
+        Some(values) => Ok(values?.into_iter().map(Some).collect()),
+        None => extract_optional(array, name),
+    }
+}
+
+/// Object path, one Python object per element. Only strings and object
+/// arrays should end up here.
+fn extract_all<'py, T>(
+    array: &Bound<'py, PyUntypedArray>,
+    name: &str,
+) -> Result<Vec<T>, FairMlError>
+where
+    T: FromPyObject<'py>,
+{
+    let unsupported = || FairMlError::UnsupportedDtype {
+        array: name.into(),
+        dtype: array.dtype().to_string(),
+    };
+    let iter = array.iter().map_err(|_| unsupported())?;
+    iter.map(|item| item.and_then(|value| value.extract::<T>()))
+        .collect::<PyResult<Vec<T>>>()
+        .map_err(|_| unsupported())
+}
+
+/// None, or a float NaN as pandas uses for missing values in object columns.
+fn is_missing(value: &Bound<'_, PyAny>) -> bool {
+    value.is_none()
+        || (value.is_instance_of::<PyFloat>()
+            && value.extract::<f64>().is_ok_and(|value| value.is_nan()))
+}
+
+/// `extract_all` with missing values as `None`.
+fn extract_optional<'py, T>(
+    array: &Bound<'py, PyUntypedArray>,
+    name: &str,
+) -> Result<Vec<Option<T>>, FairMlError>
+where
+    T: FromPyObject<'py>,
+{
+    let unsupported = || FairMlError::UnsupportedDtype {
+        array: name.into(),
+        dtype: array.dtype().to_string(),
+    };
+    let iter = array.iter().map_err(|_| unsupported())?;
+    iter.map(|item| {
+        item.and_then(|value| {
+            if is_missing(&value) {
+                Ok(None)
+            } else {
+                value.extract::<T>().map(Some)
+            }
+        })
+    })
+    .collect::<PyResult<Vec<Option<T>>>>()
+    .map_err(|_| unsupported())
+}
+
+fn is_number(value: &Bound<'_, PyAny>) -> bool {
+    value.is_instance_of::<PyFloat>() | value.is_instance_of::<PyInt>()
+}
+
+/// Whether an object array holds python numbers, judged by its first value
+/// that is not missing.
+fn holds_numbers(array: &Bound<'_, PyUntypedArray>) -> bool {
+    if array.dtype().kind() != b'O' {
+        return false;
+    }
+    let Ok(iter) = array.iter() else {
+        return false;
+    };
+    iter.flatten()
+        .find(|value| !is_missing(value))
+        .is_some_and(|first| is_number(&first))
+}
+
+/// An input column, a numpy array or anything exporting Arrow data.
+pub enum Column<'py> {
+    Numpy(Bound<'py, PyUntypedArray>),
+    Arrow(ArrowChunks),
+}
+
+impl<'py> FromPyObject<'py> for Column<'py> {
+    fn extract_bound(object: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(array) = object.downcast::<PyUntypedArray>() {
+            return Ok(Column::Numpy(array.clone()));
+        }
+        match ArrowChunks::import(object)? {
+            Some(chunks) => Ok(Column::Arrow(chunks)),
+            None => Err(PyTypeError::new_err(format!(
+                "expected a numpy array or an object exporting arrow data, got {}",
+                object.get_type().name()?
+            ))),
+        }
+    }
+}
+
+impl Column<'_> {
+    /// Values as f32 for the performance metrics, missing ones as NaN, along
+    /// with how they were stored.
+    pub(super) fn perf_values(&self, name: &str) -> Result<(PassedType, Vec<f32>), FairMlError> {
+        match self {
+            Column::Numpy(array) => {
+                let passed_type = determine_type(array, name)?;
+                Ok((
+                    passed_type,
+                    PerfEntry::convert_f32(array, passed_type, name)?,
+                ))
+            }
+            Column::Arrow(chunks) => {
+                let values = chunks.decode(name)?.decoded();
+                let as_f32 = |value: Option<f64>| value.map_or(f32::NAN, |value| value as f32);
+                match values {
+                    ArrowColumn::Float(values) => {
+                        Ok((PassedType::Float, values.into_iter().map(as_f32).collect()))
+                    }
+                    ArrowColumn::Integer(values) => Ok((
+                        PassedType::Integer,
+                        values
+                            .into_iter()
+                            .map(|value| as_f32(value.map(|value| value as f64)))
+                            .collect(),
+                    )),
+                    other => Err(FairMlError::UnsupportedDtype {
+                        array: name.into(),
+                        dtype: other.type_name().into(),
+                    }),
+                }
+            }
+        }
+    }
+
+    /// Raw outcome values at full precision, missing ones as NaN, for the
+    /// continuous data bias.
+    pub(super) fn outcome_values(&self, name: &str) -> Result<Vec<f64>, FairMlError> {
+        let array = match self {
+            Column::Numpy(array) => array,
+            Column::Arrow(chunks) => {
+                return match chunks.decode(name)?.decoded() {
+                    ArrowColumn::Float(values) => Ok(values
+                        .into_iter()
+                        .map(|value| value.unwrap_or(f64::NAN))
+                        .collect()),
+                    ArrowColumn::Integer(values) => Ok(values
+                        .into_iter()
+                        .map(|value| value.map_or(f64::NAN, |value| value as f64))
+                        .collect()),
+                    other => Err(FairMlError::UnsupportedDtype {
+                        array: name.into(),
+                        dtype: other.type_name().into(),
+                    }),
+                };
+            }
+        };
+        let passed_type = determine_type(array, name)?;
+        let fast_path = match passed_type {
+            PassedType::Float => {
+                with_float_slice!(array, |data| data.iter().map(|v| f64::from(*v)).collect())
+            }
+            PassedType::Integer => {
+                with_integer_slice!(array, |data| data
+                    .iter()
+                    .map(|v| i64::from(*v) as f64)
+                    .collect())
+            }
+            PassedType::String => None,
+        };
+        if let Some(values) = fast_path {
+            return Ok(values);
+        }
+        match passed_type {
+            PassedType::Float | PassedType::Integer => extract_all::<f64>(array, name),
+            // object arrays of python numbers, None becomes NaN for the missing policy
+            PassedType::String if holds_numbers(array) => Ok(extract_optional::<f64>(array, name)?
+                .into_iter()
+                .map(|value| value.unwrap_or(f64::NAN))
+                .collect()),
+            PassedType::String => Err(FairMlError::UnsupportedDtype {
+                array: name.into(),
+                dtype: array.dtype().to_string(),
+            }),
+        }
+    }
+
+    fn dtype(&self) -> String {
+        match self {
+            Column::Numpy(array) => array.dtype().to_string(),
+            Column::Arrow(chunks) => format!("arrow format {}", chunks.format()),
+        }
+    }
+}
+
+pub fn apply_label(column: &Column<'_>, label: Bound<'_, PyAny>, name: &str) -> PyResult<Vec<i16>> {
+    warn_bare_number(&label, name)?;
+    Ok(match column {
+        Column::Numpy(array) => {
+            let spec = resolve_numpy_label(array, &label, name)?;
+            apply_label_spec(array, &spec, name)
+        }
+        Column::Arrow(chunks) => {
+            let values = chunks.decode(name)?;
+            let spec = resolve_arrow_label(&values, &label, name)?;
+            apply_arrow_spec(&values, &spec, name)
+        }
+    }?)
+}
+
+/// The spec `label` stands for on `column`. A `LabelSpec` (or a set or dict)
+/// is used as given and a bare string is matched on equality. A bare number
+/// goes through the two value heuristic of `auto_label_float`, the rule
+/// `apply_label_float` applies, with a `DeprecationWarning`, `LabelSpec.auto`
+/// asks for the heuristic without one.
+pub fn resolve_label(
+    column: &Column<'_>,
+    label: &Bound<'_, PyAny>,
+    name: &str,
+) -> PyResult<LabelSpec<LabelValue>> {
+    warn_bare_number(label, name)?;
+    Ok(match column {
+        Column::Numpy(array) => resolve_numpy_label(array, label, name),
+        Column::Arrow(chunks) => resolve_arrow_label(&chunks.decode(name)?, label, name),
+    }?)
+}
+
+/// Bare numbers keep the two value heuristic for now, the explicit
+/// `LabelSpec.equals`/`LabelSpec.greater_equal` say which comparison is meant.
+fn warn_bare_number(label: &Bound<'_, PyAny>, name: &str) -> PyResult<()> {
+    if !is_number(label) {
+        return Ok(());
+    }
+    let py = label.py();
+    PyErr::warn_bound(
+        py,
+        &py.get_type_bound::<PyDeprecationWarning>(),
+        &format!(
+            "a bare number as the {name} label or threshold is deprecated, it is matched \
+             on equality when the data holds two distinct values and thresholded with >= \
+             otherwise, pass LabelSpec.equals or LabelSpec.greater_equal instead"
+        ),
+        1,
+    )
+}
+
+fn label_mismatch(name: &str, expected: &str) -> FairMlError {
+    FairMlError::LabelTypeMismatch {
+        array: name.into(),
+        expected: expected.into(),
+    }
+}
+
+/// A bare number label for float data, ints are widened.
+fn float_label(label: &Bound<'_, PyAny>, name: &str) -> Result<f64, FairMlError> {
+    if label.is_instance_of::<PyFloat>() {
+        label.extract::<f64>()
+    } else if label.is_instance_of::<PyInt>() {
+        label.extract::<i64>().map(|value| value as f64)
+    } else {
+        return Err(label_mismatch(name, "an int or float"));
+    }
+    .map_err(|_| label_mismatch(name, "an int or float"))
+}
+
+fn text_label(label: &Bound<'_, PyAny>, name: &str) -> Result<LabelSpec<LabelValue>, FairMlError> {
+    let data_label: String = label
+        .extract::<String>()
+        .map_err(|_| label_mismatch(name, "a string"))?;
+    Ok(LabelSpec::Equals(LabelValue::Text(data_label)))
+}
+
+fn resolve_numpy_label(
+    array: &Bound<'_, PyUntypedArray>,
+    label: &Bound<'_, PyAny>,
+    name: &str,
+) -> Result<LabelSpec<LabelValue>, FairMlError> {
+    match label_rule(label)? {
+        Some(LabelRule::Explicit(spec)) => Ok(spec),
+        Some(LabelRule::Auto(label)) => auto_numpy_label(array, label, name),
+        None => match determine_type(array, name)? {
+            PassedType::String if label.is_instance_of::<PyString>() => text_label(label, name),
+            // object arrays of python numbers, e.g. a pandas column built from mixed ints
+            PassedType::String if !(is_number(label) && holds_numbers(array)) => {
+                Err(label_mismatch(name, "a string"))
+            }
+            _ => auto_numpy_label(array, float_label(label, name)?, name),
+        },
+    }
+}
+
+/// The spec the two value heuristic picks for `label` on a numeric array,
+/// integer data compares against the label truncated like the data.
+fn auto_numpy_label(
+    array: &Bound<'_, PyUntypedArray>,
+    label: f64,
+    name: &str,
+) -> Result<LabelSpec<LabelValue>, FairMlError> {
+    let spec: LabelSpec<f64> = match determine_type(array, name)? {
+        PassedType::String if holds_numbers(array) => {
+            let data: Vec<f64> = extract_optional::<f64>(array, name)?
+                .into_iter()
+                .flatten()
+                .collect();
+            auto_label_float(&data, label)
+        }
+        PassedType::String => return Err(label_mismatch(name, "a string")),
+        PassedType::Float => match with_float_slice!(array, |data| auto_label_float(data, label)) {
+            Some(spec) => spec,
+            None => auto_label_float(&extract_all::<f64>(array, name)?, label),
+        },
+        PassedType::Integer => {
+            let label = label as i64;
+            let spec = match with_integer_slice!(array, |data| auto_label_integer(data, label)) {
+                Some(spec) => spec,
+                None => auto_label_integer(&extract_all::<i64>(array, name)?, label),
+            };
+            spec.map(|value| *value as f64)
+        }
+    };
+    Ok(spec.map(|value| LabelValue::Number(*value)))
+}
+
+/// Labels with an explicit spec. Numeric specs apply to numeric arrays,
+/// string specs to string arrays.
+fn apply_label_spec(
+    array: &Bound<'_, PyUntypedArray>,
+    spec: &LabelSpec<LabelValue>,
+    name: &str,
+) -> Result<Vec<i16>, FairMlError> {
+    let passed_type = determine_type(array, name)?;
+    if passed_type == PassedType::String && !holds_numbers(array) {
+        let spec = spec
+            .try_map(LabelValue::as_text)
+            .ok_or_else(|| label_mismatch(name, "a spec of strings"))?;
+        let data_vec: Vec<Option<String>> = text_values(array, name)?;
+        return spec.apply(&data_vec, name, Option::as_deref);
+    }
+
+    let spec = spec
+        .try_map(LabelValue::as_number)
+        .ok_or_else(|| label_mismatch(name, "a spec of ints or floats"))?;
+    let fast_path = match passed_type {
+        PassedType::Float => with_float_slice!(array, |data| spec.apply(data, name, |value| {
+            Some(f64::from(*value)).filter(|value| !value.is_nan())
+        })),
+        PassedType::Integer => with_integer_slice!(array, |data| spec
+            .apply(data, name, |value| Some(i64::from(*value) as f64))),
+        PassedType::String => None,
+    };
+    match fast_path {
+        Some(labeled) => labeled,
+        None => spec.apply(&extract_optional::<f64>(array, name)?, name, |value| {
+            value.filter(|value| !value.is_nan())
+        }),
+    }
+}
+
+fn resolve_arrow_label(
+    values: &ArrowColumn,
+    label: &Bound<'_, PyAny>,
+    name: &str,
+) -> Result<LabelSpec<LabelValue>, FairMlError> {
+    match label_rule(label)? {
+        Some(LabelRule::Explicit(spec)) => Ok(spec),
+        Some(LabelRule::Auto(label)) => auto_arrow_label(values, label, name),
+        None => match values {
+            ArrowColumn::Dictionary { values, .. } => resolve_arrow_label(values, label, name),
+            ArrowColumn::Text(_) if label.is_instance_of::<PyString>() => text_label(label, name),
+            ArrowColumn::Text(_) => Err(label_mismatch(name, "a string")),
+            ArrowColumn::Float(_) | ArrowColumn::Integer(_) => {
+                auto_arrow_label(values, float_label(label, name)?, name)
+            }
+        },
+    }
+}
+
+/// `auto_numpy_label` for an Arrow column.
+fn auto_arrow_label(
+    values: &ArrowColumn,
+    label: f64,
+    name: &str,
+) -> Result<LabelSpec<LabelValue>, FairMlError> {
+    let spec: LabelSpec<f64> = match values {
+        // the heuristic looks at the distinct values, which the dictionary holds
+        ArrowColumn::Dictionary { values, .. } => return auto_arrow_label(values, label, name),
+        ArrowColumn::Text(_) => return Err(label_mismatch(name, "a string")),
+        ArrowColumn::Float(values) => {
+            let data: Vec<f64> = values.iter().flatten().copied().collect();
+            auto_label_float(&data, label)
+        }
+        ArrowColumn::Integer(values) => {
+            let data: Vec<i64> = values.iter().flatten().copied().collect();
+            auto_label_integer(&data, label as i64).map(|value| *value as f64)
+        }
+    };
+    Ok(spec.map(|value| LabelValue::Number(*value)))
+}
+
+/// Labels an Arrow column, nulls are labeled missing.
+fn apply_arrow_spec(
+    values: &ArrowColumn,
+    spec: &LabelSpec<LabelValue>,
+    name: &str,
+) -> Result<Vec<i16>, FairMlError> {
+    let numeric = || {
+        spec.try_map(LabelValue::as_number)
+            .ok_or_else(|| label_mismatch(name, "a spec of ints or floats"))
+    };
+    match values {
+        ArrowColumn::Text(values) => spec
+            .try_map(LabelValue::as_text)
+            .ok_or_else(|| label_mismatch(name, "a spec of strings"))?
+            .apply(values, name, Option::as_deref),
+        ArrowColumn::Float(values) => {
+            numeric()?.apply(values, name, |value| value.filter(|value| !value.is_nan()))
+        }
+        ArrowColumn::Integer(values) => {
+            numeric()?.apply(values, name, |value| value.map(|value| value as f64))
+        }
+        ArrowColumn::Dictionary { values, keys } => {
+            // only entries some row uses are labeled, categoricals often
+            // carry categories that no longer occur
+            let mut used: Vec<usize> = keys.iter().flatten().copied().collect();
+            used.sort_unstable();
+            used.dedup();
+            let labeled = apply_arrow_spec(&values.select(&used), spec, name)?;
+            let mut lookup = vec![MISSING_LABEL; values.len()];
+            for (entry, label) in used.into_iter().zip(labeled) {
+                lookup[entry] = label;
+            }
+            Ok(keys
+                .iter()
+                .map(|key| key.map_or(MISSING_LABEL, |key| lookup[key]))
+                .collect())
+        }
+    }
+}
+
+/// Raw values of a categorical feature, before they are split into groups.
+pub enum GroupValues {
+    Text(Vec<String>),
+    Integer(Vec<i64>),
+}
+
+impl GroupValues {
+    /// Drops the rows the missing value policy left out of the labels.
+    pub fn retain(&mut self, keep: &[bool]) {
+        match self {
+            GroupValues::Text(values) => retain_rows(values, keep),
+            GroupValues::Integer(values) => retain_rows(values, keep),
+        }
+    }
+}
+
+pub fn group_values(column: &Column<'_>, name: &str) -> Result<GroupValues, FairMlError> {
+    match column {
+        Column::Numpy(array) => numpy_group_values(array, name),
+        Column::Arrow(chunks) => arrow_group_values(chunks.decode(name)?.decoded(), name),
+    }
+}
+
+fn numpy_group_values(
+    array: &Bound<'_, PyUntypedArray>,
+    name: &str,
+) -> Result<GroupValues, FairMlError> {
+    match determine_type(array, name)? {
+        PassedType::String if holds_numbers(array) => {
+            Ok(GroupValues::Integer(extract_all::<i64>(array, name)?))
+        }
+        PassedType::String => Ok(GroupValues::Text(match fixed_width_text(array, name) {
+            Some(values) => values?,
+            None => extract_all::<String>(array, name)?,
+        })),
+        PassedType::Integer => Ok(GroupValues::Integer(
+            match with_integer_slice!(array, |data| data.iter().map(|v| i64::from(*v)).collect()) {
+                Some(values) => values,
+                None => extract_all::<i64>(array, name)?,
+            },
+        )),
+        // continuous values have no natural groups
+        PassedType::Float => Err(FairMlError::UnsupportedDtype {
+            array: name.into(),
+            dtype: format!("{}, groups need a string or integer feature", array.dtype()),
+        }),
+    }
+}
+
+/// Every group needs a value, a null has no group to go to.
+fn present<T>(values: Vec<Option<T>>, name: &str) -> Result<Vec<T>, FairMlError> {
+    let count = values.iter().filter(|value| value.is_none()).count();
+    if count > 0 {
+        return Err(FairMlError::MissingValues {
+            array: name.into(),
+            count,
+        });
+    }
+    Ok(values.into_iter().flatten().collect())
+}
+
+fn arrow_group_values(values: ArrowColumn, name: &str) -> Result<GroupValues, FairMlError> {
+    match values {
+        ArrowColumn::Text(values) => Ok(GroupValues::Text(present(values, name)?)),
+        ArrowColumn::Integer(values) => Ok(GroupValues::Integer(present(values, name)?)),
+        other => Err(FairMlError::UnsupportedDtype {
+            array: name.into(),
+            dtype: format!(
+                "{}, groups need a string or integer feature",
+                other.type_name()
+            ),
+        }),
+    }
+}
+
+/// Reads an optional `sample_weight` column as f64. Numeric buffers are read
+/// directly, object arrays of numbers go through the object path with None
+/// read as NaN, which the weight checks reject, as are Arrow nulls.
+pub fn sample_weights(column: Option<&Column<'_>>) -> Result<Option<Vec<f64>>, FairMlError> {
+    let name = "sample_weight";
+    let array = match column {
+        None => return Ok(None),
+        Some(Column::Numpy(array)) => array,
+        Some(Column::Arrow(chunks)) => {
+            return match chunks.decode(name)?.decoded() {
+                ArrowColumn::Float(values) => Ok(Some(
+                    values
+                        .into_iter()
+                        .map(|value| value.unwrap_or(f64::NAN))
+                        .collect(),
+                )),
+                ArrowColumn::Integer(values) => Ok(Some(
+                    values
+                        .into_iter()
+                        .map(|value| value.map_or(f64::NAN, |value| value as f64))
+                        .collect(),
+                )),
+                other => Err(FairMlError::UnsupportedDtype {
+                    array: name.into(),
+                    dtype: other.type_name().into(),
+                }),
+            };
+        }
+    };
+    let passed_type = determine_type(array, name)?;
+    let fast_path = match passed_type {
+        PassedType::Float => {
+            with_float_slice!(array, |data| data.iter().map(|v| f64::from(*v)).collect())
+        }
+        PassedType::Integer => with_integer_slice!(array, |data| data
+            .iter()
+            .map(|v| i64::from(*v) as f64)
+            .collect()),
+        PassedType::String => None,
+    };
+    if let Some(values) = fast_path {
+        return Ok(Some(values));
+    }
+    match passed_type {
+        PassedType::Float | PassedType::Integer => extract_all::<f64>(array, name).map(Some),
+        PassedType::String if holds_numbers(array) => Ok(Some(
+            extract_optional::<f64>(array, name)?
+                .into_iter()
+                .map(|value| value.unwrap_or(f64::NAN))
+                .collect(),
+        )),
+        PassedType::String => Err(FairMlError::UnsupportedDtype {
+            array: name.into(),
+            dtype: array.dtype().to_string(),
+        }),
+    }
+}
+
+pub struct PerfEntry;
+
+impl PerfEntry {
+    pub fn validate_and_cast_classification(
+        y_true_src: &Column<'_>,
+        y_pred_src: &Column<'_>,
+    ) -> Result<(Vec<f32>, Vec<f32>), FairMlError> {
+        let (gt_type, y_true) = y_true_src.perf_values("y_true")?;
+        let (pred_type, y_pred) = y_pred_src.perf_values("y_pred")?;
+
+        if pred_type != gt_type {
+            return Err(FairMlError::UnsupportedDtype {
+                array: "y_pred".into(),
+                dtype: format!(
+                    "{}, expected the y_true dtype {}",
+                    y_pred_src.dtype(),
+                    y_true_src.dtype()
+                ),
+            });
+        }
+        Ok((y_true, y_pred))
+    }
+
+    pub fn validate_and_cast_regression(
+        y_true_src: &Column<'_>,
+        y_pred_src: &Column<'_>,
+    ) -> Result<(Vec<f32>, Vec<f32>), FairMlError> {
+        let (_, y_true) = y_true_src.perf_values("y_true")?;
+        let (_, y_pred) = y_pred_src.perf_values("y_pred")?;
+        Ok((y_true, y_pred))
+    }
+
+    pub fn convert_f32(
+        arr: &Bound<'_, PyUntypedArray>,
+        passed_type: PassedType,
+        name: &str,
+    ) -> Result<Vec<f32>, FairMlError> {
+        let fast_path = match passed_type {
+            PassedType::Float => with_float_slice!(arr, |data| data
+                .iter()
+                .map(|v| f64::from(*v) as f32)
+                .collect()),
+            PassedType::Integer => with_integer_slice!(arr, |data| data
+                .iter()
+                .map(|v| i64::from(*v) as f32)
+                .collect()),
+            PassedType::String => None,
+        };
+        if let Some(values) = fast_path {
+            return Ok(values);
+        }
+
+        match passed_type {
+            PassedType::Float | PassedType::Integer => Ok(extract_all::<f64>(arr, name)?
+                .into_iter()
+                .map(|value| value as f32)
+                .collect()),
+            // object arrays of python numbers, None becomes NaN for the missing policy
+            PassedType::String if holds_numbers(arr) => Ok(extract_optional::<f64>(arr, name)?
+                .into_iter()
+                .map(|value| value.map_or(f32::NAN, |value| value as f32))
+                .collect()),
+            PassedType::String => Err(FairMlError::UnsupportedDtype {
+                array: name.into(),
+                dtype: arr.dtype().to_string(),
+            }),
+        }
+    }
+}
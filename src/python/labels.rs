@@ -0,0 +1,303 @@
+use super::ingestion::{self, Column};
+use crate::data_handler::LabelSpec;
+use crate::errors::FairMlError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyFloat, PyFrozenSet, PyInt, PyList, PySet, PyString, PyTuple};
+use std::fmt;
+
+/// A value in a python label spec, matched against string or numeric arrays.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelValue {
+    Text(String),
+    Number(f64),
+}
+
+impl LabelValue {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(value) => Some(value),
+            Self::Number(_) => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            Self::Text(_) => None,
+        }
+    }
+
+    fn extract(value: &Bound<'_, PyAny>) -> Result<LabelValue, FairMlError> {
+        if value.is_instance_of::<PyString>() {
+            Ok(LabelValue::Text(
+                value.extract::<String>().map_err(invalid)?,
+            ))
+        } else if value.is_instance_of::<PyFloat>() | value.is_instance_of::<PyInt>() {
+            Ok(LabelValue::Number(value.extract::<f64>().map_err(invalid)?))
+        } else {
+            Err(FairMlError::InvalidLabelSpec {
+                reason: format!("{value} is not a string, int or float"),
+            })
+        }
+    }
+
+    fn to_object(&self, py: Python<'_>) -> PyObject {
+        match self {
+            Self::Text(value) => value.into_py(py),
+            Self::Number(value) => value.into_py(py),
+        }
+    }
+}
+
+impl fmt::Display for LabelValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(value) => write!(f, "{value:?}"),
+            Self::Number(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+fn invalid(err: PyErr) -> FairMlError {
+    FairMlError::InvalidLabelSpec {
+        reason: err.to_string(),
+    }
+}
+
+fn extract_values(values: &Bound<'_, PyAny>) -> Result<Vec<LabelValue>, FairMlError> {
+    values
+        .iter()
+        .map_err(invalid)?
+        .map(|value| LabelValue::extract(&value.map_err(invalid)?))
+        .collect()
+}
+
+fn extract_mapping(mapping: &Bound<'_, PyAny>) -> Result<Vec<(LabelValue, bool)>, FairMlError> {
+    let mapping = mapping
+        .downcast::<PyDict>()
+        .map_err(|_| FairMlError::InvalidLabelSpec {
+            reason: "a mapping takes a dict of category to favorable".into(),
+        })?;
+    mapping
+        .iter()
+        .map(|(category, favorable)| {
+            let favorable =
+                favorable
+                    .extract::<bool>()
+                    .map_err(|_| FairMlError::InvalidLabelSpec {
+                        reason: format!("{category} must map to True or False"),
+                    })?;
+            Ok((LabelValue::extract(&category)?, favorable))
+        })
+        .collect()
+}
+
+fn extract_float(value: &Bound<'_, PyAny>, kind: &str) -> Result<f64, FairMlError> {
+    LabelValue::extract(value)?
+        .as_number()
+        .ok_or_else(|| FairMlError::InvalidLabelSpec {
+            reason: format!("{kind} takes an int or float"),
+        })
+}
+
+fn extract_number(value: &Bound<'_, PyAny>, kind: &str) -> Result<LabelValue, FairMlError> {
+    extract_float(value, kind).map(LabelValue::Number)
+}
+
+/// Builds a spec from its mode and the python values, as passed to the
+/// `LabelSpec` constructor.
+fn build_spec(kind: &str, values: &Bound<'_, PyAny>) -> Result<LabelSpec<LabelValue>, FairMlError> {
+    match kind {
+        "equals" => Ok(LabelSpec::Equals(LabelValue::extract(values)?)),
+        ">=" => Ok(LabelSpec::GreaterEqual(extract_number(values, kind)?)),
+        ">" => Ok(LabelSpec::Greater(extract_number(values, kind)?)),
+        "<=" => Ok(LabelSpec::LessEqual(extract_number(values, kind)?)),
+        "<" => Ok(LabelSpec::Less(extract_number(values, kind)?)),
+        "in" | "one_of" => Ok(LabelSpec::OneOf(extract_values(values)?)),
+        "mapping" => Ok(LabelSpec::Mapping(extract_mapping(values)?)),
+        "between" => match extract_values(values)?.as_slice() {
+            [low @ LabelValue::Number(_), high @ LabelValue::Number(_)] => Ok(LabelSpec::Range {
+                low: low.clone(),
+                high: high.clone(),
+            }),
+            _ => Err(FairMlError::InvalidLabelSpec {
+                reason: "between takes a numeric low and high".into(),
+            }),
+        },
+        _ => Err(FairMlError::InvalidLabelSpec {
+            reason: format!(
+                "unknown mode {kind}, expected equals, >=, >, <=, <, in, mapping, between or auto"
+            ),
+        }),
+    }
+}
+
+/// What a python `LabelSpec` holds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelRule {
+    Explicit(LabelSpec<LabelValue>),
+    /// A label or threshold left to the two value heuristic, resolved
+    /// against the data it labels.
+    Auto(f64),
+}
+
+/// Label definition, accepted anywhere a `label_or_threshold` is. A bare
+/// string is matched on equality, a bare number goes through the deprecated
+/// two value heuristic, see `resolve_label` for the mode it picks.
+#[pyclass(frozen, eq, name = "LabelSpec", module = "fair_perf_ml._fair_perf_ml")]
+#[derive(PartialEq)]
+pub struct PyLabelSpec {
+    inner: LabelRule,
+}
+
+impl From<LabelSpec<LabelValue>> for PyLabelSpec {
+    fn from(inner: LabelSpec<LabelValue>) -> PyLabelSpec {
+        PyLabelSpec {
+            inner: LabelRule::Explicit(inner),
+        }
+    }
+}
+
+#[pymethods]
+impl PyLabelSpec {
+    #[new]
+    fn new(mode: &str, values: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if mode == "auto" {
+            return Ok(PyLabelSpec {
+                inner: LabelRule::Auto(extract_float(values, mode)?),
+            });
+        }
+        Ok(build_spec(mode, values)?.into())
+    }
+
+    /// Matched on equality when the data holds exactly two distinct values
+    /// (truncated to ints), thresholded with `>=` otherwise.
+    #[staticmethod]
+    fn auto(label_or_threshold: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::new("auto", label_or_threshold)
+    }
+
+    /// Positive when the value equals `label`.
+    #[staticmethod]
+    fn equals(label: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::new("equals", label)
+    }
+
+    /// Positive when `value >= threshold`.
+    #[staticmethod]
+    fn greater_equal(threshold: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::new(">=", threshold)
+    }
+
+    /// Positive when `value > threshold`.
+    #[staticmethod]
+    fn greater(threshold: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::new(">", threshold)
+    }
+
+    /// Positive when `value <= threshold`.
+    #[staticmethod]
+    fn less_equal(threshold: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::new("<=", threshold)
+    }
+
+    /// Positive when `value < threshold`.
+    #[staticmethod]
+    fn less(threshold: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::new("<", threshold)
+    }
+
+    /// Positive when the value is one of `values`.
+    #[staticmethod]
+    fn one_of(values: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::new("in", values)
+    }
+
+    /// Each category mapped to favorable (True) or unfavorable (False), values
+    /// outside the mapping raise UnmappedCategoryError.
+    #[staticmethod]
+    fn mapping(mapping: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Self::new("mapping", mapping)
+    }
+
+    /// Positive when `low <= value < high`.
+    #[staticmethod]
+    fn between(py: Python<'_>, low: PyObject, high: PyObject) -> PyResult<Self> {
+        Self::new("between", PyTuple::new_bound(py, [low, high]).as_any())
+    }
+
+    #[getter]
+    fn mode(&self) -> &'static str {
+        match &self.inner {
+            LabelRule::Explicit(spec) => spec.mode(),
+            LabelRule::Auto(_) => "auto",
+        }
+    }
+
+    /// The values passed to the constructor, a single label or threshold, a
+    /// list, a dict or a `(low, high)` tuple depending on the mode.
+    #[getter]
+    fn values(&self, py: Python<'_>) -> PyObject {
+        let spec = match &self.inner {
+            LabelRule::Explicit(spec) => spec,
+            LabelRule::Auto(value) => return value.into_py(py),
+        };
+        match spec {
+            LabelSpec::Equals(value)
+            | LabelSpec::GreaterEqual(value)
+            | LabelSpec::Greater(value)
+            | LabelSpec::LessEqual(value)
+            | LabelSpec::Less(value) => value.to_object(py),
+            LabelSpec::OneOf(values) => {
+                PyList::new_bound(py, values.iter().map(|value| value.to_object(py))).into()
+            }
+            LabelSpec::Mapping(mapping) => {
+                let dict = PyDict::new_bound(py);
+                for (category, favorable) in mapping {
+                    // keys come from a dict, so they are hashable
+                    let _ = dict.set_item(category.to_object(py), favorable);
+                }
+                dict.into()
+            }
+            LabelSpec::Range { low, high } => {
+                PyTuple::new_bound(py, [low.to_object(py), high.to_object(py)]).into()
+            }
+        }
+    }
+
+    fn __getnewargs__(&self, py: Python<'_>) -> (&'static str, PyObject) {
+        (self.mode(), self.values(py))
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!(
+            "LabelSpec({:?}, {})",
+            self.mode(),
+            self.values(py).bind(py).repr()?
+        ))
+    }
+}
+
+/// The rule `label` describes, None for a bare string or number. Besides
+/// `LabelSpec` itself a set is read as `in` and a dict as `mapping`.
+pub fn label_rule(label: &Bound<'_, PyAny>) -> Result<Option<LabelRule>, FairMlError> {
+    if let Ok(spec) = label.downcast::<PyLabelSpec>() {
+        Ok(Some(spec.get().inner.clone()))
+    } else if label.is_instance_of::<PySet>() | label.is_instance_of::<PyFrozenSet>() {
+        build_spec("in", label).map(|spec| Some(LabelRule::Explicit(spec)))
+    } else if label.is_instance_of::<PyDict>() {
+        build_spec("mapping", label).map(|spec| Some(LabelRule::Explicit(spec)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// The spec a label or threshold stands for on `array`. For
+/// `LabelSpec.auto` this is the mode the two value heuristic picks.
+#[pyfunction]
+pub fn resolve_label(
+    array: Column<'_>,
+    label_or_threshold: Bound<'_, PyAny>,
+) -> PyResult<PyLabelSpec> {
+    Ok(ingestion::resolve_label(&array, &label_or_threshold, "array")?.into())
+}
@@ -0,0 +1,200 @@
+use crate::data_bias::{DataBiasMetric, DataBiasRegistry, PreTraining, PreTrainingComputations};
+use crate::errors::FairMlError;
+use crate::model_bias::{ModelBiasMetric, ModelBiasRegistry, PostTrainingData};
+use crate::runtime::Direction;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::{Arc, OnceLock, RwLock};
+
+// metrics registered from python, shared by every analyzer, check and accumulator
+static DATA_BIAS_REGISTRY: OnceLock<RwLock<DataBiasRegistry>> = OnceLock::new();
+static MODEL_BIAS_REGISTRY: OnceLock<RwLock<ModelBiasRegistry>> = OnceLock::new();
+
+/// Snapshot of the data bias registry. Cloned so the lock is not held while a
+/// python metric reacquires the GIL.
+pub(crate) fn data_bias_registry() -> DataBiasRegistry {
+    DATA_BIAS_REGISTRY
+        .get_or_init(|| RwLock::new(DataBiasRegistry::new()))
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Snapshot of the model bias registry, see `data_bias_registry`.
+pub(crate) fn model_bias_registry() -> ModelBiasRegistry {
+    MODEL_BIAS_REGISTRY
+        .get_or_init(|| RwLock::new(ModelBiasRegistry::new()))
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// A metric backed by a python callable. The callable receives a dict of the
+/// per facet counts and returns a float.
+struct PyMetric {
+    name: String,
+    compute: PyObject,
+    direction: Direction,
+    ideal_value: f32,
+}
+
+impl PyMetric {
+    fn call<F>(&self, counts: F) -> Result<f32, FairMlError>
+    where
+        F: for<'py> FnOnce(&Bound<'py, PyDict>) -> PyResult<()>,
+    {
+        Python::with_gil(|py| {
+            let dict = PyDict::new_bound(py);
+            counts(&dict)?;
+            self.compute.call1(py, (dict,))?.extract::<f32>(py)
+        })
+        .map_err(|err| FairMlError::MetricComputation {
+            metric: self.name.clone(),
+            reason: err.to_string(),
+        })
+    }
+}
+
+impl DataBiasMetric for PyMetric {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(&self, data: &PreTraining, _: &PreTrainingComputations) -> Result<f32, FairMlError> {
+        self.call(|dict| {
+            dict.set_item("facet_a_positives", data.facet_a.weighted.positives)?;
+            dict.set_item("facet_a_negatives", data.facet_a.weighted.negatives)?;
+            dict.set_item("facet_d_positives", data.facet_d.weighted.positives)?;
+            dict.set_item("facet_d_negatives", data.facet_d.weighted.negatives)
+        })
+    }
+
+    fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn ideal_value(&self) -> f32 {
+        self.ideal_value
+    }
+}
+
+impl ModelBiasMetric for PyMetric {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(&self, data: &PostTrainingData) -> Result<f32, FairMlError> {
+        self.call(|dict| {
+            for (facet, counts) in [("facet_a", &data.facet_a), ("facet_d", &data.facet_d)] {
+                dict.set_item(
+                    format!("{facet}_true_positives"),
+                    counts.weighted.true_positives,
+                )?;
+                dict.set_item(
+                    format!("{facet}_false_positives"),
+                    counts.weighted.false_positives,
+                )?;
+                dict.set_item(
+                    format!("{facet}_false_negatives"),
+                    counts.weighted.false_negatives,
+                )?;
+                dict.set_item(
+                    format!("{facet}_true_negatives"),
+                    counts.weighted.true_negatives,
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn ideal_value(&self) -> f32 {
+        self.ideal_value
+    }
+}
+
+fn py_metric(
+    name: String,
+    compute: Bound<'_, PyAny>,
+    direction: &str,
+    ideal_value: f32,
+) -> PyResult<PyMetric> {
+    if !compute.is_callable() {
+        return Err(pyo3::exceptions::PyTypeError::new_err(
+            "compute must be callable",
+        ));
+    }
+    Ok(PyMetric {
+        name,
+        compute: compute.unbind(),
+        direction: Direction::try_from(direction)?,
+        ideal_value,
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    name,
+    compute,
+    direction="magnitude",
+    ideal_value=0.0
+)
+)]
+pub fn register_data_bias_metric(
+    name: String,
+    compute: Bound<'_, PyAny>,
+    direction: &str,
+    ideal_value: f32,
+) -> PyResult<()> {
+    let metric = py_metric(name, compute, direction, ideal_value)?;
+    DATA_BIAS_REGISTRY
+        .get_or_init(|| RwLock::new(DataBiasRegistry::new()))
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .register(Arc::new(metric))?;
+    Ok(())
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    name,
+    compute,
+    direction="magnitude",
+    ideal_value=0.0
+)
+)]
+pub fn register_model_bias_metric(
+    name: String,
+    compute: Bound<'_, PyAny>,
+    direction: &str,
+    ideal_value: f32,
+) -> PyResult<()> {
+    let metric = py_metric(name, compute, direction, ideal_value)?;
+    MODEL_BIAS_REGISTRY
+        .get_or_init(|| RwLock::new(ModelBiasRegistry::new()))
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .register(Arc::new(metric))?;
+    Ok(())
+}
+
+#[pyfunction]
+pub fn data_bias_metric_names() -> Vec<String> {
+    data_bias_registry()
+        .names()
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+#[pyfunction]
+pub fn model_bias_metric_names() -> Vec<String> {
+    model_bias_registry()
+        .names()
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
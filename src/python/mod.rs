@@ -0,0 +1,1128 @@
+use crate::bootstrap::{
+    bootstrap_classification, bootstrap_linear_regression, bootstrap_logistic_regression,
+    BootstrapConfig,
+};
+use crate::data_bias::{CategoricalLabels, DivergenceOptions};
+use crate::data_handler::{
+    apply_missing_policy_rows, apply_missing_policy_values, check_sample_weights, MissingPolicy,
+};
+use crate::errors::FairMlError;
+use crate::frame::{labeled_continuous_data_bias, labeled_data_bias, labeled_model_bias};
+use crate::model_perf::{
+    map_string_to_bin_metric, map_string_to_linear_metric, BinaryClassificationReport,
+    ClassificationEvaluationMetrics, ClassificationPerf, LinearRegressionEvaluationMetrics,
+    LinearRegressionPerf, LinearRegressionReport, LogisticRegressionPerf, LogisticRegressionReport,
+    FULL_BINARY_CLASSIFICATION_METRICS, FULL_LOGISTIC_REGRESSION_METRICS, FULL_REGRESSION_METRICS,
+};
+use crate::models::{FailureRuntimeReturn, ModelType, PassedRuntimeReturn};
+use crate::multi_group::{
+    conditional_demographic_disparity, group_data_bias, group_model_bias, intersectional_data_bias,
+    intersectional_model_bias, multi_group_data_bias, multi_group_model_bias, ConditionalDisparity,
+    GroupComparison,
+};
+use crate::runtime::CheckStatuses;
+use crate::significance::SignificanceConfig;
+use crate::status::SupportThresholds;
+use pyo3::exceptions::PySystemError;
+use pyo3::prelude::*;
+use pyo3::types::PyInt;
+use std::collections::HashMap;
+mod accumulators;
+mod arrow;
+mod exceptions;
+mod frame;
+mod ingestion;
+mod labels;
+mod metrics;
+mod reports;
+use ingestion::{apply_label, group_values, sample_weights, Column, GroupValues, PerfEntry};
+use metrics::{data_bias_registry, model_bias_registry};
+use reports::{
+    canonical_metric_names, canonical_metrics, metric_statuses, PyBinaryClassificationReport,
+    PyDataBiasReport, PyLinearRegressionReport, PyLogisticRegressionReport, PyModelBiasReport,
+};
+
+#[pyfunction]
+#[pyo3(signature = (
+    baseline,
+    latest,
+    threshold=0.10,
+    baseline_status=None,
+    latest_status=None
+)
+)]
+pub fn data_bias_runtime_check(
+    py: Python<'_>,
+    baseline: HashMap<String, f32>,
+    latest: HashMap<String, f32>,
+    threshold: f32,
+    baseline_status: Option<HashMap<String, HashMap<String, String>>>,
+    latest_status: Option<HashMap<String, HashMap<String, String>>>,
+) -> PyResult<String> {
+    let baseline = canonical_metrics(py, baseline)?;
+    let latest = canonical_metrics(py, latest)?;
+    let baseline_status = baseline_status.map(metric_statuses).transpose()?;
+    let latest_status = latest_status.map(metric_statuses).transpose()?;
+    let statuses = CheckStatuses {
+        baseline: baseline_status.as_ref(),
+        latest: latest_status.as_ref(),
+    };
+    let failure_report: HashMap<String, String> =
+        data_bias_registry().runtime_check(&baseline, &latest, threshold, None, statuses)?;
+
+    process_failure_report(failure_report)
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    baseline,
+    latest,
+    metrics,
+    threshold=0.10,
+    baseline_status=None,
+    latest_status=None
+)
+)]
+pub fn data_bias_partial_check(
+    py: Python<'_>,
+    baseline: HashMap<String, f32>,
+    latest: HashMap<String, f32>,
+    metrics: Vec<String>,
+    threshold: f32,
+    baseline_status: Option<HashMap<String, HashMap<String, String>>>,
+    latest_status: Option<HashMap<String, HashMap<String, String>>>,
+) -> PyResult<String> {
+    let baseline = canonical_metrics(py, baseline)?;
+    let latest = canonical_metrics(py, latest)?;
+    let baseline_status = baseline_status.map(metric_statuses).transpose()?;
+    let latest_status = latest_status.map(metric_statuses).transpose()?;
+    let statuses = CheckStatuses {
+        baseline: baseline_status.as_ref(),
+        latest: latest_status.as_ref(),
+    };
+    let metrics = canonical_metric_names(py, metrics)?;
+    let failure_report: HashMap<String, String> = data_bias_registry().runtime_check(
+        &baseline,
+        &latest,
+        threshold,
+        Some(&metrics),
+        statuses,
+    )?;
+
+    process_failure_report(failure_report)
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    baseline,
+    latest,
+    metrics,
+    threshold=0.10,
+    baseline_status=None,
+    latest_status=None
+)
+)]
+fn model_bias_partial_check(
+    py: Python<'_>,
+    baseline: HashMap<String, f32>,
+    latest: HashMap<String, f32>,
+    metrics: Vec<String>,
+    threshold: f32,
+    baseline_status: Option<HashMap<String, HashMap<String, String>>>,
+    latest_status: Option<HashMap<String, HashMap<String, String>>>,
+) -> PyResult<String> {
+    let baseline = canonical_metrics(py, baseline)?;
+    let latest = canonical_metrics(py, latest)?;
+    let baseline_status = baseline_status.map(metric_statuses).transpose()?;
+    let latest_status = latest_status.map(metric_statuses).transpose()?;
+    let statuses = CheckStatuses {
+        baseline: baseline_status.as_ref(),
+        latest: latest_status.as_ref(),
+    };
+    let metrics = canonical_metric_names(py, metrics)?;
+    let failure_report: HashMap<String, String> = model_bias_registry().runtime_check(
+        &baseline,
+        &latest,
+        threshold,
+        Some(&metrics),
+        statuses,
+    )?;
+
+    process_failure_report(failure_report)
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    baseline,
+    latest,
+    threshold=0.10,
+    baseline_status=None,
+    latest_status=None
+)
+)]
+pub fn model_bias_runtime_check(
+    py: Python<'_>,
+    baseline: HashMap<String, f32>,
+    latest: HashMap<String, f32>,
+    threshold: f32,
+    baseline_status: Option<HashMap<String, HashMap<String, String>>>,
+    latest_status: Option<HashMap<String, HashMap<String, String>>>,
+) -> PyResult<String> {
+    let baseline = canonical_metrics(py, baseline)?;
+    let latest = canonical_metrics(py, latest)?;
+    let baseline_status = baseline_status.map(metric_statuses).transpose()?;
+    let latest_status = latest_status.map(metric_statuses).transpose()?;
+    let statuses = CheckStatuses {
+        baseline: baseline_status.as_ref(),
+        latest: latest_status.as_ref(),
+    };
+    let failure_report: HashMap<String, String> =
+        model_bias_registry().runtime_check(&baseline, &latest, threshold, None, statuses)?;
+
+    process_failure_report(failure_report)
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    feature_array,
+    ground_truth_array,
+    prediction_array,
+    feature_label_or_threshold,
+    ground_truth_label_or_threshold,
+    prediction_label_or_threshold,
+    missing_policy="raise",
+    sample_weight=None,
+    confidence_level=None,
+    resamples=1000,
+    seed=0,
+    significance=false,
+    permutations=1000,
+    min_facet_size=30.0,
+    min_cell_count=5.0,
+    group_array=None)
+)]
+#[allow(clippy::too_many_arguments)]
+pub fn model_bias_analyzer<'py>(
+    py: Python<'_>,
+    feature_array: Column<'_>,
+    ground_truth_array: Column<'_>,
+    prediction_array: Column<'_>,
+    feature_label_or_threshold: Bound<'py, PyAny>, //fix
+    ground_truth_label_or_threshold: Bound<'py, PyAny>, //fix
+    prediction_label_or_threshold: Bound<'py, PyAny>, // fix
+    missing_policy: &str,
+    sample_weight: Option<Column<'_>>,
+    confidence_level: Option<f64>,
+    resamples: usize,
+    seed: u64,
+    significance: bool,
+    permutations: usize,
+    min_facet_size: f64,
+    min_cell_count: f64,
+    group_array: Option<Column<'_>>,
+) -> PyResult<PyModelBiasReport> {
+    let missing_policy = MissingPolicy::try_from(missing_policy)?;
+    let sample_weight = sample_weights(sample_weight.as_ref())?;
+    let labeled_predictions: Vec<i16> = apply_label(
+        &prediction_array,
+        prediction_label_or_threshold,
+        "prediction",
+    )?;
+    let labeled_ground_truth: Vec<i16> = apply_label(
+        &ground_truth_array,
+        ground_truth_label_or_threshold,
+        "ground_truth",
+    )?;
+    let labeled_features: Vec<i16> =
+        apply_label(&feature_array, feature_label_or_threshold, "feature")?;
+    let conditional = report_disparity(
+        py,
+        group_array.as_ref(),
+        &labeled_features,
+        ("prediction", &labeled_predictions),
+        missing_policy,
+        &sample_weight,
+    )?;
+    // labels are owned rust data from here on, other python threads can run
+    let registry = model_bias_registry();
+    let mut report = py.allow_threads(|| {
+        labeled_model_bias(
+            &registry,
+            missing_policy,
+            labeled_features,
+            labeled_predictions,
+            labeled_ground_truth,
+            sample_weight,
+            bootstrap_config(confidence_level, resamples, seed),
+            significance.then_some(SignificanceConfig { permutations, seed }),
+            Some(SupportThresholds {
+                min_facet_size,
+                min_cell_count,
+            }),
+        )
+    })?;
+    report.conditional_disparity = conditional;
+    Ok(report.into())
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    feature_array,
+    ground_truth_array,
+    feature_label_or_threshold,
+    ground_truth_label_or_threshold,
+    missing_policy="raise",
+    sample_weight=None,
+    confidence_level=None,
+    resamples=1000,
+    seed=0,
+    significance=false,
+    permutations=1000,
+    min_facet_size=30.0,
+    min_cell_count=5.0,
+    group_array=None)
+)]
+#[allow(clippy::too_many_arguments)]
+fn data_bias_analyzer<'py>(
+    py: Python<'_>,
+    feature_array: Column<'_>,
+    ground_truth_array: Column<'_>,
+    feature_label_or_threshold: Bound<'py, PyAny>, //fix
+    ground_truth_label_or_threshold: Bound<'py, PyAny>, //fix
+    missing_policy: &str,
+    sample_weight: Option<Column<'_>>,
+    confidence_level: Option<f64>,
+    resamples: usize,
+    seed: u64,
+    significance: bool,
+    permutations: usize,
+    min_facet_size: f64,
+    min_cell_count: f64,
+    group_array: Option<Column<'_>>,
+) -> PyResult<PyDataBiasReport> {
+    let missing_policy = MissingPolicy::try_from(missing_policy)?;
+    let sample_weight = sample_weights(sample_weight.as_ref())?;
+    let labeled_ground_truth = apply_label(
+        &ground_truth_array,
+        ground_truth_label_or_threshold,
+        "ground_truth",
+    )?;
+
+    let labeled_feature = apply_label(&feature_array, feature_label_or_threshold, "feature")?;
+    let conditional = report_disparity(
+        py,
+        group_array.as_ref(),
+        &labeled_feature,
+        ("ground_truth", &labeled_ground_truth),
+        missing_policy,
+        &sample_weight,
+    )?;
+
+    let registry = data_bias_registry();
+    let mut report = py.allow_threads(|| {
+        labeled_data_bias(
+            &registry,
+            missing_policy,
+            labeled_feature,
+            labeled_ground_truth,
+            sample_weight,
+            bootstrap_config(confidence_level, resamples, seed),
+            significance.then_some(SignificanceConfig { permutations, seed }),
+            Some(SupportThresholds {
+                min_facet_size,
+                min_cell_count,
+            }),
+        )
+    })?;
+    report.conditional_disparity = conditional;
+    Ok(report.into())
+}
+
+/// Bootstrap settings, `None` when no confidence level was asked for.
+fn bootstrap_config(
+    confidence_level: Option<f64>,
+    resamples: usize,
+    seed: u64,
+) -> Option<BootstrapConfig> {
+    confidence_level.map(|confidence_level| BootstrapConfig {
+        resamples,
+        confidence_level,
+        seed,
+    })
+}
+
+fn group_comparison(
+    comparison: &str,
+    reference_group: Option<Bound<'_, PyAny>>,
+) -> PyResult<GroupComparison> {
+    // integer groups are named by their value, so 3 and True match "3" and "1"
+    let reference = match reference_group {
+        Some(group) if group.is_instance_of::<PyInt>() => Some(group.extract::<i64>()?.to_string()),
+        Some(group) => Some(group.str()?.to_string()),
+        None => None,
+    };
+    Ok(GroupComparison::parse(comparison, reference)?)
+}
+
+fn to_json<T: serde::Serialize>(report: &T) -> PyResult<String> {
+    serde_json::to_string(report).map_err(|_| PySystemError::new_err("Internal error"))
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    feature_array,
+    ground_truth_array,
+    ground_truth_label_or_threshold,
+    comparison="one_vs_rest",
+    reference_group=None,
+    missing_policy="raise"
+)
+)]
+fn data_bias_multi_group_analyzer<'py>(
+    py: Python<'_>,
+    feature_array: Column<'_>,
+    ground_truth_array: Column<'_>,
+    ground_truth_label_or_threshold: Bound<'py, PyAny>,
+    comparison: &str,
+    reference_group: Option<Bound<'py, PyAny>>,
+    missing_policy: &str,
+) -> PyResult<String> {
+    let missing_policy = MissingPolicy::try_from(missing_policy)?;
+    let comparison = group_comparison(comparison, reference_group)?;
+    let mut labeled_ground_truth = apply_label(
+        &ground_truth_array,
+        ground_truth_label_or_threshold,
+        "ground_truth",
+    )?;
+    let (_, kept) = apply_missing_policy_rows(
+        missing_policy,
+        None,
+        vec![("ground_truth", &mut labeled_ground_truth)],
+        None,
+    )?;
+    let mut features = group_values(&feature_array, "feature")?;
+    features.retain(&kept);
+    let registry = data_bias_registry();
+    let report = py.allow_threads(|| {
+        let grouped = match &features {
+            GroupValues::Text(values) => group_data_bias(values, &labeled_ground_truth),
+            GroupValues::Integer(values) => group_data_bias(values, &labeled_ground_truth),
+        }?;
+        multi_group_data_bias(&grouped, &comparison, &registry)
+    })?;
+    to_json(&report)
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    feature_array,
+    ground_truth_array,
+    prediction_array,
+    ground_truth_label_or_threshold,
+    prediction_label_or_threshold,
+    comparison="one_vs_rest",
+    reference_group=None,
+    missing_policy="raise"
+)
+)]
+#[allow(clippy::too_many_arguments)]
+fn model_bias_multi_group_analyzer<'py>(
+    py: Python<'_>,
+    feature_array: Column<'_>,
+    ground_truth_array: Column<'_>,
+    prediction_array: Column<'_>,
+    ground_truth_label_or_threshold: Bound<'py, PyAny>,
+    prediction_label_or_threshold: Bound<'py, PyAny>,
+    comparison: &str,
+    reference_group: Option<Bound<'py, PyAny>>,
+    missing_policy: &str,
+) -> PyResult<String> {
+    let missing_policy = MissingPolicy::try_from(missing_policy)?;
+    let comparison = group_comparison(comparison, reference_group)?;
+    let mut labeled_predictions = apply_label(
+        &prediction_array,
+        prediction_label_or_threshold,
+        "prediction",
+    )?;
+    let mut labeled_ground_truth = apply_label(
+        &ground_truth_array,
+        ground_truth_label_or_threshold,
+        "ground_truth",
+    )?;
+    let (_, kept) = apply_missing_policy_rows(
+        missing_policy,
+        None,
+        vec![
+            ("prediction", &mut labeled_predictions),
+            ("ground_truth", &mut labeled_ground_truth),
+        ],
+        None,
+    )?;
+    let mut features = group_values(&feature_array, "feature")?;
+    features.retain(&kept);
+    let registry = model_bias_registry();
+    let report = py.allow_threads(|| {
+        let grouped = match &features {
+            GroupValues::Text(values) => {
+                group_model_bias(values, &labeled_predictions, &labeled_ground_truth)
+            }
+            GroupValues::Integer(values) => {
+                group_model_bias(values, &labeled_predictions, &labeled_ground_truth)
+            }
+        }?;
+        multi_group_model_bias(&grouped, &comparison, &registry)
+    })?;
+    to_json(&report)
+}
+
+/// Conditional demographic disparity of the labeled outcome across the strata
+/// held by `group_array`, which must be present on every row.
+fn conditional_disparity(
+    py: Python<'_>,
+    group_array: &Column<'_>,
+    mut labeled_feature: Vec<i16>,
+    (outcome_name, mut labeled_outcome): (&str, Vec<i16>),
+    missing_policy: MissingPolicy,
+) -> PyResult<ConditionalDisparity> {
+    let (_, kept) = apply_missing_policy_rows(
+        missing_policy,
+        Some(("feature", &mut labeled_feature)),
+        vec![(outcome_name, &mut labeled_outcome)],
+        None,
+    )?;
+    let mut strata = group_values(group_array, "group_variable")?;
+    strata.retain(&kept);
+    let report = py.allow_threads(|| {
+        let outcome = (outcome_name, labeled_outcome.as_slice());
+        match &strata {
+            GroupValues::Text(values) => {
+                conditional_demographic_disparity(values, &labeled_feature, outcome)
+            }
+            GroupValues::Integer(values) => {
+                conditional_demographic_disparity(values, &labeled_feature, outcome)
+            }
+        }
+    })?;
+    Ok(report)
+}
+
+/// The conditional disparity of a report, when a group variable was passed.
+/// The strata are counted per row, so sample weights are refused.
+fn report_disparity(
+    py: Python<'_>,
+    group_array: Option<&Column<'_>>,
+    labeled_feature: &[i16],
+    (outcome_name, labeled_outcome): (&str, &[i16]),
+    missing_policy: MissingPolicy,
+    sample_weight: &Option<Vec<f64>>,
+) -> PyResult<Option<ConditionalDisparity>> {
+    let Some(group_array) = group_array else {
+        return Ok(None);
+    };
+    if sample_weight.is_some() {
+        return Err(FairMlError::InvalidSampleWeight {
+            reason: "the conditional demographic disparity needs unweighted counts".into(),
+        }
+        .into());
+    }
+    conditional_disparity(
+        py,
+        group_array,
+        labeled_feature.to_vec(),
+        (outcome_name, labeled_outcome.to_vec()),
+        missing_policy,
+    )
+    .map(Some)
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    feature_array,
+    ground_truth_array,
+    group_array,
+    feature_label_or_threshold,
+    ground_truth_label_or_threshold,
+    missing_policy="raise"
+)
+)]
+fn data_bias_conditional_disparity<'py>(
+    py: Python<'_>,
+    feature_array: Column<'_>,
+    ground_truth_array: Column<'_>,
+    group_array: Column<'_>,
+    feature_label_or_threshold: Bound<'py, PyAny>,
+    ground_truth_label_or_threshold: Bound<'py, PyAny>,
+    missing_policy: &str,
+) -> PyResult<String> {
+    let labeled_ground_truth = apply_label(
+        &ground_truth_array,
+        ground_truth_label_or_threshold,
+        "ground_truth",
+    )?;
+    let labeled_feature = apply_label(&feature_array, feature_label_or_threshold, "feature")?;
+    conditional_disparity(
+        py,
+        &group_array,
+        labeled_feature,
+        ("ground_truth", labeled_ground_truth),
+        MissingPolicy::try_from(missing_policy)?,
+    )
+    .and_then(|report| to_json(&report))
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    feature_array,
+    prediction_array,
+    group_array,
+    feature_label_or_threshold,
+    prediction_label_or_threshold,
+    missing_policy="raise"
+)
+)]
+fn model_bias_conditional_disparity<'py>(
+    py: Python<'_>,
+    feature_array: Column<'_>,
+    prediction_array: Column<'_>,
+    group_array: Column<'_>,
+    feature_label_or_threshold: Bound<'py, PyAny>,
+    prediction_label_or_threshold: Bound<'py, PyAny>,
+    missing_policy: &str,
+) -> PyResult<String> {
+    let labeled_predictions = apply_label(
+        &prediction_array,
+        prediction_label_or_threshold,
+        "prediction",
+    )?;
+    let labeled_feature = apply_label(&feature_array, feature_label_or_threshold, "feature")?;
+    conditional_disparity(
+        py,
+        &group_array,
+        labeled_feature,
+        ("prediction", labeled_predictions),
+        MissingPolicy::try_from(missing_policy)?,
+    )
+    .and_then(|report| to_json(&report))
+}
+
+/// KL, JS, Lp, total variation and Kolmogorov-Smirnov over every category
+/// of the ground truth, which is read as is rather than labeled.
+#[pyfunction]
+#[pyo3(signature = (
+    feature_array,
+    ground_truth_array,
+    feature_label_or_threshold,
+    lp_order=2.0,
+    epsilon=0.0,
+    missing_policy="raise"
+)
+)]
+fn data_bias_categorical_divergences<'py>(
+    py: Python<'_>,
+    feature_array: Column<'_>,
+    ground_truth_array: Column<'_>,
+    feature_label_or_threshold: Bound<'py, PyAny>,
+    lp_order: f64,
+    epsilon: f64,
+    missing_policy: &str,
+) -> PyResult<String> {
+    let missing_policy = MissingPolicy::try_from(missing_policy)?;
+    let mut labeled_feature = apply_label(&feature_array, feature_label_or_threshold, "feature")?;
+    let (_, kept) = apply_missing_policy_rows(
+        missing_policy,
+        Some(("feature", &mut labeled_feature)),
+        vec![],
+        None,
+    )?;
+    let mut ground_truth = group_values(&ground_truth_array, "ground_truth")?;
+    ground_truth.retain(&kept);
+    let options = DivergenceOptions { lp_order, epsilon };
+    let report = py.allow_threads(|| {
+        let labels = match &ground_truth {
+            GroupValues::Text(values) => CategoricalLabels::count(&labeled_feature, values),
+            GroupValues::Integer(values) => CategoricalLabels::count(&labeled_feature, values),
+        }?;
+        labels.divergences(options)
+    })?;
+    to_json(&report)
+}
+
+/// Compares the raw ground truth values of both facets, for a continuous
+/// outcome that is not thresholded.
+#[pyfunction]
+#[pyo3(signature = (
+    feature_array,
+    ground_truth_array,
+    feature_label_or_threshold,
+    missing_policy="raise"
+)
+)]
+fn data_bias_continuous_analyzer<'py>(
+    py: Python<'_>,
+    feature_array: Column<'_>,
+    ground_truth_array: Column<'_>,
+    feature_label_or_threshold: Bound<'py, PyAny>,
+    missing_policy: &str,
+) -> PyResult<String> {
+    let missing_policy = MissingPolicy::try_from(missing_policy)?;
+    let labeled_feature = apply_label(&feature_array, feature_label_or_threshold, "feature")?;
+    let outcomes = ground_truth_array.outcome_values("ground_truth")?;
+    let report = py.allow_threads(|| {
+        labeled_continuous_data_bias(missing_policy, labeled_feature, outcomes)
+    })?;
+    to_json(&report)
+}
+
+/// Labels every protected attribute with its own label or threshold, then
+/// applies the missing value policy to the attributes and the outcomes
+/// together. A row missing an attribute has no subgroup, so `OwnGroup` drops
+/// it like `Drop`.
+fn label_attributes<'py>(
+    features: Vec<(String, Column<'py>, Bound<'py, PyAny>)>,
+    outcomes: Vec<(&str, &mut Vec<i16>)>,
+    missing_policy: &str,
+) -> PyResult<Vec<(String, Vec<i16>)>> {
+    let missing_policy = MissingPolicy::try_from(missing_policy)?;
+    let mut attributes = features
+        .into_iter()
+        .map(|(name, array, label)| {
+            let labeled = apply_label(&array, label, &name)?;
+            Ok((name, labeled))
+        })
+        .collect::<PyResult<Vec<(String, Vec<i16>)>>>()?;
+    let columns = attributes
+        .iter_mut()
+        .map(|(name, labels)| (name.as_str(), labels))
+        .chain(outcomes)
+        .collect();
+    apply_missing_policy_rows(missing_policy, None, columns, None)?;
+    Ok(attributes)
+}
+
+fn borrow_attributes(attributes: &[(String, Vec<i16>)]) -> Vec<(&str, &[i16])> {
+    attributes
+        .iter()
+        .map(|(name, labels)| (name.as_str(), labels.as_slice()))
+        .collect()
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    features,
+    ground_truth_array,
+    ground_truth_label_or_threshold,
+    min_subgroup_size=30,
+    missing_policy="raise"
+)
+)]
+fn data_bias_intersectional_analyzer<'py>(
+    py: Python<'py>,
+    features: Vec<(String, Column<'py>, Bound<'py, PyAny>)>,
+    ground_truth_array: Column<'_>,
+    ground_truth_label_or_threshold: Bound<'py, PyAny>,
+    min_subgroup_size: u64,
+    missing_policy: &str,
+) -> PyResult<String> {
+    let mut labeled_ground_truth = apply_label(
+        &ground_truth_array,
+        ground_truth_label_or_threshold,
+        "ground_truth",
+    )?;
+    let attributes = label_attributes(
+        features,
+        vec![("ground_truth", &mut labeled_ground_truth)],
+        missing_policy,
+    )?;
+    let registry = data_bias_registry();
+    let report = py.allow_threads(|| {
+        intersectional_data_bias(
+            &borrow_attributes(&attributes),
+            &labeled_ground_truth,
+            min_subgroup_size,
+            &registry,
+        )
+    })?;
+    to_json(&report)
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    features,
+    ground_truth_array,
+    prediction_array,
+    ground_truth_label_or_threshold,
+    prediction_label_or_threshold,
+    min_subgroup_size=30,
+    missing_policy="raise"
+)
+)]
+#[allow(clippy::too_many_arguments)]
+fn model_bias_intersectional_analyzer<'py>(
+    py: Python<'py>,
+    features: Vec<(String, Column<'py>, Bound<'py, PyAny>)>,
+    ground_truth_array: Column<'_>,
+    prediction_array: Column<'_>,
+    ground_truth_label_or_threshold: Bound<'py, PyAny>,
+    prediction_label_or_threshold: Bound<'py, PyAny>,
+    min_subgroup_size: u64,
+    missing_policy: &str,
+) -> PyResult<String> {
+    let mut labeled_predictions = apply_label(
+        &prediction_array,
+        prediction_label_or_threshold,
+        "prediction",
+    )?;
+    let mut labeled_ground_truth = apply_label(
+        &ground_truth_array,
+        ground_truth_label_or_threshold,
+        "ground_truth",
+    )?;
+    let attributes = label_attributes(
+        features,
+        vec![
+            ("prediction", &mut labeled_predictions),
+            ("ground_truth", &mut labeled_ground_truth),
+        ],
+        missing_policy,
+    )?;
+    let registry = model_bias_registry();
+    let report = py.allow_threads(|| {
+        intersectional_model_bias(
+            &borrow_attributes(&attributes),
+            &labeled_predictions,
+            &labeled_ground_truth,
+            min_subgroup_size,
+            &registry,
+        )
+    })?;
+    to_json(&report)
+}
+
+/// Reads and checks the sample weights of a performance analysis, then applies
+/// the missing value policy to the values and weights together.
+fn perf_weights(
+    sample_weight: Option<Column<'_>>,
+    missing_policy: MissingPolicy,
+    y_true: &mut Vec<f32>,
+    y_pred: &mut Vec<f32>,
+) -> Result<Option<Vec<f32>>, FairMlError> {
+    let mut weights = sample_weights(sample_weight.as_ref())?;
+    if let Some(weights) = &weights {
+        check_sample_weights("y_true", y_true, weights)?;
+    }
+    apply_missing_policy_values(
+        missing_policy,
+        vec![("y_true", y_true), ("y_pred", y_pred)],
+        weights.as_mut(),
+    )?;
+    Ok(weights.map(|weights| weights.into_iter().map(|w| w as f32).collect()))
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    y_pred,
+    y_true,
+    missing_policy="raise",
+    sample_weight=None,
+    confidence_level=None,
+    resamples=1000,
+    seed=0)
+)]
+#[allow(clippy::too_many_arguments)]
+fn model_performance_regression(
+    py: Python<'_>,
+    y_pred: Column<'_>,
+    y_true: Column<'_>,
+    missing_policy: &str,
+    sample_weight: Option<Column<'_>>,
+    confidence_level: Option<f64>,
+    resamples: usize,
+    seed: u64,
+) -> PyResult<PyLinearRegressionReport> {
+    let (mut y_true, mut y_pred) = PerfEntry::validate_and_cast_regression(&y_true, &y_pred)?;
+    let weights = perf_weights(
+        sample_weight,
+        MissingPolicy::try_from(missing_policy)?,
+        &mut y_true,
+        &mut y_pred,
+    )?;
+    let confidence = bootstrap_config(confidence_level, resamples, seed);
+    let report = py.allow_threads(|| {
+        let intervals = confidence
+            .map(|config| bootstrap_linear_regression(&y_true, &y_pred, weights.as_deref(), config))
+            .transpose()?;
+        let mut report =
+            LinearRegressionReport::from(LinearRegressionPerf::from_vecs(y_true, y_pred, weights)?);
+        report.confidence_intervals = intervals;
+        Ok::<_, FairMlError>(report)
+    });
+    Ok(report?.into())
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    y_pred,
+    y_true,
+    missing_policy="raise",
+    sample_weight=None,
+    confidence_level=None,
+    resamples=1000,
+    seed=0)
+)]
+#[allow(clippy::too_many_arguments)]
+fn model_performance_classification(
+    py: Python<'_>,
+    y_pred: Column<'_>,
+    y_true: Column<'_>,
+    missing_policy: &str,
+    sample_weight: Option<Column<'_>>,
+    confidence_level: Option<f64>,
+    resamples: usize,
+    seed: u64,
+) -> PyResult<PyBinaryClassificationReport> {
+    let (mut y_true, mut y_pred) = PerfEntry::validate_and_cast_classification(&y_true, &y_pred)?;
+    let weights = perf_weights(
+        sample_weight,
+        MissingPolicy::try_from(missing_policy)?,
+        &mut y_true,
+        &mut y_pred,
+    )?;
+    let confidence = bootstrap_config(confidence_level, resamples, seed);
+    let report = py.allow_threads(|| {
+        let intervals = confidence
+            .map(|config| bootstrap_classification(&y_true, &y_pred, weights.as_deref(), config))
+            .transpose()?;
+        let mut report = BinaryClassificationReport::from(ClassificationPerf::from_vecs(
+            y_true, y_pred, weights,
+        )?);
+        report.confidence_intervals = intervals;
+        Ok::<_, FairMlError>(report)
+    });
+    Ok(report?.into())
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    y_pred,
+    y_true,
+    decision_threshold=0.5,
+    missing_policy="raise",
+    sample_weight=None,
+    confidence_level=None,
+    resamples=1000,
+    seed=0
+)
+)]
+#[allow(clippy::too_many_arguments)]
+fn model_performance_logisitic_regression(
+    py: Python<'_>,
+    y_pred: Column<'_>,
+    y_true: Column<'_>,
+    decision_threshold: f32,
+    missing_policy: &str,
+    sample_weight: Option<Column<'_>>,
+    confidence_level: Option<f64>,
+    resamples: usize,
+    seed: u64,
+) -> PyResult<PyLogisticRegressionReport> {
+    let (mut y_true, mut y_proba) = PerfEntry::validate_and_cast_regression(&y_true, &y_pred)?;
+    let weights = perf_weights(
+        sample_weight,
+        MissingPolicy::try_from(missing_policy)?,
+        &mut y_true,
+        &mut y_proba,
+    )?;
+    let confidence = bootstrap_config(confidence_level, resamples, seed);
+    let report = py.allow_threads(|| {
+        let intervals = confidence
+            .map(|config| {
+                bootstrap_logistic_regression(
+                    &y_true,
+                    &y_proba,
+                    decision_threshold,
+                    weights.as_deref(),
+                    config,
+                )
+            })
+            .transpose()?;
+        let mut report = LogisticRegressionReport::from(LogisticRegressionPerf::from_vecs(
+            y_true,
+            y_proba,
+            decision_threshold,
+            weights,
+        )?);
+        report.confidence_intervals = intervals;
+        Ok::<_, FairMlError>(report)
+    });
+    Ok(report?.into())
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    model_type,
+    baseline,
+    latest,
+    evaluation_metrics,
+    threshold=0.10
+)
+)]
+fn model_performance_runtime_entry_partial(
+    model_type: String,
+    baseline: HashMap<String, f32>,
+    latest: HashMap<String, f32>,
+    evaluation_metrics: Vec<String>,
+    threshold: f32,
+) -> PyResult<String> {
+    let model_type: ModelType = ModelType::try_from(model_type.as_str())?;
+
+    match model_type {
+        ModelType::LinearRegression => {
+            let metrics_to_eval: Vec<LinearRegressionEvaluationMetrics> =
+                map_string_to_linear_metric(evaluation_metrics)?;
+            regression_performance_runtime(baseline, latest, &metrics_to_eval, threshold)
+        }
+        ModelType::LogisticRegression => {
+            let metrics_to_eval: Vec<ClassificationEvaluationMetrics> =
+                map_string_to_bin_metric(evaluation_metrics)?;
+            logistic_performance_runtime(baseline, latest, &metrics_to_eval, threshold)
+        }
+        ModelType::BinaryClassification => {
+            let metrics_to_eval: Vec<ClassificationEvaluationMetrics> =
+                map_string_to_bin_metric(evaluation_metrics)?;
+            classification_performance_runtime(baseline, latest, &metrics_to_eval, threshold)
+        }
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    model_type,
+    baseline,
+    latest,
+    threshold=0.10
+)
+)]
+fn model_performance_runtime_entry_full(
+    model_type: String,
+    baseline: HashMap<String, f32>,
+    latest: HashMap<String, f32>,
+    threshold: f32,
+) -> PyResult<String> {
+    let model_type: ModelType = ModelType::try_from(model_type.as_str())?;
+
+    match model_type {
+        ModelType::LinearRegression => {
+            regression_performance_runtime(baseline, latest, &FULL_REGRESSION_METRICS, threshold)
+        }
+        ModelType::LogisticRegression => logistic_performance_runtime(
+            baseline,
+            latest,
+            &FULL_LOGISTIC_REGRESSION_METRICS,
+            threshold,
+        ),
+        ModelType::BinaryClassification => classification_performance_runtime(
+            baseline,
+            latest,
+            &FULL_BINARY_CLASSIFICATION_METRICS,
+            threshold,
+        ),
+    }
+}
+
+fn classification_performance_runtime(
+    baseline: HashMap<String, f32>,
+    latest: HashMap<String, f32>,
+    metrics: &[ClassificationEvaluationMetrics],
+    threshold: f32,
+) -> PyResult<String> {
+    let baseline = BinaryClassificationReport::try_from(baseline)?;
+    let latest = BinaryClassificationReport::try_from(latest)?;
+    let res = latest.compare_to_baseline(metrics, &baseline, threshold)?;
+
+    process_failure_report(res)
+}
+
+fn logistic_performance_runtime(
+    baseline: HashMap<String, f32>,
+    latest: HashMap<String, f32>,
+    metrics: &[ClassificationEvaluationMetrics],
+    threshold: f32,
+) -> PyResult<String> {
+    let baseline = LogisticRegressionReport::try_from(baseline)?;
+    let latest = LogisticRegressionReport::try_from(latest)?;
+    let res = latest.compare_to_baseline(metrics, &baseline, threshold);
+    process_failure_report(res)
+}
+
+fn regression_performance_runtime(
+    baseline: HashMap<String, f32>,
+    latest: HashMap<String, f32>,
+    evaluation_metrics: &[LinearRegressionEvaluationMetrics],
+    threshold: f32,
+) -> PyResult<String> {
+    let baseline: LinearRegressionReport = LinearRegressionReport::try_from(baseline)?;
+    let latest: LinearRegressionReport = LinearRegressionReport::try_from(latest)?;
+
+    let results = latest.compare_to_baseline(evaluation_metrics, &baseline, threshold);
+    process_failure_report(results)
+}
+
+fn process_failure_report(comp_results: HashMap<String, String>) -> Result<String, PyErr> {
+    if !comp_results.is_empty() {
+        match serde_json::to_string(&FailureRuntimeReturn {
+            passed: false,
+            fail_report: Some(comp_results),
+        }) {
+            Ok(val) => Ok(val),
+            Err(_) => Err(PySystemError::new_err("Internal error")),
+        }
+    } else {
+        match serde_json::to_string(&PassedRuntimeReturn { passed: true }) {
+            Ok(val) => Ok(val),
+            Err(_) => Err(PySystemError::new_err("Internal error")),
+        }
+    }
+}
+
+/// A Python module implemented in Rust.
+#[pymodule]
+#[pyo3(name = "_fair_perf_ml")]
+fn fair_perf_ml(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    exceptions::register(m)?;
+    reports::register(m)?;
+    m.add_class::<labels::PyLabelSpec>()?;
+    m.add_function(wrap_pyfunction!(labels::resolve_label, m)?)?;
+    m.add_class::<accumulators::PyDataBiasAccumulator>()?;
+    m.add_class::<accumulators::PyModelBiasAccumulator>()?;
+    m.add_class::<accumulators::PyBinaryClassificationAccumulator>()?;
+    m.add_class::<accumulators::PyLogisticRegressionAccumulator>()?;
+    m.add_class::<accumulators::PyLinearRegressionAccumulator>()?;
+    m.add_function(wrap_pyfunction!(metrics::register_data_bias_metric, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::register_model_bias_metric, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::data_bias_metric_names, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::model_bias_metric_names, m)?)?;
+    m.add_function(wrap_pyfunction!(frame::dataframe_analyzer, m)?)?;
+    m.add_function(wrap_pyfunction!(model_bias_analyzer, m)?)?;
+    m.add_function(wrap_pyfunction!(data_bias_analyzer, m)?)?;
+    m.add_function(wrap_pyfunction!(data_bias_multi_group_analyzer, m)?)?;
+    m.add_function(wrap_pyfunction!(model_bias_multi_group_analyzer, m)?)?;
+    m.add_function(wrap_pyfunction!(data_bias_intersectional_analyzer, m)?)?;
+    m.add_function(wrap_pyfunction!(data_bias_conditional_disparity, m)?)?;
+    m.add_function(wrap_pyfunction!(model_bias_conditional_disparity, m)?)?;
+    m.add_function(wrap_pyfunction!(data_bias_categorical_divergences, m)?)?;
+    m.add_function(wrap_pyfunction!(data_bias_continuous_analyzer, m)?)?;
+    m.add_function(wrap_pyfunction!(model_bias_intersectional_analyzer, m)?)?;
+    m.add_function(wrap_pyfunction!(data_bias_runtime_check, m)?)?;
+    m.add_function(wrap_pyfunction!(data_bias_partial_check, m)?)?;
+    m.add_function(wrap_pyfunction!(model_bias_runtime_check, m)?)?;
+    m.add_function(wrap_pyfunction!(model_bias_partial_check, m)?)?;
+    m.add_function(wrap_pyfunction!(model_performance_regression, m)?)?;
+    m.add_function(wrap_pyfunction!(model_performance_classification, m)?)?;
+    m.add_function(wrap_pyfunction!(model_performance_logisitic_regression, m)?)?;
+    m.add_function(wrap_pyfunction!(model_performance_runtime_entry_full, m)?)?;
+    m.add_function(wrap_pyfunction!(
+        model_performance_runtime_entry_partial,
+        m
+    )?)?;
+    Ok(())
+}
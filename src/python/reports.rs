@@ -0,0 +1,636 @@
+use crate::bootstrap::ConfidenceIntervals;
+use crate::data_bias::DataBiasReport;
+use crate::data_handler::{EffectiveSampleSize, MissingCounts};
+use crate::errors::FairMlError;
+use crate::frame::{FacetReport, FrameReport, PerformanceData};
+use crate::model_bias::ModelBiasReport;
+use crate::model_perf::{
+    BinaryClassificationReport, LinearRegressionReport, LogisticRegressionReport,
+};
+use crate::multi_group::ConditionalDisparity;
+use crate::runtime::{canonical_name, canonicalize_keys, DEPRECATED_NAMES};
+use crate::significance::Significance;
+use crate::status::{MetricStatus, MetricStatuses};
+use pyo3::exceptions::{PyAttributeError, PyDeprecationWarning, PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyIterator, PyList, PyString, PyTuple, PyType};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+fn warn_deprecated(py: Python<'_>, deprecated: &str, current: &str) -> PyResult<()> {
+    PyErr::warn_bound(
+        py,
+        &py.get_type_bound::<PyDeprecationWarning>(),
+        &format!("metric key {deprecated} is deprecated, use {current}"),
+        1,
+    )
+}
+
+/// Renames deprecated keys in a baseline or report passed in from python,
+/// warning once per key.
+pub(crate) fn canonical_metrics(
+    py: Python<'_>,
+    mut metrics: HashMap<String, f32>,
+) -> PyResult<HashMap<String, f32>> {
+    for deprecated in canonicalize_keys(&mut metrics) {
+        if let Some(current) = canonical_name(deprecated) {
+            warn_deprecated(py, deprecated, current)?;
+        }
+    }
+    Ok(metrics)
+}
+
+/// Same as `canonical_metrics` for a list of metric names.
+pub(crate) fn canonical_metric_names(py: Python<'_>, names: Vec<String>) -> PyResult<Vec<String>> {
+    names
+        .into_iter()
+        .map(|name| match canonical_name(&name) {
+            Some(current) => {
+                warn_deprecated(py, &name, current)?;
+                Ok(current.to_string())
+            }
+            None => Ok(name),
+        })
+        .collect()
+}
+
+/// Json value of a dict passed in from python. A float that is not finite
+/// becomes null, the way serde_json writes it.
+fn json_value(value: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if value.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(flag) = value.downcast::<PyBool>() {
+        Ok(Value::Bool(flag.is_true()))
+    } else if let Ok(integer) = value.downcast::<PyInt>() {
+        Ok(match integer.extract::<i64>() {
+            Ok(integer) => Value::from(integer),
+            Err(_) => Value::from(integer.extract::<u64>()?),
+        })
+    } else if let Ok(float) = value.downcast::<PyFloat>() {
+        Ok(Number::from_f64(float.value()).map_or(Value::Null, Value::Number))
+    } else if let Ok(text) = value.downcast::<PyString>() {
+        Ok(Value::String(text.to_str()?.to_string()))
+    } else if let Ok(dict) = value.downcast::<PyDict>() {
+        dict.iter()
+            .map(|(key, value)| Ok((key.extract::<String>()?, json_value(&value)?)))
+            .collect::<PyResult<Map<String, Value>>>()
+            .map(Value::Object)
+    } else if value.is_instance_of::<PyList>() || value.is_instance_of::<PyTuple>() {
+        value
+            .iter()?
+            .map(|item| json_value(&item?))
+            .collect::<PyResult<Vec<Value>>>()
+            .map(Value::Array)
+    } else {
+        // numpy scalars and the like
+        Ok(Number::from_f64(value.extract::<f64>()?).map_or(Value::Null, Value::Number))
+    }
+}
+
+/// Reads a report, optional fields included, from its json value. Deprecated
+/// metric keys are renamed with a warning, and a missing built in metric
+/// raises like a missing baseline key.
+fn report_from_value<T: DeserializeOwned>(
+    py: Python<'_>,
+    value: Value,
+    names: &[&str],
+) -> PyResult<T> {
+    let Value::Object(mut object) = value else {
+        return Err(PyValueError::new_err("a report is a json object"));
+    };
+    for (deprecated, current) in DEPRECATED_NAMES {
+        if let Some(value) = object.remove(deprecated) {
+            warn_deprecated(py, deprecated, current)?;
+            object.entry(current).or_insert(value);
+        }
+    }
+    if let Some(name) = names.iter().find(|name| !object.contains_key(**name)) {
+        return Err(FairMlError::missing_key(name).into());
+    }
+    serde_json::from_value(Value::Object(object))
+        .map_err(|err| PyValueError::new_err(err.to_string()))
+}
+
+/// Missing value counts as a dict keyed like the report json.
+pub(crate) fn missing_dict<'py>(
+    py: Python<'py>,
+    missing: &MissingCounts,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("facetA", missing.facet_a)?;
+    dict.set_item("facetD", missing.facet_d)?;
+    dict.set_item("feature", missing.feature)?;
+    Ok(dict)
+}
+
+/// Effective sample size per facet as a dict keyed like the report json.
+fn sample_size_dict<'py>(
+    py: Python<'py>,
+    size: &EffectiveSampleSize,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("facetA", size.facet_a)?;
+    dict.set_item("facetD", size.facet_d)?;
+    Ok(dict)
+}
+
+/// Bootstrap bounds per metric, each a dict with "lower", "upper",
+/// "droppedResamples" and the status keys of `status_dict`.
+fn intervals_dict<'py>(
+    py: Python<'py>,
+    intervals: &ConfidenceIntervals,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    for (metric, interval) in intervals {
+        let bounds = PyDict::new_bound(py);
+        bounds.set_item("lower", interval.lower)?;
+        bounds.set_item("upper", interval.upper)?;
+        bounds.set_item("droppedResamples", interval.dropped_resamples)?;
+        set_status(&bounds, &interval.status)?;
+        dict.set_item(metric, bounds)?;
+    }
+    Ok(dict)
+}
+
+/// P-values per metric, each a dict keyed like the report json.
+fn significance_dict<'py>(
+    py: Python<'py>,
+    significance: &Significance,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    for (metric, p_values) in significance {
+        let tests = PyDict::new_bound(py);
+        tests.set_item("twoProportionZ", p_values.two_proportion_z)?;
+        tests.set_item("chiSquare", p_values.chi_square)?;
+        tests.set_item("fisherExact", p_values.fisher_exact)?;
+        tests.set_item("permutation", p_values.permutation)?;
+        dict.set_item(metric, tests)?;
+    }
+    Ok(dict)
+}
+
+/// Status per metric, each a dict with "status" and, unless ok, "reason".
+fn status_dict<'py>(py: Python<'py>, statuses: &MetricStatuses) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    for (metric, status) in statuses {
+        let entry = PyDict::new_bound(py);
+        set_status(&entry, status)?;
+        dict.set_item(metric, entry)?;
+    }
+    Ok(dict)
+}
+
+/// Conditional demographic disparity as a dict keyed like the report json,
+/// "value" and one entry per stratum under "strata".
+fn disparity_dict<'py>(
+    py: Python<'py>,
+    disparity: &ConditionalDisparity,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("value", disparity.value)?;
+    let strata = PyList::empty_bound(py);
+    for stratum in &disparity.strata {
+        let entry = PyDict::new_bound(py);
+        entry.set_item("group", &stratum.group)?;
+        entry.set_item("size", stratum.size)?;
+        entry.set_item("disparity", stratum.disparity)?;
+        strata.append(entry)?;
+    }
+    dict.set_item("strata", strata)?;
+    Ok(dict)
+}
+
+/// Writes "status", and "reason" unless the status is ok, into `entry`.
+fn set_status(entry: &Bound<'_, PyDict>, status: &MetricStatus) -> PyResult<()> {
+    match status {
+        MetricStatus::Ok => entry.set_item("status", "ok"),
+        MetricStatus::Undefined(reason) => {
+            entry.set_item("status", "undefined")?;
+            entry.set_item("reason", reason)
+        }
+        MetricStatus::LowSupport(reason) => {
+            entry.set_item("status", "low-support")?;
+            entry.set_item("reason", reason)
+        }
+    }
+}
+
+/// Reads statuses back from the dicts `set_status` writes, e.g. the
+/// "status" entry of a report's `to_dict`.
+pub(crate) fn metric_statuses(
+    statuses: HashMap<String, HashMap<String, String>>,
+) -> PyResult<MetricStatuses> {
+    statuses
+        .into_iter()
+        .map(|(metric, mut entry)| {
+            let reason = entry.remove("reason").unwrap_or_default();
+            let status = match entry.get("status").map(String::as_str) {
+                Some("ok") => MetricStatus::Ok,
+                Some("undefined") => MetricStatus::Undefined(reason),
+                Some("low-support") => MetricStatus::LowSupport(reason),
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "{metric} has status {other:?}, expected ok, undefined or low-support"
+                    )))
+                }
+            };
+            Ok((metric, status))
+        })
+        .collect()
+}
+
+fn sample_size(_py: Python<'_>, size: &f64) -> PyResult<f64> {
+    Ok(*size)
+}
+
+// python view of a report, attributes are the rust field names (or the key of
+// a registered metric) and to_dict/to_json use the report keys
+macro_rules! py_report {
+    (
+        $py_report:ident,
+        $report:ty,
+        $name:literal
+        $(, $(#[$doc:meta])* $optional:ident => $convert:path)*
+    ) => {
+        #[pyclass(frozen, mapping, name = $name, module = "fair_perf_ml._fair_perf_ml")]
+        #[derive(PartialEq)]
+        pub struct $py_report {
+            inner: $report,
+        }
+
+        impl From<$report> for $py_report {
+            fn from(inner: $report) -> $py_report {
+                $py_report { inner }
+            }
+        }
+
+        #[pymethods]
+        impl $py_report {
+            $(
+                $(#[$doc])*
+                #[getter]
+                fn $optional(&self, py: Python<'_>) -> PyResult<Option<PyObject>> {
+                    self.inner
+                        .$optional
+                        .as_ref()
+                        .map(|value| $convert(py, value).map(|value| value.into_py(py)))
+                        .transpose()
+                }
+            )*
+
+            fn __getattr__(&self, name: &str) -> PyResult<f32> {
+                let registered = || {
+                    self.inner
+                        .entries()
+                        .into_iter()
+                        .find(|(k, _)| *k == name)
+                        .map(|(_, v)| v)
+                };
+                self.inner.field(name).or_else(registered).ok_or_else(|| {
+                    PyAttributeError::new_err(format!("{} has no metric {name}", $name))
+                })
+            }
+
+            fn __getitem__(&self, py: Python<'_>, key: &str) -> PyResult<f32> {
+                let current = match canonical_name(key) {
+                    Some(current) => {
+                        warn_deprecated(py, key, current)?;
+                        current
+                    }
+                    None => key,
+                };
+                self.inner
+                    .entries()
+                    .into_iter()
+                    .find(|(k, _)| *k == current)
+                    .map(|(_, v)| v)
+                    .ok_or_else(|| PyKeyError::new_err(key.to_string()))
+            }
+
+            fn keys(&self) -> Vec<String> {
+                self.inner
+                    .entries()
+                    .into_iter()
+                    .map(|(k, _)| k.to_string())
+                    .collect()
+            }
+
+            fn values(&self) -> Vec<f32> {
+                self.inner.entries().into_iter().map(|(_, v)| v).collect()
+            }
+
+            fn items(&self) -> Vec<(String, f32)> {
+                self.inner
+                    .entries()
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v))
+                    .collect()
+            }
+
+            #[pyo3(signature = (key, default=None))]
+            fn get(&self, py: Python<'_>, key: &str, default: Option<PyObject>) -> PyResult<PyObject> {
+                match self.__getitem__(py, key) {
+                    Ok(value) => Ok(value.into_py(py)),
+                    Err(err) if err.is_instance_of::<PyKeyError>(py) => Ok(default.into_py(py)),
+                    Err(err) => Err(err),
+                }
+            }
+
+            fn __len__(&self) -> usize {
+                self.inner.entries().len()
+            }
+
+            fn __contains__(&self, key: &str) -> bool {
+                let current = canonical_name(key).unwrap_or(key);
+                self.inner.entries().iter().any(|(k, _)| *k == current)
+            }
+
+            fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+                PyList::new_bound(py, self.keys()).as_any().iter()
+            }
+
+            /// Equal to a report holding the same metrics and optional fields,
+            /// or to a dict equal to its `to_dict`, so a report compares like
+            /// the metric dict earlier releases returned.
+            fn __eq__(&self, py: Python<'_>, other: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+                if let Ok(other) = other.downcast::<Self>() {
+                    return Ok((self.inner == other.get().inner).into_py(py));
+                }
+                if other.is_instance_of::<PyDict>() {
+                    return self.to_dict(py)?.eq(other).map(|equal| equal.into_py(py));
+                }
+                Ok(py.NotImplemented())
+            }
+
+            /// The report as a dict, in report order.
+            fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+                let dict = PyDict::new_bound(py);
+                for (key, value) in self.inner.entries() {
+                    dict.set_item(key, value)?;
+                }
+                Ok(dict)
+            }
+
+            fn to_json(&self) -> PyResult<String> {
+                serde_json::to_string(&self.inner)
+                    .map_err(|err| PyValueError::new_err(err.to_string()))
+            }
+
+            /// Builds a report from a saved dict, deprecated keys are accepted
+            /// with a warning. Besides the metrics the dict may hold the
+            /// optional fields under their json keys, e.g. "status".
+            #[classmethod]
+            fn from_dict(
+                _cls: &Bound<'_, PyType>,
+                py: Python<'_>,
+                data: &Bound<'_, PyDict>,
+            ) -> PyResult<Self> {
+                let value = json_value(data.as_any())?;
+                Ok(report_from_value::<$report>(py, value, <$report>::NAMES)?.into())
+            }
+
+            /// Inverse of `to_json`, optional fields included.
+            #[classmethod]
+            fn from_json(_cls: &Bound<'_, PyType>, py: Python<'_>, data: &str) -> PyResult<Self> {
+                let value: Value = serde_json::from_str(data)
+                    .map_err(|err| PyValueError::new_err(err.to_string()))?;
+                Ok(report_from_value::<$report>(py, value, <$report>::NAMES)?.into())
+            }
+
+            fn __repr__(&self) -> String {
+                let fields: Vec<String> = self
+                    .inner
+                    .entries()
+                    .into_iter()
+                    .map(|(k, v)| format!("{k}={v}"))
+                    .collect();
+                format!("{}({})", $name, fields.join(", "))
+            }
+        }
+    };
+}
+
+py_report!(
+    PyDataBiasReport,
+    DataBiasReport,
+    "DataBiasReport",
+    /// Rows with a missing value per facet, `None` when the report was not
+    /// built from raw data.
+    missing => missing_dict,
+    /// Kish effective sample size per facet, `None` when the report was not
+    /// built from raw data.
+    effective_sample_size => sample_size_dict,
+    /// Bootstrap bounds per metric, `None` unless a confidence level was
+    /// asked for.
+    confidence_intervals => intervals_dict,
+    /// P-values of the rate differences, `None` unless significance tests
+    /// were asked for.
+    significance => significance_dict,
+    /// Status of every metric, `None` when the report was not built from raw
+    /// data.
+    status => status_dict,
+    /// Conditional demographic disparity in labels (CDDL), `None` unless a
+    /// group variable was passed.
+    conditional_disparity => disparity_dict
+);
+py_report!(
+    PyModelBiasReport,
+    ModelBiasReport,
+    "ModelBiasReport",
+    /// Rows with a missing value per facet, `None` when the report was not
+    /// built from raw data.
+    missing => missing_dict,
+    /// Kish effective sample size per facet, `None` when the report was not
+    /// built from raw data.
+    effective_sample_size => sample_size_dict,
+    /// Bootstrap bounds per metric, `None` unless a confidence level was
+    /// asked for.
+    confidence_intervals => intervals_dict,
+    /// P-values of the rate differences, `None` unless significance tests
+    /// were asked for.
+    significance => significance_dict,
+    /// Status of every metric, `None` when the report was not built from raw
+    /// data.
+    status => status_dict,
+    /// Conditional demographic disparity in predicted labels (CDDPL), `None`
+    /// unless a group variable was passed.
+    conditional_disparity => disparity_dict
+);
+py_report!(
+    PyBinaryClassificationReport,
+    BinaryClassificationReport,
+    "BinaryClassificationReport",
+    /// Kish effective sample size, `None` when the report was not built from
+    /// raw data.
+    effective_sample_size => sample_size,
+    /// Bootstrap bounds per metric, `None` unless a confidence level was
+    /// asked for.
+    confidence_intervals => intervals_dict
+);
+py_report!(
+    PyLogisticRegressionReport,
+    LogisticRegressionReport,
+    "LogisticRegressionReport",
+    /// Kish effective sample size, `None` when the report was not built from
+    /// raw data.
+    effective_sample_size => sample_size,
+    /// Bootstrap bounds per metric, `None` unless a confidence level was
+    /// asked for.
+    confidence_intervals => intervals_dict
+);
+py_report!(
+    PyLinearRegressionReport,
+    LinearRegressionReport,
+    "LinearRegressionReport",
+    /// Kish effective sample size, `None` when the report was not built from
+    /// raw data.
+    effective_sample_size => sample_size,
+    /// Bootstrap bounds per metric, `None` unless a confidence level was
+    /// asked for.
+    confidence_intervals => intervals_dict
+);
+
+/// Bias reports of one facet column of a frame, the same report classes the
+/// single facet analyses return.
+#[pyclass(
+    frozen,
+    eq,
+    name = "FacetReport",
+    module = "fair_perf_ml._fair_perf_ml"
+)]
+#[derive(PartialEq)]
+pub struct PyFacetReport {
+    inner: FacetReport,
+}
+
+#[pymethods]
+impl PyFacetReport {
+    /// Name of the facet column.
+    #[getter]
+    fn facet(&self) -> &str {
+        &self.inner.facet
+    }
+
+    #[getter]
+    fn data_bias(&self) -> PyDataBiasReport {
+        self.inner.data_bias.clone().into()
+    }
+
+    /// `None` when the frame was analyzed without a prediction rule.
+    #[getter]
+    fn model_bias(&self) -> Option<PyModelBiasReport> {
+        self.inner.model_bias.clone().map(Into::into)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("FacetReport(facet={:?})", self.inner.facet)
+    }
+}
+
+/// Python view of a frame analysis, one `FacetReport` per facet column and
+/// the performance report of the model when a model type was given.
+#[pyclass(
+    frozen,
+    eq,
+    name = "FrameReport",
+    module = "fair_perf_ml._fair_perf_ml"
+)]
+#[derive(PartialEq)]
+pub struct PyFrameReport {
+    inner: FrameReport,
+}
+
+impl From<FrameReport> for PyFrameReport {
+    fn from(inner: FrameReport) -> PyFrameReport {
+        PyFrameReport { inner }
+    }
+}
+
+#[pymethods]
+impl PyFrameReport {
+    /// Facet reports in the order the facet columns were passed.
+    #[getter]
+    fn facets(&self) -> Vec<PyFacetReport> {
+        self.inner
+            .facets
+            .iter()
+            .map(|facet| PyFacetReport {
+                inner: facet.clone(),
+            })
+            .collect()
+    }
+
+    /// The `BinaryClassificationReport`, `LogisticRegressionReport` or
+    /// `LinearRegressionReport` of the model, `None` without a model type.
+    #[getter]
+    fn performance(&self, py: Python<'_>) -> Option<PyObject> {
+        self.inner
+            .performance
+            .clone()
+            .map(|performance| match performance {
+                PerformanceData::BinaryClassification(report) => {
+                    PyBinaryClassificationReport::from(report).into_py(py)
+                }
+                PerformanceData::LogisticRegression(report) => {
+                    PyLogisticRegressionReport::from(report).into_py(py)
+                }
+                PerformanceData::LinearRegression(report) => {
+                    PyLinearRegressionReport::from(report).into_py(py)
+                }
+            })
+    }
+
+    /// The report of the facet column `facet`.
+    fn __getitem__(&self, facet: &str) -> PyResult<PyFacetReport> {
+        self.inner
+            .facets
+            .iter()
+            .find(|report| report.facet == facet)
+            .map(|report| PyFacetReport {
+                inner: report.clone(),
+            })
+            .ok_or_else(|| PyKeyError::new_err(facet.to_string()))
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.facets.len()
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    /// Inverse of `to_json`.
+    #[classmethod]
+    fn from_json(_cls: &Bound<'_, PyType>, data: &str) -> PyResult<Self> {
+        serde_json::from_str::<FrameReport>(data)
+            .map(Into::into)
+            .map_err(|err| PyValueError::new_err(err.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        let facets: Vec<&str> = self
+            .inner
+            .facets
+            .iter()
+            .map(|report| report.facet.as_str())
+            .collect();
+        format!("FrameReport(facets={facets:?})")
+    }
+}
+
+pub(crate) fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDataBiasReport>()?;
+    m.add_class::<PyModelBiasReport>()?;
+    m.add_class::<PyBinaryClassificationReport>()?;
+    m.add_class::<PyLogisticRegressionReport>()?;
+    m.add_class::<PyLinearRegressionReport>()?;
+    m.add_class::<PyFacetReport>()?;
+    m.add_class::<PyFrameReport>()?;
+    Ok(())
+}
@@ -1,365 +1,176 @@
-use super::data_bias::DataBiasMetrics;
-use super::model_bias::ModelBiasMetrics;
-use std::collections::HashMap;
+use crate::data_bias::DataBiasReport;
+use crate::errors::FairMlError;
+use crate::status::{MetricStatus, MetricStatuses};
+use serde::{Deserialize, Deserializer};
+use std::collections::{BTreeMap, HashMap};
 
-pub struct DataBiasRuntime {
-    ci: f32,
-    dpl: f32,
-    kl: f32,
-    js: f32,
-    lpnorm: f32,
-    tvd: f32,
-    ks: f32,
+/// Misspelled keys written by earlier releases, with the key that replaced
+/// them. Baselines and metric lists using them are still accepted.
+pub const DEPRECATED_NAMES: [(&str, &str); 3] = [
+    (
+        "TotalVarationDistance",
+        DataBiasReport::TOTAL_VARIATION_DISTANCE,
+    ),
+    (
+        "DfferenceInProportionOfLabels",
+        DataBiasReport::DIFFERENCE_IN_PROPORTION_OF_LABELS,
+    ),
+    ("JsDivergance", DataBiasReport::JS_DIVERGENCE),
+];
+
+/// The current key for `name` if it is a deprecated spelling.
+pub fn canonical_name(name: &str) -> Option<&'static str> {
+    DEPRECATED_NAMES
+        .iter()
+        .find(|(deprecated, _)| *deprecated == name)
+        .map(|(_, current)| *current)
+}
+
+/// Renames deprecated keys in place, returning the deprecated keys found.
+pub fn canonicalize_keys(map: &mut HashMap<String, f32>) -> Vec<&'static str> {
+    let mut found = Vec::new();
+    for (deprecated, current) in DEPRECATED_NAMES {
+        if let Some(value) = map.remove(deprecated) {
+            map.entry(current.to_string()).or_insert(value);
+            found.push(deprecated);
+        }
+    }
+    found
+}
+
+// serde_json writes a value that is not finite as null, these read null back
+// as NaN so a report survives its own json
+
+pub(crate) fn f32_or_nan<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    Ok(Option::<f32>::deserialize(deserializer)?.unwrap_or(f32::NAN))
+}
+
+pub(crate) fn f64_or_nan<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
 }
 
-impl TryFrom<HashMap<String, f32>> for DataBiasRuntime {
-    type Error = String;
-    fn try_from(data: HashMap<String, f32>) -> Result<Self, Self::Error> {
-        let ci = match data.get("ClassImbalance") {
-            Some(val) => *val,
-            None => return Err("ClassImbalance not present".to_string()),
-        };
-        let dpl = match data.get("DifferenceInProportionOfLabels") {
-            Some(val) => *val,
-            None => return Err("DifferenceInProportionOfLabels not present".to_string()),
-        };
-        let kl = match data.get("KlDivergence") {
-            Some(val) => *val,
-            None => return Err("KlDivergence not present".to_string()),
-        };
-        let js = match data.get("JsDivergence") {
-            Some(val) => *val,
-            None => return Err("JsDivergence not present".to_string()),
-        };
-        let lpnorm = match data.get("LpNorm") {
-            Some(val) => *val,
-            None => return Err("LpNorm not present".to_string()),
-        };
-        let tvd = match data.get("TotalVarationDistance") {
-            Some(val) => *val,
-            None => return Err("TotalVariationDifference not present".to_string()),
-        };
-        let ks = match data.get("KolmorogvSmirnov") {
-            Some(val) => *val,
-            None => return Err("KolmogorvSmirnov is not present".to_string()),
-        };
+pub(crate) fn metrics_or_nan<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, f32>, D::Error> {
+    let metrics = BTreeMap::<String, Option<f32>>::deserialize(deserializer)?;
+    Ok(metrics
+        .into_iter()
+        .map(|(name, value)| (name, value.unwrap_or(f32::NAN)))
+        .collect())
+}
 
-        Ok(DataBiasRuntime {
-            ci,
-            dpl,
-            kl,
-            js,
-            lpnorm,
-            tvd,
-            ks,
-        })
+pub(crate) fn get_metric(data: &HashMap<String, f32>, key: &str) -> Result<f32, FairMlError> {
+    data.get(key)
+        .copied()
+        .ok_or_else(|| FairMlError::missing_key(key))
+}
+
+/// How a bias metric drifting from its baseline is detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Fails when the absolute value grows, for signed differences between facets.
+    Magnitude,
+    /// Fails when the value itself grows, for divergences and ratios.
+    Increase,
+}
+
+impl TryFrom<&str> for Direction {
+    type Error = FairMlError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "magnitude" => Ok(Self::Magnitude),
+            "increase" => Ok(Self::Increase),
+            _ => Err(FairMlError::InvalidDirection {
+                direction: value.into(),
+            }),
+        }
     }
 }
 
-impl DataBiasRuntime {
-    pub fn runtime_check(
-        &self,
-        baseline: DataBiasRuntime,
-        threshold: f32,
-        metrics: &[DataBiasMetrics],
-    ) -> HashMap<String, String> {
-        let mut result: HashMap<String, String> = HashMap::with_capacity(metrics.len());
-        for m in metrics {
-            match m {
-                DataBiasMetrics::ClassImbalance => {
-                    if self.ci.abs() > baseline.ci.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "ClassImbalance".to_string(),
-                            format!(
-                                "Exceeded baseline by: {}",
-                                (self.ci.abs() - baseline.ci.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                DataBiasMetrics::DifferenceInProportionOfLabels => {
-                    if self.dpl.abs() > baseline.dpl.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "DfferenceInProportionOfLabels".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.dpl.abs() - baseline.dpl.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                DataBiasMetrics::KlDivergence => {
-                    if self.kl > baseline.kl * (1_f32 + threshold) {
-                        result.insert(
-                            "KlDivergence".to_string(),
-                            format!("Execeed baseline by: {}", self.kl - baseline.kl),
-                        );
-                    }
-                }
-                DataBiasMetrics::JsDivergence => {
-                    if self.js > baseline.js * (1_f32 + threshold) {
-                        result.insert(
-                            "JsDivergance".to_string(),
-                            format!("Execeed baseline by: {}", self.kl - baseline.kl),
-                        );
-                    }
-                }
-                DataBiasMetrics::LpNorm => {
-                    if self.lpnorm > baseline.lpnorm * (1_f32 + threshold) {
-                        result.insert(
-                            "LpNorm".to_string(),
-                            format!("Exceeded baseline by: {}", self.lpnorm - baseline.lpnorm),
-                        );
-                    }
-                }
-                DataBiasMetrics::TotalVariationDistance => {
-                    if self.tvd > baseline.tvd * (1_f32 + threshold) {
-                        result.insert(
-                            "TotalVariationDistance".to_string(),
-                            format!("Exceed baseline by: {}", self.tvd - baseline.tvd),
-                        );
-                    }
-                }
-                DataBiasMetrics::KolmorogvSmirnov => {
-                    if self.ks > baseline.ks * (1_f32 + threshold) {
-                        result.insert(
-                            "KolmorogvSmirnov".to_string(),
-                            format!("Exceed baseline by: {}", self.tvd - baseline.tvd),
-                        );
-                    }
-                }
+impl Direction {
+    /// The drift past the baseline when `current` exceeds it by more than `threshold`.
+    pub fn exceeds(&self, current: f32, baseline: f32, threshold: f32) -> Option<f32> {
+        match self {
+            Direction::Magnitude => (current.abs() > baseline.abs() * (1_f32 + threshold))
+                .then(|| (current.abs() - baseline.abs()).abs()),
+            Direction::Increase => {
+                (current > baseline * (1_f32 + threshold)).then(|| (current - baseline).abs())
             }
         }
-        result
     }
 }
 
-pub struct ModelBiasRuntime {
-    ddpl: f32,
-    di: f32,
-    ad: f32,
-    rd: f32,
-    cdacc: f32,
-    dar: f32,
-    sd: f32,
-    dcr: f32,
-    drr: f32,
-    te: f32,
-    ccdpl: f32,
-    ge: f32,
+/// Statuses reported next to the baseline and latest values, when known. A
+/// value whose status is undefined fails the runtime check even when it is
+/// finite, e.g. the 0 `DisparateImpact` falls back to when facet d has no
+/// positive predictions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CheckStatuses<'a> {
+    pub baseline: Option<&'a MetricStatuses>,
+    pub latest: Option<&'a MetricStatuses>,
 }
 
-impl TryFrom<HashMap<String, f32>> for ModelBiasRuntime {
-    type Error = String;
-    fn try_from(data: HashMap<String, f32>) -> Result<Self, Self::Error> {
-        let ddpl = match data.get("DifferenceInPositivePredictedLabels") {
-            Some(val) => *val,
-            None => return Err("DifferenceInPositivePredictedLabels is not present".to_string()),
-        };
-        let di = match data.get("DisparateImpact") {
-            Some(val) => *val,
-            None => return Err("DisparateImpact is not present".to_string()),
-        };
-        let ad = match data.get("AccuracyDifference") {
-            Some(val) => *val,
-            None => return Err("AccuracyDifference is not present".to_string()),
-        };
-        let rd = match data.get("RecallDifference") {
-            Some(val) => *val,
-            None => return Err("RecallDifference is not present".to_string()),
-        };
-        let cdacc = match data.get("DifferenceInConditionalAcceptance") {
-            Some(val) => *val,
-            None => return Err("DifferenceInConditionalAcceptance is not present".to_string()),
-        };
-        let dar = match data.get("DifferenceInAcceptanceRate") {
-            Some(val) => *val,
-            None => return Err("DifferenceInAcceptanceRates is not present".to_string()),
-        };
-        let sd = match data.get("SpecialityDifference") {
-            Some(val) => *val,
-            None => return Err("SpecialityDifference not present".to_string()),
-        };
-        let dcr = match data.get("DifferenceInConditionalRejection") {
-            Some(val) => *val,
-            None => return Err("DifferenceInConditionalRejection not present".to_string()),
-        };
-        let drr = match data.get("DifferenceInRejectionRate") {
-            Some(val) => *val,
-            None => return Err("DifferenceInRejectionRate is not present".to_string()),
-        };
-        let te = match data.get("TreatmentEquity") {
-            Some(val) => *val,
-            None => return Err("TreatmentEquity is not present".to_string()),
-        };
-        let ccdpl = match data.get("ConditionalDemographicDesparityPredictedLabels") {
-            Some(val) => *val,
-            None => {
-                return Err(
-                    "ConditionalDemographicDesparityPredictedLabels is not present".to_string(),
-                )
-            }
-        };
-        let ge = match data.get("GeneralizedEntropy") {
-            Some(val) => *val,
-            None => return Err("GeneralizedEntropy is not present".to_string()),
-        };
-        Ok(ModelBiasRuntime {
-            ddpl,
-            di,
-            ad,
-            rd,
-            cdacc,
-            dar,
-            sd,
-            dcr,
-            drr,
-            te,
-            ccdpl,
-            ge,
-        })
+impl CheckStatuses<'_> {
+    fn undefined(&self, name: &str) -> Option<String> {
+        [("latest", self.latest), ("baseline", self.baseline)]
+            .into_iter()
+            .find_map(
+                |(side, statuses)| match get_status_or_deprecated(statuses?, name)? {
+                    MetricStatus::Undefined(reason) => {
+                        Some(format!("Undefined, {side} status: {reason}"))
+                    }
+                    _ => None,
+                },
+            )
     }
 }
 
-impl ModelBiasRuntime {
-    pub fn runtime_check(
-        &self,
-        baseline: ModelBiasRuntime,
-        threshold: f32,
-        metrics: &[ModelBiasMetrics],
-    ) -> HashMap<String, String> {
-        let mut result: HashMap<String, String> = HashMap::with_capacity(metrics.len());
-        for m in metrics {
-            match m {
-                ModelBiasMetrics::DifferenceInPositivePredictedLabels => {
-                    if self.ddpl.abs() > baseline.ddpl.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "DifferenceInPositivePredictedLabels".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.ddpl.abs() - baseline.ddpl.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                ModelBiasMetrics::DisparateImpact => {
-                    if self.di > baseline.di * (1_f32 + threshold) {
-                        result.insert(
-                            "DisparateImpact".to_string(),
-                            format!("Exceed baseline by: {}", (self.di - baseline.di).abs()),
-                        );
-                    }
-                }
-                ModelBiasMetrics::AccuracyDifference => {
-                    if self.ad.abs() > baseline.ad.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "AccuracyDifference".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.ad.abs() - baseline.ad.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                ModelBiasMetrics::RecallDifference => {
-                    if self.rd.abs() > baseline.rd.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "RecallDifference".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.rd.abs() - baseline.rd.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                ModelBiasMetrics::DifferenceInConditionalAcceptance => {
-                    if self.cdacc.abs() > baseline.cdacc.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "DifferenceInConditionalAcceptance".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.cdacc.abs() - baseline.cdacc.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                ModelBiasMetrics::DifferenceInAcceptanceRate => {
-                    if self.dar.abs() > baseline.dar.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "DifferenceInAcceptanceRate".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.dar.abs() - baseline.dar.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                ModelBiasMetrics::SpecialityDifference => {
-                    if self.sd.abs() > baseline.sd.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "SpecialityDifference".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.sd.abs() - baseline.sd.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                ModelBiasMetrics::DifferenceInConditionalRejection => {
-                    if self.dcr.abs() > baseline.dcr.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "DifferenceInConditionalRejection".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.dcr.abs() - baseline.dcr.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                ModelBiasMetrics::DifferenceInRejectionRate => {
-                    if self.drr.abs() > baseline.drr.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "DifferenceInRejectionRate".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.drr.abs() - baseline.drr.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                ModelBiasMetrics::TreatmentEquity => {
-                    if self.te.abs() > baseline.te.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "TreatmentEquity".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.te.abs() - baseline.te.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                ModelBiasMetrics::ConditionalDemographicDesparityPredictedLabels => {
-                    if self.ccdpl.abs() > baseline.ccdpl.abs() * (1_f32 + threshold) {
-                        result.insert(
-                            "ConditionalDemographicDesparityPredictedLabels".to_string(),
-                            format!(
-                                "Exceed baseline by: {}",
-                                (self.ccdpl.abs() - baseline.ccdpl.abs()).abs()
-                            ),
-                        );
-                    }
-                }
-                ModelBiasMetrics::GeneralizedEntropy => {
-                    if self.ge > baseline.ge * (1_f32 + threshold) {
-                        result.insert(
-                            "GeneralizedEntropy".to_string(),
-                            format!("Exceed baseline by: {}", (self.ge - baseline.ge).abs()),
-                        );
-                    }
-                }
-            }
-        }
-
-        result
+/// Runtime check of one metric, returning the failure message if it drifted.
+/// Deprecated spellings of `name` in either map are accepted. A value that
+/// is not finite, or whose status is undefined, fails the check, as no drift
+/// can be measured against it.
+pub(crate) fn compare_to_baseline(
+    name: &str,
+    direction: Direction,
+    baseline: &HashMap<String, f32>,
+    latest: &HashMap<String, f32>,
+    threshold: f32,
+    statuses: CheckStatuses<'_>,
+) -> Result<Option<String>, FairMlError> {
+    let current = get_current_or_deprecated(latest, name)?;
+    let baseline = get_current_or_deprecated(baseline, name)?;
+    if let Some(failure) = statuses.undefined(name) {
+        return Ok(Some(failure));
+    }
+    if !current.is_finite() {
+        return Ok(Some(format!("Undefined, latest value is {current}")));
     }
+    if !baseline.is_finite() {
+        return Ok(Some(format!("Undefined, baseline value is {baseline}")));
+    }
+    Ok(direction
+        .exceeds(current, baseline, threshold)
+        .map(|diff| format!("Exceed baseline by: {diff}")))
+}
+
+fn get_status_or_deprecated<'a>(
+    statuses: &'a MetricStatuses,
+    key: &str,
+) -> Option<&'a MetricStatus> {
+    statuses.get(key).or_else(|| {
+        DEPRECATED_NAMES
+            .iter()
+            .filter(|(_, current)| *current == key)
+            .find_map(|(deprecated, _)| statuses.get(*deprecated))
+    })
+}
+
+fn get_current_or_deprecated(data: &HashMap<String, f32>, key: &str) -> Result<f32, FairMlError> {
+    get_metric(data, key).or_else(|err| {
+        DEPRECATED_NAMES
+            .iter()
+            .filter(|(_, current)| *current == key)
+            .find_map(|(deprecated, _)| data.get(*deprecated).copied())
+            .ok_or(err)
+    })
 }
@@ -0,0 +1,400 @@
+use crate::bootstrap::stream_rng;
+use crate::data_bias::{DataBiasReport, PreTraining};
+use crate::errors::FairMlError;
+use crate::model_bias::{ModelBiasReport, PostTrainingData};
+use rand::Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Settings of the permutation test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignificanceConfig {
+    pub permutations: usize,
+    pub seed: u64,
+}
+
+impl Default for SignificanceConfig {
+    fn default() -> Self {
+        SignificanceConfig {
+            permutations: 1000,
+            seed: 0,
+        }
+    }
+}
+
+impl SignificanceConfig {
+    fn validate(&self) -> Result<(), FairMlError> {
+        if self.permutations == 0 {
+            return Err(FairMlError::InvalidSignificanceConfig {
+                reason: "permutations must be at least 1".into(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Two sided p-values for the null hypothesis that both facets share the
+/// same rate.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PValues {
+    /// Two-proportion z-test with the pooled rate.
+    #[serde(deserialize_with = "crate::runtime::f64_or_nan")]
+    pub two_proportion_z: f64,
+    /// Pearson chi-square with Yates' continuity correction.
+    #[serde(deserialize_with = "crate::runtime::f64_or_nan")]
+    pub chi_square: f64,
+    #[serde(deserialize_with = "crate::runtime::f64_or_nan")]
+    pub fisher_exact: f64,
+    /// Share of facet label permutations whose rate difference is at least
+    /// as large as the observed one, counting the observed labels as one.
+    #[serde(deserialize_with = "crate::runtime::f64_or_nan")]
+    pub permutation: f64,
+}
+
+/// P-values per metric, keyed like the report the metric comes from.
+pub type Significance = BTreeMap<String, PValues>;
+
+/// Complementary error function, Chebyshev fit with a fractional error
+/// below 1.2e-7 (Numerical Recipes, erfcc).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1_f64 / (1_f64 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.265_512_23
+            + t * (1.000_023_68
+                + t * (0.374_091_96
+                    + t * (0.096_784_18
+                        + t * (-0.186_288_06
+                            + t * (0.278_868_07
+                                + t * (-1.135_203_98
+                                    + t * (1.488_515_87
+                                        + t * (-0.822_152_23 + t * 0.170_872_77)))))))))
+            .exp();
+    if x >= 0_f64 {
+        r
+    } else {
+        2_f64 - r
+    }
+}
+
+/// ln(Gamma(x)) for x > 0, Lanczos approximation with g = 7.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    let x = x - 1_f64;
+    let t = x + 7.5;
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1_f64)
+        });
+    0.5 * (2_f64 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+fn ln_choose(n: u64, k: u64) -> f64 {
+    ln_gamma(n as f64 + 1_f64) - ln_gamma(k as f64 + 1_f64) - ln_gamma((n - k) as f64 + 1_f64)
+}
+
+/// Successes out of trials in each facet, the 2x2 table behind a rate
+/// difference. Counts are unweighted row counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TwoByTwo {
+    pub a_successes: u64,
+    pub a_trials: u64,
+    pub d_successes: u64,
+    pub d_trials: u64,
+}
+
+impl TwoByTwo {
+    fn trials(&self) -> u64 {
+        self.a_trials + self.d_trials
+    }
+
+    fn successes(&self) -> u64 {
+        self.a_successes + self.d_successes
+    }
+
+    fn rate_difference(&self, a_successes: u64) -> f64 {
+        let d_successes = self.successes() - a_successes;
+        (a_successes as f64 / self.a_trials as f64 - d_successes as f64 / self.d_trials as f64)
+            .abs()
+    }
+
+    /// Without a success, or without a failure, the facets cannot differ.
+    fn is_constant(&self) -> bool {
+        self.successes() == 0 || self.successes() == self.trials()
+    }
+
+    pub fn two_proportion_z(&self) -> f64 {
+        let pooled = self.successes() as f64 / self.trials() as f64;
+        let standard_error = (pooled
+            * (1_f64 - pooled)
+            * (1_f64 / self.a_trials as f64 + 1_f64 / self.d_trials as f64))
+            .sqrt();
+        let z = self.rate_difference(self.a_successes) / standard_error;
+        erfc(z / std::f64::consts::SQRT_2)
+    }
+
+    pub fn chi_square(&self) -> f64 {
+        let trials = self.trials() as f64;
+        let successes = self.successes() as f64;
+        let failures = trials - successes;
+        let expected = [
+            self.a_trials as f64 * successes / trials,
+            self.a_trials as f64 * failures / trials,
+            self.d_trials as f64 * successes / trials,
+            self.d_trials as f64 * failures / trials,
+        ];
+        // every cell is off its expected count by the same amount in a 2x2 table
+        let deviation = (self.a_successes as f64 - expected[0]).abs();
+        let corrected = (deviation - 0.5).max(0_f64);
+        let statistic: f64 = expected
+            .iter()
+            .map(|expected| corrected * corrected / expected)
+            .sum();
+        erfc((statistic / 2_f64).sqrt())
+    }
+
+    /// The facet a success counts a table with the same margins can hold.
+    fn support(&self) -> RangeInclusive<u64> {
+        let failures = self.trials() - self.successes();
+        self.a_trials.saturating_sub(failures)..=self.successes().min(self.a_trials)
+    }
+
+    /// ln of the hypergeometric probability that facet a holds `a_successes`
+    /// once the facet labels are shuffled over the rows.
+    fn ln_null_probability(&self, a_successes: u64) -> f64 {
+        let (trials, successes) = (self.trials(), self.successes());
+        ln_choose(successes, a_successes)
+            + ln_choose(trials - successes, self.a_trials - a_successes)
+            - ln_choose(trials, self.a_trials)
+    }
+
+    /// Sums the hypergeometric probabilities of every table with the same
+    /// margins that is no more likely than the observed one.
+    pub fn fisher_exact(&self) -> f64 {
+        let observed = self.ln_null_probability(self.a_successes);
+        // relative tolerance, so tables as likely as the observed one are not
+        // lost to rounding
+        let cutoff = observed + 1e-7_f64.ln_1p();
+        let p: f64 = self
+            .support()
+            .map(|x| self.ln_null_probability(x))
+            .filter(|ln_p| *ln_p <= cutoff)
+            .map(f64::exp)
+            .sum();
+        p.min(1_f64)
+    }
+
+    /// Shuffles the facet labels over the rows `permutations` times. A
+    /// shuffle puts a hypergeometric number of the successes in facet a, so
+    /// permutation `i` draws that number by inverting its cumulative
+    /// distribution at one uniform from stream `i` of the seed.
+    pub fn permutation(&self, config: SignificanceConfig) -> Result<f64, FairMlError> {
+        config.validate()?;
+        let observed = self.rate_difference(self.a_successes);
+        let support = self.support();
+        let lowest = *support.start();
+        let cumulative: Vec<f64> = support
+            .map(|x| self.ln_null_probability(x).exp())
+            .scan(0_f64, |total, p| {
+                *total += p;
+                Some(*total)
+            })
+            .collect();
+        // rounding leaves the total a little off 1, so the uniform is scaled
+        // to it
+        let total = cumulative.last().copied().unwrap_or(0_f64);
+        let as_extreme = (0..config.permutations)
+            .into_par_iter()
+            .filter(|permutation| {
+                let uniform = stream_rng(config.seed, *permutation).gen::<f64>() * total;
+                let drawn = cumulative.partition_point(|p| *p <= uniform);
+                self.rate_difference(lowest + drawn as u64) >= observed * (1_f64 - 1e-12)
+            })
+            .count();
+        Ok((as_extreme + 1) as f64 / (config.permutations + 1) as f64)
+    }
+
+    /// Every p-value is 1 when the table has no success or no failure, and
+    /// NaN when a facet has no trials.
+    pub fn p_values(&self, config: SignificanceConfig) -> Result<PValues, FairMlError> {
+        config.validate()?;
+        if self.a_trials == 0 || self.d_trials == 0 {
+            return Ok(PValues {
+                two_proportion_z: f64::NAN,
+                chi_square: f64::NAN,
+                fisher_exact: f64::NAN,
+                permutation: f64::NAN,
+            });
+        }
+        if self.is_constant() {
+            return Ok(PValues {
+                two_proportion_z: 1_f64,
+                chi_square: 1_f64,
+                fisher_exact: 1_f64,
+                permutation: 1_f64,
+            });
+        }
+        Ok(PValues {
+            two_proportion_z: self.two_proportion_z(),
+            chi_square: self.chi_square(),
+            fisher_exact: self.fisher_exact(),
+            permutation: self.permutation(config)?,
+        })
+    }
+}
+
+/// Significance of `DifferenceInProportionOfLabels`, positive labels out of
+/// the rows of each facet.
+pub fn data_bias_significance(
+    data: &PreTraining,
+    config: SignificanceConfig,
+) -> Result<Significance, FairMlError> {
+    let table = TwoByTwo {
+        a_successes: data.facet_a.positives,
+        a_trials: data.facet_a.len(),
+        d_successes: data.facet_d.positives,
+        d_trials: data.facet_d.len(),
+    };
+    Ok(Significance::from([(
+        DataBiasReport::DIFFERENCE_IN_PROPORTION_OF_LABELS.to_string(),
+        table.p_values(config)?,
+    )]))
+}
+
+/// Significance of `DifferenceInPositivePredictedLabels`, `RecallDifference`
+/// (true positives out of the actual positives) and `AccuracyDifference`
+/// (correct predictions out of the rows). The metric value of
+/// `DifferenceInPositivePredictedLabels` scales the predicted positives by
+/// the actual positives, but the disparity it stands for is a gap in the
+/// predicted positive rate, so its table is the predicted positives out of
+/// the rows of each facet.
+pub fn model_bias_significance(
+    data: &PostTrainingData,
+    config: SignificanceConfig,
+) -> Result<Significance, FairMlError> {
+    let (a, d) = (&data.facet_a, &data.facet_d);
+    let tables = [
+        (
+            ModelBiasReport::DIFFERENCE_IN_POSITIVE_PREDICTED_LABELS,
+            TwoByTwo {
+                a_successes: a.predicted_positives(),
+                a_trials: a.len(),
+                d_successes: d.predicted_positives(),
+                d_trials: d.len(),
+            },
+        ),
+        (
+            ModelBiasReport::RECALL_DIFFERENCE,
+            TwoByTwo {
+                a_successes: a.true_positives,
+                a_trials: a.actual_positives(),
+                d_successes: d.true_positives,
+                d_trials: d.actual_positives(),
+            },
+        ),
+        (
+            ModelBiasReport::ACCURACY_DIFFERENCE,
+            TwoByTwo {
+                a_successes: a.true_positives + a.true_negatives,
+                a_trials: a.len(),
+                d_successes: d.true_positives + d.true_negatives,
+                d_trials: d.len(),
+            },
+        ),
+    ];
+    tables
+        .into_iter()
+        .map(|(metric, table)| Ok((metric.to_string(), table.p_values(config)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Facet a has 8 successes out of 10, facet d 1 out of 6.
+    const TABLE: TwoByTwo = TwoByTwo {
+        a_successes: 8,
+        a_trials: 10,
+        d_successes: 1,
+        d_trials: 6,
+    };
+
+    #[test]
+    fn p_values_match_reference_values() {
+        let p_values = TABLE.p_values(SignificanceConfig::default()).unwrap();
+        assert!((p_values.two_proportion_z - 0.0134254).abs() < 1e-6);
+        assert!((p_values.chi_square - 0.0509619).abs() < 1e-6);
+        assert!((p_values.fisher_exact - 0.0349650).abs() < 1e-6);
+        assert!((1_f64 / 1001_f64..=1_f64).contains(&p_values.permutation));
+        // the tests are two sided, so swapping the facets changes nothing
+        let swapped = TwoByTwo {
+            a_successes: 1,
+            a_trials: 6,
+            d_successes: 8,
+            d_trials: 10,
+        };
+        assert!((swapped.two_proportion_z() - p_values.two_proportion_z).abs() < 1e-12);
+        assert!((swapped.chi_square() - p_values.chi_square).abs() < 1e-12);
+        assert!((swapped.fisher_exact() - p_values.fisher_exact).abs() < 1e-12);
+    }
+
+    #[test]
+    fn permutations_are_seeded_and_approach_the_exact_tail() {
+        let config = SignificanceConfig {
+            permutations: 500,
+            seed: 3,
+        };
+        assert_eq!(
+            TABLE.permutation(config).unwrap(),
+            TABLE.permutation(config).unwrap()
+        );
+        let many = TABLE
+            .permutation(SignificanceConfig {
+                permutations: 20000,
+                seed: 3,
+            })
+            .unwrap();
+        assert!((many - TABLE.fisher_exact()).abs() < 0.005);
+    }
+
+    #[test]
+    fn degenerate_tables() {
+        let config = SignificanceConfig::default();
+        let constant = TwoByTwo {
+            a_successes: 10,
+            a_trials: 10,
+            d_successes: 6,
+            d_trials: 6,
+        };
+        assert_eq!(constant.p_values(config).unwrap().fisher_exact, 1_f64);
+        let empty = TwoByTwo {
+            d_successes: 0,
+            d_trials: 0,
+            ..TABLE
+        };
+        assert!(empty.p_values(config).unwrap().two_proportion_z.is_nan());
+        assert!(matches!(
+            TABLE.p_values(SignificanceConfig {
+                permutations: 0,
+                seed: 0
+            }),
+            Err(FairMlError::InvalidSignificanceConfig { .. })
+        ));
+    }
+}
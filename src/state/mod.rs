@@ -0,0 +1,88 @@
+use crate::errors::FairMlError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Layout version of serialized accumulator state. Bumped whenever a field is
+/// added, removed or reordered in any accumulator, so state written by an
+/// incompatible build is rejected rather than misread.
+pub const STATE_FORMAT_VERSION: u32 = 4;
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    format_version: u32,
+    crate_version: &'a str,
+    kind: &'a str,
+    state: &'a T,
+}
+
+/// The leading fields of an envelope, read before the state itself so a
+/// version mismatch is reported as such instead of as a decode failure.
+#[derive(Deserialize)]
+struct Header {
+    format_version: u32,
+    crate_version: String,
+    kind: String,
+}
+
+impl Header {
+    fn check(&self, kind: &str) -> Result<(), FairMlError> {
+        if self.format_version != STATE_FORMAT_VERSION {
+            return Err(FairMlError::StateVersionMismatch {
+                found: format!(
+                    "{} (written by fair_perf_ml {})",
+                    self.format_version, self.crate_version
+                ),
+                expected: STATE_FORMAT_VERSION.to_string(),
+            });
+        }
+        if self.kind != kind {
+            return Err(FairMlError::invalid_state(format!(
+                "expected {kind} state, found {}",
+                self.kind
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Serialization shared by every accumulator. Both formats wrap the state in
+/// an envelope carrying the format version, the writing crate version and the
+/// accumulator kind.
+pub trait AccumulatorState: Serialize + DeserializeOwned {
+    /// Tag stored with the state so it cannot be loaded into another accumulator.
+    const KIND: &'static str;
+
+    fn to_bytes(&self) -> Result<Vec<u8>, FairMlError> {
+        bincode::serialize(&envelope(self)).map_err(FairMlError::invalid_state)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, FairMlError> {
+        // bincode reads fields in order and ignores whatever follows the header
+        let header: Header = bincode::deserialize(bytes).map_err(FairMlError::invalid_state)?;
+        header.check(Self::KIND)?;
+        let (_, state): (Header, Self) =
+            bincode::deserialize(bytes).map_err(FairMlError::invalid_state)?;
+        Ok(state)
+    }
+
+    fn to_json(&self) -> Result<String, FairMlError> {
+        serde_json::to_string(&envelope(self)).map_err(FairMlError::invalid_state)
+    }
+
+    fn from_json(json: &str) -> Result<Self, FairMlError> {
+        let value: Value = serde_json::from_str(json).map_err(FairMlError::invalid_state)?;
+        let header = Header::deserialize(&value).map_err(FairMlError::invalid_state)?;
+        header.check(Self::KIND)?;
+        Self::deserialize(&value["state"]).map_err(FairMlError::invalid_state)
+    }
+}
+
+fn envelope<T: AccumulatorState>(state: &T) -> Envelope<'_, T> {
+    Envelope {
+        format_version: STATE_FORMAT_VERSION,
+        crate_version: env!("CARGO_PKG_VERSION"),
+        kind: T::KIND,
+        state,
+    }
+}
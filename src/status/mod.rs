@@ -0,0 +1,119 @@
+use crate::errors::FairMlError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Smallest counts a metric is trusted on. Counts are weighted totals when
+/// the rows carry sample weights.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SupportThresholds {
+    /// Rows each facet needs.
+    pub min_facet_size: f64,
+    /// Smallest count a metric may divide by, e.g. the false positives of a
+    /// facet for `TreatmentEquity`.
+    pub min_cell_count: f64,
+}
+
+impl Default for SupportThresholds {
+    fn default() -> Self {
+        SupportThresholds {
+            min_facet_size: 30_f64,
+            min_cell_count: 5_f64,
+        }
+    }
+}
+
+impl SupportThresholds {
+    pub(crate) fn validate(&self) -> Result<(), FairMlError> {
+        for (name, value) in [
+            ("min_facet_size", self.min_facet_size),
+            ("min_cell_count", self.min_cell_count),
+        ] {
+            if !value.is_finite() || value < 0_f64 {
+                return Err(FairMlError::InvalidSupportThresholds {
+                    reason: format!("{name} must not be negative, got {value}"),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a metric value can be trusted, with the reason when it cannot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "reason", rename_all = "kebab-case")]
+pub enum MetricStatus {
+    Ok,
+    /// The metric divides by zero, or its value is not finite.
+    Undefined(String),
+    /// The metric is defined but rests on fewer rows than the thresholds ask
+    /// for.
+    LowSupport(String),
+}
+
+/// Status per metric, keyed like the report the metrics come from.
+pub type MetricStatuses = BTreeMap<String, MetricStatus>;
+
+/// One of the two facets a count belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Facet {
+    A,
+    D,
+}
+
+impl Facet {
+    pub fn name(self) -> &'static str {
+        match self {
+            Facet::A => "facet a",
+            Facet::D => "facet d",
+        }
+    }
+
+    pub(crate) fn pick<'a, T>(self, facet_a: &'a T, facet_d: &'a T) -> &'a T {
+        match self {
+            Facet::A => facet_a,
+            Facet::D => facet_d,
+        }
+    }
+}
+
+/// Status of one metric from its value, the size of each facet and the
+/// named counts it divides by. A zero denominator makes the metric undefined
+/// even when it falls back to a finite value, e.g. `DisparateImpact` is 0
+/// when facet d has no positive predictions.
+pub(crate) fn metric_status(
+    metric: &str,
+    value: f32,
+    facet_sizes: [f64; 2],
+    denominators: &[(String, f64)],
+    thresholds: SupportThresholds,
+) -> MetricStatus {
+    if let Some((denominator, _)) = denominators.iter().find(|(_, count)| *count <= 0_f64) {
+        let error = FairMlError::DegenerateDenominator {
+            metric: metric.into(),
+            denominator: denominator.clone(),
+        };
+        return MetricStatus::Undefined(error.to_string());
+    }
+    if !value.is_finite() {
+        return MetricStatus::Undefined(format!("{metric} is {value}"));
+    }
+    for (facet, size) in [Facet::A, Facet::D].into_iter().zip(facet_sizes) {
+        if size < thresholds.min_facet_size {
+            return MetricStatus::LowSupport(format!(
+                "{} has {size} rows, below the minimum facet size of {}",
+                facet.name(),
+                thresholds.min_facet_size
+            ));
+        }
+    }
+    if let Some((denominator, count)) = denominators
+        .iter()
+        .find(|(_, count)| *count < thresholds.min_cell_count)
+    {
+        return MetricStatus::LowSupport(format!(
+            "{denominator} is {count}, below the minimum cell count of {}",
+            thresholds.min_cell_count
+        ));
+    }
+    MetricStatus::Ok
+}
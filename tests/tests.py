@@ -1,9 +1,58 @@
 import pandas as pd
 import numpy as np
 from fair_perf_ml import data_bias, model_bias, model_perf
+from fair_perf_ml._fair_perf_ml import data_bias_analyzer, model_performance_regression
+from fair_perf_ml.data_bias import DataBiasAccumulator
+from fair_perf_ml.model_bias import ModelBiasAccumulator
+from fair_perf_ml.model_perf import (
+    BinaryClassificationAccumulator,
+    LinearRegressionAccumulator,
+    LogisticRegressionAccumulator,
+)
+from fair_perf_ml.reports import (
+    DataBiasReport,
+    FrameReport,
+    LinearRegressionReport,
+    ModelBiasReport,
+)
+from fair_perf_ml.labels import LabelSpec, resolve_label
+from fair_perf_ml.frame import analyze_dataframe
+from fair_perf_ml.exceptions import (
+    FairMlError,
+    LengthMismatchError,
+    EmptyFacetError,
+    LabelTypeMismatchError,
+    UnsupportedDtypeError,
+    MissingBaselineKeyError,
+    StateVersionMismatchError,
+    InvalidStateError,
+    InvalidMetricNameError,
+    DuplicateMetricNameError,
+    InvalidDirectionError,
+    UnknownGroupError,
+    UnmappedCategoryError,
+    InvalidLabelSpecError,
+    MissingValuesError,
+    InvalidMissingPolicyError,
+    InvalidSampleWeightError,
+    UnknownColumnError,
+    InvalidDivergenceOptionsError,
+    InvalidBootstrapConfigError,
+    InvalidSignificanceConfigError,
+    InvalidSupportThresholdsError,
+)
 from numpy.typing import NDArray
 from typing import Tuple
 import argparse
+import json
+import pickle
+import time
+import warnings
+from concurrent.futures import ThreadPoolExecutor
+
+# a bare number is matched exactly, rings and predictions are thresholded
+RING_THRESHOLD = LabelSpec.greater_equal(15)
+PRED_THRESHOLD = LabelSpec.greater_equal(15.0)
 
 
 def generate_binary_data(len: int) -> Tuple[NDArray, NDArray, NDArray]:
@@ -53,6 +102,18 @@ def get_data() -> pd.DataFrame:
     return pd.read_csv("abalone.data", names=headers)
 
 
+def matches_model_bias(result, expected) -> bool:
+    """
+    GeneralizedEntropy is summed from facet counts rather than row by row, so
+    it is compared to float precision, every other metric exactly
+    """
+    entropy = "GeneralizedEntropy"
+    if list(result.keys()) != list(expected.keys()):
+        return False
+    exact = all(result[k] == expected[k] for k in expected if k != entropy)
+    return exact and np.isclose(result[entropy], expected[entropy], rtol=1e-4)
+
+
 def test_db_numpy(bl_df, runtime_test) -> bool:
     """
     testing using a numpy array with the db methods
@@ -68,7 +129,7 @@ def test_db_numpy(bl_df, runtime_test) -> bool:
         "KlDivergence": 0.007977087050676346,
         "JsDivergence": 0.002021726220846176,
         "LpNorm": 0.23192767798900604,
-        "TotalVarationDistance": 0.039049260318279266,
+        "TotalVariationDistance": 0.039049260318279266,
         "KolmorogvSmirnov": 0.039049260318279266,
     }
 
@@ -81,7 +142,7 @@ def test_db_numpy(bl_df, runtime_test) -> bool:
         "KlDivergence": 0.006829630583524704,
         "JsDivergence": 0.0016257409006357193,
         "LpNorm": 0.11823293566703796,
-        "TotalVarationDistance": 0.026097401976585388,
+        "TotalVariationDistance": 0.026097401976585388,
         "KolmorogvSmirnov": 0.026097401976585388,
     }
 
@@ -107,7 +168,7 @@ def test_db_numpy_partial(bl_df, runtime_test) -> bool:
         "KlDivergence": 0.007977087050676346,
         "JsDivergence": 0.002021726220846176,
         "LpNorm": 0.23192767798900604,
-        "TotalVarationDistance": 0.039049260318279266,
+        "TotalVariationDistance": 0.039049260318279266,
         "KolmorogvSmirnov": 0.039049260318279266,
     }
 
@@ -121,7 +182,7 @@ def test_db_numpy_partial(bl_df, runtime_test) -> bool:
         "KlDivergence": 0.006829630583524704,
         "JsDivergence": 0.0016257409006357193,
         "LpNorm": 0.11823293566703796,
-        "TotalVarationDistance": 0.026097401976585388,
+        "TotalVariationDistance": 0.026097401976585388,
         "KolmorogvSmirnov": 0.026097401976585388,
     }
 
@@ -157,7 +218,7 @@ def test_db_list(bl_df, runtime_test) -> bool:
         "KlDivergence": 0.007977087050676346,
         "JsDivergence": 0.002021726220846176,
         "LpNorm": 0.23192767798900604,
-        "TotalVarationDistance": 0.039049260318279266,
+        "TotalVariationDistance": 0.039049260318279266,
         "KolmorogvSmirnov": 0.039049260318279266,
     }
 
@@ -170,7 +231,7 @@ def test_db_list(bl_df, runtime_test) -> bool:
         "KlDivergence": 0.006829630583524704,
         "JsDivergence": 0.0016257409006357193,
         "LpNorm": 0.11823293566703796,
-        "TotalVarationDistance": 0.026097401976585388,
+        "TotalVariationDistance": 0.026097401976585388,
         "KolmorogvSmirnov": 0.026097401976585388,
     }
 
@@ -193,20 +254,23 @@ def test_mb_numpy(bl_df, runtime_test) -> bool:
         15.0,
     )
 
-    res[0] = bl == {
-        "DifferenceInPositivePredictedLabels": 0.002093970775604248,
-        "DisparateImpact": 0.8409091234207153,
-        "AccuracyDifference": -8.034706115722656e-05,
-        "RecallDifference": 0.04676508903503418,
-        "DifferenceInConditionalAcceptance": -0.0026617050170898438,
-        "DifferenceInAcceptanceRate": 0.05057328939437866,
-        "SpecialityDifference": -0.0018883943557739258,
-        "DifferenceInConditionalRejection": 0.005205392837524414,
-        "DifferenceInRejectionRate": 0.0032941699028015137,
-        "TreatmentEquity": -0.8666665554046631,
-        "ConditionalDemographicDesparityPredictedLabels": 0.09311360120773315,
-        "GeneralizedEntropy": 139933.03125,
-    }
+    res[0] = matches_model_bias(
+        bl,
+        {
+            "DifferenceInPositivePredictedLabels": 0.002093970775604248,
+            "DisparateImpact": 0.8409091234207153,
+            "AccuracyDifference": -8.034706115722656e-05,
+            "RecallDifference": 0.04676508903503418,
+            "DifferenceInConditionalAcceptance": -0.0026617050170898438,
+            "DifferenceInAcceptanceRate": 0.05057328939437866,
+            "SpecialityDifference": -0.0018883943557739258,
+            "DifferenceInConditionalRejection": 0.005205392837524414,
+            "DifferenceInRejectionRate": 0.0032941699028015137,
+            "TreatmentEquity": -0.8666665554046631,
+            "ConditionalDemographicDesparityPredictedLabels": 0.09311360120773315,
+            "GeneralizedEntropy": 139933.03125,
+        },
+    )
 
     runtime = model_bias.perform_analysis(
         runtime_test["sex"].to_numpy(),
@@ -217,20 +281,23 @@ def test_mb_numpy(bl_df, runtime_test) -> bool:
         15.0,
     )
 
-    res[1] = runtime == {
-        "DifferenceInPositivePredictedLabels": -0.09821432828903198,
-        "DisparateImpact": 0.7872340083122253,
-        "AccuracyDifference": -0.00829547643661499,
-        "RecallDifference": -0.0654761791229248,
-        "DifferenceInConditionalAcceptance": 0.11385858058929443,
-        "DifferenceInAcceptanceRate": 0.025301873683929443,
-        "SpecialityDifference": 0.0005710124969482422,
-        "DifferenceInConditionalRejection": 0.008004844188690186,
-        "DifferenceInRejectionRate": 0.008531749248504639,
-        "TreatmentEquity": -1.4666666984558105,
-        "ConditionalDemographicDesparityPredictedLabels": 0.0892782211303711,
-        "GeneralizedEntropy": 27914.095703125,
-    }
+    res[1] = matches_model_bias(
+        runtime,
+        {
+            "DifferenceInPositivePredictedLabels": -0.09821432828903198,
+            "DisparateImpact": 0.7872340083122253,
+            "AccuracyDifference": -0.00829547643661499,
+            "RecallDifference": -0.0654761791229248,
+            "DifferenceInConditionalAcceptance": 0.11385858058929443,
+            "DifferenceInAcceptanceRate": 0.025301873683929443,
+            "SpecialityDifference": 0.0005710124969482422,
+            "DifferenceInConditionalRejection": 0.008004844188690186,
+            "DifferenceInRejectionRate": 0.008531749248504639,
+            "TreatmentEquity": -1.4666666984558105,
+            "ConditionalDemographicDesparityPredictedLabels": 0.0892782211303711,
+            "GeneralizedEntropy": 27914.095703125,
+        },
+    )
 
     runtime_check = model_bias.runtime_comparison(bl, runtime, 0.15)
 
@@ -260,20 +327,23 @@ def test_mb_numpy_partial(bl_df, runtime_test) -> bool:
         15.0,
     )
 
-    res[0] = bl == {
-        "DifferenceInPositivePredictedLabels": 0.002093970775604248,
-        "DisparateImpact": 0.8409091234207153,
-        "AccuracyDifference": -8.034706115722656e-05,
-        "RecallDifference": 0.04676508903503418,
-        "DifferenceInConditionalAcceptance": -0.0026617050170898438,
-        "DifferenceInAcceptanceRate": 0.05057328939437866,
-        "SpecialityDifference": -0.0018883943557739258,
-        "DifferenceInConditionalRejection": 0.005205392837524414,
-        "DifferenceInRejectionRate": 0.0032941699028015137,
-        "TreatmentEquity": -0.8666665554046631,
-        "ConditionalDemographicDesparityPredictedLabels": 0.09311360120773315,
-        "GeneralizedEntropy": 139933.03125,
-    }
+    res[0] = matches_model_bias(
+        bl,
+        {
+            "DifferenceInPositivePredictedLabels": 0.002093970775604248,
+            "DisparateImpact": 0.8409091234207153,
+            "AccuracyDifference": -8.034706115722656e-05,
+            "RecallDifference": 0.04676508903503418,
+            "DifferenceInConditionalAcceptance": -0.0026617050170898438,
+            "DifferenceInAcceptanceRate": 0.05057328939437866,
+            "SpecialityDifference": -0.0018883943557739258,
+            "DifferenceInConditionalRejection": 0.005205392837524414,
+            "DifferenceInRejectionRate": 0.0032941699028015137,
+            "TreatmentEquity": -0.8666665554046631,
+            "ConditionalDemographicDesparityPredictedLabels": 0.09311360120773315,
+            "GeneralizedEntropy": 139933.03125,
+        },
+    )
 
     runtime = model_bias.perform_analysis(
         runtime_test["sex"].to_numpy(),
@@ -284,20 +354,23 @@ def test_mb_numpy_partial(bl_df, runtime_test) -> bool:
         15.0,
     )
 
-    res[1] = runtime == {
-        "DifferenceInPositivePredictedLabels": -0.09821432828903198,
-        "DisparateImpact": 0.7872340083122253,
-        "AccuracyDifference": -0.00829547643661499,
-        "RecallDifference": -0.0654761791229248,
-        "DifferenceInConditionalAcceptance": 0.11385858058929443,
-        "DifferenceInAcceptanceRate": 0.025301873683929443,
-        "SpecialityDifference": 0.0005710124969482422,
-        "DifferenceInConditionalRejection": 0.008004844188690186,
-        "DifferenceInRejectionRate": 0.008531749248504639,
-        "TreatmentEquity": -1.4666666984558105,
-        "ConditionalDemographicDesparityPredictedLabels": 0.0892782211303711,
-        "GeneralizedEntropy": 27914.095703125,
-    }
+    res[1] = matches_model_bias(
+        runtime,
+        {
+            "DifferenceInPositivePredictedLabels": -0.09821432828903198,
+            "DisparateImpact": 0.7872340083122253,
+            "AccuracyDifference": -0.00829547643661499,
+            "RecallDifference": -0.0654761791229248,
+            "DifferenceInConditionalAcceptance": 0.11385858058929443,
+            "DifferenceInAcceptanceRate": 0.025301873683929443,
+            "SpecialityDifference": 0.0005710124969482422,
+            "DifferenceInConditionalRejection": 0.008004844188690186,
+            "DifferenceInRejectionRate": 0.008531749248504639,
+            "TreatmentEquity": -1.4666666984558105,
+            "ConditionalDemographicDesparityPredictedLabels": 0.0892782211303711,
+            "GeneralizedEntropy": 27914.095703125,
+        },
+    )
 
     runtime_check = model_bias.partial_runtime_comparison(
         bl,
@@ -631,20 +704,23 @@ def test_mb_list(bl_df, runtime_test):
     )
 
 
-    res[0] = bl == {
-        "DifferenceInPositivePredictedLabels": 0.002093970775604248,
-        "DisparateImpact": 0.8409091234207153,
-        "AccuracyDifference": -8.034706115722656e-05,
-        "RecallDifference": 0.04676508903503418,
-        "DifferenceInConditionalAcceptance": -0.0026617050170898438,
-        "DifferenceInAcceptanceRate": 0.05057328939437866,
-        "SpecialityDifference": -0.0018883943557739258,
-        "DifferenceInConditionalRejection": 0.005205392837524414,
-        "DifferenceInRejectionRate": 0.0032941699028015137,
-        "TreatmentEquity": -0.8666665554046631,
-        "ConditionalDemographicDesparityPredictedLabels": 0.09311360120773315,
-        "GeneralizedEntropy": 139933.03125,
-    }
+    res[0] = matches_model_bias(
+        bl,
+        {
+            "DifferenceInPositivePredictedLabels": 0.002093970775604248,
+            "DisparateImpact": 0.8409091234207153,
+            "AccuracyDifference": -8.034706115722656e-05,
+            "RecallDifference": 0.04676508903503418,
+            "DifferenceInConditionalAcceptance": -0.0026617050170898438,
+            "DifferenceInAcceptanceRate": 0.05057328939437866,
+            "SpecialityDifference": -0.0018883943557739258,
+            "DifferenceInConditionalRejection": 0.005205392837524414,
+            "DifferenceInRejectionRate": 0.0032941699028015137,
+            "TreatmentEquity": -0.8666665554046631,
+            "ConditionalDemographicDesparityPredictedLabels": 0.09311360120773315,
+            "GeneralizedEntropy": 139933.03125,
+        },
+    )
     runtime = model_bias.perform_analysis(
         runtime_test["sex"].to_list(),
         runtime_test["rings"].to_list(),
@@ -655,20 +731,23 @@ def test_mb_list(bl_df, runtime_test):
     )
 
 
-    res[1] = runtime == {
-        "DifferenceInPositivePredictedLabels": -0.09821432828903198,
-        "DisparateImpact": 0.7872340083122253,
-        "AccuracyDifference": -0.00829547643661499,
-        "RecallDifference": -0.0654761791229248,
-        "DifferenceInConditionalAcceptance": 0.11385858058929443,
-        "DifferenceInAcceptanceRate": 0.025301873683929443,
-        "SpecialityDifference": 0.0005710124969482422,
-        "DifferenceInConditionalRejection": 0.008004844188690186,
-        "DifferenceInRejectionRate": 0.008531749248504639,
-        "TreatmentEquity": -1.4666666984558105,
-        "ConditionalDemographicDesparityPredictedLabels": 0.0892782211303711,
-        "GeneralizedEntropy": 27914.095703125,
-    }
+    res[1] = matches_model_bias(
+        runtime,
+        {
+            "DifferenceInPositivePredictedLabels": -0.09821432828903198,
+            "DisparateImpact": 0.7872340083122253,
+            "AccuracyDifference": -0.00829547643661499,
+            "RecallDifference": -0.0654761791229248,
+            "DifferenceInConditionalAcceptance": 0.11385858058929443,
+            "DifferenceInAcceptanceRate": 0.025301873683929443,
+            "SpecialityDifference": 0.0005710124969482422,
+            "DifferenceInConditionalRejection": 0.008004844188690186,
+            "DifferenceInRejectionRate": 0.008531749248504639,
+            "TreatmentEquity": -1.4666666984558105,
+            "ConditionalDemographicDesparityPredictedLabels": 0.0892782211303711,
+            "GeneralizedEntropy": 27914.095703125,
+        },
+    )
     runtime_check = model_bias.runtime_comparison(bl, runtime, 0.15)
 
     res[2] = runtime_check == {
@@ -687,15 +766,1803 @@ def test_mb_list(bl_df, runtime_test):
     return all(res)
 
 
+def raises(exc, fn, *args, **kwargs) -> bool:
+    try:
+        fn(*args, **kwargs)
+    except exc:
+        return True
+    return False
+
+
+def test_errors(bl_df) -> bool:
+    """
+    every failure should surface as its own FairMlError subclass, never a panic
+    """
+    res = [False] * 8
+    res[0] = raises(
+        LengthMismatchError,
+        data_bias.perform_analysis,
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy()[:-1],
+        "M",
+        RING_THRESHOLD,
+    )
+    res[1] = raises(
+        EmptyFacetError,
+        data_bias.perform_analysis,
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        "not a category",
+        RING_THRESHOLD,
+    )
+    res[2] = raises(
+        LabelTypeMismatchError,
+        data_bias.perform_analysis,
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        1,
+        RING_THRESHOLD,
+    )
+    res[3] = raises(
+        UnsupportedDtypeError,
+        data_bias.perform_analysis,
+        np.array(["M", {"a": 1}, "F"], dtype=object),
+        np.array([1, 2, 3]),
+        "M",
+        2,
+    )
+    db_bl = data_bias.perform_analysis(
+        bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy(), "M", RING_THRESHOLD
+    )
+    missing = {k: v for k, v in db_bl.items() if k != "LpNorm"}
+    res[4] = raises(
+        MissingBaselineKeyError, data_bias.runtime_comparison, db_bl, missing
+    )
+    res[5] = raises(
+        InvalidMetricNameError,
+        data_bias.partial_runtime_comparison,
+        db_bl,
+        db_bl,
+        ["NotAMetric"],
+    )
+    # the base class still behaves like the ValueError raised previously
+    res[6] = issubclass(FairMlError, ValueError)
+    # None in an object array is a missing value, not an unsupported element
+    res[7] = raises(
+        MissingValuesError,
+        data_bias.perform_analysis,
+        np.array(["M", None, "F"], dtype=object),
+        np.array([1, 2, 3]),
+        "M",
+        2,
+    )
+    return all(res)
+
+
+def test_missing_values(bl_df) -> bool:
+    """
+    missing values raise by default, every other policy reports them per facet
+    """
+    res = [False] * 9
+    feature = bl_df["sex"].to_numpy().astype(object)
+    ground_truth = bl_df["rings"].to_numpy().astype(np.float64)
+    ground_truth[:10] = np.nan
+    feature[10:15] = None
+    res[0] = raises(
+        MissingValuesError, data_bias.perform_analysis, feature, ground_truth, "M", RING_THRESHOLD
+    )
+    res[1] = raises(
+        InvalidMissingPolicyError,
+        data_bias.perform_analysis,
+        feature,
+        ground_truth,
+        "M",
+        RING_THRESHOLD,
+        "ignore",
+    )
+
+    keep = np.ones(len(feature), dtype=bool)
+    keep[:15] = False
+    expected = data_bias.perform_analysis(feature[keep], ground_truth[keep], "M", RING_THRESHOLD)
+    dropped = data_bias.perform_analysis(
+        feature, ground_truth, "M", RING_THRESHOLD, missing_policy="drop"
+    )
+    res[2] = dropped.to_dict() == expected.to_dict()
+    n_missing_a = int((bl_df["sex"].to_numpy()[:10] == "M").sum())
+    res[3] = dropped.missing == {
+        "facetA": n_missing_a,
+        "facetD": 10 - n_missing_a,
+        "feature": 5,
+    }
+
+    # a missing feature is in neither facet, so own_group matches drop here
+    own_group = data_bias.perform_analysis(
+        feature, ground_truth, "M", RING_THRESHOLD, missing_policy="own_group"
+    )
+    res[4] = close_reports(own_group, expected)
+
+    # treating missing as negative keeps every row
+    negative_feature = np.where(feature == None, "I", feature)  # noqa: E711
+    negative_gt = np.where(np.isnan(ground_truth), 0.0, ground_truth)
+    res[5] = close_reports(
+        data_bias.perform_analysis(
+            feature, ground_truth, "M", RING_THRESHOLD, missing_policy="negative"
+        ),
+        data_bias.perform_analysis(negative_feature, negative_gt, "M", RING_THRESHOLD),
+    )
+
+    acc = DataBiasAccumulator("M", RING_THRESHOLD, missing_policy="drop")
+    acc.update(feature[:2000], ground_truth[:2000])
+    acc.update(feature[2000:], ground_truth[2000:])
+    res[6] = close_reports(acc.finalize(), expected) and acc.missing == missing
+    restored = pickle.loads(pickle.dumps(acc))
+    res[7] = restored.missing == missing
+
+    y_true = bl_df["rings"].to_numpy().astype(np.float64)
+    y_pred = y_true + 0.5
+    y_pred[:3] = np.nan
+    perf_keep = np.ones(len(y_true), dtype=bool)
+    perf_keep[:3] = False
+    res[8] = raises(
+        MissingValuesError, model_perf.linear_regression_analysis, y_true, y_pred
+    ) and model_perf.linear_regression_analysis(
+        y_true, y_pred, missing_policy="drop"
+    ) == model_perf.linear_regression_analysis(
+        y_true[perf_keep], y_pred[perf_keep]
+    )
+    return all(res)
+
+
+def test_group_missing_values(bl_df) -> bool:
+    """
+    the group, intersectional, conditional disparity and categorical analyses
+    raise on missing values by default and drop them like the filtered data
+    """
+    res = [False] * 6
+    sex = bl_df["sex"].to_numpy().astype(object)
+    rings = bl_df["rings"].to_numpy().astype(np.float64)
+    preds = bl_df["preds"].to_numpy().astype(np.float64)
+    rings[:10] = np.nan
+    keep = np.ones(len(rings), dtype=bool)
+    keep[:10] = False
+
+    res[0] = raises(
+        MissingValuesError, data_bias.perform_multi_group_analysis, sex, rings, RING_THRESHOLD
+    )
+    res[1] = data_bias.perform_multi_group_analysis(
+        sex, rings, RING_THRESHOLD, missing_policy="drop"
+    ) == data_bias.perform_multi_group_analysis(sex[keep], rings[keep], RING_THRESHOLD)
+    res[2] = model_bias.perform_multi_group_analysis(
+        sex, rings, preds, RING_THRESHOLD, PRED_THRESHOLD, missing_policy="drop"
+    ) == model_bias.perform_multi_group_analysis(
+        sex[keep], rings[keep], preds[keep], RING_THRESHOLD, PRED_THRESHOLD
+    )
+
+    features = {"sex": sex}
+    labels = {"sex": "M"}
+    res[3] = data_bias.perform_intersectional_analysis(
+        features, labels, rings, RING_THRESHOLD, 1, missing_policy="drop"
+    ) == data_bias.perform_intersectional_analysis(
+        {"sex": sex[keep]}, labels, rings[keep], RING_THRESHOLD, 1
+    )
+
+    # a missing feature stays in its stratum but in neither facet
+    feature = sex.copy()
+    feature[10:15] = None
+    strata = np.arange(len(sex)) % 3
+    feature_keep = keep.copy()
+    feature_keep[10:15] = False
+    res[4] = data_bias.conditional_demographic_disparity(
+        feature, rings, strata, "M", RING_THRESHOLD, missing_policy="drop"
+    ) == data_bias.conditional_demographic_disparity(
+        feature[feature_keep], rings[feature_keep], strata[feature_keep], "M", RING_THRESHOLD
+    )
+
+    grades = bl_df["rings"].to_numpy() // 5
+    present = np.ones(len(feature), dtype=bool)
+    present[10:15] = False
+    res[5] = raises(
+        MissingValuesError, data_bias.categorical_divergences, feature, grades, "M"
+    ) and data_bias.categorical_divergences(
+        feature, grades, "M", missing_policy="drop"
+    ) == data_bias.categorical_divergences(feature[present], grades[present], "M")
+    return all(res)
+
+
+def test_sample_weights(bl_df) -> bool:
+    """
+    integer weights give the same report as repeating every row that many
+    times, and unit weights the same report as no weights
+    """
+    res = [False] * 10
+    np.random.seed(15)
+    feature = bl_df["sex"].to_numpy()
+    ground_truth = bl_df["rings"].to_numpy()
+    predictions = bl_df["preds"].to_numpy()
+    weights = np.random.randint(1, 4, len(feature))
+
+    unweighted = data_bias.perform_analysis(feature, ground_truth, "M", RING_THRESHOLD)
+    res[0] = close_reports(
+        data_bias.perform_analysis(
+            feature, ground_truth, "M", RING_THRESHOLD, sample_weight=np.ones(len(feature))
+        ),
+        unweighted,
+    )
+    n_a = int((feature == "M").sum())
+    res[1] = unweighted.effective_sample_size == {"facetA": n_a, "facetD": len(feature) - n_a}
+
+    res[2] = close_reports(
+        data_bias.perform_analysis(
+            feature, ground_truth, "M", RING_THRESHOLD, sample_weight=weights
+        ),
+        data_bias.perform_analysis(
+            np.repeat(feature, weights), np.repeat(ground_truth, weights), "M", RING_THRESHOLD
+        ),
+    )
+    res[3] = close_reports(
+        model_bias.perform_analysis(
+            feature, ground_truth, predictions, "M", RING_THRESHOLD, PRED_THRESHOLD, sample_weight=weights
+        ),
+        model_bias.perform_analysis(
+            np.repeat(feature, weights),
+            np.repeat(ground_truth, weights),
+            np.repeat(predictions, weights),
+            "M",
+            RING_THRESHOLD,
+            PRED_THRESHOLD,
+        ),
+    )
+
+    # repeating rows shrinks the effective sample size below the row count
+    weighted = data_bias.perform_analysis(
+        feature, ground_truth, "M", RING_THRESHOLD, sample_weight=weights
+    )
+    res[4] = weighted.effective_sample_size["facetA"] < n_a
+
+    negative = weights.astype(np.float64)
+    negative[0] = -1.0
+    nan = weights.astype(np.float64)
+    nan[0] = np.nan
+    res[5] = (
+        raises(
+            InvalidSampleWeightError,
+            data_bias.perform_analysis,
+            feature,
+            ground_truth,
+            "M",
+            RING_THRESHOLD,
+            sample_weight=negative,
+        )
+        and raises(
+            InvalidSampleWeightError,
+            model_perf.linear_regression_analysis,
+            ground_truth,
+            predictions,
+            sample_weight=nan,
+        )
+        and raises(
+            LengthMismatchError,
+            data_bias.perform_analysis,
+            feature,
+            ground_truth,
+            "M",
+            RING_THRESHOLD,
+            sample_weight=weights[1:],
+        )
+    )
+
+    y_true = (ground_truth >= 15).astype(np.float64)
+    y_pred = (predictions >= 15).astype(np.float64)
+    y_proba = np.random.rand(len(y_true))
+    res[6] = close_reports(
+        model_perf.binary_classification_analysis(
+            y_true, y_pred, sample_weight=weights
+        )["performanceData"],
+        model_perf.binary_classification_analysis(
+            np.repeat(y_true, weights), np.repeat(y_pred, weights)
+        )["performanceData"],
+    )
+    res[7] = close_reports(
+        model_perf.logistic_regression_analysis(
+            y_true, y_proba, sample_weight=weights
+        )["performanceData"],
+        model_perf.logistic_regression_analysis(
+            np.repeat(y_true, weights), np.repeat(y_proba, weights)
+        )["performanceData"],
+    )
+    res[8] = close_reports(
+        model_perf.linear_regression_analysis(
+            ground_truth, predictions, sample_weight=weights
+        )["performanceData"],
+        model_perf.linear_regression_analysis(
+            np.repeat(ground_truth, weights), np.repeat(predictions, weights)
+        )["performanceData"],
+    ) and model_performance_regression(
+        y_true=ground_truth, y_pred=predictions
+    ).effective_sample_size == len(ground_truth)
+
+    acc = DataBiasAccumulator("M", RING_THRESHOLD)
+    acc.update(feature[:2000], ground_truth[:2000], weights[:2000])
+    acc.update(feature[2000:], ground_truth[2000:], weights[2000:])
+    perf_acc = LinearRegressionAccumulator()
+    perf_acc.update(ground_truth[:2000], predictions[:2000], weights[:2000])
+    perf_acc.update(ground_truth[2000:], predictions[2000:], weights[2000:])
+    res[9] = close_reports(
+        acc.finalize(),
+        data_bias.perform_analysis(
+            feature, ground_truth, "M", RING_THRESHOLD, sample_weight=weights
+        ),
+    ) and close_reports(
+        perf_acc.finalize()["performanceData"],
+        model_perf.linear_regression_analysis(
+            ground_truth, predictions, sample_weight=weights
+        )["performanceData"],
+    )
+    return all(res)
+
+
+def test_dtypes(bl_df) -> bool:
+    """
+    every numeric width, booleans and numpy's fixed width strings read the
+    same values as the int64 and object arrays, other dtypes are rejected
+    """
+    res = [False] * 8
+    feature = bl_df["sex"].to_numpy()
+    ground_truth = bl_df["rings"].to_numpy().astype(np.int64)
+    expected = data_bias.perform_analysis(feature, ground_truth, "M", RING_THRESHOLD)
+
+    res[0] = all(
+        data_bias.perform_analysis(feature, ground_truth.astype(dtype), "M", RING_THRESHOLD)
+        == expected
+        for dtype in [
+            np.int8,
+            np.int16,
+            np.uint8,
+            np.uint16,
+            np.uint32,
+            np.uint64,
+            np.float16,
+            ">i4",
+        ]
+    )
+    res[1] = all(
+        data_bias.perform_analysis(feature.astype(dtype), ground_truth, "M", RING_THRESHOLD)
+        == expected
+        for dtype in ["<U1", ">U1", "<U8", "S1"]
+    )
+    unicode = feature.astype(str)
+    strided = np.stack([unicode, unicode], axis=1)[:, 0]
+    res[2] = data_bias.perform_analysis(strided, ground_truth, "M", RING_THRESHOLD) == expected
+
+    # True is the positive value of a boolean array
+    res[3] = data_bias.perform_analysis(
+        feature == "M", ground_truth, True, RING_THRESHOLD
+    ) == expected and data_bias.perform_analysis(
+        feature, ground_truth >= 15, "M", True
+    ) == expected
+
+    res[4] = raises(
+        UnsupportedDtypeError,
+        data_bias.perform_analysis,
+        np.arange(len(feature), dtype="datetime64[D]"),
+        ground_truth,
+        "M",
+        RING_THRESHOLD,
+    ) and raises(
+        UnsupportedDtypeError,
+        model_perf.linear_regression_analysis,
+        ground_truth.astype(np.complex64),
+        ground_truth,
+    )
+    res[5] = data_bias.perform_multi_group_analysis(
+        unicode, ground_truth, RING_THRESHOLD
+    ) == data_bias.perform_multi_group_analysis(feature, ground_truth, RING_THRESHOLD)
+
+    predictions = bl_df["preds"].to_numpy().astype(np.float16)
+    res[6] = (
+        model_perf.linear_regression_analysis(
+            ground_truth.astype(np.uint16), predictions
+        )
+        == model_perf.linear_regression_analysis(
+            ground_truth.astype(np.float64), predictions.astype(np.float64)
+        )
+    )
+    return all(res)
+
+
+def test_arrow(bl_df) -> bool:
+    """
+    pyarrow arrays, chunked arrays, dictionary encoded and string view
+    columns are read without a numpy copy, arrow nulls go through the missing
+    value policy
+    """
+    try:
+        import pyarrow as pa
+    except ImportError:
+        print("pyarrow not installed, skipping...")
+        return True
+
+    res = [False] * 7
+    feature = bl_df["sex"].to_numpy()
+    ground_truth = bl_df["rings"].to_numpy().astype(np.int64)
+    predictions = bl_df["preds"].to_numpy()
+    expected = data_bias.perform_analysis(feature, ground_truth, "M", RING_THRESHOLD)
+
+    res[0] = (
+        data_bias.perform_analysis(
+            pa.array(feature.tolist()), pa.array(ground_truth), "M", RING_THRESHOLD
+        )
+        == expected
+    )
+    half = len(feature) // 2
+    res[1] = (
+        data_bias.perform_analysis(
+            pa.chunked_array(
+                [feature[:half].tolist(), feature[half:].tolist()], type=pa.string()
+            ),
+            pa.chunked_array([ground_truth[:half], ground_truth[half:]]),
+            "M",
+            RING_THRESHOLD,
+        )
+        == expected
+    )
+    res[2] = (
+        data_bias.perform_analysis(
+            pa.array(feature.tolist()).dictionary_encode(), ground_truth, "M", RING_THRESHOLD
+        )
+        == expected
+    )
+
+    nullable = [None if i % 10 == 0 else v for i, v in enumerate(feature.tolist())]
+    kept = np.arange(len(feature)) % 10 != 0
+    res[3] = raises(
+        MissingValuesError,
+        data_bias.perform_analysis,
+        pa.array(nullable),
+        ground_truth,
+        "M",
+        RING_THRESHOLD,
+    ) and data_bias.perform_analysis(
+        pa.array(nullable), ground_truth, "M", RING_THRESHOLD, missing_policy="drop"
+    ) == data_bias.perform_analysis(
+        feature[kept], ground_truth[kept], "M", RING_THRESHOLD
+    )
+
+    res[4] = model_bias.perform_analysis(
+        pa.array(feature.tolist()).dictionary_encode(),
+        pa.array(ground_truth),
+        pa.array(predictions),
+        "M",
+        RING_THRESHOLD,
+        PRED_THRESHOLD,
+    ) == model_bias.perform_analysis(feature, ground_truth, predictions, "M", RING_THRESHOLD, PRED_THRESHOLD)
+    res[5] = model_perf.linear_regression_analysis(
+        pa.array(ground_truth.astype(np.float64)), pa.array(predictions)
+    ) == model_perf.linear_regression_analysis(
+        ground_truth.astype(np.float64), predictions
+    )
+
+    weights = np.linspace(0.5, 2.0, len(feature))
+    res[6] = data_bias.perform_analysis(
+        pa.array(feature.tolist()), ground_truth, "M", RING_THRESHOLD, sample_weight=pa.array(weights)
+    ) == data_bias.perform_analysis(feature, ground_truth, "M", RING_THRESHOLD, sample_weight=weights)
+
+    # polars exports String columns as Utf8View, long strings live in the data buffers
+    long_feature = np.char.add(feature.astype(str), "_with_a_long_suffix")
+    long_label = "M_with_a_long_suffix"
+    views = [
+        data_bias.perform_analysis(
+            pa.array(long_feature.tolist(), type=pa.string_view()), ground_truth, long_label, RING_THRESHOLD
+        )
+    ]
+    try:
+        import polars as pl
+
+        views += [
+            data_bias.perform_analysis(pl.Series(feature.tolist(), dtype=pl.String), ground_truth, "M", RING_THRESHOLD),
+            data_bias.perform_analysis(pl.Series(long_feature.tolist()), ground_truth, long_label, RING_THRESHOLD),
+        ]
+    except ImportError:
+        print("polars not installed, skipping the polars String column...")
+    res[7] = all(view == expected for view in views)
+    return all(res)
+
+
+def test_dataframe(bl_df) -> bool:
+    """
+    the frame analysis matches the array analyses for every facet column, the
+    missing value policy is applied per facet, and the statuses, bounds and
+    p-values are carried by the reports like those of perform_analysis
+    """
+    res = [False] * 9
+    df = bl_df.assign(infant=bl_df["sex"] == "I").reset_index(drop=True)
+    sex, rings, preds = (
+        df["sex"].to_numpy(),
+        df["rings"].to_numpy(),
+        df["preds"].to_numpy(),
+    )
+
+    report = analyze_dataframe(
+        df,
+        facet={"sex": "M", "infant": True},
+        label="rings",
+        label_rule=RING_THRESHOLD,
+        prediction="preds",
+        prediction_rule=PRED_THRESHOLD,
+        model_type="LinearRegression",
+    )
+    res[0] = (
+        isinstance(report, FrameReport)
+        and [entry.facet for entry in report.facets] == ["sex", "infant"]
+        and isinstance(report["sex"].data_bias, DataBiasReport)
+        and close_reports(
+            report["sex"].data_bias, data_bias.perform_analysis(sex, rings, "M", RING_THRESHOLD)
+        )
+        and FrameReport.from_json(report.to_json()) == report
+    )
+    res[1] = close_reports(
+        report["sex"].model_bias,
+        model_bias.perform_analysis(sex, rings, preds, "M", RING_THRESHOLD, PRED_THRESHOLD),
+    ) and close_reports(
+        report["infant"].data_bias,
+        data_bias.perform_analysis(df["infant"].to_numpy(), rings, True, RING_THRESHOLD),
+    )
+    expected = model_perf.linear_regression_analysis(rings.astype(np.float64), preds)
+    res[2] = isinstance(report.performance, LinearRegressionReport) and close_reports(
+        report.performance, expected["performanceData"]
+    )
+
+    # a shared rule over a list of facets, and no model bias without a prediction rule
+    shared = analyze_dataframe(
+        df, facet=["sex"], facet_rule="M", label="rings", label_rule=RING_THRESHOLD
+    )
+    res[3] = (
+        shared.facets[0].model_bias is None
+        and shared.performance is None
+        and close_reports(shared.facets[0].data_bias, report["sex"].data_bias)
+    )
+
+    res[4] = raises(
+        UnknownColumnError,
+        analyze_dataframe,
+        df,
+        facet="gender",
+        facet_rule="M",
+        label="rings",
+        label_rule=RING_THRESHOLD,
+    )
+
+    holes = df.copy()
+    holes["sex"] = holes["sex"].astype(object)
+    holes.loc[::10, "sex"] = None
+    dropped = analyze_dataframe(
+        holes,
+        facet={"sex": "M", "infant": True},
+        label="rings",
+        label_rule=RING_THRESHOLD,
+        missing_policy="drop",
+    )
+    kept = np.arange(len(df)) % 10 != 0
+    res[5] = (
+        close_reports(
+            dropped["sex"].data_bias,
+            data_bias.perform_analysis(sex[kept], rings[kept], "M", RING_THRESHOLD),
+        )
+        and dropped["sex"].data_bias.missing["feature"] == (~kept).sum()
+        and close_reports(dropped["infant"].data_bias, report["infant"].data_bias)
+    )
+
+    try:
+        import polars as pl
+
+        polars_df = pl.from_pandas(df)
+    except ImportError:
+        res[6] = True
+    else:
+        from_polars = analyze_dataframe(
+            polars_df,
+            facet={"sex": "M", "infant": True},
+            label="rings",
+            label_rule=RING_THRESHOLD,
+            prediction="preds",
+            prediction_rule=PRED_THRESHOLD,
+            model_type="LinearRegression",
+        )
+        res[6] = all(
+            close_reports(a.data_bias, b.data_bias) and close_reports(a.model_bias, b.model_bias)
+            for a, b in zip(from_polars.facets, report.facets)
+        )
+
+    options = dict(confidence_level=0.9, resamples=200, seed=7)
+    detailed = analyze_dataframe(
+        df,
+        facet="sex",
+        facet_rule="M",
+        label="rings",
+        label_rule=RING_THRESHOLD,
+        prediction="preds",
+        prediction_rule=PRED_THRESHOLD,
+        model_type="LinearRegression",
+        significance=True,
+        permutations=200,
+        min_facet_size=2000,
+        **options,
+    )
+    entry = detailed["sex"]
+    db_report = data_bias.perform_analysis(
+        sex,
+        rings,
+        "M",
+        RING_THRESHOLD,
+        significance=True,
+        permutations=200,
+        min_facet_size=2000,
+        **options,
+    )
+    mb_report = model_bias.perform_analysis(
+        sex,
+        rings,
+        preds,
+        "M",
+        RING_THRESHOLD,
+        PRED_THRESHOLD,
+        significance=True,
+        permutations=200,
+        min_facet_size=2000,
+        **options,
+    )
+    res[7] = (
+        close_reports(entry.data_bias, report["sex"].data_bias)
+        and close_reports(entry.model_bias, report["sex"].model_bias)
+        and entry.data_bias.status == db_report.status
+        and entry.model_bias.status == mb_report.status
+        and db_report.status["ClassImbalance"]["status"] == "low-support"
+        and all(
+            close_reports(
+                {k: ours.confidence_intervals[m][k] for k in ["lower", "upper"]},
+                {k: theirs.confidence_intervals[m][k] for k in ["lower", "upper"]},
+            )
+            for ours, theirs in [(entry.data_bias, db_report), (entry.model_bias, mb_report)]
+            for m in theirs.confidence_intervals
+        )
+        and all(
+            close_reports(ours.significance[m], theirs.significance[m])
+            for ours, theirs in [(entry.data_bias, db_report), (entry.model_bias, mb_report)]
+            for m in theirs.significance
+        )
+    )
+    expected_bounds = model_perf.linear_regression_analysis(
+        rings.astype(np.float64), preds, **options
+    )["confidenceIntervals"]
+    perf_bounds = detailed.performance.confidence_intervals
+    res[8] = (
+        report["sex"].data_bias.confidence_intervals is None
+        and report.performance.confidence_intervals is None
+        and close_reports(detailed.performance, expected["performanceData"])
+        and all(
+            close_reports(
+                {k: perf_bounds[m][k] for k in ["lower", "upper"]},
+                {k: expected_bounds[m][k] for k in ["lower", "upper"]},
+            )
+            for m in expected_bounds
+        )
+    )
+    return all(res)
+
+
+def test_polars_expressions(bl_df) -> bool:
+    """
+    metric expressions in a lazy group_by match the array analysis of each group
+    """
+    try:
+        import polars as pl
+        from fair_perf_ml import expressions as fair
+
+        df = pl.from_pandas(bl_df[["sex", "rings", "preds"]].reset_index(drop=True))
+    except ImportError:
+        print("polars not installed, skipping...")
+        return True
+
+    res = [False] * 4
+    df = df.with_row_index("row").with_columns(part=pl.col("row") % 3)
+    per_part = (
+        df.lazy()
+        .group_by("part")
+        .agg(
+            fair.class_imbalance("rings", "sex", RING_THRESHOLD, "M"),
+            fair.disparate_impact("preds", "sex", "rings", PRED_THRESHOLD, "M", RING_THRESHOLD),
+            fair.model_bias_metric(
+                "RecallDifference", "preds", "sex", "rings", PRED_THRESHOLD, "M", RING_THRESHOLD
+            ),
+        )
+        .sort("part")
+        .collect()
+    )
+
+    checks = []
+    for row in per_part.iter_rows(named=True):
+        part = df.filter(pl.col("part") == row["part"])
+        sex, rings, preds = (
+            part["sex"].to_numpy(),
+            part["rings"].to_numpy(),
+            part["preds"].to_numpy(),
+        )
+        data = data_bias.perform_analysis(sex, rings, "M", RING_THRESHOLD)
+        model = model_bias.perform_analysis(sex, rings, preds, "M", RING_THRESHOLD, PRED_THRESHOLD)
+        checks.append(
+            np.isclose(row["ClassImbalance"], data["ClassImbalance"])
+            and np.isclose(row["DisparateImpact"], model["DisparateImpact"])
+            and np.isclose(row["RecallDifference"], model["RecallDifference"])
+        )
+    res[0] = len(checks) == 3 and all(checks)
+
+    whole = df.select(fair.data_bias_metric("LpNorm", "rings", "sex", RING_THRESHOLD, "M"))
+    res[1] = np.isclose(
+        whole["LpNorm"][0],
+        data_bias.perform_analysis(
+            df["sex"].to_numpy(), df["rings"].to_numpy(), "M", RING_THRESHOLD
+        )["LpNorm"],
+    )
+    res[2] = raises(
+        Exception,
+        df.select,
+        fair.data_bias_metric("NotAMetric", "rings", "sex", RING_THRESHOLD, "M"),
+    )
+
+    # a series without the arrow c stream interface is refused, not copied
+    class NoStream:
+        pass
+
+    res[3] = raises(TypeError, fair._column, NoStream())
+    return all(res)
+
+
+def benchmark_ingestion(n_rows: int = 10_000_000) -> bool:
+    """
+    typed numpy arrays are read straight from the buffer, the same values as an
+    object array go through the per element extract path
+    """
+    np.random.seed(3)
+    feature = np.random.randint(0, 4, n_rows).astype(np.int64)
+    ground_truth = np.random.rand(n_rows)
+
+    start = time.perf_counter()
+    fast = data_bias.perform_analysis(feature, ground_truth, 1, LabelSpec.greater_equal(0.5))
+    fast_elapsed = time.perf_counter() - start
+
+    start = time.perf_counter()
+    slow = data_bias.perform_analysis(
+        feature.astype(object), ground_truth.astype(object), 1, LabelSpec.greater_equal(0.5)
+    )
+    slow_elapsed = time.perf_counter() - start
+
+    # strided views have to produce the same result as their contiguous copy
+    strided = np.stack([feature, feature], axis=1)[:, 0]
+    strided_res = data_bias.perform_analysis(strided, ground_truth, 1, LabelSpec.greater_equal(0.5))
+
+    print(
+        f"{n_rows} rows: typed {fast_elapsed:.3f}s, object {slow_elapsed:.3f}s, "
+        f"speedup {slow_elapsed / fast_elapsed:.1f}x"
+    )
+    return fast == slow and strided_res == fast and fast_elapsed < slow_elapsed
+
+
+def test_reports(bl_df, reg_pred, reg_true) -> bool:
+    """
+    typed reports expose attributes, round trip through dicts and json with
+    their optional fields, and keys written by earlier releases are accepted
+    with a deprecation warning. Every check is named, so a failure says which
+    """
+    checks = {}
+    report = data_bias_analyzer(bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy(), "M", RING_THRESHOLD)
+    as_dict = report.to_dict()
+    optional = {
+        "missing": report.missing,
+        "effectiveSampleSize": report.effective_sample_size,
+        "confidenceIntervals": report.confidence_intervals,
+        "significance": report.significance,
+        "status": report.status,
+    }
+    checks["typed"] = isinstance(report, DataBiasReport)
+    checks["attribute"] = report.total_variation_distance == as_dict["TotalVariationDistance"]
+    checks["item"] = report["ClassImbalance"] == report.class_imbalance
+    checks["keys"] = list(as_dict.keys()) == report.keys()
+    checks["optional fields set"] = (
+        report.missing is not None
+        and report.effective_sample_size is not None
+        and report.status is not None
+    )
+    checks["json round trip"] = DataBiasReport.from_json(report.to_json()) == report
+    checks["dict round trip"] = DataBiasReport.from_dict({**as_dict, **optional}) == report
+    metrics_only = DataBiasReport.from_dict(as_dict)
+    checks["metrics only dict"] = metrics_only.to_dict() == as_dict and metrics_only.status is None
+    bootstrapped = data_bias_analyzer(
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        "M",
+        RING_THRESHOLD,
+        confidence_level=0.9,
+        resamples=50,
+        significance=True,
+        permutations=50,
+    )
+    checks["bootstrap json round trip"] = (
+        DataBiasReport.from_json(bootstrapped.to_json()) == bootstrapped
+    )
+    checks["missing metric"] = raises(
+        MissingBaselineKeyError,
+        DataBiasReport.from_dict,
+        {k: v for k, v in as_dict.items() if k != "LpNorm"},
+    )
+
+    perf = model_performance_regression(y_pred=reg_pred, y_true=reg_true)
+    checks["perf typed"] = isinstance(perf, LinearRegressionReport)
+    checks["perf attribute"] = perf.r_squared == perf.to_dict()["RSquared"]
+    checks["perf json round trip"] = LinearRegressionReport.from_json(perf.to_json()) == perf
+
+    # the wrappers hand back the same report, read like the metric dict
+    wrapped = data_bias.perform_analysis(
+        bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy(), "M", RING_THRESHOLD
+    )
+    checks["wrapper"] = isinstance(wrapped, DataBiasReport) and wrapped.to_dict() == as_dict
+    checks["reads like a dict"] = (
+        wrapped == as_dict
+        and dict(wrapped) == as_dict
+        and len(wrapped) == len(as_dict)
+        and "LpNorm" in wrapped
+        and wrapped.get("NotAMetric", 1.0) == 1.0
+        and wrapped.missing is not None
+        and wrapped.status is not None
+    )
+    checks["runtime check of reports"] = data_bias.runtime_comparison(
+        wrapped, wrapped, 0.10
+    ) == data_bias.runtime_comparison(as_dict, as_dict, 0.10, wrapped.status, wrapped.status)
+    mb_args = (bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy(), bl_df["preds"].to_numpy())
+    mb_report = model_bias.perform_analysis(*mb_args, "M", RING_THRESHOLD, PRED_THRESHOLD)
+    checks["model bias wrapper"] = isinstance(mb_report, ModelBiasReport)
+    checks["model bias json round trip"] = ModelBiasReport.from_json(mb_report.to_json()) == mb_report
+    acc = ModelBiasAccumulator("M", RING_THRESHOLD, PRED_THRESHOLD)
+    acc.update(*mb_args)
+    checks["accumulator"] = isinstance(acc.finalize(as_report=True), ModelBiasReport)
+    perf_acc = LinearRegressionAccumulator()
+    perf_acc.update(reg_true, reg_pred)
+    checks["perf accumulator"] = isinstance(
+        perf_acc.finalize(as_report=True), LinearRegressionReport
+    )
+
+    legacy = dict(as_dict)
+    legacy["TotalVarationDistance"] = legacy.pop("TotalVariationDistance")
+    legacy["JsDivergance"] = legacy.pop("JsDivergence")
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        checks["legacy dict"] = DataBiasReport.from_dict(legacy).to_dict() == as_dict
+        checks["legacy runtime check"] = data_bias.runtime_comparison(legacy, as_dict) == {
+            "passed": True
+        }
+        check = data_bias.partial_runtime_comparison(
+            legacy, as_dict, ["TotalVarationDistance"]
+        )
+        checks["legacy partial check"] = check == {"passed": True}
+    checks["deprecation warnings"] = len(caught) > 0 and all(
+        issubclass(w.category, DeprecationWarning) for w in caught
+    )
+
+    failed = [name for name, passed in checks.items() if not passed]
+    assert not failed, f"report checks failed: {failed}"
+    return True
+
+
+def test_custom_metrics(bl_df) -> bool:
+    """
+    metrics registered from python show up in analysis, runtime checks and
+    accumulators next to the built in ones. Registration is process wide, so
+    this runs after the tests comparing against fixed reports
+    """
+    res = []
+
+    def positive_rate_ratio(counts: dict[str, int]) -> float:
+        q_a = counts["facet_a_positives"] / (
+            counts["facet_a_positives"] + counts["facet_a_negatives"]
+        )
+        q_d = counts["facet_d_positives"] / (
+            counts["facet_d_positives"] + counts["facet_d_negatives"]
+        )
+        return q_d / q_a
+
+    def false_positive_difference(counts: dict[str, int]) -> float:
+        return float(counts["facet_a_false_positives"] - counts["facet_d_false_positives"])
+
+    data_bias.register_metric(
+        "PositiveRateRatio", positive_rate_ratio, direction="increase", ideal_value=1.0
+    )
+    model_bias.register_metric("FalsePositiveDifference", false_positive_difference)
+    res.append(data_bias.metric_names()[-1] == "PositiveRateRatio")
+    res.append(model_bias.metric_names()[-1] == "FalsePositiveDifference")
+
+    db = data_bias.perform_analysis(
+        bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy(), "M", RING_THRESHOLD
+    )
+    res.append(list(db.keys())[-1] == "PositiveRateRatio")
+    latest = {**db, "PositiveRateRatio": db["PositiveRateRatio"] * 2}
+    check = data_bias.partial_runtime_comparison(db, latest, ["PositiveRateRatio"])
+    res.append(not check["passed"] and "PositiveRateRatio" in check["fail_report"])
+    res.append(not data_bias.runtime_comparison(db, latest)["passed"])
+
+    mb_args = (
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        bl_df["preds"].to_numpy(),
+    )
+    mb = model_bias.perform_analysis(*mb_args, "M", RING_THRESHOLD, PRED_THRESHOLD)
+    res.append("FalsePositiveDifference" in mb)
+    acc = ModelBiasAccumulator("M", RING_THRESHOLD, PRED_THRESHOLD)
+    acc.update(*mb_args)
+    res.append(acc.finalize() == mb)
+
+    res.append(
+        raises(
+            DuplicateMetricNameError,
+            data_bias.register_metric,
+            "ClassImbalance",
+            positive_rate_ratio,
+        )
+    )
+    res.append(
+        raises(
+            InvalidDirectionError,
+            model_bias.register_metric,
+            "Other",
+            false_positive_difference,
+            direction="decrease",
+        )
+    )
+
+    return all(res)
+
+
 def eval_str_to_bool(v: str) -> bool:
     if v.lower() not in ["true", "false"]:
         raise ValueError("Invalid value")
     return v.lower() == "true"
 
 
+def test_threaded(bl_df) -> bool:
+    """
+    analysis runs without the GIL, concurrent calls should match a serial call
+    """
+    args = (bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy(), "M", RING_THRESHOLD)
+    serial = data_bias.perform_analysis(*args)
+    mb_args = (
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        bl_df["preds"].to_numpy(),
+        "M",
+        RING_THRESHOLD,
+        PRED_THRESHOLD,
+    )
+    mb_serial = model_bias.perform_analysis(*mb_args)
+
+    with ThreadPoolExecutor(max_workers=4) as pool:
+        db_results = list(pool.map(lambda _: data_bias.perform_analysis(*args), range(8)))
+        mb_results = list(
+            pool.map(lambda _: model_bias.perform_analysis(*mb_args), range(8))
+        )
+
+    return all(r == serial for r in db_results) and all(
+        r == mb_serial for r in mb_results
+    )
+
+
+def test_accumulators(bl_df) -> bool:
+    """
+    streaming batches, and merging accumulators built on separate batches,
+    should give the same report as a single call over all the data
+    """
+    res = [False] * 4
+    batches = np.array_split(np.arange(bl_df.shape[0]), 3)
+
+    db_full = data_bias.perform_analysis(
+        bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy(), "M", RING_THRESHOLD
+    )
+    db_stream = DataBiasAccumulator("M", RING_THRESHOLD)
+    db_parts = [DataBiasAccumulator("M", RING_THRESHOLD) for _ in batches]
+    for idx, part in zip(batches, db_parts):
+        batch = bl_df.iloc[idx]
+        db_stream.update(batch["sex"].to_numpy(), batch["rings"].to_numpy())
+        part.update(batch["sex"].to_list(), batch["rings"].to_list())
+    for part in db_parts[1:]:
+        db_parts[0].merge(part)
+    res[0] = db_stream.finalize() == db_full
+    res[1] = db_parts[0].finalize() == db_full
+
+    mb_full = model_bias.perform_analysis(
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        bl_df["preds"].to_numpy(),
+        "M",
+        RING_THRESHOLD,
+        PRED_THRESHOLD,
+    )
+    mb_stream = ModelBiasAccumulator("M", RING_THRESHOLD, PRED_THRESHOLD)
+    mb_parts = [ModelBiasAccumulator("M", RING_THRESHOLD, PRED_THRESHOLD) for _ in batches]
+    for idx, part in zip(batches, mb_parts):
+        batch = bl_df.iloc[idx]
+        args = (
+            batch["sex"].to_numpy(),
+            batch["rings"].to_numpy(),
+            batch["preds"].to_numpy(),
+        )
+        mb_stream.update(*args)
+        part.update(*args)
+    for part in mb_parts[1:]:
+        mb_parts[0].merge(part)
+    res[2] = mb_stream.finalize() == mb_full
+    res[3] = mb_parts[0].finalize() == mb_full
+
+    return all(res)
+
+
+def close_reports(result, expected) -> bool:
+    """
+    multi group reports go through json, so values are compared to f32 precision
+    """
+    return list(result.keys()) == list(expected.keys()) and all(
+        np.isclose(result[k], expected[k], rtol=1e-5, equal_nan=True) for k in expected
+    )
+
+
+def test_conditional_disparity(bl_df) -> bool:
+    """
+    the disparity is averaged over strata weighted by size, a single stratum
+    reduces to the unconditional disparity, and perform_analysis reports it
+    next to the metrics when given the group variable
+    """
+    res = [False] * 8
+    feature = ["F"] * 8 + ["M"] * 2 + ["F"] * 2 + ["M"] * 8
+    labels = [1] * 6 + [0] * 2 + [1] * 2 + [0] * 2 + [1] * 2 + [0] * 6
+    strata = ["A"] * 10 + ["B"] * 10
+    cddl = data_bias.conditional_demographic_disparity(
+        np.array(feature), np.array(labels), np.array(strata), "M", 1
+    )
+    # A: facet d holds 2/2 of the negatives and 6/8 of the positives
+    # B: facet d holds 2/8 of the negatives and 0/2 of the positives
+    res[0] = (
+        [(s["group"], s["size"]) for s in cddl["strata"]] == [("A", 10), ("B", 10)]
+        and np.isclose(cddl["strata"][0]["disparity"], 0.25)
+        and np.isclose(cddl["strata"][1]["disparity"], 0.25)
+        and np.isclose(cddl["value"], 0.25)
+    )
+    res[1] = data_bias.conditional_demographic_disparity(
+        np.array(feature), np.array(labels), np.array([0] * 5 + [1] * 15), "M", 1
+    )["strata"][1]["size"] == 15
+
+    sex, rings, preds = (
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        bl_df["preds"].to_numpy(),
+    )
+    single = np.zeros(len(sex), dtype=np.int64)
+    cddpl = model_bias.conditional_demographic_disparity(sex, preds, single, "M", PRED_THRESHOLD)
+    res[2] = np.isclose(
+        cddpl["value"],
+        model_bias.perform_analysis(sex, rings, preds, "M", RING_THRESHOLD, PRED_THRESHOLD)[
+            "ConditionalDemographicDesparityPredictedLabels"
+        ],
+        rtol=1e-5,
+    )
+    facet_d, positive = sex != "M", rings >= 15
+    expected = facet_d[~positive].mean() - facet_d[positive].mean()
+    res[3] = np.isclose(
+        data_bias.conditional_demographic_disparity(sex, rings, single, "M", RING_THRESHOLD)["value"],
+        expected,
+        rtol=1e-5,
+    )
+    res[4] = raises(
+        LengthMismatchError,
+        data_bias.conditional_demographic_disparity,
+        sex,
+        rings,
+        single[:-1],
+        "M",
+        RING_THRESHOLD,
+    )
+
+    departments = np.where(np.arange(len(sex)) % 3 == 0, "A", "B")
+    data_report = data_bias.perform_analysis(
+        sex, rings, "M", RING_THRESHOLD, group_variable=departments
+    )
+    model_report = model_bias.perform_analysis(
+        sex, rings, preds, "M", RING_THRESHOLD, PRED_THRESHOLD, group_variable=departments
+    )
+    res[5] = (
+        data_report.conditional_disparity
+        == data_bias.conditional_demographic_disparity(sex, rings, departments, "M", RING_THRESHOLD)
+        and model_report.conditional_disparity
+        == model_bias.conditional_demographic_disparity(sex, preds, departments, "M", PRED_THRESHOLD)
+        and data_bias.perform_analysis(sex, rings, "M", RING_THRESHOLD).conditional_disparity
+        is None
+        and DataBiasReport.from_json(data_report.to_json()) == data_report
+    )
+    # every feature missing and kept as its own group leaves both facets empty
+    res[6] = raises(
+        EmptyFacetError,
+        data_bias.conditional_demographic_disparity,
+        np.full(4, np.nan),
+        np.array([1, 0, 1, 0]),
+        np.array(["A", "A", "B", "B"]),
+        LabelSpec.equals(1.0),
+        LabelSpec.equals(1),
+        missing_policy="own_group",
+    )
+    res[7] = raises(
+        InvalidSampleWeightError,
+        data_bias.perform_analysis,
+        sex,
+        rings,
+        "M",
+        RING_THRESHOLD,
+        sample_weight=np.ones(len(sex)),
+        group_variable=departments,
+    )
+    return all(res)
+
+
+def test_categorical_divergences(bl_df) -> bool:
+    """
+    every number of categories, a binary label included, follows the textbook
+    definitions over the category shares
+    """
+    res = [False] * 6
+    sex, rings = bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy()
+    positive = (rings >= 15).astype(np.int64)
+    binary = data_bias.categorical_divergences(sex, positive, "M")
+    p_a = np.bincount(positive[sex == "M"], minlength=2) / np.sum(sex == "M")
+    p_d = np.bincount(positive[sex != "M"], minlength=2) / np.sum(sex != "M")
+    mixture = 0.5 * (p_a + p_d)
+    res[0] = binary["categories"] == ["0", "1"] and close_reports(
+        binary["divergences"],
+        {
+            "KlDivergence": np.sum(p_a * np.log(p_a / p_d)),
+            "JsDivergence": 0.5 * np.sum(p_a * np.log(p_a / mixture) + p_d * np.log(p_d / mixture)),
+            "LpNorm": np.sqrt(np.sum((p_a - p_d) ** 2)),
+            "TotalVariationDistance": 0.5 * np.sum(np.abs(p_a - p_d)),
+            "KolmorogvSmirnov": np.abs(p_a[0] - p_d[0]),
+        },
+    )
+
+    feature = np.array(["F"] * 4 + ["M"] * 4)
+    grades = np.array(["A", "A", "B", "C", "A", "B", "B", "C"])
+    # facet a (F) is 1/2, 1/4, 1/4 and facet d (M) 1/4, 1/2, 1/4
+    cat = data_bias.categorical_divergences(feature, grades, "F")
+    res[1] = cat["categories"] == ["A", "B", "C"] and close_reports(
+        cat["divergences"],
+        {
+            "KlDivergence": 0.25 * np.log(2),
+            "JsDivergence": 0.5 * np.log(0.5 / 0.375) + 0.25 * np.log(0.25 / 0.375),
+            "LpNorm": np.sqrt(0.125),
+            "TotalVariationDistance": 0.25,
+            "KolmorogvSmirnov": 0.25,
+        },
+    )
+    res[2] = np.isclose(
+        data_bias.categorical_divergences(feature, grades, "F", lp_order=1.0)["divergences"][
+            "LpNorm"
+        ],
+        0.5,
+    )
+    # facet d never sees grade C, smoothing keeps the KL divergence finite
+    unseen = np.array(["A", "A", "B", "C", "A", "B", "B", "A"])
+    smoothed = data_bias.categorical_divergences(feature, unseen, "F", epsilon=0.01)
+    res[3] = np.isfinite(smoothed["divergences"]["KlDivergence"])
+    res[4] = raises(
+        InvalidDivergenceOptionsError,
+        data_bias.categorical_divergences,
+        feature,
+        grades,
+        "F",
+        lp_order=0.5,
+    )
+    # a missing feature kept as its own group is in neither facet, and so is
+    # grade D that only it holds
+    with_missing = np.array(["F"] * 4 + ["M"] * 4 + [None] * 2, dtype=object)
+    extra_grades = np.concatenate([grades, ["B", "D"]])
+    own_group = data_bias.categorical_divergences(
+        with_missing, extra_grades, "F", missing_policy="own_group"
+    )
+    res[5] = own_group == cat
+    return all(res)
+
+
+def test_continuous_analysis(bl_df) -> bool:
+    """
+    the facet outcome distributions are compared on their raw values, read
+    at full precision
+    """
+    res = [False] * 6
+    sex, rings = bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy().astype(np.float64)
+    report = data_bias.continuous_analysis(sex, rings, "M")
+    a, d = rings[sex == "M"], rings[sex != "M"]
+    values = np.unique(rings)
+    cdf_gap = np.abs(
+        np.searchsorted(np.sort(a), values, side="right") / len(a)
+        - np.searchsorted(np.sort(d), values, side="right") / len(d)
+    )
+    res[0] = close_reports(
+        {
+            k: v
+            for k, v in report.items()
+            if k not in ("KolmorogvSmirnovPValue", "AndersonDarling", "missing")
+        },
+        {
+            "DifferenceInMeans": a.mean() - d.mean(),
+            "DifferenceInMedians": np.median(a) - np.median(d),
+            "KolmorogvSmirnov": cdf_gap.max(),
+            "WassersteinDistance": (cdf_gap[:-1] * np.diff(values)).sum(),
+        },
+    ) and 0.0 <= report["KolmorogvSmirnovPValue"] <= 1.0
+
+    feature = np.array(["F"] * 3 + ["M"] * 3)
+    outcome = np.array([1.0, 2.0, 3.0, 4.0, 5.0, 6.0])
+    separated = data_bias.continuous_analysis(feature, outcome, "F")
+    res[1] = (
+        np.isclose(separated["KolmorogvSmirnov"], 1.0)
+        and np.isclose(separated["WassersteinDistance"], 3.0)
+        and np.isclose(separated["AndersonDarling"], 2.4844007, rtol=1e-5)
+        and np.isclose(separated["DifferenceInMeans"], -3.0)
+    )
+    same = data_bias.continuous_analysis(feature, np.array([1.0, 2.0, 3.0] * 2), "F")
+    res[2] = np.isclose(same["KolmorogvSmirnov"], 0.0) and np.isclose(
+        same["KolmorogvSmirnovPValue"], 1.0
+    )
+
+    outcome[1] = np.nan
+    res[3] = raises(MissingValuesError, data_bias.continuous_analysis, feature, outcome, "F")
+    dropped = data_bias.continuous_analysis(feature, outcome, "F", missing_policy="drop")
+    res[4] = dropped["missing"] == {"facetA": 1, "facetD": 0, "feature": 0} and np.isclose(
+        dropped["DifferenceInMeans"], 2.0 - 5.0
+    )
+
+    # a float32 outcome would round both facets to 1e9
+    large = data_bias.continuous_analysis(feature, np.array([1e9 + 1] * 3 + [1e9] * 3), "F")
+    res[5] = large["DifferenceInMeans"] == 1.0 and large["WassersteinDistance"] == 1.0
+    return all(res)
+
+
+def test_bootstrap(bl_df) -> bool:
+    """
+    seeded bootstrap bounds are reproducible, cover every metric and keep
+    the facet sizes fixed
+    """
+    res = [False] * 6
+    sex, rings, preds = (
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        bl_df["preds"].to_numpy(),
+    )
+    report = data_bias.perform_analysis(
+        sex, rings, "M", RING_THRESHOLD, confidence_level=0.9, resamples=200, seed=7
+    )
+    intervals = report.confidence_intervals
+    again = data_bias.perform_analysis(
+        sex, rings, "M", RING_THRESHOLD, confidence_level=0.9, resamples=200, seed=7
+    ).confidence_intervals
+    other_seed = data_bias.perform_analysis(
+        sex, rings, "M", RING_THRESHOLD, confidence_level=0.9, resamples=200, seed=8
+    ).confidence_intervals
+    res[0] = (
+        list(intervals) == report.keys()
+        and intervals == again
+        and intervals != other_seed
+        and all(b["lower"] <= b["upper"] for b in intervals.values())
+    )
+    # resampling within each facet never changes the facet sizes
+    res[1] = np.isclose(
+        intervals["ClassImbalance"]["lower"], report["ClassImbalance"]
+    ) and np.isclose(intervals["ClassImbalance"]["upper"], report["ClassImbalance"])
+
+    model_report = model_bias.perform_analysis(
+        sex,
+        rings,
+        preds,
+        "M",
+        RING_THRESHOLD,
+        PRED_THRESHOLD,
+        confidence_level=0.95,
+        resamples=100,
+    )
+    res[2] = (
+        list(model_report.confidence_intervals) == model_report.keys()
+        and model_report.missing["feature"] == 0
+    )
+
+    y_true = (rings >= 15).astype(np.float64)
+    y_pred = (preds >= 15.0).astype(np.float64)
+    perf = model_perf.binary_classification_analysis(
+        y_true, y_pred, confidence_level=0.95, resamples=100
+    )
+    res[3] = (
+        perf["confidenceIntervals"].keys() == perf["performanceData"].keys()
+        and "confidenceIntervals"
+        not in model_perf.binary_classification_analysis(y_true, y_pred)
+    )
+    res[4] = raises(
+        InvalidBootstrapConfigError,
+        data_bias.perform_analysis,
+        sex,
+        rings,
+        "M",
+        RING_THRESHOLD,
+        confidence_level=1.5,
+    )
+    # facet d holds a single positive, the resamples that miss it have no
+    # finite KlDivergence and leave its interval undefined
+    small_feature = np.array(["a"] * 10 + ["d"] * 5)
+    small_labels = np.array([1, 0] * 5 + [1, 0, 0, 0, 0])
+    small = data_bias.perform_analysis(
+        small_feature, small_labels, "a", 1, confidence_level=0.9, resamples=200, seed=3
+    ).confidence_intervals
+    res[5] = (
+        small["KlDivergence"]["droppedResamples"] > 0
+        and small["KlDivergence"]["status"] == "undefined"
+        and small["ClassImbalance"]["droppedResamples"] == 0
+        and small["ClassImbalance"]["status"] == "ok"
+    )
+    return all(res)
+
+
+def test_significance(bl_df) -> bool:
+    """
+    p-values of the 2x2 facet tables match hand computed references, the
+    permutation test is reproducible by seed and weights are refused
+    """
+    res = [False] * 5
+    # facet a has 8 positives out of 10, facet d 1 out of 6
+    feature = np.array(["a"] * 10 + ["d"] * 6)
+    labels = np.array([1] * 8 + [0] * 2 + [1] * 1 + [0] * 5)
+    report = data_bias.perform_analysis(feature, labels, "a", 1, significance=True, seed=3)
+    p_values = report.significance
+    dpl = p_values["DifferenceInProportionOfLabels"]
+    res[0] = (
+        list(p_values) == ["DifferenceInProportionOfLabels"]
+        and np.isclose(dpl["twoProportionZ"], 0.0134254, atol=1e-6)
+        # Yates corrected chi-square
+        and np.isclose(dpl["chiSquare"], 0.0509619, atol=1e-6)
+        and np.isclose(dpl["fisherExact"], 0.0349650, atol=1e-6)
+        and 1 / 1001 <= dpl["permutation"] <= 1
+        and "significance" not in report
+        and data_bias.perform_analysis(feature, labels, "a", 1).significance is None
+    )
+    again = data_bias.perform_analysis(
+        feature, labels, "a", 1, significance=True, seed=3
+    ).significance
+    # the hypergeometric draws approach the exact tail with enough permutations
+    many = data_bias.perform_analysis(
+        feature, labels, "a", 1, significance=True, permutations=20000, seed=3
+    ).significance["DifferenceInProportionOfLabels"]
+    res[1] = again == p_values and np.isclose(many["permutation"], 0.035, atol=0.005)
+
+    # predicting the labels, the predicted positive rate is the label rate
+    model_p_values = model_bias.perform_analysis(
+        feature, labels, labels, "a", 1, 1, significance=True, seed=3
+    ).significance
+    res[2] = (
+        sorted(model_p_values)
+        == ["AccuracyDifference", "DifferenceInPositivePredictedLabels", "RecallDifference"]
+        # every prediction is correct, the facets cannot differ
+        and model_p_values["AccuracyDifference"]["fisherExact"] == 1.0
+        and model_p_values["DifferenceInPositivePredictedLabels"]
+        == p_values["DifferenceInProportionOfLabels"]
+    )
+
+    sex, rings = bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy()
+    res[3] = raises(
+        InvalidSampleWeightError,
+        data_bias.perform_analysis,
+        sex,
+        rings,
+        "M",
+        RING_THRESHOLD,
+        sample_weight=np.ones(len(sex)),
+        significance=True,
+    )
+    res[4] = raises(
+        InvalidSignificanceConfigError,
+        data_bias.perform_analysis,
+        sex,
+        rings,
+        "M",
+        RING_THRESHOLD,
+        significance=True,
+        permutations=0,
+    )
+    return all(res)
+
+
+def test_metric_status(bl_df) -> bool:
+    """
+    a metric dividing by a zero count is undefined, small facets and cells
+    are low support, and a value that is not finite or undefined fails the
+    runtime check
+    """
+    res = [False] * 7
+    # facet a predicts every row positive, facet d has no false positives
+    feature = ["a"] * 4 + ["d"] * 3
+    preds = [1, 1, 1, 1, 0, 0, 1]
+    labels = [1, 0, 1, 1, 0, 1, 1]
+    report = model_bias.perform_analysis(feature, labels, preds, "a", 1, 1)
+    status = report.status
+    res[0] = (
+        list(status) == report.keys()
+        and status["TreatmentEquity"]
+        == {
+            "status": "undefined",
+            "reason": "TreatmentEquity is undefined, false positives of facet d is zero",
+        }
+        and status["DifferenceInRejectionRate"]["status"] == "undefined"
+        and status["RecallDifference"]
+        == {
+            "status": "low-support",
+            "reason": "facet a has 4 rows, below the minimum facet size of 30",
+        }
+    )
+    relaxed = model_bias.perform_analysis(
+        feature,
+        labels,
+        preds,
+        "a",
+        1,
+        1,
+        min_facet_size=0,
+        min_cell_count=0,
+    ).status
+    res[1] = relaxed["RecallDifference"] == {"status": "ok"} and relaxed[
+        "TreatmentEquity"
+    ] == status["TreatmentEquity"]
+
+    baseline = {k: (v if np.isfinite(v) else 0.0) for k, v in report.items()}
+    check = model_bias.runtime_comparison(baseline, report, 100.0)
+    res[2] = check["passed"] is False and check["failReport"][
+        "TreatmentEquity"
+    ].startswith("Undefined")
+
+    sex, rings = bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy()
+    db_report = data_bias.perform_analysis(sex, rings, "M", RING_THRESHOLD)
+    res[3] = (
+        list(db_report.status) == db_report.keys()
+        and db_report.status["ClassImbalance"] == {"status": "ok"}
+        and "status" not in db_report
+    )
+    res[4] = raises(
+        InvalidSupportThresholdsError,
+        data_bias.perform_analysis,
+        sex,
+        rings,
+        "M",
+        RING_THRESHOLD,
+        min_cell_count=-1,
+    )
+
+    # facet d has no positive predictions, DisparateImpact falls back to 0
+    fallback = model_bias.perform_analysis(feature, labels, [1, 1, 1, 1, 0, 0, 0], "a", 1, 1)
+    fallback_status = fallback.status
+    # plain dicts carry no status, a report brings its own
+    unchecked = model_bias.partial_runtime_comparison(
+        fallback.to_dict(), fallback.to_dict(), ["DisparateImpact"], 0.10
+    )
+    checked = model_bias.partial_runtime_comparison(
+        fallback, fallback, ["DisparateImpact"], 0.10
+    )
+    res[5] = (
+        fallback["DisparateImpact"] == 0.0
+        and fallback_status["DisparateImpact"]["status"] == "undefined"
+        and unchecked["passed"] is True
+        and checked["passed"] is False
+        and checked["failReport"]["DisparateImpact"]
+        == "Undefined, latest status: " + fallback_status["DisparateImpact"]["reason"]
+    )
+    res[6] = raises(
+        ValueError,
+        model_bias.runtime_comparison,
+        fallback,
+        fallback,
+        100.0,
+        {"DisparateImpact": {"status": "unknown"}},
+    )
+    return all(res)
+
+
+def test_multi_group(bl_df) -> bool:
+    """
+    one vs rest for a group matches the two facet analysis with that group as
+    the label, and the worst case group is the one furthest from ideal
+    """
+    res = []
+    sex, rings, preds = (
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        bl_df["preds"].to_numpy(),
+    )
+
+    db = data_bias.perform_multi_group_analysis(sex, rings, RING_THRESHOLD)
+    by_group = {r["group"]: r for r in db["results"]}
+    res.append(sorted(by_group) == ["F", "I", "M"])
+    res.append(all(r["versus"] is None for r in db["results"]))
+    for group, result in by_group.items():
+        expected = data_bias.perform_analysis(sex, rings, group, RING_THRESHOLD)
+        res.append(close_reports(result["report"], expected))
+    ci = db["worstCase"]["ClassImbalance"]
+    res.append(
+        ci["value"] == max((r["report"]["ClassImbalance"] for r in db["results"]), key=abs)
+    )
+
+    pairwise = data_bias.perform_multi_group_analysis(sex, rings, RING_THRESHOLD, "pairwise")
+    res.append(
+        [(r["group"], r["versus"]) for r in pairwise["results"]]
+        == [("F", "I"), ("F", "M"), ("I", "M")]
+    )
+    reference = data_bias.perform_multi_group_analysis(
+        sex, rings, RING_THRESHOLD, "reference", reference_group="I"
+    )
+    res.append([r["group"] for r in reference["results"]] == ["F", "M"])
+    res.append(
+        raises(
+            UnknownGroupError,
+            data_bias.perform_multi_group_analysis,
+            sex,
+            rings,
+            RING_THRESHOLD,
+            "reference",
+            reference_group="X",
+        )
+    )
+
+    mb = model_bias.perform_multi_group_analysis(sex, rings, preds, RING_THRESHOLD, PRED_THRESHOLD)
+    for result in mb["results"]:
+        expected = model_bias.perform_analysis(sex, rings, preds, result["group"], RING_THRESHOLD, PRED_THRESHOLD)
+        res.append(close_reports(result["report"], expected))
+    res.append(set(mb["worstCase"]) == set(expected))
+
+    return all(res)
+
+
+def test_intersectional(bl_df) -> bool:
+    """
+    a single attribute reduces to the two facet analysis, subgroups partition
+    the rows and small subgroups are suppressed
+    """
+    res = []
+    sex, length, rings, preds = (
+        bl_df["sex"].to_numpy(),
+        bl_df["length"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        bl_df["preds"].to_numpy(),
+    )
+
+    single = data_bias.perform_intersectional_analysis({"sex": sex}, {"sex": "M"}, rings, RING_THRESHOLD)
+    res.append([r["subgroup"] for r in single["results"]] == [{"sex": False}, {"sex": True}])
+    expected = data_bias.perform_analysis(sex, rings, "M", RING_THRESHOLD)
+    res.append(close_reports(single["results"][1]["report"], expected))
+
+    features = {"sex": sex, "length": length}
+    labels = {"sex": "M", "length": LabelSpec.greater_equal(0.5)}
+    db = data_bias.perform_intersectional_analysis(features, labels, rings, RING_THRESHOLD, 1)
+    res.append(len(db["results"]) == 4)
+    res.append(sum(r["size"] for r in db["results"]) == len(rings))
+    res.append(not any(r["suppressed"] for r in db["results"]))
+
+    suppressed = model_bias.perform_intersectional_analysis(
+        features, labels, rings, preds, RING_THRESHOLD, PRED_THRESHOLD, min_subgroup_size=len(rings)
+    )
+    res.append(suppressed["minSubgroupSize"] == len(rings))
+    res.append(
+        all(r["suppressed"] and r["report"] is None for r in suppressed["results"])
+    )
+
+    return all(res)
+
+
+def test_label_specs(bl_df) -> bool:
+    """
+    set, mapping and range specs match the same split written as a single label
+    """
+    res = []
+    sex, rings, preds = (
+        bl_df["sex"].to_numpy(),
+        bl_df["rings"].to_numpy(),
+        bl_df["preds"].to_numpy(),
+    )
+    adult = np.where(sex == "I", "N", "Y")
+    expected = data_bias.perform_analysis(adult, rings, "Y", RING_THRESHOLD)
+    res.append(close_reports(data_bias.perform_analysis(sex, rings, {"M", "F"}, RING_THRESHOLD), expected))
+    res.append(
+        close_reports(
+            data_bias.perform_analysis(sex, rings, LabelSpec.one_of(["M", "F"]), RING_THRESHOLD), expected
+        )
+    )
+    mapping = {"M": True, "F": True, "I": False}
+    res.append(close_reports(data_bias.perform_analysis(sex, rings, mapping, RING_THRESHOLD), expected))
+
+    in_range = np.where((rings >= 10) & (rings < 15), 1, 0)
+    res.append(
+        close_reports(
+            data_bias.perform_analysis(sex, rings, "M", LabelSpec.between(10, 15)),
+            data_bias.perform_analysis(sex, in_range, "M", 1),
+        )
+    )
+    pred_range = np.where((preds >= 10.0) & (preds < 15.0), 1, 0)
+    res.append(
+        close_reports(
+            model_bias.perform_analysis(
+                sex, rings, preds, mapping, LabelSpec.between(10, 15), LabelSpec.between(10.0, 15.0)
+            ),
+            model_bias.perform_analysis(adult, in_range, pred_range, "Y", 1, 1),
+        )
+    )
+
+    res.append(raises(UnmappedCategoryError, data_bias.perform_analysis, sex, rings, {"M": True}, RING_THRESHOLD))
+    res.append(raises(LabelTypeMismatchError, data_bias.perform_analysis, sex, rings, {1, 2}, RING_THRESHOLD))
+    res.append(raises(InvalidLabelSpecError, LabelSpec, "below", [1]))
+    res.append(raises(InvalidLabelSpecError, LabelSpec.between, "a", "b"))
+
+    spec = LabelSpec.mapping(mapping)
+    res.append(pickle.loads(pickle.dumps(spec)) == spec)
+    acc = DataBiasAccumulator(spec, LabelSpec.between(10, 15))
+    acc.update(sex, rings)
+    res.append(close_reports(pickle.loads(pickle.dumps(acc)).finalize(), acc.finalize()))
+
+    return all(res)
+
+
+def test_label_modes(bl_df) -> bool:
+    """
+    a bare number goes through the deprecated heuristic with a warning, the
+    same as LabelSpec.auto, and explicit modes can threshold data it would
+    have matched on equality
+    """
+    res = []
+    sex, rings = bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy()
+
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        res.append(resolve_label(rings, 15) == LabelSpec.greater_equal(15))
+        res.append(
+            data_bias.perform_analysis(sex, rings, "M", 15)
+            == data_bias.perform_analysis(sex, rings, "M", RING_THRESHOLD)
+        )
+    res.append(len(caught) == 2 and all(w.category is DeprecationWarning for w in caught))
+    with warnings.catch_warnings():
+        warnings.simplefilter("error")
+        res.append(resolve_label(rings, LabelSpec.auto(15)) == LabelSpec.greater_equal(15))
+        res.append(resolve_label(sex, "M").mode == "equals")
+        res.append(raises(DeprecationWarning, resolve_label, rings, 15))
+    probabilities = np.array([0.2, 1.0, 0.7, 0.4])
+    res.append(resolve_label(probabilities, LabelSpec.auto(0.5)).mode == "equals")
+    res.append(resolve_label(probabilities, LabelSpec.greater_equal(0.5)).mode == ">=")
+
+    res.append(
+        close_reports(
+            data_bias.perform_analysis(sex, rings, "M", LabelSpec.equals(15)),
+            data_bias.perform_analysis(sex, (rings == 15).astype(int), "M", LabelSpec.equals(1)),
+        )
+    )
+    for spec, mask in [
+        (LabelSpec.greater(15), rings > 15),
+        (LabelSpec.less_equal(15), rings <= 15),
+        (LabelSpec.less(15), rings < 15),
+        (LabelSpec("in", [9, 10]), np.isin(rings, [9, 10])),
+    ]:
+        res.append(
+            close_reports(
+                data_bias.perform_analysis(sex, rings, "M", spec),
+                data_bias.perform_analysis(sex, mask.astype(int), "M", 1),
+            )
+        )
+
+    # two numeric codes, thresholded rather than matched on equality
+    codes = np.where(sex == "M", 1, 3)
+    res.append(
+        close_reports(
+            data_bias.perform_analysis(codes, rings, LabelSpec.greater_equal(2), RING_THRESHOLD),
+            data_bias.perform_analysis(sex, rings, {"F", "I"}, RING_THRESHOLD),
+        )
+    )
+
+    spec = LabelSpec.less(15)
+    res.append((spec.mode, spec.values) == ("<", 15))
+    res.append(pickle.loads(pickle.dumps(spec)) == spec)
+    res.append(raises(InvalidLabelSpecError, LabelSpec.greater_equal, "M"))
+
+    return all(res)
+
+
+def ship(acc, fmt: str):
+    """
+    round trip an accumulator the way a worker would hand it to a reducer
+    """
+    cls = type(acc)
+    args = acc.__getnewargs__() if hasattr(acc, "__getnewargs__") else ()
+    if fmt == "bytes":
+        return cls.from_bytes(acc.to_bytes(), *args)
+    if fmt == "json":
+        return cls.from_json(acc.to_json(), *args)
+    return pickle.loads(pickle.dumps(acc))
+
+
+def test_merged_state(bl_df, reg_pred, reg_true, bin_pred, bin_true, bin_proba) -> bool:
+    """
+    finalize(merge(a, b)) == finalize(a U b) with a and b shipped through every
+    serialization format. Count based accumulators match exactly, the float
+    sums behind regression and log loss are compared to float precision
+    """
+    res = []
+    half = bl_df.shape[0] // 2
+
+    def check(make, columns, exact=True):
+        first = [c[:half] for c in columns]
+        second = [c[half:] for c in columns]
+        union = make()
+        union.update(*columns)
+        expected = union.finalize()
+        for fmt in ("bytes", "json", "pickle"):
+            a, b = make(), make()
+            a.update(*first)
+            b.update(*second)
+            a, b = ship(a, fmt), ship(b, fmt)
+            a.merge(b)
+            got = a.finalize()
+            if exact:
+                res.append(got == expected)
+            else:
+                got, want = got["performanceData"], expected["performanceData"]
+                res.append(
+                    got.keys() == want.keys()
+                    and all(np.isclose(got[k], want[k], rtol=1e-5) for k in want)
+                )
+
+    check(
+        lambda: DataBiasAccumulator("M", RING_THRESHOLD),
+        [bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy()],
+    )
+    check(
+        lambda: ModelBiasAccumulator("M", RING_THRESHOLD, PRED_THRESHOLD),
+        [bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy(), bl_df["preds"].to_numpy()],
+    )
+    check(BinaryClassificationAccumulator, [bin_true, bin_pred])
+    check(LogisticRegressionAccumulator, [bin_true, bin_proba], exact=False)
+    check(LinearRegressionAccumulator, [reg_true, reg_pred], exact=False)
+
+    # state from an incompatible format version, or another accumulator, is rejected
+    acc = DataBiasAccumulator("M", RING_THRESHOLD)
+    acc.update(bl_df["sex"].to_numpy(), bl_df["rings"].to_numpy())
+    state = json.loads(acc.to_json())
+    state["format_version"] += 1
+    res.append(
+        raises(
+            StateVersionMismatchError,
+            DataBiasAccumulator.from_json,
+            json.dumps(state),
+            "M",
+            RING_THRESHOLD,
+        )
+    )
+    res.append(
+        raises(
+            InvalidStateError,
+            ModelBiasAccumulator.from_bytes,
+            acc.to_bytes(),
+            "M",
+            RING_THRESHOLD,
+            PRED_THRESHOLD,
+        )
+    )
+
+    return all(res)
+
+
 if __name__ == "__main__":
     parser = argparse.ArgumentParser()
     parser.add_argument("--generate-data", type=eval_str_to_bool, default=False)
+    parser.add_argument("--benchmark", type=eval_str_to_bool, default=False)
     args = parser.parse_args()
     generate = args.generate_data
     if generate:
@@ -716,6 +2583,10 @@ if __name__ == "__main__":
 
     bl_df = df.iloc[: int(0.60 * df.shape[0]), :]
     runtime_test = df.iloc[int(0.60 * df.shape[0]) + 1 :, :]
+    print("TESTING ERRORS...")
+    assert test_errors(bl_df)
+    print("passed...")
+
     print("TESTING DATA BIAS WITH NUMPY ARRAYS...")
     assert test_db_numpy(bl_df, runtime_test)
     print("passed...")
@@ -740,6 +2611,94 @@ if __name__ == "__main__":
     assert test_mb_list(bl_df, runtime_test)
     print("passed...")
 
+    print("TESTING concurrent analysis...")
+    assert test_threaded(bl_df)
+    print("passed...")
+
+    print("TESTING streaming accumulators...")
+    assert test_accumulators(bl_df)
+    print("passed...")
+
+    print("TESTING multi group analysis...")
+    assert test_multi_group(bl_df)
+    print("passed...")
+
+    print("TESTING intersectional analysis...")
+    assert test_intersectional(bl_df)
+    print("passed...")
+
+    print("TESTING label specs...")
+    assert test_label_specs(bl_df)
+    print("passed...")
+
+    print("TESTING explicit label modes...")
+    assert test_label_modes(bl_df)
+    print("passed...")
+
+    print("TESTING missing value policies...")
+    assert test_missing_values(bl_df)
+    print("passed...")
+
+    print("TESTING missing values in group analyses...")
+    assert test_group_missing_values(bl_df)
+    print("passed...")
+
+    print("TESTING sample weights...")
+    assert test_sample_weights(bl_df)
+    print("passed...")
+
+    print("TESTING numpy dtypes...")
+    assert test_dtypes(bl_df)
+    print("passed...")
+
+    print("TESTING arrow ingestion...")
+    assert test_arrow(bl_df)
+    print("passed...")
+
+    print("TESTING dataframe analysis...")
+    assert test_dataframe(bl_df)
+    print("passed...")
+
+    print("TESTING polars expressions...")
+    assert test_polars_expressions(bl_df)
+    print("passed...")
+
+    print("TESTING conditional demographic disparity...")
+    assert test_conditional_disparity(bl_df)
+    print("passed...")
+
+    print("TESTING categorical label divergences...")
+    assert test_categorical_divergences(bl_df)
+    print("passed...")
+
+    print("TESTING continuous outcome distributions...")
+    assert test_continuous_analysis(bl_df)
+    print("passed...")
+
+    print("TESTING bootstrap confidence intervals...")
+    assert test_bootstrap(bl_df)
+    print("passed...")
+
+    print("TESTING significance of the facet rate differences...")
+    assert test_significance(bl_df)
+    print("passed...")
+
+    print("TESTING metric status...")
+    assert test_metric_status(bl_df)
+    print("passed...")
+
+    print("TESTING serialized accumulator state...")
+    assert test_merged_state(bl_df, reg_pred, reg_true, bin_pred, bin_true, bin_proba)
+    print("passed...")
+
+    print("TESTING typed reports...")
+    assert test_reports(bl_df, reg_pred, reg_true)
+    print("passed...")
+
+    print("TESTING custom bias metrics...")
+    assert test_custom_metrics(bl_df)
+    print("passed...")
+
     print("TESTING PERF WITH NUMPY ARRAYS")
     assert test_perf_reg_numpy(reg_pred, reg_true)
     print("passed...")
@@ -763,3 +2722,8 @@ if __name__ == "__main__":
     print("TESTING logisitc PERF WITH list")
     assert test_perf_logisitc_reg_list(bin_proba.tolist(), bin_true.tolist())
     print("passed...")
+
+    if args.benchmark:
+        print("BENCHMARKING numpy ingestion...")
+        assert benchmark_ingestion()
+        print("passed...")